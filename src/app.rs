@@ -1,46 +1,360 @@
+use crate::artifacts::{ArtifactEntry, MAX_VIEWED_FILE_SIZE};
+use crate::color::ColorSupport;
+use crate::config::settings::GitLabProfile;
 use crate::events::actions::{Action, Effect};
-use crate::gitlab::{Job, JobStatus, MergeRequest, Note, Pipeline};
-use std::collections::HashMap;
+use crate::events::keymap::{bindable_action, KeyContext, Keymap, BINDABLE_ACTIONS};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use crate::gitlab::{Job, JobStatus, MergeRequest, Note, Pipeline, PipelineStatus, TestSuite, TraceChunk};
+use crate::log_buffer::LogBuffer;
+use crate::log_diff::LogDiff;
+use crate::log_processor::{JobOutcome, LogSection, ProcessedLines};
+use crate::ui::theme::{self, Theme};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum TimestampDisplayMode {
     Hidden,      // Don't show timestamps
     DateOnly,    // Show date only (e.g., "2024-01-15")
     Full,        // Show full timestamp (e.g., "2024-01-15 10:30:45")
+    Relative,    // Show offset from the first timestamp (e.g., "+00:12.345")
+}
+
+/// What happens on first opening a failed pipeline, set by `[app] failed_pipeline`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailedPipelineJump {
+    #[default]
+    Off,       // Keep the first job selected
+    SelectJob, // Select the failed job of the earliest stage
+    OpenLog,   // Also open that job's log
+}
+
+/// How a watched job finishing is announced, set by `[app] notify`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyMode {
+    Off,
+    Bell,    // Ring the terminal bell
+    Desktop, // Show a desktop notification
+    #[default]
+    Both,
+}
+
+/// Job logs fetched ahead on opening a pipeline, set by `[app] prefetch_logs`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogPrefetch {
+    #[default]
+    Off,
+    Failed,   // Logs of failed jobs
+    Finished, // Logs of every job that ran to an end
+}
+
+impl LogPrefetch {
+    fn matches(self, status: &JobStatus) -> bool {
+        match self {
+            LogPrefetch::Off => false,
+            LogPrefetch::Failed => *status == JobStatus::Failed,
+            LogPrefetch::Finished => matches!(status, JobStatus::Success | JobStatus::Failed | JobStatus::Canceled),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    Literal,         // Exact, case-sensitive substring match
+    CaseInsensitive, // Substring match ignoring case
+    Regex,           // Full regular expression
+}
+
+impl SearchMode {
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::Literal => SearchMode::CaseInsensitive,
+            SearchMode::CaseInsensitive => SearchMode::Regex,
+            SearchMode::Regex => SearchMode::Literal,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Literal => "Literal",
+            SearchMode::CaseInsensitive => "Case-insensitive",
+            SearchMode::Regex => "Regex",
+        }
+    }
+}
+
+/// Order of the pipeline list, cycled with ctrl+s
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PipelineSort {
+    #[default]
+    Newest, // As GitLab lists them
+    Oldest,
+    Status,  // Failed first, then running, pending, ...
+    Slowest, // Longest from creation to last update first
+    Fastest,
+}
+
+impl PipelineSort {
+    pub fn next(self) -> Self {
+        match self {
+            PipelineSort::Newest => PipelineSort::Oldest,
+            PipelineSort::Oldest => PipelineSort::Status,
+            PipelineSort::Status => PipelineSort::Slowest,
+            PipelineSort::Slowest => PipelineSort::Fastest,
+            PipelineSort::Fastest => PipelineSort::Newest,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PipelineSort::Newest => "newest first",
+            PipelineSort::Oldest => "oldest first",
+            PipelineSort::Status => "by status",
+            PipelineSort::Slowest => "slowest first",
+            PipelineSort::Fastest => "fastest first",
+        }
+    }
+}
+
+/// Order of the job list, cycled with S; anything but stage order lists jobs without stage headers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JobSort {
+    #[default]
+    Stage, // Stages in pipeline order, jobs needing attention first within each
+    NameAscending,
+    NameDescending,
+    Slowest,
+    Fastest,
+}
+
+impl JobSort {
+    pub fn next(self) -> Self {
+        match self {
+            JobSort::Stage => JobSort::NameAscending,
+            JobSort::NameAscending => JobSort::NameDescending,
+            JobSort::NameDescending => JobSort::Slowest,
+            JobSort::Slowest => JobSort::Fastest,
+            JobSort::Fastest => JobSort::Stage,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            JobSort::Stage => "in stage order",
+            JobSort::NameAscending => "by name A-Z",
+            JobSort::NameDescending => "by name Z-A",
+            JobSort::Slowest => "slowest first",
+            JobSort::Fastest => "fastest first",
+        }
+    }
 }
 
+/// Columns panned per left/right key press when wrapping is off
+const HORIZONTAL_SCROLL_STEP: usize = 4;
+
+/// Size of the log viewer popup in percent of the screen, by default and at the smallest
+pub const DEFAULT_LOG_POPUP_PERCENT: u16 = 90;
+pub const MIN_LOG_POPUP_PERCENT: u16 = 50;
+
+/// Percent the log viewer grows or shrinks per + / - key press
+const LOG_POPUP_STEP: u16 = 5;
+
+/// Delay between log re-fetches while following a running job
+const LOG_FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Bytes requested per ranged trace fetch, so large logs open after the first chunk
+pub const LOG_FETCH_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Log lines searched per step, input is handled between steps so huge logs don't stall the UI
+pub const SEARCH_BATCH_LINES: usize = 20_000;
+
+/// Lines kept processed above and below the viewport, so scrolling a little doesn't reprocess
+const LOG_WINDOW_MARGIN: usize = 1000;
+
+/// Lines scanned for sections and errors at a time, a block is read back from disk for a log
+/// too big to be kept in memory
+const STRUCTURE_SCAN_LINES: usize = 10_000;
+
+/// Default pause in typing after which the search bar's query is searched for
+pub const DEFAULT_SEARCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Most lines of context kept around each filter match
+pub const MAX_FILTER_CONTEXT: usize = 20;
+
+/// Number of submitted search queries kept for recall
+const SEARCH_HISTORY_LIMIT: usize = 50;
+
+/// Closed logs whose viewer state is kept for when they are reopened, the least recent go first
+const SAVED_LOG_VIEWS_LIMIT: usize = 32;
+
+/// A count prefix is forgotten if the next key doesn't follow within this delay
+const COUNT_PREFIX_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Largest accepted count prefix, so a stray run of digits can't stall the UI
+const MAX_COUNT_PREFIX: usize = 9999;
+
+/// Default delay between re-polls of running pipelines and jobs
+pub const DEFAULT_LIVE_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Shown instead of watching when live refresh is off, since nothing would see the job finish
+const WATCH_NEEDS_LIVE_REFRESH: &str = "Live refresh is off, set live_refresh_interval_secs to watch jobs";
+
+/// How long transient status messages stay visible
+const TRANSIENT_STATUS_DURATION: std::time::Duration = std::time::Duration::from_secs(4);
+
 pub struct App {
     // UI State
     pub should_quit: bool,
     pub selected_mr_index: usize,
-    pub selected_job_index: usize,
+    pub selected_job_index: usize, // Among the visible jobs, see `visible_jobs`
+    pub collapsed_stages: HashSet<String>, // Folded stage names, shared by all pipelines
+    pub stage_filter: Option<String>, // Only jobs of this stage are listed
+    pub job_filter: Option<JobFilter>, // Only jobs whose name fuzzy-matches are listed
+    pub last_selected_jobs: HashMap<u64, u64>, // Job last selected in each pipeline, by pipeline id
+    pipeline_users_requested: HashSet<u64>, // Pipelines fetched on their own for who started them
+    pub failed_pipeline_jump: FailedPipelineJump,
+    pub pipeline_sort: PipelineSort,
+    pub job_sort: JobSort,
+    job_sort_time: chrono::DateTime<chrono::Utc>, // Running jobs are timed at this instant when sorting by duration
+    pub restore_location: Option<crate::config::LastLocation>, // Reselected as its MR, pipeline and jobs load
+    restore_pipeline_requested: bool, // The restored pipeline wasn't listed and was asked for by id
 
     // Data State
     pub tracked_mrs: Vec<TrackedMergeRequest>,
     pub project_id: u64,
     pub current_branch: Option<String>,
     pub focus_current_branch: bool,
+    pub profiles: Vec<GitLabProfile>, // Configured GitLab instances, `[gitlab]` first
+    pub active_profile: usize, // Index into profiles, the instance every request goes to
+    pub profile_generation: u64, // Bumped on each profile switch, answers to older requests are dropped
+    pub profile_picker: Option<usize>, // Highlighted profile while the picker is open
+    pub head_pipelines: Option<HeadPipelines>, // Pipelines of the local HEAD commit, while their popup is open
+    pub group: Option<String>, // Group browsed with G, the project's namespace when unset
+    pub group_pipelines: Option<GroupPipelines>, // Pipelines across the group's projects, while their popup is open
+    pending_pipeline_focus: Option<Pipeline>, // Opened from the group view, selected once its project's MRs load
+    pipeline_retry: Option<PipelineRetry>,    // Sent, until the pipeline's jobs reload
+    pub instance_host: String, // GitLab host the token is used with, shown in the status bar
+    pub username: Option<String>, // Owner of the token, fetched once at startup
+    pub project_path: Option<String>, // e.g. "group/project"
 
     // UI Modes
     pub mode: AppMode,
+    pub help_scroll: u16,
+    pub help_max_scroll: u16, // Set by the render loop from the popup size
+    pub theme: &'static Theme,
+    pub color_support: ColorSupport, // Colors are reduced to this after each frame is drawn
+    pub show_status_bar: bool, // Bottom line with instance, user, project and what is selected
 
     // Log Viewer State
-    pub log_content: Option<String>,
-    pub log_processed_lines: Vec<ratatui::text::Line<'static>>, // Cached processed lines
-    pub log_scroll_offset: usize,
+    pub log_tabs: Vec<LogTab>, // One per open log, the active one's state is in the fields below
+    saved_log_views: HashMap<u64, SavedLogView>, // Viewer state of closed logs by job id
+    pub active_log_tab: usize,
+    pub log_content: Option<LogBuffer>,
+    pub log_loading_since: Option<Instant>, // First chunk of the log requested and not arrived yet
+    pub log_error: Option<String>, // Why fetching the log failed, shown in the viewer until retried
+    pub log_expired: bool, // GitLab erased the trace, there is nothing to fetch
+    pub log_fetch_complete: bool, // Every chunk of the trace has been downloaded
+    pub log_total_bytes: Option<u64>, // Trace size reported by the server while downloading
+    pub log_processed_lines: ProcessedLines, // Rendered rows of the lines around the viewport
+    pub log_lines_key: Option<(u64, TimestampDisplayMode, bool)>, // Content hash, timestamp mode and raw flag the cached lines were built from
+    pub log_sections: Vec<LogSection>, // Collapsible sections, ordered by start line
+    pub collapsed_sections: HashSet<String>, // Names of folded sections
+    pub log_visible_lines: Vec<usize>, // Source line indices currently displayed (after folding)
+    pub log_error_lines: Vec<usize>, // Source lines that look like errors, detected once on load
+    pub log_warning_lines: Vec<usize>, // Source lines that look like warnings, for the minimap
+    pub log_row_break_lines: Vec<usize>, // Source lines that may take several rows even unwrapped
+    log_started_at: Option<(usize, chrono::DateTime<chrono::Utc>)>, // First timestamped line, the baseline of relative timestamps
+    pub log_outcome: Option<JobOutcome>, // The runner's final status line, pinned below the log
+    pub show_minimap: bool, // Error/warning/section overview beside the scrollbar of long logs
+    pub current_error_line: Option<usize>, // Index into log_error_lines
+    pub log_bookmarks: HashMap<u64, BTreeSet<usize>>, // Bookmarked source lines per job id, kept for the session
+    pub log_scroll_offset: usize, // Index into log_visible_lines
+    pub log_scroll_row: usize, // Rows of the top line scrolled past, when wrapping splits it over several rows
+    pub horizontal_scroll_offset: usize, // First displayed column when wrapping is off
+    pub wrap_enabled: bool,
+    pub show_line_numbers: bool, // Gutter with original source line numbers
     pub log_viewport_height: usize, // Height of visible log area (set by renderer)
+    pub log_content_area: Option<ratatui::layout::Rect>, // Screen area of the log lines, for mouse hit-testing
+    pub log_selection: Option<(usize, usize)>, // Selected source lines (anchor, end), set by clicking or `V`
+    pub log_visual_mode: bool, // Line-wise visual selection: moving keys move the selection's end
+    pub mouse_scroll_lines: usize, // Lines scrolled per mouse wheel tick
+    pub scroll_step: usize, // Lines scrolled per j / k
+    pub log_popup_percent: u16, // Width and height of the log viewer, 100 fills the screen
+    pub fast_scroll_step: usize, // Lines scrolled per J / K
+    pub match_position: f64, // Where jumps to a match or error put the line, 0.0 top to 1.0 bottom
     pub log_job_name: Option<String>,
+    pub log_job_id: Option<u64>,
+    pub log_job_running: bool, // Job is still in progress (updated while following)
+    pub follow_mode: bool, // Keep the view pinned to the end of the log, like `tail -f`
+    pub follow_by_default: bool, // Start following when a running job's log is opened
+    pub log_poll_in_flight: bool, // A follow-mode re-fetch is scheduled or running
     pub timestamp_mode: TimestampDisplayMode,
+    pub raw_log_view: bool, // Lines exactly as received: no prefix stripping, timestamp formatting or folding
     pub search_query: String,
+    pub search_mode: SearchMode,
+    pub search_whole_word: bool, // Matches must be whole words, kept for the session
+    pub search_wrap: bool, // n / N wrap around the ends of the log instead of stopping there
+    pub search_regex: Option<Regex>, // Compiled once per executed query
+    pub search_regex_error: bool, // Regex mode query failed to compile
     pub search_results: Vec<usize>, // Line numbers where matches are found
     pub current_search_result: usize, // Index into search_results
+    pub search_scan: Option<SearchScan>, // Search still running through the log, None once done
+    search_generation: u64, // Identifies the latest scan, steps of older ones are dropped
     pub is_searching: bool, // Whether in search input mode
+    pub search_debounce: Option<std::time::Duration>, // Search while typing after this pause, None searches on Enter only
+    search_input_generation: u64, // Identifies the latest edit of the query, older debounces are dropped
+    search_origin: Option<(usize, usize)>, // Scroll position before searching while typing, restored on Esc
+    pub search_history: Vec<String>, // Submitted queries, oldest first, without duplicates
+    pub search_history_index: Option<usize>, // Entry recalled with Up/Down, None while typing a new query
+    search_draft: String, // Query being typed before browsing the history
+    pub pending_key: Option<char>, // First key of a two-key sequence (e.g. 'z' in "za")
+    pub keymap: Keymap,
+    pub pending_confirmation: Option<Confirmation>,
+    pub artifact_download: Option<ArtifactDownload>, // One download at a time
+    pub exports_in_flight: usize, // Log exports still being written
+    pub artifact_browser: Option<ArtifactBrowser>, // Files of a job's artifacts, while their popup is open
+    pub test_report: Option<TestReportView>, // Test results of a job, while their popup is open
+    pub job_graph: Option<JobGraphView>,
+    pub trigger_input: Option<String>, // Run-pipeline prompt text while it is open
+    pub play_prompt: Option<PlayPrompt>, // Manual job about to be played, with the variables typed so far
+    pub command_palette: Option<CommandPalette>,
+    pub compare_job: Option<(u64, String)>, // Job marked as the left side of a log comparison
+    pub watched_jobs: HashSet<u64>, // Jobs announced when they finish
+    pub watched_pipelines: HashSet<u64>, // Pipelines whose jobs are all announced when they finish
+    pub notify_mode: NotifyMode,
+    pub log_prefetch_mode: LogPrefetch,
+    log_prefetch: Option<LogPrefetchRun>,
+    pub log_diff: Option<LogDiff>, // Open comparison, shown in ComparingLogs mode
+    pub pending_count: Option<(usize, Instant)>, // Count prefix typed so far and when its last digit came in
+    pub filter_query: String,
+    pub filter_inverse: bool, // Hide matching lines instead of keeping them, like `grep -v`
+    pub filter_regex: Option<Regex>, // Applied filter pattern
+    pub filtered_line_indices: Option<Vec<usize>>, // Source lines passing the filter, None when unfiltered
+    pub filter_context: usize, // Lines kept before and after each filter match, like `grep -C`
+    pub is_filtering: bool, // Whether in filter input mode
 
     // Status
     pub status_message: Option<String>,
+    pub status_expires_at: Option<Instant>, // Transient messages disappear after this
     pub error_message: Option<String>,
     pub last_refresh: Option<chrono::DateTime<chrono::Utc>>,
+    pub live_refresh_interval: Option<std::time::Duration>, // None disables live polling
+    pub pipeline_status_filter: Option<PipelineStatus>, // Only pipelines with this status are listed
+    live_poll_scheduled: bool, // A LivePollDue is already on its way
+    rate_limited_until: Option<chrono::DateTime<chrono::Utc>>, // Polling is paused until then
+    pub offline_cached_at: Option<chrono::DateTime<chrono::Utc>>, // Oldest cached data shown while GitLab is unreachable
 
     // Auto-refresh
     pub last_auto_refresh: Instant,
@@ -49,12 +363,228 @@ pub struct App {
     pub selected_note_id_before_refresh: Option<u64>, // Track selected note ID to restore after refresh
 }
 
+
+/// Progress of a search through the log, continued one `SEARCH_BATCH_LINES` step at a time
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchScan {
+    pub generation: u64,
+    pub next_line: usize, // First source line not searched yet
+    jump_to_first: bool, // Center the first match found, for a search the user just ran
+    select_line: Option<usize>, // Select the match at or after this source line once it is reached
+    scheduled: bool, // A ContinueSearch step is on its way
+}
+
+/// An artifacts archive being streamed to disk
+#[derive(Debug, Clone)]
+pub struct ArtifactDownload {
+    pub job_id: u64,
+    pub job_name: String,
+    pub path: PathBuf,
+    pub received: u64,
+    pub total: Option<u64>, // From Content-Length, falling back to the size GitLab reported for the job
+    cancel: Arc<AtomicBool>, // Shared with the download task
+}
+
+/// Logs of the selected pipeline's jobs being fetched ahead, dropped on moving to another pipeline
+#[derive(Debug, Clone)]
+pub struct LogPrefetchRun {
+    mr_index: usize,
+    pipeline_id: u64,
+    requested: HashSet<u64>, // Jobs already asked for, not asked again on the next jobs poll
+    pending: usize,
+    fetched: usize, // Since the last report
+    cancel: Arc<AtomicBool>, // Shared with the fetch tasks
+}
+
+/// Logs prefetched at the same time, the rest wait for one of them to finish
+pub const LOG_PREFETCH_CONCURRENCY: usize = 4;
+
+/// Files of a job's artifacts archive, one of them possibly open
+#[derive(Debug, Clone)]
+pub struct ArtifactBrowser {
+    pub job_id: u64,
+    pub job_name: String,
+    pub entries: Option<Vec<ArtifactEntry>>, // None while the archive's index loads
+    pub error: Option<String>,
+    pub selected: usize,
+    pub file: Option<ArtifactFileView>,
+}
+
+/// An artifact file shown as text
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactFileView {
+    pub path: String,
+    pub lines: Option<Vec<String>>, // None while it loads
+    pub error: Option<String>,
+    pub scroll: usize, // First line shown
+    pub height: usize, // Lines that fit, set by the render loop
+}
+
+/// Test results of a job, from the test report GitLab parses out of its JUnit artifacts
+#[derive(Debug, Clone)]
+pub struct TestReportView {
+    pub job_id: u64,
+    pub job_name: String,
+    pub suite: Option<TestSuite>, // None while the report loads
+    pub error: Option<String>,
+    pub show_all: bool, // Every test instead of only the failing ones
+    pub collapsed: HashSet<usize>, // Failing tests with their message hidden, by index into the suite's cases
+    pub selected: usize, // Index into visible_cases
+}
+
+impl TestReportView {
+    /// Indices of the listed test cases: failures first, the others only when showing all
+    pub fn visible_cases(&self) -> Vec<usize> {
+        let Some(suite) = &self.suite else {
+            return Vec::new();
+        };
+        (0..suite.test_cases.len()).filter(|&i| self.show_all || suite.test_cases[i].is_failure()).collect()
+    }
+}
+
+/// What the jobs of a pipeline `needs:`, shown as a tree while its popup is open
+#[derive(Debug, Clone)]
+pub struct JobGraphView {
+    pub pipeline_id: u64,
+    pub pipeline_iid: u64,
+    pub needs: Option<HashMap<String, Vec<String>>>, // Job name -> names it needs, None while loading
+    pub error: Option<String>,
+    pub selected: usize, // Row of the tree
+}
+
+/// A job in the needs tree, under the job it needs; a job needing several is listed under each
+#[derive(Debug)]
+pub struct JobGraphRow<'a> {
+    pub depth: usize,
+    pub job: &'a Job,
+    pub repeated: bool,           // Already listed higher up, with the jobs that need it
+    pub blocked_by: Vec<&'a Job>, // Failed, canceled or skipped needs of a job that never ran
+}
+
+/// Jobs of the pipeline that `needs` names: a parallel job ("rspec") stands for all of its instances
+fn needed_jobs(jobs: &[Job], needs: &[String]) -> Vec<usize> {
+    (0..jobs.len())
+        .filter(|&i| {
+            let name = jobs[i].name.as_str();
+            let base = name.rsplit_once(' ').filter(|(_, index)| index.contains('/')).map_or(name, |(base, _)| base);
+            needs.iter().any(|need| need == name || need == base)
+        })
+        .collect()
+}
+
+/// Rows of the needs tree: jobs needing nothing at the top in pipeline order, each followed by the
+/// jobs that need it
+pub fn job_graph_rows<'a>(jobs: &'a [Job], needs: &HashMap<String, Vec<String>>) -> Vec<JobGraphRow<'a>> {
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    order.sort_by_key(|&i| jobs[i].id);
+    let parents: Vec<Vec<usize>> = jobs
+        .iter()
+        .map(|job| needs.get(&job.name).map_or_else(Vec::new, |names| needed_jobs(jobs, names)))
+        .collect();
+
+    fn visit<'a>(
+        index: usize,
+        depth: usize,
+        jobs: &'a [Job],
+        order: &[usize],
+        parents: &[Vec<usize>],
+        listed: &mut Vec<bool>,
+        rows: &mut Vec<JobGraphRow<'a>>,
+    ) {
+        let job = &jobs[index];
+        let never_ran = matches!(job.status, JobStatus::Created | JobStatus::Skipped);
+        let blocked_by = parents[index]
+            .iter()
+            .map(|&parent| &jobs[parent])
+            .filter(|parent| {
+                never_ran && matches!(parent.status, JobStatus::Failed | JobStatus::Canceled | JobStatus::Skipped)
+            })
+            .collect();
+        rows.push(JobGraphRow { depth, job, repeated: listed[index], blocked_by });
+        if listed[index] {
+            return;
+        }
+        listed[index] = true;
+        for &child in order.iter().filter(|&&child| parents[child].contains(&index)) {
+            visit(child, depth + 1, jobs, order, parents, listed, rows);
+        }
+    }
+
+    let mut listed = vec![false; jobs.len()];
+    let mut rows = Vec::new();
+    for &root in order.iter().filter(|&&i| parents[i].is_empty()) {
+        visit(root, 0, jobs, &order, &parents, &mut listed, &mut rows);
+    }
+    rows
+}
+
+/// Split run-pipeline prompt input `<ref> [KEY=value ...]` into the ref and its variables
+pub fn parse_trigger_input(input: &str) -> std::result::Result<(String, Vec<(String, String)>), String> {
+    let mut tokens = input.split_whitespace();
+    let ref_name = tokens.next().ok_or_else(|| "Enter a branch, tag or commit to run".to_string())?;
+    let variables = parse_variables(tokens)?;
+    Ok((ref_name.to_string(), variables))
+}
+
+/// Parse `KEY=value` tokens, as typed in the run-pipeline and manual job prompts
+pub fn parse_variables<'a>(
+    tokens: impl Iterator<Item = &'a str>,
+) -> std::result::Result<Vec<(String, String)>, String> {
+    tokens
+        .map(|token| match token.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+            _ => Err(format!("Invalid variable '{}', expected KEY=value", token)),
+        })
+        .collect()
+}
+
+/// A manual job waiting to be played, Enter in its prompt plays it with the typed variables
+#[derive(Debug, Clone)]
+pub struct PlayPrompt {
+    pub mr_index: usize,
+    pub pipeline_id: u64,
+    pub job_id: u64,
+    pub job_name: String,
+    pub input: String, // `KEY=value ...`
+}
+
+/// Jobs a pipeline retry was asked for, checked against the reloaded jobs for any GitLab left alone
+#[derive(Debug, Clone)]
+pub struct PipelineRetry {
+    pub pipeline_id: u64,
+    pub pipeline_iid: u64,
+    pub jobs: Vec<(u64, String)>, // Id and name of each failed or canceled job
+    pub accepted: bool,           // GitLab answered, jobs loaded from then on show the retry
+}
+
+impl PipelineRetry {
+    /// Whether a job is one of those a pipeline retry starts over
+    fn retries(job: &Job) -> bool {
+        matches!(job.status, JobStatus::Failed | JobStatus::Canceled)
+    }
+}
+
+/// `1 job`, `3 jobs`
+fn job_count(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+/// A destructive or outward-facing action waiting for the user to answer y/n
 #[derive(Debug, Clone)]
+pub struct Confirmation {
+    pub prompt: String,
+    pub action: Action, // Run when confirmed
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct TrackedMergeRequest {
     pub mr: MergeRequest,
     pub pipelines: Vec<Pipeline>,
     pub jobs: HashMap<u64, Vec<Job>>, // pipeline_id -> jobs
-    pub job_logs_cache: HashMap<u64, String>, // job_id -> cached log content
+    pub job_logs_cache: HashMap<u64, Arc<String>>, // job_id -> cached log content, shared with the viewer
     pub notes: Vec<Note>,              // MR comments/notes
     pub notes_loaded: bool,            // Track if notes have been fetched
     pub selected_pipeline_index: usize,
@@ -62,6 +592,330 @@ pub struct TrackedMergeRequest {
     pub loading: bool,
     #[allow(dead_code)]
     pub error: Option<String>,         // Reserved for future per-MR error tracking
+    pub pipelines_next_page: Option<u32>, // Older pipelines left to fetch
+    pub loading_more_pipelines: bool,  // Next page of pipelines is in flight
+}
+
+/// Pipelines run for the commit checked out locally
+#[derive(Debug, Clone, Default)]
+pub struct HeadPipelines {
+    pub sha: Option<String>, // Known once the first lookup answers
+    pub pipelines: Option<Vec<Pipeline>>, // None while loading
+    pub error: Option<String>,
+    pub selected: usize,
+    pub polling: bool, // Looking again every live refresh interval until a pipeline shows up
+}
+
+/// Newest pipelines fetched for each project of the group view
+pub const GROUP_PIPELINES_PER_PROJECT: u32 = 5;
+
+/// Projects whose pipelines are fetched at the same time in the group view
+pub const GROUP_FETCH_CONCURRENCY: usize = 4;
+
+/// Recent pipelines across the projects of a group
+#[derive(Debug, Clone, Default)]
+pub struct GroupPipelines {
+    pub group: String,
+    pub pipelines: Vec<GroupPipeline>, // Of every project listed so far
+    pub selected: usize, // Index into `visible()`
+    pub status_filter: Option<PipelineStatus>,
+    pub sort: PipelineSort,
+    pub projects_loading: usize, // Projects whose pipelines are still being fetched
+    pub failed_projects: Vec<String>,
+    pub next_page: Option<u32>, // More projects left to list
+    pub loading_page: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupPipeline {
+    pub project_id: u64,
+    pub project_path: String,
+    pub pipeline: Pipeline,
+}
+
+impl GroupPipelines {
+    fn new(group: String) -> Self {
+        GroupPipelines { group, loading_page: true, ..Default::default() }
+    }
+
+    /// Pipelines passing the status filter, in the chosen order
+    pub fn visible(&self) -> Vec<&GroupPipeline> {
+        let mut rows: Vec<&GroupPipeline> = self
+            .pipelines
+            .iter()
+            .filter(|row| self.status_filter.as_ref().is_none_or(|status| &row.pipeline.status == status))
+            .collect();
+        sort_by_pipeline(&mut rows, self.sort, |row| &row.pipeline);
+        rows
+    }
+
+    fn selected_id(&self) -> Option<u64> {
+        self.visible().get(self.selected).map(|row| row.pipeline.id)
+    }
+
+    /// Select this pipeline again after the list changed, or stay within bounds
+    fn reselect(&mut self, id: Option<u64>) {
+        let visible = self.visible();
+        self.selected = id
+            .and_then(|id| visible.iter().position(|row| row.pipeline.id == id))
+            .unwrap_or(self.selected)
+            .min(visible.len().saturating_sub(1));
+    }
+}
+
+/// Command palette state while it is open
+#[derive(Debug, Clone, Default)]
+pub struct CommandPalette {
+    pub query: String,
+    pub selected: usize, // Index into the matching entries
+}
+
+/// What a tick on the log scrollbar points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollbarMark {
+    SearchMatch,
+    Bookmark,
+}
+
+/// What a row of the log minimap summarizes, most important first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MinimapMark {
+    Error,
+    Warning,
+    Section, // A CI section starts here
+}
+
+/// Where a closed log was left, brought back when the same job's log is opened again
+#[derive(Debug, Clone)]
+struct SavedLogView {
+    top_line: usize, // Source line at the top of the viewport
+    top_row: usize,
+    horizontal_scroll_offset: usize,
+    timestamp_mode: TimestampDisplayMode,
+    search_query: String,
+    search_mode: SearchMode, // What the query meant when it was saved
+    search_whole_word: bool,
+    search_line: Option<usize>, // Source line of the current match
+    filter_query: String,
+    filter_inverse: bool,
+    saved_at: Instant,
+}
+
+/// Log viewer state of a tab in the background. The active tab's state lives in the `log_*`,
+/// search and filter fields of `App`, and is swapped with a `LogTab` when switching tabs
+#[derive(Debug)]
+pub struct LogTab {
+    log_content: Option<LogBuffer>,
+    log_loading_since: Option<Instant>,
+    log_error: Option<String>,
+    log_expired: bool,
+    log_fetch_complete: bool,
+    log_total_bytes: Option<u64>,
+    log_processed_lines: ProcessedLines,
+    log_lines_key: Option<(u64, TimestampDisplayMode, bool)>,
+    log_sections: Vec<LogSection>,
+    collapsed_sections: HashSet<String>,
+    log_visible_lines: Vec<usize>,
+    log_error_lines: Vec<usize>,
+    log_warning_lines: Vec<usize>,
+    log_row_break_lines: Vec<usize>,
+    log_started_at: Option<(usize, chrono::DateTime<chrono::Utc>)>,
+    log_outcome: Option<JobOutcome>,
+    current_error_line: Option<usize>,
+    log_scroll_offset: usize,
+    log_scroll_row: usize,
+    horizontal_scroll_offset: usize,
+    log_selection: Option<(usize, usize)>,
+    log_visual_mode: bool,
+    log_job_name: Option<String>,
+    log_job_id: Option<u64>,
+    log_job_running: bool,
+    follow_mode: bool,
+    log_poll_in_flight: bool,
+    timestamp_mode: TimestampDisplayMode,
+    raw_log_view: bool,
+    search_query: String,
+    search_regex: Option<Regex>,
+    search_regex_error: bool,
+    search_results: Vec<usize>,
+    current_search_result: usize,
+    search_scan: Option<SearchScan>,
+    filter_query: String,
+    filter_inverse: bool,
+    filter_regex: Option<Regex>,
+    filtered_line_indices: Option<Vec<usize>>,
+}
+
+impl Default for LogTab {
+    fn default() -> Self {
+        LogTab {
+            log_content: None,
+            log_loading_since: None,
+            log_error: None,
+            log_expired: false,
+            log_fetch_complete: true,
+            log_total_bytes: None,
+            log_processed_lines: ProcessedLines::default(),
+            log_lines_key: None,
+            log_sections: Vec::new(),
+            collapsed_sections: HashSet::new(),
+            log_visible_lines: Vec::new(),
+            log_error_lines: Vec::new(),
+            log_warning_lines: Vec::new(),
+            log_row_break_lines: Vec::new(),
+            log_started_at: None,
+            log_outcome: None,
+            current_error_line: None,
+            log_scroll_offset: 0,
+            log_scroll_row: 0,
+            horizontal_scroll_offset: 0,
+            log_selection: None,
+            log_visual_mode: false,
+            log_job_name: None,
+            log_job_id: None,
+            log_job_running: false,
+            follow_mode: false,
+            log_poll_in_flight: false,
+            timestamp_mode: TimestampDisplayMode::Hidden,
+            raw_log_view: false,
+            search_query: String::new(),
+            search_regex: None,
+            search_regex_error: false,
+            search_results: Vec::new(),
+            current_search_result: 0,
+            search_scan: None,
+            filter_query: String::new(),
+            filter_inverse: false,
+            filter_regex: None,
+            filtered_line_indices: None,
+        }
+    }
+}
+
+/// Filter typed in the job list: fuzzy on job names, or words found in any column
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    pub query: String,
+    pub editing: bool, // Keys go to the query until Enter
+    pub columns: bool, // Every word must be in the name, stage or status, ignoring case
+}
+
+/// Character positions of `text` covered by any of the words, ignoring case
+fn word_positions(text: &str, words: &[Vec<char>]) -> Vec<usize> {
+    let chars: Vec<char> = text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect();
+    let mut positions = BTreeSet::new();
+    for word in words.iter().filter(|word| !word.is_empty() && word.len() <= chars.len()) {
+        for start in 0..=chars.len() - word.len() {
+            if chars[start..start + word.len()] == word[..] {
+                positions.extend(start..start + word.len());
+            }
+        }
+    }
+    positions.into_iter().collect()
+}
+
+/// An action offered by the command palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub keys: String, // Keys bound to it in the current view, empty if none
+}
+
+/// Jobs of one stage of the selected pipeline, as shown under a stage header
+#[derive(Debug)]
+pub struct StageGroup<'a> {
+    pub name: &'a str,
+    pub status: JobStatus, // Most significant job status, e.g. failed if any job failed
+    pub collapsed: bool,
+    pub jobs: Vec<&'a Job>,
+    first_job_id: u64,
+}
+
+/// At-a-glance health of the selected pipeline, from its loaded jobs
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineSummary {
+    pub status: PipelineStatus,
+    pub counts: Vec<(JobStatus, usize)>, // Statuses present, in legend order; created counts as pending
+    pub seconds: Option<f64>,            // From the first job start to the last finish, or now while active
+}
+
+/// Statuses counted in a pipeline summary, in the order they are shown
+const SUMMARY_STATUSES: [JobStatus; 7] = [
+    JobStatus::Success,
+    JobStatus::Failed,
+    JobStatus::Running,
+    JobStatus::Pending,
+    JobStatus::Manual,
+    JobStatus::Canceled,
+    JobStatus::Skipped,
+];
+
+/// Put an MR's pipelines in the given order, keeping the same one selected
+fn sort_pipelines(mr: &mut TrackedMergeRequest, sort: PipelineSort) {
+    let selected = mr.pipelines.get(mr.selected_pipeline_index).map(|p| p.id);
+    sort_pipelines_selecting(mr, sort, selected);
+}
+
+/// Put an MR's pipelines in the given order, then select the pipeline with id `selected`
+fn sort_pipelines_selecting(mr: &mut TrackedMergeRequest, sort: PipelineSort, selected: Option<u64>) {
+    sort_by_pipeline(&mut mr.pipelines, sort, |p| p);
+    if let Some(index) = selected.and_then(|id| mr.pipelines.iter().position(|p| p.id == id)) {
+        mr.selected_pipeline_index = index;
+    }
+}
+
+/// Order pipelines, or rows holding one, the way `sort` asks
+fn sort_by_pipeline<T>(items: &mut [T], sort: PipelineSort, pipeline: impl Fn(&T) -> &Pipeline) {
+    let now = chrono::Utc::now();
+    // Pipelines are listed without a duration, the time until their last update stands in for it
+    let seconds = |item: &T| {
+        let pipeline = pipeline(item);
+        match pipeline.status.is_active() {
+            true => (now - pipeline.created_at).num_seconds(),
+            false => (pipeline.updated_at - pipeline.created_at).num_seconds(),
+        }
+    };
+    // Pipeline ids grow across the whole instance, so they order pipelines of any project by age
+    match sort {
+        PipelineSort::Newest => items.sort_by_key(|item| std::cmp::Reverse(pipeline(item).id)),
+        PipelineSort::Oldest => items.sort_by_key(|item| pipeline(item).id),
+        PipelineSort::Status => items.sort_by(|a, b| {
+            let (a, b) = (pipeline(a), pipeline(b));
+            pipeline_status_rank(&a.status).cmp(&pipeline_status_rank(&b.status)).then(b.id.cmp(&a.id))
+        }),
+        PipelineSort::Slowest => items.sort_by_key(|item| std::cmp::Reverse(seconds(item))),
+        PipelineSort::Fastest => items.sort_by_key(seconds),
+    }
+}
+
+/// Order of pipeline statuses when sorting by status: what needs attention first
+fn pipeline_status_rank(status: &PipelineStatus) -> u8 {
+    match status {
+        PipelineStatus::Failed => 0,
+        PipelineStatus::Running => 1,
+        PipelineStatus::Pending | PipelineStatus::Preparing | PipelineStatus::WaitingForResource => 2,
+        PipelineStatus::Canceling | PipelineStatus::Canceled => 3,
+        PipelineStatus::Created => 4,
+        PipelineStatus::Manual => 5,
+        PipelineStatus::Success => 6,
+        PipelineStatus::Skipped => 7,
+    }
+}
+
+/// Order of job statuses in the job list: what needs attention first
+fn job_status_rank(status: &JobStatus) -> u8 {
+    match status {
+        JobStatus::Failed => 0,
+        JobStatus::Running => 1,
+        JobStatus::Pending => 2,
+        JobStatus::Canceling | JobStatus::Canceled => 3,
+        JobStatus::Created => 4,
+        JobStatus::Manual => 5,
+        JobStatus::Success => 6,
+        JobStatus::Skipped => 7,
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -71,38 +925,150 @@ pub enum AppMode {
     ViewingLog,       // Viewing job log internally
     SelectingMr,      // MR selection dialog
     ShowingHelp,      // Help popup visible
+    ComparingLogs,    // Two job logs side by side
 }
 
 impl App {
     pub fn new(project_id: u64, current_branch: Option<String>, focus_current_branch: bool, auto_refresh_interval_minutes: u64) -> Self {
-        let status_message = if focus_current_branch && current_branch.is_some() {
-            Some(format!("Loading MR for branch '{}'...", current_branch.as_ref().unwrap()))
-        } else {
-            Some("Loading merge requests...".to_string())
+        let status_message = match &current_branch {
+            Some(branch) if focus_current_branch => {
+                Some(format!("Loading MR for branch '{}'...", branch))
+            }
+            _ => Some("Loading merge requests...".to_string()),
         };
 
         Self {
             should_quit: false,
             selected_mr_index: 0,
             selected_job_index: 0,
+            collapsed_stages: HashSet::new(),
+            stage_filter: None,
+            job_filter: None,
+            last_selected_jobs: HashMap::new(),
+            pipeline_users_requested: HashSet::new(),
+            failed_pipeline_jump: FailedPipelineJump::Off,
+            pipeline_sort: PipelineSort::default(),
+            job_sort: JobSort::default(),
+            job_sort_time: chrono::Utc::now(),
+            restore_location: None,
+            restore_pipeline_requested: false,
             tracked_mrs: Vec::new(),
             project_id,
             current_branch,
             focus_current_branch,
+            profiles: Vec::new(),
+            active_profile: 0,
+            profile_generation: 0,
+            profile_picker: None,
+            head_pipelines: None,
+            group: None,
+            group_pipelines: None,
+            pending_pipeline_focus: None,
+            pipeline_retry: None,
+            instance_host: String::new(),
+            username: None,
+            project_path: None,
             mode: AppMode::Normal,
+            log_tabs: Vec::new(),
+            saved_log_views: HashMap::new(),
+            active_log_tab: 0,
             log_content: None,
-            log_processed_lines: Vec::new(),
+            log_loading_since: None,
+            log_error: None,
+            log_expired: false,
+            log_fetch_complete: true,
+            log_total_bytes: None,
+            log_processed_lines: ProcessedLines::default(),
+            log_lines_key: None,
+            log_sections: Vec::new(),
+            collapsed_sections: HashSet::new(),
+            log_visible_lines: Vec::new(),
+            log_error_lines: Vec::new(),
+            log_warning_lines: Vec::new(),
+            log_row_break_lines: Vec::new(),
+            log_started_at: None,
+            log_outcome: None,
+            show_minimap: true,
+            current_error_line: None,
+            log_bookmarks: HashMap::new(),
             log_scroll_offset: 0,
+            log_scroll_row: 0,
+            horizontal_scroll_offset: 0,
+            wrap_enabled: true,
+            show_line_numbers: false,
             log_viewport_height: 30, // Default, will be updated by renderer
+            log_content_area: None,
+            log_selection: None,
+            log_visual_mode: false,
+            mouse_scroll_lines: 3,
+            scroll_step: 1,
+            log_popup_percent: DEFAULT_LOG_POPUP_PERCENT,
+            fast_scroll_step: 10,
+            match_position: 0.5,
             log_job_name: None,
+            log_job_id: None,
+            help_scroll: 0,
+            help_max_scroll: 0,
+            theme: &theme::DARK,
+            color_support: ColorSupport::TrueColor,
+            show_status_bar: true,
+            log_job_running: false,
+            follow_mode: false,
+            follow_by_default: false,
+            log_poll_in_flight: false,
             timestamp_mode: TimestampDisplayMode::Hidden,
+            raw_log_view: false,
             search_query: String::new(),
+            search_mode: SearchMode::CaseInsensitive,
+            search_whole_word: false,
+            search_wrap: true,
+            search_regex: None,
+            search_regex_error: false,
             search_results: Vec::new(),
+            search_scan: None,
+            search_generation: 0,
             current_search_result: 0,
             is_searching: false,
+            search_debounce: Some(DEFAULT_SEARCH_DEBOUNCE),
+            search_input_generation: 0,
+            search_origin: None,
+            search_history: Vec::new(),
+            search_history_index: None,
+            search_draft: String::new(),
+            pending_key: None,
+            keymap: Keymap::default(),
+            pending_confirmation: None,
+            artifact_download: None,
+            exports_in_flight: 0,
+            artifact_browser: None,
+            test_report: None,
+            job_graph: None,
+            trigger_input: None,
+            play_prompt: None,
+            command_palette: None,
+            compare_job: None,
+            watched_jobs: HashSet::new(),
+            watched_pipelines: HashSet::new(),
+            notify_mode: NotifyMode::default(),
+            log_prefetch_mode: LogPrefetch::Off,
+            log_prefetch: None,
+            log_diff: None,
+            pending_count: None,
+            filter_query: String::new(),
+            filter_inverse: false,
+            filter_regex: None,
+            filtered_line_indices: None,
+            filter_context: 0,
+            is_filtering: false,
             status_message,
+            status_expires_at: None,
             error_message: None,
             last_refresh: None,
+            live_refresh_interval: Some(DEFAULT_LIVE_REFRESH_INTERVAL),
+            pipeline_status_filter: None,
+            live_poll_scheduled: false,
+            rate_limited_until: None,
+            offline_cached_at: None,
             last_auto_refresh: Instant::now(),
             auto_refresh_interval_minutes,
             refetch_notes_after_refresh: false,
@@ -132,6 +1098,208 @@ impl App {
         None
     }
 
+    /// Key bindings that apply to the current mode
+    pub fn key_context(&self) -> Option<KeyContext> {
+        match self.mode {
+            AppMode::Normal => Some(KeyContext::Jobs),
+            AppMode::ViewingComments => Some(KeyContext::Comments),
+            AppMode::ViewingLog => Some(KeyContext::Log),
+            AppMode::ShowingHelp => Some(KeyContext::Help),
+            AppMode::ComparingLogs => Some(KeyContext::Diff),
+            AppMode::SelectingMr => None,
+        }
+    }
+
+    /// Actions of the current view matching the palette query, best match first
+    pub fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let (Some(context), Some(palette)) = (self.key_context(), &self.command_palette) else {
+            return Vec::new();
+        };
+        let help = self.keymap.help_entries(context);
+        let matcher = SkimMatcherV2::default();
+        let mut scored: Vec<(i64, PaletteEntry)> = BINDABLE_ACTIONS
+            .iter()
+            .filter(|bindable| bindable.name != "command_palette")
+            .filter(|bindable| self.keymap.is_available(context, bindable.name))
+            .filter_map(|bindable| {
+                let text = format!("{} {}", bindable.description, bindable.name);
+                let score = matcher.fuzzy_match(&text, &palette.query)?;
+                let keys = help
+                    .iter()
+                    .find(|entry| entry.description == bindable.description)
+                    .map(|entry| entry.keys_label())
+                    .unwrap_or_default();
+                Some((score, PaletteEntry { name: bindable.name, description: bindable.description, keys }))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    fn move_palette_selection(&mut self, forward: bool) {
+        let count = self.palette_entries().len();
+        if let Some(palette) = self.command_palette.as_mut().filter(|_| count > 0) {
+            palette.selected = if forward {
+                (palette.selected + 1) % count
+            } else {
+                palette.selected.checked_sub(1).unwrap_or(count - 1)
+            };
+        }
+    }
+
+    /// Job counts by status, status and duration of the selected pipeline, once its jobs are loaded
+    pub fn pipeline_summary(&self, now: chrono::DateTime<chrono::Utc>) -> Option<PipelineSummary> {
+        let pipeline = self.get_selected_pipeline()?;
+        let jobs = self.get_selected_jobs().filter(|jobs| !jobs.is_empty())?;
+
+        let counts = SUMMARY_STATUSES
+            .into_iter()
+            .map(|status| {
+                let count = jobs
+                    .iter()
+                    .filter(|job| match job.status {
+                        JobStatus::Created => status == JobStatus::Pending,
+                        JobStatus::Canceling => status == JobStatus::Canceled,
+                        ref other => *other == status,
+                    })
+                    .count();
+                (status, count)
+            })
+            .filter(|&(_, count)| count > 0)
+            .collect();
+
+        let started = jobs.iter().filter_map(|job| job.started_at).min();
+        let finished = match pipeline.status.is_active() {
+            true => Some(now),
+            false => jobs.iter().filter_map(|job| job.finished_at).max(),
+        };
+        let seconds = started
+            .zip(finished)
+            .map(|(started, finished)| (finished - started).num_milliseconds().max(0) as f64 / 1000.0);
+
+        Some(PipelineSummary { status: pipeline.status.clone(), counts, seconds })
+    }
+
+    /// Jobs of the selected pipeline grouped by stage, in pipeline order, restricted to the stage filter
+    pub fn job_stage_groups(&self) -> Vec<StageGroup<'_>> {
+        let mut groups: Vec<StageGroup> = Vec::new();
+        let jobs = self.get_selected_jobs().unwrap_or_default();
+        for job in jobs {
+            if self.stage_filter.as_ref().is_some_and(|stage| stage != &job.stage) {
+                continue;
+            }
+            if !self.job_matches_filter(job) {
+                continue;
+            }
+            match groups.iter_mut().find(|group| group.name == job.stage) {
+                Some(group) => {
+                    if job_status_rank(&job.status) < job_status_rank(&group.status) {
+                        group.status = job.status.clone();
+                    }
+                    group.first_job_id = group.first_job_id.min(job.id);
+                    group.jobs.push(job);
+                }
+                None => groups.push(StageGroup {
+                    name: &job.stage,
+                    status: job.status.clone(),
+                    // Matches of a name filter are listed even in folded stages
+                    collapsed: self.collapsed_stages.contains(&job.stage) && self.job_filter.is_none(),
+                    first_job_id: job.id,
+                    jobs: vec![job],
+                }),
+            }
+        }
+        // Jobs are created stage by stage, so the oldest job tells the stage order
+        groups.sort_by_key(|group| group.first_job_id);
+        if self.job_sort == JobSort::Stage {
+            return groups;
+        }
+
+        // Any other order lists all jobs together, in one group that can't be folded
+        let Some(status) = groups.iter().map(|group| group.status.clone()).min_by_key(job_status_rank) else {
+            return groups;
+        };
+        let mut jobs: Vec<&Job> = groups.into_iter().flat_map(|group| group.jobs).collect();
+        let seconds = |job: &Job| job.run_seconds(self.job_sort_time).unwrap_or(0.0);
+        match self.job_sort {
+            JobSort::Stage => {}
+            JobSort::NameAscending => jobs.sort_by(|a, b| a.name.cmp(&b.name)),
+            JobSort::NameDescending => jobs.sort_by(|a, b| b.name.cmp(&a.name)),
+            JobSort::Slowest => jobs.sort_by(|a, b| seconds(b).total_cmp(&seconds(a))),
+            JobSort::Fastest => jobs.sort_by(|a, b| seconds(a).total_cmp(&seconds(b))),
+        }
+        let first_job_id = jobs.iter().map(|job| job.id).min().unwrap_or_default();
+        vec![StageGroup { name: "All stages", status, collapsed: false, jobs, first_job_id }]
+    }
+
+    /// Score and matched character positions of a job name against the job filter,
+    /// `Some` with no positions when no filter is typed; a column search scores every name alike
+    pub fn job_filter_match(&self, name: &str) -> Option<(i64, Vec<usize>)> {
+        match self.job_filter.as_ref().filter(|filter| !filter.query.is_empty()) {
+            Some(filter) if filter.columns => Some((0, self.job_search_positions(name))),
+            Some(filter) => SkimMatcherV2::default().fuzzy_indices(name, &filter.query),
+            None => Some((0, Vec::new())),
+        }
+    }
+
+    /// Lowercased words of a column search, none for a fuzzy filter
+    fn job_search_words(&self) -> Vec<Vec<char>> {
+        match self.job_filter.as_ref().filter(|filter| filter.columns) {
+            Some(filter) => filter
+                .query
+                .split_whitespace()
+                .map(|word| word.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Characters of a job's name, stage or status matching a column search, to highlight them
+    pub fn job_search_positions(&self, text: &str) -> Vec<usize> {
+        word_positions(text, &self.job_search_words())
+    }
+
+    /// Whether a job is listed under the job filter
+    fn job_matches_filter(&self, job: &Job) -> bool {
+        if !self.job_filter.as_ref().is_some_and(|filter| filter.columns) {
+            return self.job_filter_match(&job.name).is_some();
+        }
+        let columns = [job.name.as_str(), job.stage.as_str(), job.status.api_name()];
+        self.job_search_words()
+            .iter()
+            .all(|word| columns.iter().any(|column| !word_positions(column, std::slice::from_ref(word)).is_empty()))
+    }
+
+    /// Whether the job list shows times that count up, for running or queued jobs
+    pub fn shows_live_job_times(&self) -> bool {
+        self.mode == AppMode::Normal
+            && self
+                .get_selected_jobs()
+                .is_some_and(|jobs| jobs.iter().any(|job| matches!(job.status, JobStatus::Running | JobStatus::Pending)))
+    }
+
+    /// Jobs that can be selected: those of expanded stages, in display order
+    pub fn visible_jobs(&self) -> Vec<&Job> {
+        self.job_stage_groups()
+            .into_iter()
+            .filter(|group| !group.collapsed)
+            .flat_map(|group| group.jobs)
+            .collect()
+    }
+
+    /// The job whose log is open, as last loaded in the job lists
+    pub fn log_job(&self) -> Option<&Job> {
+        let job_id = self.log_job_id?;
+        self.tracked_mrs
+            .iter()
+            .flat_map(|mr| mr.jobs.values().flatten())
+            .find(|job| job.id == job_id)
+    }
+
+    pub fn selected_job(&self) -> Option<&Job> {
+        self.visible_jobs().get(self.selected_job_index).copied()
+    }
+
     pub fn get_selected_notes(&self) -> Option<&[Note]> {
         self.get_selected_mr()
             .map(|mr| mr.notes.as_slice())
@@ -144,966 +1312,8179 @@ impl App {
         })
     }
 
-    pub fn is_viewing_comments(&self) -> bool {
-        self.mode == AppMode::ViewingComments
+    /// Show a status message that disappears after a few seconds
+    pub fn set_transient_status(&mut self, message: String) {
+        self.status_message = Some(message);
+        self.status_expires_at = Some(Instant::now() + TRANSIENT_STATUS_DURATION);
     }
 
-    /// Center a line in the log viewer viewport
-    fn center_log_line(&mut self, line_number: usize) {
-        let total_lines = self.log_processed_lines.len();
-        if total_lines == 0 {
-            return;
+    /// The status message to display, if any and not yet expired
+    pub fn visible_status(&self) -> Option<&str> {
+        match self.status_expires_at {
+            Some(expires_at) if Instant::now() >= expires_at => None,
+            _ => self.status_message.as_deref(),
         }
+    }
 
-        // Calculate offset to center the line
-        let half_viewport = self.log_viewport_height / 2;
+    /// Placeholder shown instead of the log when nothing in it would be visible, e.g. a log
+    /// made only of section markers or blank lines
+    pub fn log_empty_message(&self) -> Option<&'static str> {
+        if self.log_line_count() == 0 {
+            return Some("(empty log)");
+        }
+        // Only told apart when the whole log is around the viewport, a longer one is rarely blank
+        if self.log_processed_lines.lines() != (0..self.log_line_count()) {
+            return None;
+        }
+        let displayable = self
+            .log_processed_lines
+            .rows()
+            .iter()
+            .any(|line| line.spans.iter().any(|span| !span.content.trim().is_empty()));
+        (!displayable).then_some("(no displayable output)")
+    }
 
-        // Try to position the line in the middle
-        if line_number >= half_viewport {
-            self.log_scroll_offset = line_number - half_viewport;
-        } else {
-            // If line is near the top, just show from the beginning
-            self.log_scroll_offset = 0;
+    /// Plain text of the given source lines as displayed (prefixes/timestamps processed),
+    /// skipping section marker lines
+    pub fn processed_log_text(&self, line_numbers: &[usize]) -> String {
+        let sections = self.displayed_sections();
+        let end_lines: HashSet<usize> = sections.iter().filter_map(|s| s.end_line).collect();
+        let line_count = self.log_line_count();
+        let line_numbers: Vec<usize> = line_numbers
+            .iter()
+            .copied()
+            .filter(|&idx| {
+                !end_lines.contains(&idx) && sections.binary_search_by_key(&idx, |s| s.start_line).is_err()
+            })
+            .filter(|&idx| idx < line_count)
+            .collect();
+        // Lines away from the viewport are processed a block at a time rather than all at once
+        let mut texts = Vec::with_capacity(line_numbers.len());
+        for block in line_numbers.chunk_by(|a, b| a / SEARCH_BATCH_LINES == b / SEARCH_BATCH_LINES) {
+            let processed = self.processed_around(block[0]..block[block.len() - 1] + 1);
+            texts.extend(block.iter().map(|&idx| processed.line_text(idx).into_owned()));
         }
+        texts.join("\n")
+    }
 
-        // Don't scroll past the end
-        let max_offset = total_lines.saturating_sub(self.log_viewport_height);
-        self.log_scroll_offset = self.log_scroll_offset.min(max_offset);
+    fn copy_log_lines(&self, line_numbers: &[usize]) -> Option<Effect> {
+        let text = self.processed_log_text(line_numbers);
+        let line_count = if text.is_empty() { 0 } else { text.lines().count() };
+        Some(Effect::CopyToClipboard { text, line_count })
     }
 
-    pub fn update(&mut self, action: Action) -> Option<Effect> {
-        match action {
-            Action::Quit => {
-                self.should_quit = true;
-                None
-            }
+    /// Apply preferences saved by a previous session
+    pub fn apply_preferences(&mut self, preferences: &crate::config::Preferences) {
+        self.timestamp_mode = preferences.timestamp_mode.clone();
+        self.wrap_enabled = preferences.wrap_enabled;
+        self.follow_by_default = preferences.follow_running_jobs;
+        self.search_history = preferences.search_history.clone();
+        self.show_status_bar = preferences.show_status_bar;
+        self.log_popup_percent = preferences.log_popup_percent.clamp(MIN_LOG_POPUP_PERCENT, 100);
+        let excess = self.search_history.len().saturating_sub(SEARCH_HISTORY_LIMIT);
+        self.search_history.drain(..excess);
+    }
 
-            Action::NextMr => {
-                if !self.tracked_mrs.is_empty() {
-                    self.selected_mr_index = (self.selected_mr_index + 1) % self.tracked_mrs.len();
-                    self.selected_job_index = 0;
-                }
-                None
-            }
+    /// Current viewer choices, saved on exit
+    pub fn preferences(&self) -> crate::config::Preferences {
+        crate::config::Preferences {
+            timestamp_mode: self.timestamp_mode.clone(),
+            wrap_enabled: self.wrap_enabled,
+            follow_running_jobs: self.follow_by_default,
+            search_history: self.search_history.clone(),
+            show_status_bar: self.show_status_bar,
+            log_popup_percent: self.log_popup_percent,
+            // Quitting before the last location was restored keeps it for next time
+            last_location: self.current_location().or_else(|| self.restore_location.clone()),
+        }
+    }
 
-            Action::PrevMr => {
-                if !self.tracked_mrs.is_empty() {
-                    self.selected_mr_index = self
-                        .selected_mr_index
-                        .checked_sub(1)
-                        .unwrap_or(self.tracked_mrs.len() - 1);
-                    self.selected_job_index = 0;
-                }
-                None
+    /// The selected merge request, pipeline and job, to come back to next session
+    fn current_location(&self) -> Option<crate::config::LastLocation> {
+        let mr = self.tracked_mrs.get(self.selected_mr_index)?;
+        let pipeline = self.get_selected_pipeline()?;
+        let job_id = self.selected_job().map(|job| job.id);
+        Some(crate::config::LastLocation {
+            project_id: self.project_id,
+            mr_iid: mr.mr.iid,
+            pipeline_id: pipeline.id,
+            job_id,
+            log_open: self.mode == AppMode::ViewingLog && job_id.is_some() && self.log_job_id == job_id,
+        })
+    }
+
+    /// Select the pipeline of the location being restored once its MR's pipelines loaded,
+    /// fetching its jobs. A pipeline past the first page is asked for by id
+    fn restore_pipeline(&mut self, mr_index: usize) -> Option<Effect> {
+        let location = self.restore_location.as_ref()?;
+        let mr = self.tracked_mrs.get_mut(mr_index).filter(|mr| mr.mr.iid == location.mr_iid)?;
+        let Some(index) = mr.pipelines.iter().position(|p| p.id == location.pipeline_id) else {
+            if self.restore_pipeline_requested {
+                return None; // Its answer is on the way
             }
+            self.restore_pipeline_requested = true;
+            return Some(Effect::FetchPipeline { mr_index, project_id: self.project_id, pipeline_id: location.pipeline_id });
+        };
+        mr.selected_pipeline_index = index;
+        if let Some(job_id) = location.job_id {
+            self.last_selected_jobs.insert(location.pipeline_id, job_id);
+        }
+        Some(Effect::FetchJobs { mr_index, project_id: self.project_id, pipeline_id: location.pipeline_id })
+    }
 
-            Action::NextJob => {
-                if let Some(jobs) = self.get_selected_jobs() {
-                    if !jobs.is_empty() {
-                        self.selected_job_index = (self.selected_job_index + 1) % jobs.len();
-                    }
-                }
+    /// Finish restoring the last location once its pipeline's jobs loaded, reopening its log
+    fn restore_job(&mut self, mr_index: usize, pipeline_id: u64) -> Option<Effect> {
+        if mr_index != self.selected_mr_index || self.restore_location.as_ref()?.pipeline_id != pipeline_id {
+            return None;
+        }
+        let location = self.restore_location.take()?;
+        let selected = self.selected_job().map(|job| job.id);
+        match location.job_id {
+            Some(job_id) if selected != Some(job_id) => {
+                self.set_transient_status(format!("Couldn't restore the last location, job #{} is gone", job_id));
                 None
             }
-
-            Action::PrevJob => {
-                if let Some(jobs) = self.get_selected_jobs() {
-                    if !jobs.is_empty() {
-                        self.selected_job_index = self
-                            .selected_job_index
-                            .checked_sub(1)
-                            .unwrap_or(jobs.len() - 1);
-                    }
-                }
+            _ if location.log_open && self.mode == AppMode::Normal => self.handle_action(Action::OpenSelectedJobLog),
+            _ => {
+                self.set_transient_status(format!("Restored pipeline #{}", pipeline_id));
                 None
             }
+        }
+    }
 
-            Action::NextPipeline => {
-                let mr_index = self.selected_mr_index;
-                let project_id = self.project_id;
-
-                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
-                    if !mr.pipelines.is_empty() {
-                        mr.selected_pipeline_index =
-                            (mr.selected_pipeline_index + 1) % mr.pipelines.len();
+    /// Remember a submitted query as the most recent one
+    fn push_search_history(&mut self, query: &str) {
+        if query.is_empty() {
+            return;
+        }
+        self.search_history.retain(|entry| entry != query);
+        self.search_history.push(query.to_string());
+        let excess = self.search_history.len().saturating_sub(SEARCH_HISTORY_LIMIT);
+        self.search_history.drain(..excess);
+    }
 
-                        // Fetch jobs for this pipeline if we don't have them yet
-                        if let Some(pipeline) = mr.pipelines.get(mr.selected_pipeline_index) {
-                            let pipeline_id = pipeline.id;
-                            let needs_fetch = !mr.jobs.contains_key(&pipeline_id);
+    pub fn is_viewing_comments(&self) -> bool {
+        self.mode == AppMode::ViewingComments
+    }
 
-                            // Drop the borrow so we can modify self
-                            let _ = mr;
-                            self.selected_job_index = 0;
+    /// Load a log into the viewer: process lines, detect sections and reset the view
+    fn set_log_content(&mut self, job_id: u64, content: impl Into<LogBuffer>, job_name: String) {
+        self.log_job_id = Some(job_id);
+        self.log_error = None;
+        self.log_expired = false;
+        self.log_sections.clear();
+        self.collapsed_sections.clear();
+        self.current_error_line = None;
+        self.log_content = Some(content.into());
+        self.reset_processed_lines();
+        self.log_fetch_complete = true;
+        self.log_total_bytes = None;
+        self.log_job_name = Some(job_name);
+        self.log_scroll_offset = 0;
+        self.log_scroll_row = 0;
+        self.horizontal_scroll_offset = 0;
+        self.log_selection = None;
+        self.log_visual_mode = false;
+        self.follow_mode = self.follow_by_default && self.log_job_running;
+        self.update_log_structure();
+        if self.follow_mode {
+            self.scroll_log_to_bottom();
+        }
+        self.update_log_window();
+    }
 
-                            if needs_fetch {
-                                return Some(Effect::FetchJobs {
-                                    mr_index,
-                                    project_id,
-                                    pipeline_id,
-                                });
-                            }
-                        }
-                    }
-                }
+    /// Show the log viewer on a loading state until the first chunk of `job_id` arrives
+    fn begin_log_fetch(&mut self, job_id: u64, job_name: String) {
+        self.log_content = None;
+        self.log_job_id = Some(job_id);
+        self.log_job_name = Some(job_name);
+        self.log_loading_since = Some(Instant::now());
+        self.log_error = None;
+        self.log_expired = false;
+        self.mode = AppMode::ViewingLog;
+    }
 
-                self.selected_job_index = 0;
-                None
-            }
+    /// Show the viewer's expired state for a job whose trace GitLab erased
+    fn show_log_expired(&mut self, job_id: u64, job_name: String) {
+        self.begin_log_fetch(job_id, job_name);
+        self.log_loading_since = None;
+        self.log_fetch_complete = true;
+        self.log_expired = true;
+    }
 
-            Action::PrevPipeline => {
-                let mr_index = self.selected_mr_index;
-                let project_id = self.project_id;
+    /// Whether an empty trace means it was erased: the job ran and is over, so it had output once
+    fn trace_erased(&self, job_id: u64) -> bool {
+        self.get_selected_mr()
+            .and_then(|mr| mr.jobs.values().flatten().find(|job| job.id == job_id))
+            .is_some_and(|job| job.erased_at.is_some() || (job.started_at.is_some() && !job.status.is_active()))
+    }
 
-                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
-                    if !mr.pipelines.is_empty() {
-                        mr.selected_pipeline_index = mr
-                            .selected_pipeline_index
-                            .checked_sub(1)
-                            .unwrap_or(mr.pipelines.len() - 1);
+    /// Exchange the active tab's log state with `tab`
+    fn swap_log_tab_state(&mut self, tab: &mut LogTab) {
+        std::mem::swap(&mut self.log_content, &mut tab.log_content);
+        std::mem::swap(&mut self.log_loading_since, &mut tab.log_loading_since);
+        std::mem::swap(&mut self.log_error, &mut tab.log_error);
+        std::mem::swap(&mut self.log_expired, &mut tab.log_expired);
+        std::mem::swap(&mut self.log_fetch_complete, &mut tab.log_fetch_complete);
+        std::mem::swap(&mut self.log_total_bytes, &mut tab.log_total_bytes);
+        std::mem::swap(&mut self.log_processed_lines, &mut tab.log_processed_lines);
+        std::mem::swap(&mut self.log_lines_key, &mut tab.log_lines_key);
+        std::mem::swap(&mut self.log_sections, &mut tab.log_sections);
+        std::mem::swap(&mut self.collapsed_sections, &mut tab.collapsed_sections);
+        std::mem::swap(&mut self.log_visible_lines, &mut tab.log_visible_lines);
+        std::mem::swap(&mut self.log_error_lines, &mut tab.log_error_lines);
+        std::mem::swap(&mut self.log_warning_lines, &mut tab.log_warning_lines);
+        std::mem::swap(&mut self.log_row_break_lines, &mut tab.log_row_break_lines);
+        std::mem::swap(&mut self.log_started_at, &mut tab.log_started_at);
+        std::mem::swap(&mut self.log_outcome, &mut tab.log_outcome);
+        std::mem::swap(&mut self.current_error_line, &mut tab.current_error_line);
+        std::mem::swap(&mut self.log_scroll_offset, &mut tab.log_scroll_offset);
+        std::mem::swap(&mut self.log_scroll_row, &mut tab.log_scroll_row);
+        std::mem::swap(&mut self.horizontal_scroll_offset, &mut tab.horizontal_scroll_offset);
+        std::mem::swap(&mut self.log_selection, &mut tab.log_selection);
+        std::mem::swap(&mut self.log_visual_mode, &mut tab.log_visual_mode);
+        std::mem::swap(&mut self.log_job_name, &mut tab.log_job_name);
+        std::mem::swap(&mut self.log_job_id, &mut tab.log_job_id);
+        std::mem::swap(&mut self.log_job_running, &mut tab.log_job_running);
+        std::mem::swap(&mut self.follow_mode, &mut tab.follow_mode);
+        std::mem::swap(&mut self.log_poll_in_flight, &mut tab.log_poll_in_flight);
+        std::mem::swap(&mut self.timestamp_mode, &mut tab.timestamp_mode);
+        std::mem::swap(&mut self.raw_log_view, &mut tab.raw_log_view);
+        std::mem::swap(&mut self.search_query, &mut tab.search_query);
+        std::mem::swap(&mut self.search_regex, &mut tab.search_regex);
+        std::mem::swap(&mut self.search_regex_error, &mut tab.search_regex_error);
+        std::mem::swap(&mut self.search_results, &mut tab.search_results);
+        std::mem::swap(&mut self.current_search_result, &mut tab.current_search_result);
+        std::mem::swap(&mut self.search_scan, &mut tab.search_scan);
+        std::mem::swap(&mut self.filter_query, &mut tab.filter_query);
+        std::mem::swap(&mut self.filter_inverse, &mut tab.filter_inverse);
+        std::mem::swap(&mut self.filter_regex, &mut tab.filter_regex);
+        std::mem::swap(&mut self.filtered_line_indices, &mut tab.filtered_line_indices);
+    }
 
-                        // Fetch jobs for this pipeline if we don't have them yet
-                        if let Some(pipeline) = mr.pipelines.get(mr.selected_pipeline_index) {
-                            let pipeline_id = pipeline.id;
-                            let needs_fetch = !mr.jobs.contains_key(&pipeline_id);
+    /// Make the tab at `index` the active one, the previous one's state goes to its slot
+    fn switch_log_tab(&mut self, index: usize) {
+        if index == self.active_log_tab || index >= self.log_tabs.len() {
+            return;
+        }
+        let mut tab = std::mem::take(&mut self.log_tabs[index]);
+        self.swap_log_tab_state(&mut tab);
+        self.log_tabs[self.active_log_tab] = tab;
+        self.active_log_tab = index;
+        self.pending_key = None;
+        self.pending_count = None;
+        // Search steps of a tab in the background were dropped, pick them up again
+        if let Some(scan) = &mut self.search_scan {
+            scan.scheduled = false;
+        }
+    }
 
-                            // Drop the borrow so we can modify self
-                            let _ = mr;
-                            self.selected_job_index = 0;
+    /// Run `f` with the tab at `index` active, then switch back
+    fn in_log_tab<T>(&mut self, index: usize, f: impl FnOnce(&mut Self) -> T) -> T {
+        let previous = self.active_log_tab;
+        self.switch_log_tab(index);
+        let result = f(self);
+        self.switch_log_tab(previous);
+        result
+    }
 
-                            if needs_fetch {
-                                return Some(Effect::FetchJobs {
-                                    mr_index,
-                                    project_id,
-                                    pipeline_id,
-                                });
-                            }
-                        }
-                    }
-                }
+    /// Keep the active log's scroll position, search, filter and timestamp mode for when it is reopened
+    fn save_log_view(&mut self) {
+        let Some(job_id) = self.log_job_id.filter(|_| self.log_content.is_some()) else {
+            return; // Nothing was shown, keep what an earlier viewing left
+        };
+        if self.saved_log_views.len() >= SAVED_LOG_VIEWS_LIMIT && !self.saved_log_views.contains_key(&job_id) {
+            let oldest = self.saved_log_views.iter().min_by_key(|(_, view)| view.saved_at).map(|(&id, _)| id);
+            oldest.and_then(|id| self.saved_log_views.remove(&id));
+        }
+        let view = SavedLogView {
+            top_line: self.log_visible_lines.get(self.log_scroll_offset).copied().unwrap_or(0),
+            top_row: self.log_scroll_row,
+            horizontal_scroll_offset: self.horizontal_scroll_offset,
+            timestamp_mode: self.timestamp_mode.clone(),
+            search_query: self.search_query.clone(),
+            search_mode: self.search_mode,
+            search_whole_word: self.search_whole_word,
+            search_line: self.search_results.get(self.current_search_result).copied(),
+            filter_query: self.filter_regex.as_ref().map_or_else(String::new, |_| self.filter_query.clone()),
+            filter_inverse: self.filter_inverse,
+            saved_at: Instant::now(),
+        };
+        self.saved_log_views.insert(job_id, view);
+    }
 
-                self.selected_job_index = 0;
-                None
+    /// Bring back how the loaded log was left when it was last closed. What was changed since it
+    /// started loading is kept
+    fn restore_log_view(&mut self) {
+        let Some(view) = self.log_job_id.and_then(|job_id| self.saved_log_views.remove(&job_id)) else {
+            return;
+        };
+        if view.timestamp_mode != self.timestamp_mode {
+            self.timestamp_mode = view.timestamp_mode;
+            self.redisplay_log_lines();
+        }
+        if self.filter_regex.is_none() && !view.filter_query.is_empty() {
+            if let Ok(regex) = Regex::new(&view.filter_query) {
+                self.filter_query = view.filter_query;
+                self.filter_inverse = view.filter_inverse;
+                self.filter_regex = Some(regex);
+                self.build_filtered_lines();
+                self.rebuild_visible_lines();
             }
+        }
+        if self.search_query.is_empty() && !view.search_query.is_empty() {
+            self.search_query = view.search_query;
+            self.search_mode = view.search_mode;
+            self.search_whole_word = view.search_whole_word;
+            self.build_search_results();
+            self.current_search_result = 0;
+            // The match to return to may be past the first step of the scan, it is selected once reached
+            let line = view.search_line.unwrap_or(0);
+            match &mut self.search_scan {
+                Some(scan) if scan.next_line <= line => scan.select_line = Some(line),
+                _ => self.select_search_line(line),
+            }
+        }
+        if !self.follow_mode && (self.log_scroll_offset, self.log_scroll_row) == (0, 0) {
+            let top = self.log_visible_lines.partition_point(|&idx| idx < view.top_line);
+            (self.log_scroll_offset, self.log_scroll_row) = (top, view.top_row).min(self.max_log_scroll_position());
+            self.horizontal_scroll_offset = view.horizontal_scroll_offset;
+        }
+    }
 
-            Action::OpenSelectedJobLog => {
-                let job_info = self.get_selected_jobs()
-                    .and_then(|jobs| jobs.get(self.selected_job_index))
-                    .map(|job| (job.name.clone(), job.id));
+    /// Add a tab for a log about to be opened and make it active. The new tab starts out empty
+    /// with the timestamp mode of the one it was opened from
+    fn open_log_tab(&mut self) {
+        if !self.log_tabs.is_empty() {
+            let mut tab = LogTab { timestamp_mode: self.timestamp_mode.clone(), ..LogTab::default() };
+            self.swap_log_tab_state(&mut tab);
+            self.log_tabs[self.active_log_tab] = tab;
+        }
+        self.log_tabs.push(LogTab::default());
+        self.active_log_tab = self.log_tabs.len() - 1;
+    }
 
-                if let Some((job_name, job_id)) = job_info {
-                    // Check if log is already cached
-                    if let Some(mr) = self.tracked_mrs.get(self.selected_mr_index) {
-                        if let Some(cached_log) = mr.job_logs_cache.get(&job_id) {
-                            // Use cached log
-                            self.status_message = None;
-                            self.log_processed_lines = crate::log_processor::process_log_content(cached_log, &self.timestamp_mode);
-                            self.log_content = Some(cached_log.clone());
-                            self.log_job_name = Some(job_name);
-                            self.log_scroll_offset = 0;
-                            self.mode = AppMode::ViewingLog;
-                            return None;
-                        }
-                    }
+    /// Tab showing the log of `job_id`, if one is open
+    fn log_tab_of_job(&self, job_id: u64) -> Option<usize> {
+        (0..self.log_tabs.len()).find(|&index| self.log_tab_job_id(index) == Some(job_id))
+    }
 
-                    // Not cached, fetch from API
-                    self.status_message = Some(format!("Fetching log for job '{}'...", job_name));
-                    return Some(Effect::FetchJobTrace {
-                        project_id: self.project_id,
-                        job_id,
-                        job_name,
-                    });
-                }
-                None
-            }
+    fn log_tab_job_id(&self, index: usize) -> Option<u64> {
+        if index == self.active_log_tab {
+            self.log_job_id
+        } else {
+            self.log_tabs.get(index)?.log_job_id
+        }
+    }
 
-            Action::Refresh => {
-                // Reset auto-refresh timer on manual refresh
-                self.last_auto_refresh = Instant::now();
+    /// Job names of the open log tabs, in order
+    pub fn log_tab_names(&self) -> Vec<&str> {
+        (0..self.log_tabs.len())
+            .map(|index| {
+                let name = if index == self.active_log_tab {
+                    &self.log_job_name
+                } else {
+                    &self.log_tabs[index].log_job_name
+                };
+                name.as_deref().unwrap_or("log")
+            })
+            .collect()
+    }
 
-                // Set flag to refetch notes after refresh if currently viewing comments
-                self.refetch_notes_after_refresh = self.mode == AppMode::ViewingComments;
+    /// The log viewer is shown, or hidden behind the job list with its tabs kept open
+    fn log_viewer_open(&self) -> bool {
+        self.mode == AppMode::ViewingLog || !self.log_tabs.is_empty()
+    }
 
-                // Save the currently selected note ID if viewing comments
-                if self.refetch_notes_after_refresh {
-                    self.selected_note_id_before_refresh = self.get_selected_note_id();
-                }
+    /// Tab in the background a download, poll or search step belongs to
+    fn background_log_tab(&self, action: &Action) -> Option<usize> {
+        let (Action::JobTraceChunkLoaded { job_id, .. }
+        | Action::JobTraceFailed { job_id, .. }
+        | Action::JobTraceExpired { job_id }
+        | Action::JobTraceRefreshed { job_id, .. }
+        | Action::JobTraceStreamed { job_id, .. }
+        | Action::JobTracePollFailed { job_id, .. }) = action
+        else {
+            return match action {
+                Action::ContinueSearch { generation } => (0..self.log_tabs.len()).find(|&index| {
+                    index != self.active_log_tab
+                        && self.log_tabs[index].search_scan.as_ref().is_some_and(|scan| scan.generation == *generation)
+                }),
+                _ => None,
+            };
+        };
+        self.log_tab_of_job(*job_id).filter(|&index| index != self.active_log_tab)
+    }
 
-                // Clear all cached data including notes and job logs
-                for mr in &mut self.tracked_mrs {
-                    mr.notes_loaded = false;
-                    mr.notes.clear();
-                    mr.job_logs_cache.clear();
-                }
+    /// Make another profile the active one, dropping everything loaded from the previous
+    /// instance, then load the project there
+    fn switch_profile(&mut self, index: usize) -> Option<Effect> {
+        let profile = self.profiles.get(index)?.clone();
+        self.active_profile = index;
+        self.profile_generation += 1;
+        self.clear_project_state();
+        // Nothing sent to the previous instance comes back, including the next poll and download progress
+        self.live_poll_scheduled = false;
+        if let Some(download) = self.artifact_download.take() {
+            download.cancel.store(true, Ordering::Relaxed);
+        }
 
-                self.status_message = Some("Refreshing...".to_string());
-                Some(Effect::RefreshAll {
-                    project_id: self.project_id,
-                    source_branch: if self.focus_current_branch {
-                        self.current_branch.clone()
-                    } else {
-                        None
-                    },
-                })
+        self.instance_host = profile.host();
+        self.username = None;
+        self.project_path = None;
+        self.status_message = Some(format!("Switched to profile '{}' ({})", profile.name, self.instance_host));
+        match profile.default_project_id {
+            Some(project_id) => {
+                self.handle_action(Action::ProjectResolved { profile: index, project_id, project_path: None })
             }
+            None => Some(Effect::ResolveProject { profile: index }),
+        }
+    }
 
-            Action::RemoveCurrentMr => {
-                if !self.tracked_mrs.is_empty() {
-                    self.tracked_mrs.remove(self.selected_mr_index);
-                    if self.selected_mr_index > 0 {
-                        self.selected_mr_index -= 1;
-                    }
-                    self.selected_job_index = 0;
-                }
-                None
-            }
+    /// Drop everything loaded for the current project, before loading another one
+    fn clear_project_state(&mut self) {
+        self.log_tabs.clear();
+        self.handle_action(Action::CloseLogViewer);
+        self.saved_log_views.clear();
+        self.log_bookmarks.clear();
+        self.log_diff = None;
+        self.compare_job = None;
+        self.tracked_mrs.clear();
+        self.selected_mr_index = 0;
+        self.selected_job_index = 0;
+        self.last_selected_jobs.clear();
+        self.job_filter = None;
+        self.stage_filter = None;
+        self.pipeline_status_filter = None;
+        self.error_message = None;
+        self.rate_limited_until = None;
+        self.offline_cached_at = None;
+        self.restore_location = None;
+        self.pending_pipeline_focus = None;
+        self.pipeline_retry = None;
+        self.mode = AppMode::Normal;
+    }
 
-            Action::MergeRequestsLoaded(mrs) => {
-                // Initialize tracked MRs with the loaded data
-                for mr in mrs {
-                    if !self.tracked_mrs.iter().any(|tmr| tmr.mr.iid == mr.iid) {
-                        let tracked_mr = TrackedMergeRequest {
-                            mr: mr.clone(),
-                            pipelines: Vec::new(),
-                            jobs: HashMap::new(),
-                            job_logs_cache: HashMap::new(),
-                            notes: Vec::new(),
-                            notes_loaded: false,
-                            selected_pipeline_index: 0,
-                            selected_note_index: 0,
-                            loading: true,
-                            error: None,
-                        };
-                        self.tracked_mrs.push(tracked_mr);
-                    }
-                }
+    /// The artifact file being shown, if it is still this one
+    fn open_artifact_file(&mut self, job_id: u64, path: &str) -> Option<&mut ArtifactFileView> {
+        let browser = self.artifact_browser.as_mut().filter(|b| b.job_id == job_id)?;
+        browser.file.as_mut().filter(|file| file.path == path)
+    }
 
-                self.status_message = Some(format!("Loaded {} merge requests", self.tracked_mrs.len()));
+    /// The tracked MR a pipeline ran for, by its merge request ref or source branch
+    fn tracked_mr_of(&self, pipeline: &Pipeline) -> Option<usize> {
+        self.tracked_mrs.iter().position(|tmr| {
+            tmr.pipelines.iter().any(|p| p.id == pipeline.id)
+                || pipeline.merge_request_iid() == Some(tmr.mr.iid)
+                || (!tmr.mr.source_branch.is_empty() && tmr.mr.source_branch == pipeline.ref_name)
+        })
+    }
 
-                // Fetch pipelines for each MR
-                let effects: Vec<Effect> = self
-                    .tracked_mrs
-                    .iter()
-                    .enumerate()
-                    .map(|(index, tmr)| Effect::FetchPipelines {
-                        mr_index: index,
-                        project_id: self.project_id,
-                        mr_iid: tmr.mr.iid,
-                    })
-                    .collect();
+    /// Group browsed with G: the configured one, or the namespace of the current project
+    pub fn group_path(&self) -> Option<String> {
+        self.group.clone().or_else(|| {
+            let path = self.project_path.as_deref()?;
+            Some(path.rsplit_once('/')?.0.to_string())
+        })
+    }
 
-                // Return the first effect; in a real implementation, we'd handle multiple
-                effects.into_iter().next()
-            }
+    /// Show a pipeline from the group view in its project's view, switching to that project when
+    /// it isn't the current one; the pipeline is selected once its MR is listed
+    fn open_group_pipeline(&mut self, row: GroupPipeline) -> Option<Effect> {
+        let GroupPipeline { project_id, project_path, pipeline } = row;
+        if project_id == self.project_id {
+            return match self.tracked_mr_of(&pipeline) {
+                Some(mr_index) => self.focus_pipeline(mr_index, pipeline),
+                None => {
+                    self.set_transient_status(format!(
+                        "No tracked merge request runs pipeline #{}, opened it in the browser",
+                        pipeline.iid
+                    ));
+                    Some(Effect::OpenUrl(pipeline.web_url))
+                }
+            };
+        }
 
-            Action::PipelinesLoaded { mr_index, pipelines } => {
-                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
-                    mr.pipelines = pipelines;
-                    mr.loading = false;
-
-                    // Check if we need to refetch notes after refresh (only for selected MR)
-                    if self.refetch_notes_after_refresh && mr_index == self.selected_mr_index {
-                        self.refetch_notes_after_refresh = false;
-                        self.status_message = Some("Reloading comments...".to_string());
-                        return Some(Effect::FetchNotes {
-                            mr_index,
-                            project_id: self.project_id,
-                            mr_iid: mr.mr.iid,
-                        });
-                    }
+        self.clear_project_state();
+        // The local branch belongs to another repository
+        self.focus_current_branch = false;
+        self.status_message = Some(format!("Opening pipeline #{} of {}", pipeline.iid, project_path));
+        self.pending_pipeline_focus = Some(pipeline);
+        let profile = self.active_profile;
+        self.handle_action(Action::ProjectResolved { profile, project_id, project_path: Some(project_path) })
+    }
 
-                    // Fetch jobs for the latest pipeline
-                    if let Some(pipeline) = mr.pipelines.first() {
-                        return Some(Effect::FetchJobs {
-                            mr_index,
-                            project_id: self.project_id,
-                            pipeline_id: pipeline.id,
-                        });
-                    }
-                }
-                None
-            }
+    /// Select the pipeline opened from the group view once its MR's pipelines loaded
+    fn focus_pending_pipeline(&mut self, mr_index: usize) -> Option<Effect> {
+        let pipeline = self.pending_pipeline_focus.as_ref()?;
+        if mr_index != self.selected_mr_index || self.tracked_mr_of(pipeline) != Some(mr_index) {
+            return None;
+        }
+        let pipeline = self.pending_pipeline_focus.take()?;
+        self.set_transient_status(format!("Showing pipeline #{}", pipeline.iid));
+        self.focus_pipeline(mr_index, pipeline)
+    }
 
-            Action::JobsLoaded {
-                mr_index,
-                pipeline_id,
-                mut jobs,
-            } => {
-                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
-                    // Sort jobs: failed first, then running, pending, etc.
-                    jobs.sort_by_key(|job| match job.status {
-                        JobStatus::Failed => 0,
-                        JobStatus::Running => 1,
-                        JobStatus::Pending => 2,
-                        JobStatus::Canceled => 3,
-                        JobStatus::Created => 4,
-                        JobStatus::Manual => 5,
-                        JobStatus::Success => 6,
-                        JobStatus::Skipped => 7,
-                    });
-                    mr.jobs.insert(pipeline_id, jobs);
-                }
-                self.last_refresh = Some(chrono::Utc::now());
-                None
+    /// Select a pipeline in its MR's view, adding it first if it isn't listed yet, and load its jobs
+    fn focus_pipeline(&mut self, mr_index: usize, pipeline: Pipeline) -> Option<Effect> {
+        let pipeline_id = pipeline.id;
+        let mr = &mut self.tracked_mrs[mr_index];
+        if !mr.pipelines.iter().any(|p| p.id == pipeline_id) {
+            mr.pipelines.insert(0, pipeline);
+            if self.pipeline_sort != PipelineSort::Newest {
+                sort_pipelines(mr, self.pipeline_sort);
             }
+        }
+        mr.selected_pipeline_index = mr.pipelines.iter().position(|p| p.id == pipeline_id).unwrap_or(0);
+        self.selected_mr_index = mr_index;
+        self.selected_job_index = 0;
+        if self.mode == AppMode::ViewingComments {
+            self.mode = AppMode::Normal;
+        }
+        let effect = Some(Effect::FetchJobs { mr_index, project_id: self.project_id, pipeline_id });
+        self.with_live_poll(effect)
+    }
 
-            Action::JobTraceLoaded { job_id, job_name, trace } => {
-                self.status_message = None;
+    /// Move the comparison's top row, given the current one and the page height, within bounds
+    fn scroll_diff_to(&mut self, target: impl FnOnce(usize, usize) -> usize) -> Option<Effect> {
+        let page = self.log_viewport_height.max(1);
+        let diff = self.log_diff.as_mut()?;
+        let max_scroll = diff.row_count().saturating_sub(page);
+        diff.scroll = target(diff.scroll, page).min(max_scroll);
+        None
+    }
 
-                // Cache the log in the current MR
-                if let Some(mr) = self.tracked_mrs.get_mut(self.selected_mr_index) {
-                    mr.job_logs_cache.insert(job_id, trace.clone());
+    /// Replace the log with a newer version of the same trace, keeping folds, search and scroll
+    fn refresh_log_content(&mut self, content: impl Into<LogBuffer>) {
+        self.log_content = Some(content.into());
+        self.reset_processed_lines();
+        self.update_log_structure();
+        self.after_log_growth(0);
+    }
+
+    /// Append a downloaded chunk of the trace, scanning only the lines it touches
+    fn append_log_chunk(&mut self, chunk: &TraceChunk) {
+        let Some(buffer) = self.log_content.as_mut() else {
+            return;
+        };
+
+        // The previous chunk may have ended mid-line, in which case that line is redone
+        let line_count = buffer.line_count();
+        let reprocess_from = if buffer.ends_with_newline() { line_count } else { line_count - 1 };
+
+        buffer.append_bytes(&chunk.data);
+        // A running job's trace ends wherever its output got to, maybe mid-character
+        if chunk.complete && !self.log_job_running {
+            buffer.flush_pending();
+        }
+
+        // New lines are processed once the window around the viewport reaches them
+        self.log_processed_lines.truncate(reprocess_from);
+        self.log_lines_key = Some((buffer.content_hash(), self.timestamp_mode.clone(), self.raw_log_view));
+        self.log_total_bytes = chunk.total_size;
+        self.log_fetch_complete = chunk.complete;
+
+        self.update_log_structure_from(reprocess_from);
+        self.after_log_growth(reprocess_from);
+    }
+
+    /// Drop the processed lines unless they were built from this content and mode, the lines
+    /// around the viewport are processed again by the next `update_log_window`
+    fn reset_processed_lines(&mut self) {
+        let Some(buffer) = &self.log_content else {
+            return;
+        };
+        let key = (buffer.content_hash(), self.timestamp_mode.clone(), self.raw_log_view);
+        if self.log_lines_key.as_ref() == Some(&key) {
+            return;
+        }
+        self.log_processed_lines = ProcessedLines::default();
+        self.log_lines_key = Some(key);
+    }
+
+    /// Process source lines of the log for display
+    fn process_log_range(&self, lines: std::ops::Range<usize>) -> ProcessedLines {
+        let Some(buffer) = &self.log_content else {
+            return ProcessedLines::default();
+        };
+        let text = buffer.text(lines.clone());
+        let processed = if self.raw_log_view {
+            crate::log_processor::raw_log_content(&text)
+        } else {
+            // Relative timestamps are measured from the start of the whole log, not these lines
+            let start = self.log_started_at.map(|(_, at)| at);
+            crate::log_processor::process_log_content_from(&text, &self.timestamp_mode, start)
+        };
+        processed.starting_at(lines.start)
+    }
+
+    /// Keep the lines around the viewport processed and their text in memory, letting go of the
+    /// rest. Lines further away are processed again whenever they are needed
+    fn update_log_window(&mut self) {
+        let line_count = self.log_line_count();
+        let viewport = self.log_viewport_lines();
+        let first = self.log_visible_lines.get(viewport.start).copied().unwrap_or(0).min(line_count);
+        let last = viewport.end.checked_sub(1).and_then(|offset| self.log_visible_lines.get(offset));
+        let shown = first..last.map_or(first, |&line| line + 1).min(line_count);
+        let processed = self.log_processed_lines.lines();
+        if processed.start <= shown.start && shown.end <= processed.end {
+            return;
+        }
+
+        let window = first.saturating_sub(LOG_WINDOW_MARGIN)..(shown.end + LOG_WINDOW_MARGIN).min(line_count);
+        if let Some(buffer) = self.log_content.as_mut() {
+            buffer.keep_resident(window.clone());
+        }
+        if self.log_processed_lines.is_empty() || !(processed.start..=processed.end).contains(&window.start) {
+            self.log_processed_lines = self.process_log_range(window);
+            return;
+        }
+        // Moving down, e.g. following new output, only lines below the window are processed
+        self.log_processed_lines.drain_before(window.start);
+        self.log_processed_lines.truncate(window.end);
+        let more = self.process_log_range(self.log_processed_lines.lines().end..window.end);
+        self.log_processed_lines.append(more);
+    }
+
+    /// Number of source lines of the log
+    pub fn log_line_count(&self) -> usize {
+        self.log_content.as_ref().map_or(0, LogBuffer::line_count)
+    }
+
+    /// Rendered rows of a source line, processed now when it is away from the viewport
+    pub fn log_line_rendered_rows(&self, line_number: usize) -> Cow<'_, [ratatui::text::Line<'static>]> {
+        if self.log_processed_lines.lines().contains(&line_number) {
+            return Cow::Borrowed(self.log_processed_lines.line_rows(line_number));
+        }
+        Cow::Owned(self.process_log_range(line_number..line_number + 1).into_rows())
+    }
+
+    /// Rebuild the processed lines after how they are shown changed, and the filter and search
+    /// matches, which are found in the displayed text
+    fn redisplay_log_lines(&mut self) {
+        self.reset_processed_lines();
+        self.build_filtered_lines();
+        self.rebuild_visible_lines();
+        if self.search_regex.is_some() {
+            self.search_from(0);
+            self.current_search_result = self.current_search_result.min(self.search_results.len().saturating_sub(1));
+        }
+    }
+
+    /// Download progress of the current log in percent, while chunks are still arriving
+    pub fn log_load_progress(&self) -> Option<u8> {
+        if self.log_fetch_complete {
+            return None;
+        }
+        let received = self.log_content.as_ref().map_or(0, |b| b.len_bytes()) as u64;
+        let percent = match self.log_total_bytes {
+            Some(total) if total > 0 => (received * 100 / total).min(99),
+            _ => 0,
+        };
+        Some(percent as u8)
+    }
+
+    /// Re-detect sections and error lines after the log content changed
+    fn update_log_structure(&mut self) {
+        self.update_log_structure_from(0);
+    }
+
+    /// Update sections, error and warning lines for lines from `changed_from` on, keeping what was
+    /// found before them, so a log streaming in chunks isn't rescanned from the start each time
+    fn update_log_structure_from(&mut self, changed_from: usize) {
+        let Some(buffer) = &self.log_content else {
+            return;
+        };
+
+        // Newly appeared sections get their default fold state, known ones keep the user's choice.
+        // A start marker cut off by a chunk boundary is only known once its options arrived
+        let known: HashSet<(String, bool)> =
+            self.log_sections.iter().map(|s| (s.name.clone(), s.collapsed_by_default)).collect();
+        self.log_sections.retain(|section| section.start_line < changed_from);
+        for section in &mut self.log_sections {
+            if section.end_line.is_some_and(|end| end >= changed_from) {
+                section.end_line = None;
+                section.end_time = None;
+            }
+        }
+        let kept_sections = self.log_sections.len();
+        for lines in [&mut self.log_error_lines, &mut self.log_warning_lines, &mut self.log_row_break_lines] {
+            lines.truncate(lines.partition_point(|&idx| idx < changed_from));
+        }
+        if self.log_started_at.is_some_and(|(line, _)| line >= changed_from) {
+            self.log_started_at = None;
+        }
+
+        // Scanned a block of lines at a time, a log too big for memory is read back from its file
+        let line_count = buffer.line_count();
+        for first in (changed_from..line_count).step_by(STRUCTURE_SCAN_LINES) {
+            let text = buffer.text(first..(first + STRUCTURE_SCAN_LINES).min(line_count));
+            crate::log_processor::extend_sections(&mut self.log_sections, &text, first);
+            let at = |lines: Vec<usize>| lines.into_iter().map(move |idx| first + idx);
+            self.log_error_lines.extend(at(crate::log_processor::detect_error_lines(&text)));
+            self.log_warning_lines.extend(at(crate::log_processor::detect_warning_lines(&text)));
+            self.log_row_break_lines.extend(at(crate::log_processor::detect_row_break_lines(&text)));
+            if self.log_started_at.is_none() {
+                self.log_started_at = text.lines().enumerate().find_map(|(idx, line)| {
+                    crate::log_processor::log_start_time(line).map(|at| (first + idx, at))
+                });
+            }
+        }
+        for section in &self.log_sections[kept_sections..] {
+            if section.collapsed_by_default && !known.contains(&(section.name.clone(), true)) {
+                self.collapsed_sections.insert(section.name.clone());
+            }
+        }
+
+        // The runner's status line is near the end, only the last lines need looking at
+        let tail_from = line_count.saturating_sub(crate::log_processor::OUTCOME_SEARCH_LINES);
+        self.log_outcome = crate::log_processor::detect_job_outcome(&buffer.text(tail_from..line_count))
+            .map(|outcome| JobOutcome { line: tail_from + outcome.line, ..outcome });
+        self.build_filtered_lines();
+        self.rebuild_visible_lines();
+    }
+
+    /// Keep search results and the follow position current after lines from `changed_from` on changed
+    fn after_log_growth(&mut self, changed_from: usize) {
+        if self.search_regex.is_some() {
+            self.search_from(changed_from);
+            self.current_search_result = self.current_search_result.min(self.search_results.len().saturating_sub(1));
+        }
+
+        if self.follow_mode {
+            self.scroll_log_to_bottom();
+        }
+    }
+
+    /// Width of the gutter in front of log lines: line numbers when enabled, then the error marker
+    pub fn log_gutter_width(&self) -> usize {
+        let numbers = if self.show_line_numbers {
+            self.log_line_count().max(1).to_string().len() + 1
+        } else {
+            0
+        };
+        let marker = if self.log_error_lines.is_empty() { 0 } else { 1 };
+        let bookmark = if self.current_bookmarks().is_some() { 1 } else { 0 };
+        numbers + marker + bookmark
+    }
+
+    /// Columns log text wraps at, None when wrapping is off or the viewer was never laid out
+    pub fn log_wrap_width(&self) -> Option<usize> {
+        let area = self.log_content_area.filter(|_| self.wrap_enabled)?;
+        Some((area.width as usize).saturating_sub(self.log_gutter_width()).max(1))
+    }
+
+    /// Screen rows a source line takes: one per rendered row, more where wrapping splits them
+    /// (section headers take a single row)
+    pub fn log_line_rows(&self, line_number: usize) -> usize {
+        if self.displayed_sections().binary_search_by_key(&line_number, |s| s.start_line).is_ok() {
+            return 1;
+        }
+        let rows = self.log_line_rendered_rows(line_number);
+        let rows = match self.log_wrap_width() {
+            Some(width) => rows.iter().map(|row| crate::log_processor::wrapped_rows(row, width)).sum(),
+            None => rows.len(),
+        };
+        rows.max(1)
+    }
+
+    /// Rows of the visible line at `offset` (an index into log_visible_lines)
+    fn visible_line_rows(&self, offset: usize) -> usize {
+        let separator = self.starts_filter_group(offset) as usize;
+        self.log_visible_lines.get(offset).map_or(1, |&line| self.log_line_rows(line) + separator)
+    }
+
+    /// Whether a `--` row goes above the visible line at `offset`, where a filter with context
+    /// skipped lines since the previous group
+    pub fn starts_filter_group(&self, offset: usize) -> bool {
+        let (Some(filtered), Some(&line)) = (&self.filtered_line_indices, self.log_visible_lines.get(offset)) else {
+            return false;
+        };
+        if self.filter_context == 0 || offset == 0 {
+            return false;
+        }
+        let position = filtered.partition_point(|&idx| idx < line);
+        position > 0 && filtered[position - 1] + 1 != line
+    }
+
+    /// Whether every visible line takes exactly one row, so scrolling can skip counting rows
+    fn rows_are_lines(&self) -> bool {
+        self.log_wrap_width().is_none()
+            && self.log_row_break_lines.is_empty()
+            && (self.filter_context == 0 || self.filtered_line_indices.is_none())
+    }
+
+    /// Rows of the top line hidden above the viewport, ignoring a row left over from another line
+    pub fn log_top_rows_skipped(&self) -> usize {
+        self.log_scroll_row.min(self.visible_line_rows(self.log_scroll_offset).saturating_sub(1))
+    }
+
+    /// Last scroll position (offset, rows skipped in the top line), where the last row of the
+    /// log sits at the bottom of the viewport
+    pub fn max_log_scroll_position(&self) -> (usize, usize) {
+        if self.rows_are_lines() {
+            return (self.log_visible_lines.len().saturating_sub(self.log_viewport_height), 0);
+        }
+        let mut remaining = self.log_viewport_height.max(1);
+        for offset in (0..self.log_visible_lines.len()).rev() {
+            let rows = self.visible_line_rows(offset);
+            if rows >= remaining {
+                return (offset, rows - remaining);
+            }
+            remaining -= rows;
+        }
+        (0, 0)
+    }
+
+    /// Largest scroll offset, where the last line sits at the bottom of the viewport
+    pub fn max_log_scroll_offset(&self) -> usize {
+        self.max_log_scroll_position().0
+    }
+
+    /// Visible lines (indices into log_visible_lines) that fill the viewport from the scroll position
+    pub fn log_viewport_lines(&self) -> std::ops::Range<usize> {
+        let start = self.log_scroll_offset.min(self.log_visible_lines.len());
+        let mut rows = 0;
+        let mut end = start;
+        while end < self.log_visible_lines.len() && rows < self.log_viewport_height {
+            rows += self.visible_line_rows(end) - if end == start { self.log_top_rows_skipped() } else { 0 };
+            end += 1;
+        }
+        start..end
+    }
+
+    /// Apply a change to how lines are laid out (their length or wrapping) while the line at
+    /// the top of the viewport stays there, or the view stays at the bottom when following
+    fn keep_top_log_line(&mut self, change: impl FnOnce(&mut Self)) {
+        let top_line = self.log_visible_lines.get(self.log_scroll_offset).copied();
+        change(self);
+        if self.follow_mode {
+            self.scroll_log_to_bottom();
+            return;
+        }
+        if let Some(top_line) = top_line {
+            self.log_scroll_offset = self.log_visible_lines.partition_point(|&idx| idx < top_line);
+        }
+        // The rows of the top line changed, start from its first one
+        self.log_scroll_row = 0;
+    }
+
+    /// Lay the log viewer out in a new screen area, e.g. after a terminal resize. The line at the
+    /// top stays there, and the scroll position is clamped so the view never runs past the end
+    pub fn set_log_layout(&mut self, area: ratatui::layout::Rect) {
+        if self.log_content_area == Some(area) {
+            return;
+        }
+        self.keep_top_log_line(|app| {
+            app.log_content_area = Some(area);
+            app.log_viewport_height = (area.height as usize).max(1);
+        });
+        let max_position = self.max_log_scroll_position();
+        if (self.log_scroll_offset, self.log_scroll_row) > max_position {
+            (self.log_scroll_offset, self.log_scroll_row) = max_position;
+        }
+    }
+
+    /// Scroll so the last line sits at the bottom of the viewport
+    fn scroll_log_to_bottom(&mut self) {
+        (self.log_scroll_offset, self.log_scroll_row) = self.max_log_scroll_position();
+    }
+
+    /// Move the end of the visual selection by `delta` displayed lines and scroll it into view
+    fn move_visual_selection(&mut self, delta: isize) {
+        let Some((anchor, end)) = self.log_selection else {
+            return;
+        };
+        let Some(last) = self.log_visible_lines.len().checked_sub(1) else {
+            return;
+        };
+        // The end may have been folded or filtered away, continue from where it would be
+        let position = self.log_visible_lines.partition_point(|&line| line < end).min(last);
+        let position = position.saturating_add_signed(delta).min(last);
+        self.log_selection = Some((anchor, self.log_visible_lines[position]));
+
+        if position < self.log_scroll_offset {
+            (self.log_scroll_offset, self.log_scroll_row) = (position, 0);
+        }
+        while !self.log_viewport_lines().contains(&position) && self.log_scroll_offset < position {
+            (self.log_scroll_offset, self.log_scroll_row) = (self.log_scroll_offset + 1, 0);
+        }
+    }
+
+    /// Source line shown on a row of the log viewport; a wrapped line covers several rows
+    fn log_line_at_row(&self, row: usize) -> Option<usize> {
+        let mut rows_left = row + self.log_top_rows_skipped();
+        let mut offset = self.log_scroll_offset.min(self.max_log_scroll_offset());
+        while rows_left >= self.visible_line_rows(offset) && offset < self.log_visible_lines.len() {
+            rows_left -= self.visible_line_rows(offset);
+            offset += 1;
+        }
+        self.log_visible_lines.get(offset).copied()
+    }
+
+    /// Open the first link found on these lines, in order
+    fn open_log_url(&mut self, line_numbers: &[usize]) -> Option<Effect> {
+        let url = line_numbers.iter().find_map(|&line_number| {
+            let text = self.processed_log_text(&[line_number]);
+            crate::log_processor::find_urls(&text).first().map(|url| url.to_string())
+        });
+        match url {
+            Some(url) => {
+                self.set_transient_status(format!("Opening {}", url));
+                Some(Effect::OpenUrl(url))
+            }
+            None => {
+                self.set_transient_status("No link found".to_string());
+                None
+            }
+        }
+    }
+
+    /// Whether a source line is inside the mouse selection
+    pub fn is_log_line_selected(&self, line_number: usize) -> bool {
+        self.log_selection.is_some_and(|(anchor, end)| {
+            anchor.min(end) <= line_number && line_number <= anchor.max(end)
+        })
+    }
+
+    /// Whether any loaded pipeline or job is still running, pending or created
+    pub fn has_active_pipelines(&self) -> bool {
+        self.tracked_mrs.iter().any(|mr| {
+            mr.pipelines.iter().any(|p| p.status.is_active())
+                || mr.jobs.values().flatten().any(|job| job.status.is_active())
+        })
+    }
+
+    /// Start the live polling loop if something is running and no poll is pending
+    fn with_live_poll(&mut self, effect: Option<Effect>) -> Option<Effect> {
+        let Some(delay) = self.live_refresh_interval else {
+            return effect;
+        };
+        if self.live_poll_scheduled || !self.has_active_pipelines() {
+            return effect;
+        }
+        self.live_poll_scheduled = true;
+        Effect::batch(effect.into_iter().chain([Effect::ScheduleLivePoll { delay }]).collect())
+    }
+
+    /// Drop the loaded pipelines and fetch them again with the new status filter
+    fn set_pipeline_status_filter(&mut self, filter: Option<PipelineStatus>) -> Option<Effect> {
+        if filter == self.pipeline_status_filter {
+            return None;
+        }
+        self.pipeline_status_filter = filter;
+        self.selected_job_index = 0;
+        for mr in &mut self.tracked_mrs {
+            mr.pipelines.clear();
+            mr.pipelines_next_page = None;
+            mr.loading_more_pipelines = false;
+            mr.selected_pipeline_index = 0;
+            mr.loading = true;
+        }
+        let effects = self
+            .tracked_mrs
+            .iter()
+            .enumerate()
+            .map(|(mr_index, mr)| Effect::FetchPipelines {
+                mr_index,
+                project_id: self.project_id,
+                mr_iid: mr.mr.iid,
+                page: 1,
+                status: self.pipeline_status_filter.clone(),
+            })
+            .collect();
+        Effect::batch(effects)
+    }
+
+    fn remember_selected_job(&mut self) {
+        if let (Some(pipeline), Some(job)) = (self.get_selected_pipeline(), self.selected_job()) {
+            let (pipeline_id, job_id) = (pipeline.id, job.id);
+            self.last_selected_jobs.insert(pipeline_id, job_id);
+        }
+    }
+
+    /// Select the job last selected in the current pipeline, or the first one if it is gone.
+    /// A failed pipeline opened for the first time gets its failed job selected instead,
+    /// and its log opened, when `failed_pipeline_jump` asks for it
+    fn restore_job_selection(&mut self) -> Option<Effect> {
+        let pipeline = self.get_selected_pipeline().map(|p| (p.id, p.status.clone()));
+        let first_visit = pipeline.as_ref().is_some_and(|(id, _)| !self.last_selected_jobs.contains_key(id));
+        let failed = pipeline.as_ref().is_some_and(|(_, status)| *status == PipelineStatus::Failed);
+        if first_visit && failed && self.failed_pipeline_jump != FailedPipelineJump::Off && self.select_first_failed_job() {
+            if self.failed_pipeline_jump == FailedPipelineJump::OpenLog && self.mode == AppMode::Normal {
+                return self.handle_action(Action::OpenSelectedJobLog);
+            }
+            return None;
+        }
+
+        let remembered = pipeline.and_then(|(id, _)| self.last_selected_jobs.get(&id));
+        self.selected_job_index = remembered
+            .and_then(|&job_id| self.visible_jobs().iter().position(|job| job.id == job_id))
+            .unwrap_or(0);
+        None
+    }
+
+    /// Select the failed job of the earliest stage, unfolding its stage; false if none failed
+    fn select_first_failed_job(&mut self) -> bool {
+        let Some(job) = self
+            .job_stage_groups()
+            .into_iter()
+            .flat_map(|group| group.jobs)
+            .find(|job| job.status == JobStatus::Failed)
+            .map(|job| job.id)
+        else {
+            return false;
+        };
+        self.select_job(job)
+    }
+
+    /// Select a job in the job list, unfolding its stage and dropping filters that hide it
+    fn select_job(&mut self, job_id: u64) -> bool {
+        let job = self.get_selected_jobs().and_then(|jobs| jobs.iter().find(|j| j.id == job_id));
+        let Some(stage) = job.map(|j| j.stage.clone()) else {
+            return false;
+        };
+        self.collapsed_stages.remove(&stage);
+        if !self.visible_jobs().iter().any(|j| j.id == job_id) {
+            self.stage_filter = None;
+            self.job_filter = None;
+        }
+        self.selected_job_index = self.visible_jobs().iter().position(|j| j.id == job_id).unwrap_or(0);
+        true
+    }
+
+    /// Rows of the needs tree popup, once the needs are loaded
+    pub fn job_graph_rows(&self) -> Vec<JobGraphRow<'_>> {
+        let Some(view) = &self.job_graph else {
+            return Vec::new();
+        };
+        let jobs = self.get_selected_mr().and_then(|mr| mr.jobs.get(&view.pipeline_id));
+        match (jobs, &view.needs) {
+            (Some(jobs), Some(needs)) => job_graph_rows(jobs, needs),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Keep the selection on a visible job after stages were folded
+    fn clamp_job_selection(&mut self) {
+        let count = self.visible_jobs().len();
+        self.selected_job_index = self.selected_job_index.min(count.saturating_sub(1));
+    }
+
+    /// A failed page fetch must not leave a loading row behind
+    fn stop_loading_more(&mut self) {
+        for mr in &mut self.tracked_mrs {
+            mr.loading_more_pipelines = false;
+        }
+    }
+
+    /// When polling resumes, while GitLab is rate limiting us
+    pub fn rate_limit_resume_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.rate_limited_until.filter(|&until| until > chrono::Utc::now())
+    }
+
+    /// "offline, cached 14:02" while cached data is shown, with the date when it is older than today
+    pub fn offline_label(&self) -> Option<String> {
+        let saved_at = self.offline_cached_at?.with_timezone(&chrono::Local);
+        let format = if saved_at.date_naive() == chrono::Local::now().date_naive() { "%H:%M" } else { "%Y-%m-%d %H:%M" };
+        Some(format!("offline, cached {}", saved_at.format(format)))
+    }
+
+    /// Fetches for every MR and pipeline that still has something in progress
+    fn live_poll_effects(&self) -> Vec<Effect> {
+        let mut effects = Vec::new();
+        for (mr_index, mr) in self.tracked_mrs.iter().enumerate() {
+            // Re-fetching the pipeline list also reloads the latest pipeline's jobs
+            let refetch_pipelines = mr.pipelines.iter().any(|p| p.status.is_active());
+            let latest = mr.pipelines.iter().map(|p| p.id).max();
+            if refetch_pipelines {
+                effects.push(Effect::FetchPipelines {
+                    mr_index,
+                    project_id: self.project_id,
+                    mr_iid: mr.mr.iid,
+                    page: 1,
+                    status: self.pipeline_status_filter.clone(),
+                });
+            }
+            for pipeline in &mr.pipelines {
+                if refetch_pipelines && Some(pipeline.id) == latest {
+                    continue;
+                }
+                let jobs_active = mr
+                    .jobs
+                    .get(&pipeline.id)
+                    .is_some_and(|jobs| jobs.iter().any(|job| job.status.is_active()));
+                if jobs_active || (pipeline.status.is_active() && mr.jobs.contains_key(&pipeline.id)) {
+                    effects.push(Effect::FetchJobs {
+                        mr_index,
+                        project_id: self.project_id,
+                        pipeline_id: pipeline.id,
+                    });
+                }
+            }
+        }
+        effects
+    }
+
+    /// Notifications for watched jobs that went from running to success or failure in a fresh
+    /// job list. Jobs that are over stop being watched, and pipelines once none of their jobs runs
+    fn finished_watched_jobs(&mut self, mr_index: usize, pipeline_id: u64, jobs: &[Job]) -> Vec<Effect> {
+        let pipeline_watched = self.watched_pipelines.contains(&pipeline_id);
+        let previous: HashMap<u64, JobStatus> = self
+            .tracked_mrs
+            .get(mr_index)
+            .and_then(|mr| mr.jobs.get(&pipeline_id))
+            .map(|old| old.iter().map(|job| (job.id, job.status.clone())).collect())
+            .unwrap_or_default();
+
+        let mut effects = Vec::new();
+        for job in jobs {
+            let watched = self.watched_jobs.contains(&job.id) || pipeline_watched;
+            if !watched || job.status.is_active() {
+                continue;
+            }
+            self.watched_jobs.remove(&job.id);
+            let was_active = previous.get(&job.id).is_none_or(JobStatus::is_active);
+            if self.notify_mode == NotifyMode::Off
+                || !was_active
+                || !matches!(job.status, JobStatus::Success | JobStatus::Failed)
+            {
+                continue;
+            }
+            effects.push(Effect::Notify {
+                mode: self.notify_mode,
+                summary: format!("{} {}", job.status.symbol(), job.name),
+                body: format!("Job '{}' finished: {}", job.name, job.status.api_name()),
+                urgent: job.status == JobStatus::Failed,
+            });
+        }
+        if pipeline_watched && !jobs.iter().any(|job| job.status.is_active()) {
+            self.watched_pipelines.remove(&pipeline_id);
+        }
+        effects
+    }
+
+    /// Background work lost on quitting, described for the quit confirmation
+    pub fn running_operations(&self) -> Vec<String> {
+        let mut running = Vec::new();
+        if let Some(download) = &self.artifact_download {
+            let progress = match download.total {
+                Some(total) if total > 0 => format!(" ({}%)", (download.received * 100 / total).min(100)),
+                _ => String::new(),
+            };
+            running.push(format!("downloading the artifacts of '{}'{}", download.job_name, progress));
+        }
+        match self.exports_in_flight {
+            0 => {}
+            1 => running.push("exporting a log".to_string()),
+            count => running.push(format!("exporting {} logs", count)),
+        }
+        running
+    }
+
+    /// Re-fetch an MR's pipelines and one pipeline's jobs after acting on them
+    fn reload_pipeline(&self, mr_index: usize, pipeline_id: u64) -> Option<Effect> {
+        let mr_iid = self.tracked_mrs.get(mr_index)?.mr.iid;
+        Effect::batch(vec![
+            Effect::FetchPipelines {
+                mr_index,
+                project_id: self.project_id,
+                mr_iid,
+                page: 1,
+                status: self.pipeline_status_filter.clone(),
+            },
+            Effect::FetchJobs { mr_index, project_id: self.project_id, pipeline_id },
+        ])
+    }
+
+    /// Say how many jobs the retry started over; a job still listed under its old id was not retried
+    fn report_pipeline_retry(&mut self, retry: PipelineRetry, jobs: &[Job]) {
+        let skipped: Vec<&str> = retry
+            .jobs
+            .iter()
+            .filter(|(id, _)| jobs.iter().any(|job| job.id == *id))
+            .map(|(_, name)| name.as_str())
+            .collect();
+        let retried = retry.jobs.len() - skipped.len();
+        let message = if skipped.is_empty() {
+            format!("Retried {} of pipeline #{}", job_count(retried, "job"), retry.pipeline_iid)
+        } else {
+            format!(
+                "Retried {} of {} jobs of pipeline #{}, GitLab did not retry: {}",
+                retried,
+                retry.jobs.len(),
+                retry.pipeline_iid,
+                skipped.join(", ")
+            )
+        };
+        self.set_transient_status(message);
+    }
+
+    /// Count prefix waiting for a motion, unless it timed out
+    pub fn active_count(&self) -> Option<usize> {
+        self.pending_count
+            .filter(|(_, typed_at)| typed_at.elapsed() < COUNT_PREFIX_TIMEOUT)
+            .map(|(count, _)| count)
+    }
+
+    /// Scroll down by screen rows, stepping through the rows of wrapped lines
+    fn scroll_log_down_by(&mut self, rows: usize) {
+        if self.rows_are_lines() {
+            self.log_scroll_offset = self.log_scroll_offset.saturating_add(rows).min(self.max_log_scroll_offset());
+            return;
+        }
+        let max = self.max_log_scroll_position();
+        self.log_scroll_row = self.log_top_rows_skipped();
+        for _ in 0..rows {
+            if (self.log_scroll_offset, self.log_scroll_row) >= max {
+                (self.log_scroll_offset, self.log_scroll_row) = max;
+                break;
+            }
+            if self.log_scroll_row + 1 < self.visible_line_rows(self.log_scroll_offset) {
+                self.log_scroll_row += 1;
+            } else {
+                self.log_scroll_offset += 1;
+                self.log_scroll_row = 0;
+            }
+        }
+    }
+
+    /// Scroll up by screen rows, stepping through the rows of wrapped lines
+    fn scroll_log_up_by(&mut self, rows: usize) {
+        self.follow_mode = false; // Reading back disables following
+        if self.rows_are_lines() {
+            self.log_scroll_offset = self.log_scroll_offset.saturating_sub(rows);
+            return;
+        }
+        self.log_scroll_row = self.log_top_rows_skipped();
+        for _ in 0..rows {
+            if self.log_scroll_row > 0 {
+                self.log_scroll_row -= 1;
+            } else if self.log_scroll_offset > 0 {
+                self.log_scroll_offset -= 1;
+                self.log_scroll_row = self.visible_line_rows(self.log_scroll_offset) - 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Schedule the next follow-mode re-fetch if following a running job
+    fn next_log_poll(&mut self) -> Option<Effect> {
+        if !self.log_fetch_complete {
+            return None;
+        }
+        self.log_poll(LOG_FOLLOW_POLL_INTERVAL)
+    }
+
+    /// Ask for the trace bytes past those received, after `delay`
+    fn log_poll(&mut self, delay: std::time::Duration) -> Option<Effect> {
+        if !self.follow_mode || !self.log_job_running || self.log_poll_in_flight || !self.log_viewer_open() {
+            return None;
+        }
+        let job_id = self.log_job_id?;
+        let offset = self.log_content.as_ref().map_or(0, |b| b.len_bytes()) as u64;
+        self.log_poll_in_flight = true;
+        Some(Effect::PollJobTrace { project_id: self.project_id, job_id, offset, delay })
+    }
+
+    /// Sections shown as fold headers, none in the raw view where markers stay plain lines
+    pub fn displayed_sections(&self) -> &[LogSection] {
+        if self.raw_log_view {
+            &[]
+        } else {
+            &self.log_sections
+        }
+    }
+
+    /// Text of a source line as the viewer shows it: a section's header on its start line, the
+    /// processed line otherwise. `processed` holds the line
+    fn displayed_line_text<'a>(&'a self, processed: &'a ProcessedLines, line_number: usize) -> Cow<'a, str> {
+        let sections = self.displayed_sections();
+        if let Ok(idx) = sections.binary_search_by_key(&line_number, |s| s.start_line) {
+            let header = crate::log_processor::ansi_line(&sections[idx].header);
+            return Cow::Owned(crate::log_processor::line_text(&header).into_owned());
+        }
+        processed.line_text(line_number)
+    }
+
+    /// Processed lines covering `lines`: the window around the viewport when it does, or the
+    /// lines processed now
+    fn processed_around(&self, lines: std::ops::Range<usize>) -> Cow<'_, ProcessedLines> {
+        let window = self.log_processed_lines.lines();
+        if window.start <= lines.start && lines.end <= window.end {
+            return Cow::Borrowed(&self.log_processed_lines);
+        }
+        Cow::Owned(self.process_log_range(lines))
+    }
+
+    /// Recompute which source lines are displayed, hiding end markers and folded section bodies
+    fn rebuild_visible_lines(&mut self) {
+        let total = self.log_line_count();
+        let mut hidden = vec![false; total];
+
+        for section in self.displayed_sections() {
+            if let Some(end) = section.end_line {
+                if end < total {
+                    hidden[end] = true;
+                }
+            }
+            // A filtered view is flat, folds only apply to the full log
+            if self.filtered_line_indices.is_none() && self.collapsed_sections.contains(&section.name) {
+                let body_end = section.end_line.unwrap_or(total.saturating_sub(1));
+                for flag in hidden.iter_mut().take(body_end + 1).skip(section.start_line + 1) {
+                    *flag = true;
+                }
+            }
+        }
+
+        self.log_visible_lines = match &self.filtered_line_indices {
+            Some(filtered) => filtered.iter().copied().filter(|&idx| idx < total && !hidden[idx]).collect(),
+            None => (0..total).filter(|&idx| !hidden[idx]).collect(),
+        };
+    }
+
+    /// Recompute which source lines pass the active filter, with their context lines
+    fn build_filtered_lines(&mut self) {
+        // Matched against the displayed text, so prefixes, raw timestamps and ANSI codes don't match
+        self.filtered_line_indices = match (&self.filter_regex, &self.log_content) {
+            (Some(regex), Some(_)) => {
+                let context = self.filter_context;
+                let line_count = self.log_line_count();
+                let mut lines: Vec<usize> = Vec::new();
+                for first in (0..line_count).step_by(SEARCH_BATCH_LINES) {
+                    let block = first..(first + SEARCH_BATCH_LINES).min(line_count);
+                    let processed = self.processed_around(block.clone());
+                    let matched = block
+                        .filter(|&idx| regex.is_match(&self.displayed_line_text(&processed, idx)) != self.filter_inverse);
+                    for idx in matched {
+                        // Context of neighbouring matches overlaps, only add what isn't there yet
+                        let from = lines.last().map_or(0, |&last| last + 1).max(idx.saturating_sub(context));
+                        lines.extend(from..=idx + context);
+                    }
                 }
+                lines.retain(|&idx| idx < line_count);
+                Some(lines)
+            }
+            _ => None,
+        };
+    }
+
+    /// Rebuild the visible lines after the filter changed, keeping the top line in view
+    fn refilter_log(&mut self) {
+        let top_line = self.log_visible_lines.get(self.log_scroll_offset).copied().unwrap_or(0);
+        self.build_filtered_lines();
+        self.rebuild_visible_lines();
+        self.log_scroll_offset = self.log_visible_lines.partition_point(|&idx| idx < top_line);
+        self.log_scroll_offset = self
+            .log_scroll_offset
+            .min(self.max_log_scroll_offset());
+
+        // Search only covers what the filter shows
+        if self.search_regex.is_some() {
+            self.build_search_results();
+            self.current_search_result = 0;
+        }
+    }
+
+    /// Find the row in the visible list that displays a source line
+    pub fn visible_row_of_line(&self, line_number: usize) -> Option<usize> {
+        self.log_visible_lines.binary_search(&line_number).ok()
+    }
+
+    /// Expand any folded sections that hide the given source line
+    fn reveal_log_line(&mut self, line_number: usize) {
+        let to_expand: Vec<String> = self
+            .log_sections
+            .iter()
+            .filter(|section| {
+                section.start_line < line_number
+                    && section.end_line.is_none_or(|end| line_number <= end)
+                    && self.collapsed_sections.contains(&section.name)
+            })
+            .map(|section| section.name.clone())
+            .collect();
+
+        if !to_expand.is_empty() {
+            for name in to_expand {
+                self.collapsed_sections.remove(&name);
+            }
+            self.rebuild_visible_lines();
+        }
+    }
+
+    /// Bookmarks of the job whose log is open, None if it has none
+    pub fn current_bookmarks(&self) -> Option<&BTreeSet<usize>> {
+        self.log_bookmarks.get(&self.log_job_id?).filter(|lines| !lines.is_empty())
+    }
+
+    pub fn is_bookmarked(&self, line_number: usize) -> bool {
+        self.current_bookmarks().is_some_and(|lines| lines.contains(&line_number))
+    }
+
+    /// Row of a scrollbar or minimap track of `track_len` rows for a source line.
+    /// Lines hidden in a folded section map to the section header
+    fn track_row(&self, line_number: usize, track_len: usize) -> usize {
+        let position = match self.log_visible_lines.binary_search(&line_number) {
+            Ok(position) => position,
+            Err(position) => position.saturating_sub(1),
+        };
+        (position * track_len / self.log_visible_lines.len().max(1)).min(track_len.saturating_sub(1))
+    }
+
+    /// Ticks along a scrollbar track of `track_len` rows, bookmarks winning over matches on the same row
+    pub fn scrollbar_marks(&self, track_len: usize) -> Vec<(usize, ScrollbarMark)> {
+        if self.log_visible_lines.is_empty() || track_len == 0 {
+            return Vec::new();
+        }
+        let mut rows: Vec<Option<ScrollbarMark>> = vec![None; track_len];
+        for &line in &self.search_results {
+            rows[self.track_row(line, track_len)] = Some(ScrollbarMark::SearchMatch);
+        }
+        for line in self.reachable_bookmarks() {
+            rows[self.track_row(line, track_len)] = Some(ScrollbarMark::Bookmark);
+        }
+        rows.into_iter().enumerate().filter_map(|(row, kind)| Some((row, kind?))).collect()
+    }
+
+    /// Whether the minimap is drawn beside a log viewport of `height` rows: only when the log is longer
+    pub fn minimap_visible(&self, height: usize) -> bool {
+        self.show_minimap && self.log_visible_lines.len() > height
+    }
+
+    /// The most important mark of each row of a minimap `track_len` rows tall, over the whole log
+    pub fn minimap_marks(&self, track_len: usize) -> Vec<(usize, MinimapMark)> {
+        if self.log_visible_lines.is_empty() || track_len == 0 {
+            return Vec::new();
+        }
+        let mut rows: Vec<Option<MinimapMark>> = vec![None; track_len];
+        let sections = self.log_sections.iter().map(|section| (section.start_line, MinimapMark::Section));
+        let warnings = self.log_warning_lines.iter().map(|&line| (line, MinimapMark::Warning));
+        let errors = self.log_error_lines.iter().map(|&line| (line, MinimapMark::Error));
+        for (line, mark) in sections.chain(warnings).chain(errors) {
+            let row = &mut rows[self.track_row(line, track_len)];
+            *row = Some(row.map_or(mark, |existing| existing.min(mark)));
+        }
+        rows.into_iter().enumerate().filter_map(|(row, mark)| Some((row, mark?))).collect()
+    }
+
+    /// Scroll a bookmarked line to the top of the view, unfolding its section
+    fn jump_to_bookmark(&mut self, line_number: usize) {
+        self.reveal_log_line(line_number);
+        if let Some(row) = self.visible_row_of_line(line_number) {
+            self.log_scroll_offset = row.min(self.max_log_scroll_offset());
+            self.log_scroll_row = 0;
+            self.follow_mode = false;
+        }
+    }
+
+    /// Bookmarks the filter doesn't hide, the ones navigation can reach
+    fn reachable_bookmarks(&self) -> Vec<usize> {
+        let filtered = self.filtered_line_indices.as_ref();
+        self.current_bookmarks()
+            .map(|lines| {
+                lines
+                    .iter()
+                    .copied()
+                    .filter(|line| filtered.is_none_or(|shown| shown.binary_search(line).is_ok()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Section to fold/unfold: the innermost one containing the top visible line,
+    /// or else the first section header within the viewport
+    fn section_at_cursor(&self) -> Option<&LogSection> {
+        let top_line = *self.log_visible_lines.get(self.log_scroll_offset)?;
+
+        let containing = self
+            .displayed_sections()
+            .iter()
+            .filter(|section| {
+                section.start_line <= top_line && section.end_line.is_none_or(|end| top_line <= end)
+            })
+            .max_by_key(|section| section.start_line);
+
+        containing.or_else(|| {
+            let visible = &self.log_visible_lines[self.log_viewport_lines()];
+            self.displayed_sections()
+                .iter()
+                .find(|section| visible.binary_search(&section.start_line).is_ok())
+        })
+    }
+
+    /// Scroll so a line lands at `match_position` of the viewport (the middle by default),
+    /// counting the rows wrapped lines above it take
+    fn center_log_line(&mut self, line_number: usize) {
+        self.reveal_log_line(line_number);
+        let Some(offset) = self.visible_row_of_line(line_number) else {
+            return;
+        };
+
+        let fraction = self.match_position.clamp(0.0, 1.0);
+        let mut rows_above = (self.log_viewport_height.saturating_sub(1) as f64 * fraction).round() as usize;
+        let (mut top, mut top_row) = (offset, 0);
+        while top > 0 && rows_above > 0 {
+            top -= 1;
+            let rows = self.visible_line_rows(top);
+            if rows > rows_above {
+                // Only the last rows of this line fit above
+                top_row = rows - rows_above;
+                break;
+            }
+            rows_above -= rows;
+        }
+
+        // Don't scroll past the end
+        (self.log_scroll_offset, self.log_scroll_row) = (top, top_row).min(self.max_log_scroll_position());
+    }
+
+    /// Compile the search query according to the active search mode
+    fn compile_search_regex(&self) -> Option<std::result::Result<Regex, regex::Error>> {
+        if self.search_query.is_empty() {
+            return None;
+        }
+
+        let pattern = match self.search_mode {
+            SearchMode::Literal | SearchMode::CaseInsensitive => regex::escape(&self.search_query),
+            SearchMode::Regex => self.search_query.clone(),
+        };
+        let pattern = if self.search_whole_word {
+            format!(r"\b(?:{})\b", pattern)
+        } else {
+            pattern
+        };
+        let result = RegexBuilder::new(&pattern)
+            .case_insensitive(self.search_mode == SearchMode::CaseInsensitive)
+            .build();
+        Some(result)
+    }
+
+    /// Rebuild search results for the current query, compiling the regex only once
+    ///
+    /// The first step runs right away, the rest of a large log is searched in further steps
+    fn build_search_results(&mut self) {
+        self.search_results.clear();
+        self.search_regex = None;
+        self.search_regex_error = false;
+        self.search_scan = None;
+
+        let regex = match self.compile_search_regex() {
+            Some(Ok(regex)) => regex,
+            Some(Err(_)) => {
+                self.search_regex_error = true;
+                return;
+            }
+            None => return,
+        };
+
+        self.search_regex = Some(regex);
+        self.search_from(0);
+    }
+
+    /// (Re)search the log from a source line on, keeping the results before it
+    fn search_from(&mut self, line: usize) {
+        let kept = self.search_results.partition_point(|&idx| idx < line);
+        self.search_results.truncate(kept);
+        match &mut self.search_scan {
+            Some(scan) => scan.next_line = scan.next_line.min(line),
+            None => {
+                self.search_generation += 1;
+                self.search_scan = Some(SearchScan {
+                    generation: self.search_generation,
+                    next_line: line,
+                    jump_to_first: false,
+                    select_line: None,
+                    scheduled: false,
+                });
+            }
+        }
+        self.search_step();
+    }
+
+    /// Search the next `SEARCH_BATCH_LINES` lines of the running scan
+    fn search_step(&mut self) {
+        let (Some(scan), Some(regex), Some(_)) = (&self.search_scan, &self.search_regex, &self.log_content) else {
+            self.search_scan = None;
+            return;
+        };
+
+        // Like the filter, search what is displayed rather than the raw trace
+        let line_count = self.log_line_count();
+        let end = (scan.next_line + SEARCH_BATCH_LINES).min(line_count);
+        let filtered = self.filtered_line_indices.as_ref();
+        let processed = self.processed_around(scan.next_line..end);
+        let found: Vec<usize> = (scan.next_line..end)
+            .filter(|idx| filtered.is_none_or(|lines| lines.binary_search(idx).is_ok()))
+            .filter(|&idx| regex.is_match(&self.displayed_line_text(&processed, idx)))
+            .collect();
+        drop(processed);
+        let found_before = self.search_results.len();
+        self.search_results.extend(found);
+        let Some(scan) = &mut self.search_scan else {
+            return;
+        };
+        scan.next_line = end;
+
+        let jump = scan.jump_to_first && found_before == 0 && !self.search_results.is_empty();
+        if jump {
+            scan.jump_to_first = false;
+        }
+        let select = scan.select_line.take_if(|&mut line| line < end || end >= line_count);
+        if end >= line_count {
+            self.search_scan = None;
+        }
+        if jump {
+            self.center_log_line(self.search_results[0]);
+        }
+        if let Some(line) = select {
+            self.select_search_line(line);
+        }
+    }
+
+    /// Make the first match at or after a source line the current one, or the last match
+    fn select_search_line(&mut self, line: usize) {
+        self.current_search_result =
+            self.search_results.partition_point(|&idx| idx < line).min(self.search_results.len().saturating_sub(1));
+    }
+
+    /// Search for the edited query once typing pauses, superseding pending searches
+    fn debounce_search(&mut self) -> Option<Effect> {
+        self.search_input_generation += 1;
+        self.search_debounce.map(|delay| Effect::DebounceSearch { generation: self.search_input_generation, delay })
+    }
+
+    /// Search for the query and jump to the first match, now or as soon as one is found
+    fn search_and_jump(&mut self) {
+        self.build_search_results();
+        self.current_search_result = 0;
+        if !self.search_results.is_empty() {
+            self.center_log_line(self.search_results[0]);
+        } else if let Some(scan) = &mut self.search_scan {
+            scan.jump_to_first = true;
+        }
+    }
+
+    /// Fraction of the log searched so far, in percent, while a search is still running
+    pub fn search_progress(&self) -> Option<u8> {
+        let scan = self.search_scan.as_ref()?;
+        let total = self.log_content.as_ref().map_or(0, |content| content.line_count()).max(1);
+        Some((scan.next_line * 100 / total).min(99) as u8)
+    }
+
+    /// Handle an action, then schedule the next step of a search that is still running
+    pub fn update(&mut self, action: Action) -> Option<Effect> {
+        // Downloads, polls and searches of a log in another tab carry on in that tab
+        if let Some(index) = self.background_log_tab(&action) {
+            return self.in_log_tab(index, |app| {
+                let effect = app.handle_action(action);
+                app.update_log_window();
+                app.schedule_search_step(effect)
+            });
+        }
+        let effect = self.handle_action(action);
+        self.remember_selected_job();
+        self.cancel_stale_log_prefetch();
+        self.update_log_window();
+        self.schedule_search_step(effect)
+    }
+
+    /// Fetch the logs of the selected pipeline's jobs that `prefetch` picks and aren't cached or
+    /// already asked for
+    fn prefetch_logs(&mut self, prefetch: LogPrefetch) -> Option<Effect> {
+        let pipeline_id = self.get_selected_pipeline()?.id;
+        let mr_index = self.selected_mr_index;
+        let mr = self.tracked_mrs.get(mr_index)?;
+        let run = self.log_prefetch.get_or_insert_with(|| LogPrefetchRun {
+            mr_index,
+            pipeline_id,
+            requested: HashSet::new(),
+            pending: 0,
+            fetched: 0,
+            cancel: Arc::new(AtomicBool::new(false)),
+        });
+        let job_ids: Vec<u64> = mr
+            .jobs
+            .get(&pipeline_id)?
+            .iter()
+            .filter(|job| prefetch.matches(&job.status))
+            .filter(|job| !mr.job_logs_cache.contains_key(&job.id) && !run.requested.contains(&job.id))
+            .map(|job| job.id)
+            .collect();
+        if job_ids.is_empty() {
+            return None;
+        }
+        run.requested.extend(&job_ids);
+        run.pending += job_ids.len();
+        let cancel = run.cancel.clone();
+        Some(Effect::PrefetchJobLogs { project_id: self.project_id, pipeline_id, job_ids, cancel })
+    }
+
+    /// Stop prefetching logs once another pipeline is selected
+    fn cancel_stale_log_prefetch(&mut self) {
+        let selected = self.get_selected_pipeline().map(|p| (self.selected_mr_index, p.id));
+        let stale = self.log_prefetch.as_ref().is_some_and(|run| selected != Some((run.mr_index, run.pipeline_id)));
+        if let Some(run) = self.log_prefetch.take_if(|_| stale) {
+            run.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a prefetched log as done, reporting the batch once the last one is in
+    fn finish_log_prefetch(&mut self, pipeline_id: u64, fetched: bool) -> Option<&LogPrefetchRun> {
+        let run = self.log_prefetch.as_mut().filter(|run| run.pipeline_id == pipeline_id)?;
+        run.pending = run.pending.saturating_sub(1);
+        run.fetched += fetched as usize;
+        if run.pending == 0 && run.fetched > 0 {
+            let count = std::mem::take(&mut run.fetched);
+            self.set_transient_status(format!("Prefetched {} job logs", count));
+        }
+        self.log_prefetch.as_ref()
+    }
+
+    /// Add the next step of a search still running through the log to `effect`
+    fn schedule_search_step(&mut self, effect: Option<Effect>) -> Option<Effect> {
+        match &mut self.search_scan {
+            Some(scan) if !scan.scheduled => {
+                scan.scheduled = true;
+                let step = Effect::ContinueSearch { generation: scan.generation };
+                Effect::batch(effect.into_iter().chain(std::iter::once(step)).collect())
+            }
+            _ => effect,
+        }
+    }
+
+    fn handle_action(&mut self, action: Action) -> Option<Effect> {
+        match action {
+            Action::Quit => {
+                let running = self.running_operations();
+                if running.is_empty() {
+                    self.should_quit = true;
+                } else {
+                    // Quitting would kill them, ask first
+                    self.pending_confirmation = Some(Confirmation {
+                        prompt: format!("Still {}. Quit anyway?", running.join(", ")),
+                        action: Action::ForceQuit,
+                    });
+                }
+                None
+            }
+
+            Action::ForceQuit => {
+                self.should_quit = true;
+                None
+            }
+
+            Action::NextMr => {
+                if !self.tracked_mrs.is_empty() {
+                    self.selected_mr_index = (self.selected_mr_index + 1) % self.tracked_mrs.len();
+                    return self.restore_job_selection();
+                }
+                None
+            }
+
+            Action::PrevMr => {
+                if !self.tracked_mrs.is_empty() {
+                    self.selected_mr_index = self
+                        .selected_mr_index
+                        .checked_sub(1)
+                        .unwrap_or(self.tracked_mrs.len() - 1);
+                    return self.restore_job_selection();
+                }
+                None
+            }
+
+            Action::NextJob => {
+                let count = self.visible_jobs().len();
+                if count > 0 {
+                    self.selected_job_index = (self.selected_job_index + 1) % count;
+                }
+                None
+            }
+
+            Action::PrevJob => {
+                let count = self.visible_jobs().len();
+                if count > 0 {
+                    self.selected_job_index = self
+                        .selected_job_index
+                        .checked_sub(1)
+                        .unwrap_or(count - 1);
+                }
+                None
+            }
+
+            Action::FoldSelectedStage => {
+                let stage = self.selected_job()?.stage.clone();
+                self.collapsed_stages.insert(stage);
+                self.clamp_job_selection();
+                None
+            }
+
+            Action::ToggleAllStagesFold => {
+                let groups = self.job_stage_groups();
+                if groups.iter().any(|group| group.collapsed) {
+                    self.collapsed_stages.clear();
+                } else {
+                    let stages: Vec<String> = groups.iter().map(|group| group.name.to_string()).collect();
+                    self.collapsed_stages.extend(stages);
+                }
+                self.clamp_job_selection();
+                None
+            }
+
+            Action::ToggleStageFilter => {
+                self.stage_filter = match self.stage_filter.take() {
+                    Some(_) => None,
+                    None => Some(self.selected_job()?.stage.clone()),
+                };
+                self.selected_job_index = 0;
+                None
+            }
+
+            Action::SelectFirstFailedJob => {
+                if !self.select_first_failed_job() {
+                    self.set_transient_status("No failed job in this pipeline".to_string());
+                }
+                None
+            }
+
+            Action::StartJobFilter | Action::StartJobSearch => {
+                let columns = matches!(action, Action::StartJobSearch);
+                // Switching kinds starts over, the query means something else
+                let filter = self.job_filter.take_if(|filter| filter.columns == columns);
+                let filter = self.job_filter.insert(filter.unwrap_or(JobFilter { columns, ..JobFilter::default() }));
+                filter.editing = true;
+                None
+            }
+
+            Action::UpdateJobFilter(query) => {
+                if let Some(filter) = &mut self.job_filter {
+                    filter.query = query;
+                }
+                // Select the best match, the first listed one on ties
+                let best = self
+                    .visible_jobs()
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, job)| Some((self.job_filter_match(&job.name)?.0, i)))
+                    .max_by_key(|&(score, i)| (score, std::cmp::Reverse(i)))
+                    .map(|(_, i)| i);
+                self.selected_job_index = best.unwrap_or(0);
+                None
+            }
+
+            Action::ApplyJobFilter => {
+                match &mut self.job_filter {
+                    Some(filter) if filter.query.is_empty() => self.job_filter = None,
+                    Some(filter) => filter.editing = false,
+                    None => {}
+                }
+                None
+            }
+
+            Action::ClearJobFilter => {
+                // Keep the selected job selected in the full list
+                let selected_id = self.selected_job().map(|job| job.id);
+                self.job_filter = None;
+                self.selected_job_index = selected_id
+                    .and_then(|id| self.visible_jobs().iter().position(|job| job.id == id))
+                    .unwrap_or(0);
+                None
+            }
+
+            Action::NextPipeline => {
+                let mr_index = self.selected_mr_index;
+                let project_id = self.project_id;
+
+                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
+                    let at_end = mr.selected_pipeline_index + 1 >= mr.pipelines.len();
+                    if let (true, Some(page)) = (at_end, mr.pipelines_next_page) {
+                        // Older pipelines are fetched lazily instead of wrapping around
+                        if mr.loading_more_pipelines {
+                            return None;
+                        }
+                        mr.loading_more_pipelines = true;
+                        let status = self.pipeline_status_filter.clone();
+                        return Some(Effect::FetchPipelines { mr_index, project_id, mr_iid: mr.mr.iid, page, status });
+                    }
+
+                    if !mr.pipelines.is_empty() {
+                        mr.selected_pipeline_index =
+                            (mr.selected_pipeline_index + 1) % mr.pipelines.len();
+
+                        // Fetch jobs for this pipeline if we don't have them yet
+                        if let Some(pipeline) = mr.pipelines.get(mr.selected_pipeline_index) {
+                            let pipeline_id = pipeline.id;
+                            let needs_fetch = !mr.jobs.contains_key(&pipeline_id);
+
+                            // Drop the borrow so we can modify self
+                            let _ = mr;
+
+                            if needs_fetch {
+                                self.selected_job_index = 0;
+                                return Some(Effect::FetchJobs {
+                                    mr_index,
+                                    project_id,
+                                    pipeline_id,
+                                });
+                            }
+                            return self.restore_job_selection();
+                        }
+                    }
+                }
+
+                self.restore_job_selection()
+            }
+
+            Action::TogglePipelineStatusFilter(status) => {
+                let filter = (self.pipeline_status_filter.as_ref() != Some(&status)).then_some(status);
+                self.set_pipeline_status_filter(filter)
+            }
+
+            Action::ClearPipelineStatusFilter => self.set_pipeline_status_filter(None),
+
+            Action::CyclePipelineSort => {
+                self.pipeline_sort = self.pipeline_sort.next();
+                for mr in &mut self.tracked_mrs {
+                    sort_pipelines(mr, self.pipeline_sort);
+                }
+                self.set_transient_status(format!("Pipelines sorted {}", self.pipeline_sort.label()));
+                None
+            }
+
+            Action::CycleJobSort => {
+                let selected_id = self.selected_job().map(|job| job.id);
+                self.job_sort = self.job_sort.next();
+                self.job_sort_time = chrono::Utc::now();
+                self.selected_job_index = selected_id
+                    .and_then(|id| self.visible_jobs().iter().position(|job| job.id == id))
+                    .unwrap_or(0);
+                self.set_transient_status(format!("Jobs sorted {}", self.job_sort.label()));
+                None
+            }
+
+            Action::PrevPipeline => {
+                let mr_index = self.selected_mr_index;
+                let project_id = self.project_id;
+
+                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
+                    if !mr.pipelines.is_empty() {
+                        mr.selected_pipeline_index = mr
+                            .selected_pipeline_index
+                            .checked_sub(1)
+                            .unwrap_or(mr.pipelines.len() - 1);
+
+                        // Fetch jobs for this pipeline if we don't have them yet
+                        if let Some(pipeline) = mr.pipelines.get(mr.selected_pipeline_index) {
+                            let pipeline_id = pipeline.id;
+                            let needs_fetch = !mr.jobs.contains_key(&pipeline_id);
+
+                            // Drop the borrow so we can modify self
+                            let _ = mr;
+
+                            if needs_fetch {
+                                self.selected_job_index = 0;
+                                return Some(Effect::FetchJobs {
+                                    mr_index,
+                                    project_id,
+                                    pipeline_id,
+                                });
+                            }
+                            return self.restore_job_selection();
+                        }
+                    }
+                }
+
+                self.restore_job_selection()
+            }
+
+            Action::OpenSelectedJobLog => {
+                let job_info = self.selected_job()
+                    .map(|job| (job.name.clone(), job.id, job.status.clone(), job.erased_at.is_some()));
+
+                if let Some((job_name, job_id, job_status, erased)) = job_info {
+                    if let Some(index) = self.log_tab_of_job(job_id) {
+                        self.switch_log_tab(index);
+                        self.mode = AppMode::ViewingLog;
+                        return None;
+                    }
+                    self.open_log_tab();
+                    self.log_job_running = matches!(job_status, JobStatus::Running | JobStatus::Pending);
+
+                    // Fetching an erased trace would only fail
+                    if erased {
+                        self.show_log_expired(job_id, job_name);
+                        return None;
+                    }
+
+                    // Check if log is already cached
+                    if let Some(mr) = self.tracked_mrs.get(self.selected_mr_index) {
+                        if let Some(cached_log) = mr.job_logs_cache.get(&job_id) {
+                            // Use cached log
+                            let cached_log = Arc::clone(cached_log);
+                            self.status_message = None;
+                            self.set_log_content(job_id, cached_log, job_name);
+                            self.restore_log_view();
+                            self.mode = AppMode::ViewingLog;
+                            return self.next_log_poll();
+                        }
+                    }
+
+                    // Not cached, open the viewer on a loading state and fetch from API
+                    self.begin_log_fetch(job_id, job_name.clone());
+                    return Some(Effect::FetchJobTrace {
+                        project_id: self.project_id,
+                        job_id,
+                        job_name,
+                        offset: 0,
+                    });
+                }
+                None
+            }
+
+            Action::PrefetchJobLogs => {
+                self.get_selected_pipeline()?;
+                // Failed jobs are what triage needs, unless the config asks for more
+                let prefetch = match self.log_prefetch_mode {
+                    LogPrefetch::Off => LogPrefetch::Failed,
+                    mode => mode,
+                };
+                let effect = self.prefetch_logs(prefetch);
+                let label = if prefetch == LogPrefetch::Failed { "failed" } else { "finished" };
+                match &effect {
+                    Some(Effect::PrefetchJobLogs { job_ids, .. }) => {
+                        self.set_transient_status(format!("Prefetching the logs of {} {} jobs", job_ids.len(), label))
+                    }
+                    _ => self.set_transient_status(format!("No {} job logs left to prefetch", label)),
+                }
+                effect
+            }
+
+            Action::JobLogPrefetched { pipeline_id, job_id, trace } => {
+                let mr_index = self.finish_log_prefetch(pipeline_id, true)?.mr_index;
+                // A log opened meanwhile is already cached, and maybe more recent
+                let Entry::Vacant(entry) = self.tracked_mrs.get_mut(mr_index)?.job_logs_cache.entry(job_id) else {
+                    return None;
+                };
+                let log = Arc::clone(entry.insert(Arc::new(trace)));
+                Some(Effect::SaveJobLog { project_id: self.project_id, job_id, log })
+            }
+
+            Action::JobLogPrefetchFailed { pipeline_id, .. } => {
+                self.finish_log_prefetch(pipeline_id, false);
+                None
+            }
+
+            Action::CompareSelectedJob => {
+                let job = self.selected_job()?;
+                let selected = (job.id, job.name.clone());
+                match self.compare_job.take() {
+                    None => {
+                        self.set_transient_status(format!(
+                            "Marked '{}' for comparison, compare again on another job to see the diff",
+                            selected.1
+                        ));
+                        self.compare_job = Some(selected);
+                        None
+                    }
+                    Some(marked) if marked.0 == selected.0 => {
+                        self.set_transient_status(format!("'{}' is no longer marked for comparison", marked.1));
+                        None
+                    }
+                    Some(marked) => {
+                        let (left_id, right_id) = (marked.0, selected.0);
+                        self.log_diff = Some(LogDiff::new(marked, selected));
+                        self.mode = AppMode::ComparingLogs;
+                        Some(Effect::FetchLogDiff { project_id: self.project_id, left_id, right_id })
+                    }
+                }
+            }
+
+            Action::LogDiffLoaded { left_id, right_id, content } => {
+                let diff = self.log_diff.as_mut().filter(|d| d.left_job.0 == left_id && d.right_job.0 == right_id)?;
+                diff.content = Some(content);
+                None
+            }
+
+            Action::LogDiffFailed { left_id, right_id, error } => {
+                let diff = self.log_diff.as_mut().filter(|d| d.left_job.0 == left_id && d.right_job.0 == right_id)?;
+                diff.error = Some(error);
+                None
+            }
+
+            Action::CloseLogDiff => {
+                self.log_diff = None;
+                self.mode = AppMode::Normal;
+                None
+            }
+
+            Action::DiffScrollUp => self.scroll_diff_to(|scroll, _| scroll.saturating_sub(1)),
+            Action::DiffScrollDown => self.scroll_diff_to(|scroll, _| scroll + 1),
+            Action::DiffPageUp => self.scroll_diff_to(|scroll, page| scroll.saturating_sub(page)),
+            Action::DiffPageDown => self.scroll_diff_to(|scroll, page| scroll + page),
+            Action::DiffTop => self.scroll_diff_to(|_, _| 0),
+            Action::DiffBottom => self.scroll_diff_to(|_, _| usize::MAX),
+
+            Action::NextDiffChange | Action::PrevDiffChange => {
+                let diff = self.log_diff.as_mut()?;
+                let found = if matches!(action, Action::NextDiffChange) {
+                    diff.next_change()
+                } else {
+                    diff.prev_change()
+                };
+                if !found {
+                    self.set_transient_status("No more differences".to_string());
+                }
+                None
+            }
+
+            Action::RetrySelectedJob => {
+                let pipeline_id = self.get_selected_pipeline()?.id;
+                let job = self.selected_job()?.clone();
+                if !matches!(job.status, JobStatus::Failed | JobStatus::Canceled | JobStatus::Success) {
+                    let status = format!("{:?}", job.status).to_lowercase();
+                    self.set_transient_status(format!("Job '{}' is {} and can't be retried", job.name, status));
+                    return None;
+                }
+                self.pending_confirmation = Some(Confirmation {
+                    prompt: format!("Retry job '{}' (#{})?", job.name, job.id),
+                    action: Action::RetryJob { mr_index: self.selected_mr_index, pipeline_id, job_id: job.id },
+                });
+                None
+            }
+
+            Action::RetryJob { mr_index, pipeline_id, job_id } => {
+                self.set_transient_status("Retrying job...".to_string());
+                Some(Effect::RetryJob { mr_index, project_id: self.project_id, pipeline_id, job_id })
+            }
+
+            Action::PlaySelectedJob => {
+                let pipeline_id = self.get_selected_pipeline()?.id;
+                let job = self.selected_job()?.clone();
+                if job.status != JobStatus::Manual {
+                    self.set_transient_status(format!("Job '{}' is not a manual job", job.name));
+                    return None;
+                }
+                self.play_prompt = Some(PlayPrompt {
+                    mr_index: self.selected_mr_index,
+                    pipeline_id,
+                    job_id: job.id,
+                    job_name: job.name,
+                    input: String::new(),
+                });
+                None
+            }
+
+            Action::UpdatePlayInput(input) => {
+                if let Some(prompt) = &mut self.play_prompt {
+                    prompt.input = input;
+                }
+                None
+            }
+
+            Action::CancelPlayJob => {
+                self.play_prompt = None;
+                None
+            }
+
+            Action::SubmitPlayJob => {
+                let prompt = self.play_prompt.clone()?;
+                match parse_variables(prompt.input.split_whitespace()) {
+                    Ok(variables) => {
+                        self.play_prompt = None;
+                        self.update(Action::PlayJob {
+                            mr_index: prompt.mr_index,
+                            pipeline_id: prompt.pipeline_id,
+                            job_id: prompt.job_id,
+                            variables,
+                        })
+                    }
+                    Err(message) => {
+                        // Keep the prompt open so the input can be fixed
+                        self.set_transient_status(message);
+                        None
+                    }
+                }
+            }
+
+            Action::PlayJob { mr_index, pipeline_id, job_id, variables } => {
+                self.set_transient_status("Playing job...".to_string());
+                Some(Effect::PlayJob { mr_index, project_id: self.project_id, pipeline_id, job_id, variables })
+            }
+
+            Action::StartTriggerPipeline => {
+                let default_ref = self
+                    .current_branch
+                    .clone()
+                    .or_else(|| self.get_selected_mr().map(|mr| mr.mr.source_branch.clone()))
+                    .unwrap_or_default();
+                self.trigger_input = Some(default_ref);
+                None
+            }
+
+            Action::UpdateTriggerInput(input) => {
+                if self.trigger_input.is_some() {
+                    self.trigger_input = Some(input);
+                }
+                None
+            }
+
+            Action::CancelTriggerPipeline => {
+                self.trigger_input = None;
+                None
+            }
+
+            Action::SubmitTriggerPipeline => {
+                let input = self.trigger_input.clone()?;
+                match parse_trigger_input(&input) {
+                    Ok((ref_name, variables)) => {
+                        self.trigger_input = None;
+                        self.set_transient_status(format!("Starting pipeline for '{}'...", ref_name));
+                        Some(Effect::TriggerPipeline { project_id: self.project_id, ref_name, variables })
+                    }
+                    Err(message) => {
+                        // Keep the prompt open so the input can be fixed
+                        self.set_transient_status(message);
+                        None
+                    }
+                }
+            }
+
+            Action::PipelineTriggered(pipeline) => {
+                let Some(mr_index) = self
+                    .tracked_mrs
+                    .iter()
+                    .position(|tmr| !tmr.mr.source_branch.is_empty() && tmr.mr.source_branch == pipeline.ref_name)
+                else {
+                    self.set_transient_status(format!(
+                        "Started pipeline #{} for '{}' (no tracked MR for this ref)",
+                        pipeline.iid, pipeline.ref_name
+                    ));
+                    return None;
+                };
+
+                self.set_transient_status(format!("Started pipeline #{} for '{}'", pipeline.iid, pipeline.ref_name));
+                self.focus_pipeline(mr_index, pipeline)
+            }
+
+            Action::ShowHeadPipelines => {
+                self.head_pipelines = Some(HeadPipelines::default());
+                Some(Effect::FetchHeadPipelines { project_id: self.project_id, delay: std::time::Duration::ZERO })
+            }
+
+            Action::HeadPipelinesLoaded { sha, pipelines } => {
+                let head = self.head_pipelines.as_mut()?; // Closed meanwhile
+                let appeared = head.polling && !pipelines.is_empty();
+                head.selected = head.selected.min(pipelines.len().saturating_sub(1));
+                head.sha = Some(sha);
+                head.error = None;
+                head.pipelines = Some(pipelines);
+                if appeared {
+                    head.polling = false;
+                    self.set_transient_status("A pipeline started for HEAD".to_string());
+                    return None;
+                }
+                head.polling.then(|| Effect::FetchHeadPipelines {
+                    project_id: self.project_id,
+                    delay: self.live_refresh_interval.unwrap_or(DEFAULT_LIVE_REFRESH_INTERVAL),
+                })
+            }
+
+            Action::HeadPipelinesFailed(error) => {
+                let head = self.head_pipelines.as_mut()?;
+                head.error = Some(error);
+                head.polling = false;
+                None
+            }
+
+            Action::HeadPipelinesNext | Action::HeadPipelinesPrev => {
+                let head = self.head_pipelines.as_mut()?;
+                let count = head.pipelines.as_ref().map_or(0, Vec::len);
+                if count > 0 {
+                    let step = if matches!(action, Action::HeadPipelinesNext) { 1 } else { count - 1 };
+                    head.selected = (head.selected + step) % count;
+                }
+                None
+            }
+
+            Action::RefreshHeadPipelines => {
+                let head = self.head_pipelines.as_mut()?;
+                head.error = None;
+                Some(Effect::FetchHeadPipelines { project_id: self.project_id, delay: std::time::Duration::ZERO })
+            }
+
+            Action::ToggleHeadPipelinePolling => {
+                let head = self.head_pipelines.as_mut()?;
+                if head.polling {
+                    head.polling = false; // The scheduled lookup's answer won't schedule another one
+                    return None;
+                }
+                if !head.pipelines.as_ref().is_some_and(Vec::is_empty) {
+                    return None; // Only offered while HEAD has no pipeline
+                }
+                head.polling = true;
+                Some(Effect::FetchHeadPipelines {
+                    project_id: self.project_id,
+                    delay: self.live_refresh_interval.unwrap_or(DEFAULT_LIVE_REFRESH_INTERVAL),
+                })
+            }
+
+            Action::OpenHeadPipeline => {
+                let head = self.head_pipelines.take()?;
+                let pipeline = head.pipelines?.get(head.selected)?.clone();
+                // Shown in the MR that ran it when that one is tracked, in the browser otherwise
+                match self.tracked_mr_of(&pipeline) {
+                    Some(mr_index) => self.focus_pipeline(mr_index, pipeline),
+                    None => Some(Effect::OpenUrl(pipeline.web_url)),
+                }
+            }
+
+            Action::CloseHeadPipelines => {
+                self.head_pipelines = None;
+                None
+            }
+
+            Action::ShowGroupPipelines => {
+                let Some(group) = self.group_path() else {
+                    self.set_transient_status("Set group under [gitlab] to browse a group's pipelines".to_string());
+                    return None;
+                };
+                self.group_pipelines = Some(GroupPipelines::new(group.clone()));
+                Some(Effect::FetchGroupPipelines { group, page: 1 })
+            }
+
+            Action::GroupProjectsLoaded { group, project_count, next_page } => {
+                let view = self.group_pipelines.as_mut().filter(|view| view.group == group)?;
+                view.loading_page = false;
+                view.projects_loading += project_count;
+                view.next_page = next_page;
+                None
+            }
+
+            Action::GroupPipelinesLoaded { group, project_id, project_path, pipelines } => {
+                let view = self.group_pipelines.as_mut().filter(|view| view.group == group)?;
+                let selected = view.selected_id();
+                view.projects_loading = view.projects_loading.saturating_sub(1);
+                // A refresh replaces what was listed for the project
+                view.pipelines.retain(|row| row.project_id != project_id);
+                view.pipelines.extend(pipelines.into_iter().map(|pipeline| GroupPipeline {
+                    project_id,
+                    project_path: project_path.clone(),
+                    pipeline,
+                }));
+                view.reselect(selected);
+                None
+            }
+
+            Action::GroupProjectFailed { group, project_path, error } => {
+                let view = self.group_pipelines.as_mut().filter(|view| view.group == group)?;
+                view.projects_loading = view.projects_loading.saturating_sub(1);
+                if !view.failed_projects.contains(&project_path) {
+                    view.failed_projects.push(project_path.clone());
+                }
+                self.set_transient_status(format!("Couldn't load the pipelines of {}: {}", project_path, error));
+                None
+            }
+
+            Action::GroupPipelinesFailed { group, error } => {
+                let view = self.group_pipelines.as_mut().filter(|view| view.group == group)?;
+                view.loading_page = false;
+                view.error = Some(error);
+                None
+            }
+
+            Action::GroupPipelinesNext | Action::GroupPipelinesPrev => {
+                let view = self.group_pipelines.as_mut()?;
+                let count = view.visible().len();
+                if matches!(action, Action::GroupPipelinesNext) && view.selected + 1 >= count {
+                    // List the next projects past the end, or wrap around when there are none
+                    if let Some(page) = view.next_page.filter(|_| !view.loading_page) {
+                        view.loading_page = true;
+                        return Some(Effect::FetchGroupPipelines { group: view.group.clone(), page });
+                    }
+                }
+                if count > 0 {
+                    let step = if matches!(action, Action::GroupPipelinesNext) { 1 } else { count - 1 };
+                    view.selected = (view.selected + step) % count;
+                }
+                None
+            }
+
+            Action::ToggleGroupStatusFilter(status) => {
+                let view = self.group_pipelines.as_mut()?;
+                view.status_filter = if view.status_filter.as_ref() == Some(&status) { None } else { Some(status) };
+                view.selected = 0;
+                None
+            }
+
+            Action::ClearGroupStatusFilter => {
+                let view = self.group_pipelines.as_mut()?;
+                view.status_filter = None;
+                view.selected = 0;
+                None
+            }
+
+            Action::CycleGroupSort => {
+                let view = self.group_pipelines.as_mut()?;
+                let selected = view.selected_id();
+                view.sort = view.sort.next();
+                view.reselect(selected);
+                let label = view.sort.label();
+                self.set_transient_status(format!("Group pipelines: {}", label));
+                None
+            }
+
+            Action::RefreshGroupPipelines => {
+                let view = self.group_pipelines.as_mut()?;
+                view.error = None;
+                view.failed_projects.clear();
+                view.projects_loading = 0;
+                view.loading_page = true;
+                Some(Effect::FetchGroupPipelines { group: view.group.clone(), page: 1 })
+            }
+
+            Action::OpenGroupPipeline => {
+                let view = self.group_pipelines.take()?;
+                let row = view.visible().get(view.selected).map(|row| (*row).clone())?;
+                self.open_group_pipeline(row)
+            }
+
+            Action::CloseGroupPipelines => {
+                self.group_pipelines = None;
+                None
+            }
+
+            Action::PipelineTriggerFailed(message) => {
+                self.set_transient_status(format!("Could not start pipeline: {}", message));
+                None
+            }
+
+            Action::DownloadSelectedArtifacts => {
+                let job = self.selected_job()?.clone();
+                if let Some(download) = &self.artifact_download {
+                    let message = format!("Already downloading artifacts of '{}' (Esc to cancel)", download.job_name);
+                    self.set_transient_status(message);
+                    return None;
+                }
+                let Some(artifacts) = &job.artifacts_file else {
+                    self.set_transient_status(format!("Job '{}' has no artifacts", job.name));
+                    return None;
+                };
+
+                let path = crate::export::unused_path(crate::export::default_artifacts_filename(job.id, &job.name));
+                let cancel = Arc::new(AtomicBool::new(false));
+                self.artifact_download = Some(ArtifactDownload {
+                    job_id: job.id,
+                    job_name: job.name.clone(),
+                    path: path.clone(),
+                    received: 0,
+                    total: Some(artifacts.size),
+                    cancel: cancel.clone(),
+                });
+                Some(Effect::DownloadArtifacts { project_id: self.project_id, job_id: job.id, path, cancel })
+            }
+
+            Action::BrowseArtifacts => {
+                let job = self.selected_job()?.clone();
+                if job.artifacts_file.is_none() {
+                    self.set_transient_status(format!("Job '{}' has no artifacts", job.name));
+                    return None;
+                }
+                self.artifact_browser = Some(ArtifactBrowser {
+                    job_id: job.id,
+                    job_name: job.name,
+                    entries: None,
+                    error: None,
+                    selected: 0,
+                    file: None,
+                });
+                Some(Effect::FetchArtifactList { project_id: self.project_id, job_id: job.id })
+            }
+
+            Action::ArtifactListLoaded { job_id, entries } => {
+                let browser = self.artifact_browser.as_mut().filter(|b| b.job_id == job_id)?;
+                browser.entries = Some(entries);
+                None
+            }
+
+            Action::ArtifactListFailed { job_id, error } => {
+                let browser = self.artifact_browser.as_mut().filter(|b| b.job_id == job_id)?;
+                browser.error = Some(error);
+                None
+            }
+
+            Action::ArtifactBrowserNext | Action::ArtifactBrowserPrev => {
+                let browser = self.artifact_browser.as_mut()?;
+                let count = browser.entries.as_ref().map_or(0, Vec::len);
+                if count > 0 {
+                    let step = if matches!(action, Action::ArtifactBrowserNext) { 1 } else { count - 1 };
+                    browser.selected = (browser.selected + step) % count;
+                }
+                None
+            }
+
+            Action::OpenArtifactFile => {
+                let browser = self.artifact_browser.as_mut()?;
+                let entry = browser.entries.as_ref()?.get(browser.selected)?.clone();
+                if entry.size > MAX_VIEWED_FILE_SIZE {
+                    let message = format!("'{}' is too large to view, download the artifacts instead", entry.path);
+                    self.set_transient_status(message);
+                    return None;
+                }
+                browser.file = Some(ArtifactFileView { path: entry.path.clone(), ..Default::default() });
+                Some(Effect::FetchArtifactFile { project_id: self.project_id, job_id: browser.job_id, path: entry.path })
+            }
+
+            Action::ArtifactFileLoaded { job_id, path, text } => {
+                let file = self.open_artifact_file(job_id, &path)?;
+                file.lines = Some(text.lines().map(|line| line.replace('\t', "    ")).collect());
+                None
+            }
+
+            Action::ArtifactFileFailed { job_id, path, error } => {
+                let file = self.open_artifact_file(job_id, &path)?;
+                file.error = Some(error);
+                None
+            }
+
+            Action::ScrollArtifactFile(delta) => {
+                let file = self.artifact_browser.as_mut()?.file.as_mut()?;
+                let lines = file.lines.as_ref().map_or(0, Vec::len);
+                let max_scroll = lines.saturating_sub(file.height.max(1));
+                file.scroll = file.scroll.saturating_add_signed(delta).min(max_scroll);
+                None
+            }
+
+            Action::CloseArtifactFile => {
+                self.artifact_browser.as_mut()?.file = None;
+                None
+            }
+
+            Action::CloseArtifactBrowser => {
+                self.artifact_browser = None;
+                None
+            }
+
+            Action::ShowTestReport => {
+                let job = self.selected_job()?.clone();
+                if !job.has_test_report() {
+                    self.set_transient_status(format!("Job '{}' uploaded no JUnit report", job.name));
+                    return None;
+                }
+                if job.artifacts_file.is_none() {
+                    self.set_transient_status(format!("Job '{}' kept no artifacts archive to read its report from", job.name));
+                    return None;
+                }
+                self.test_report = Some(TestReportView {
+                    job_id: job.id,
+                    job_name: job.name.clone(),
+                    suite: None,
+                    error: None,
+                    show_all: false,
+                    collapsed: HashSet::new(),
+                    selected: 0,
+                });
+                Some(Effect::FetchTestReport { project_id: self.project_id, job_id: job.id, job_name: job.name })
+            }
+
+            Action::TestReportLoaded { job_id, suite } => {
+                let report = self.test_report.as_mut().filter(|r| r.job_id == job_id)?;
+                report.suite = Some(suite);
+                None
+            }
+
+            Action::TestReportFailed { job_id, error } => {
+                let report = self.test_report.as_mut().filter(|r| r.job_id == job_id)?;
+                report.error = Some(error);
+                None
+            }
+
+            Action::TestReportNext | Action::TestReportPrev => {
+                let report = self.test_report.as_mut()?;
+                let count = report.visible_cases().len();
+                if count > 0 {
+                    let step = if matches!(action, Action::TestReportNext) { 1 } else { count - 1 };
+                    report.selected = (report.selected + step) % count;
+                }
+                None
+            }
+
+            Action::ToggleTestCase => {
+                let report = self.test_report.as_mut()?;
+                let case = *report.visible_cases().get(report.selected)?;
+                if !report.collapsed.remove(&case) {
+                    report.collapsed.insert(case);
+                }
+                None
+            }
+
+            Action::ToggleAllTests => {
+                let report = self.test_report.as_mut()?;
+                // Keep the same test selected; failures are listed first either way
+                let case = report.visible_cases().get(report.selected).copied();
+                report.show_all = !report.show_all;
+                report.selected = case.and_then(|case| report.visible_cases().iter().position(|&i| i == case)).unwrap_or(0);
+                None
+            }
+
+            Action::CloseTestReport => {
+                self.test_report = None;
+                None
+            }
+
+            Action::ShowJobGraph => {
+                let pipeline = self.get_selected_pipeline()?;
+                let (pipeline_id, pipeline_iid) = (pipeline.id, pipeline.iid);
+                let Some(project_path) = self.project_path.clone() else {
+                    self.set_transient_status("The project's path isn't known yet, try again in a moment".to_string());
+                    return None;
+                };
+                self.job_graph = Some(JobGraphView { pipeline_id, pipeline_iid, needs: None, error: None, selected: 0 });
+                Some(Effect::FetchJobNeeds { project_path, pipeline_id, pipeline_iid })
+            }
+
+            Action::JobNeedsLoaded { pipeline_id, needs } => {
+                let view = self.job_graph.as_mut().filter(|view| view.pipeline_id == pipeline_id)?;
+                view.needs = Some(needs);
+                None
+            }
+
+            Action::JobNeedsFailed { pipeline_id, error } => {
+                let view = self.job_graph.as_mut().filter(|view| view.pipeline_id == pipeline_id)?;
+                view.error = Some(error);
+                None
+            }
+
+            Action::JobGraphNext | Action::JobGraphPrev => {
+                let count = self.job_graph_rows().len();
+                let view = self.job_graph.as_mut()?;
+                if count > 0 {
+                    let step = if matches!(action, Action::JobGraphNext) { 1 } else { count - 1 };
+                    view.selected = (view.selected + step) % count;
+                }
+                None
+            }
+
+            Action::OpenJobGraphLog => {
+                let selected = self.job_graph.as_ref()?.selected;
+                let job_id = self.job_graph_rows().get(selected)?.job.id;
+                self.job_graph = None;
+                if !self.select_job(job_id) {
+                    return None;
+                }
+                self.update(Action::OpenSelectedJobLog)
+            }
+
+            Action::CloseJobGraph => {
+                self.job_graph = None;
+                None
+            }
+
+            Action::CancelArtifactDownload => {
+                if let Some(download) = &self.artifact_download {
+                    download.cancel.store(true, Ordering::Relaxed);
+                }
+                None
+            }
+
+            Action::ArtifactDownloadProgress { job_id, received, total } => {
+                if let Some(download) = self.artifact_download.as_mut().filter(|d| d.job_id == job_id) {
+                    download.received = received;
+                    download.total = total.or(download.total);
+                }
+                None
+            }
+
+            Action::ArtifactsDownloaded { job_id, path } => {
+                if self.artifact_download.as_ref().is_some_and(|d| d.job_id == job_id) {
+                    self.artifact_download = None;
+                }
+                self.set_transient_status(format!("Artifacts saved to {}", path.display()));
+                None
+            }
+
+            Action::ArtifactDownloadCanceled { job_id } => {
+                if self.artifact_download.as_ref().is_some_and(|d| d.job_id == job_id) {
+                    self.artifact_download = None;
+                }
+                self.set_transient_status("Artifacts download canceled".to_string());
+                None
+            }
+
+            Action::ArtifactDownloadFailed { job_id, error } => {
+                if self.artifact_download.as_ref().is_some_and(|d| d.job_id == job_id) {
+                    self.artifact_download = None;
+                }
+                self.set_transient_status(format!("Artifacts download failed: {}", error));
+                None
+            }
+
+            Action::ConfirmPending => {
+                let confirmation = self.pending_confirmation.take()?;
+                self.update(confirmation.action)
+            }
+
+            Action::CancelConfirmation => {
+                self.pending_confirmation = None;
+                None
+            }
+
+            Action::JobRetried { mr_index, pipeline_id, job } => {
+                self.set_transient_status(format!("Retried job '{}' as #{}", job.name, job.id));
+                self.reload_pipeline(mr_index, pipeline_id)
+            }
+
+            Action::JobPlayed { mr_index, pipeline_id, job } => {
+                self.set_transient_status(format!("Started manual job '{}'", job.name));
+                self.reload_pipeline(mr_index, pipeline_id)
+            }
+
+            Action::CancelSelectedJob => {
+                let pipeline_id = self.get_selected_pipeline()?.id;
+                let job = self.selected_job()?.clone();
+                if !job.status.is_active() || job.status == JobStatus::Canceling {
+                    self.set_transient_status(format!("Job '{}' is not running", job.name));
+                    return None;
+                }
+                self.pending_confirmation = Some(Confirmation {
+                    prompt: format!("Cancel job '{}' (#{})?", job.name, job.id),
+                    action: Action::CancelJob { mr_index: self.selected_mr_index, pipeline_id, job_id: job.id },
+                });
+                None
+            }
+
+            Action::CancelSelectedPipeline => {
+                let pipeline = self.get_selected_pipeline()?.clone();
+                if !pipeline.status.is_active() || pipeline.status == PipelineStatus::Canceling {
+                    self.set_transient_status(format!("Pipeline #{} is not running", pipeline.iid));
+                    return None;
+                }
+                self.pending_confirmation = Some(Confirmation {
+                    prompt: format!("Cancel pipeline #{} and all its running jobs?", pipeline.iid),
+                    action: Action::CancelPipeline { mr_index: self.selected_mr_index, pipeline_id: pipeline.id },
+                });
+                None
+            }
+
+            Action::RetrySelectedPipeline => {
+                let pipeline = self.get_selected_pipeline()?.clone();
+                let jobs: Vec<(u64, String)> = self
+                    .get_selected_jobs()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|job| PipelineRetry::retries(job))
+                    .map(|job| (job.id, job.name.clone()))
+                    .collect();
+                if jobs.is_empty() {
+                    self.set_transient_status(format!("Pipeline #{} has no failed or canceled jobs", pipeline.iid));
+                    return None;
+                }
+                self.pending_confirmation = Some(Confirmation {
+                    prompt: format!(
+                        "Retry {} of pipeline #{}?",
+                        job_count(jobs.len(), "failed or canceled job"),
+                        pipeline.iid
+                    ),
+                    action: Action::RetryPipeline { mr_index: self.selected_mr_index, pipeline_id: pipeline.id },
+                });
+                None
+            }
+
+            Action::RetryPipeline { mr_index, pipeline_id } => {
+                let mr = self.tracked_mrs.get(mr_index)?;
+                let pipeline_iid = mr.pipelines.iter().find(|p| p.id == pipeline_id)?.iid;
+                let jobs = mr.jobs.get(&pipeline_id).map(Vec::as_slice).unwrap_or_default();
+                let jobs = jobs.iter().filter(|job| PipelineRetry::retries(job)).map(|job| (job.id, job.name.clone()));
+                self.pipeline_retry =
+                    Some(PipelineRetry { pipeline_id, pipeline_iid, jobs: jobs.collect(), accepted: false });
+                self.set_transient_status("Retrying pipeline...".to_string());
+                Some(Effect::RetryPipeline { mr_index, project_id: self.project_id, pipeline_id })
+            }
+
+            Action::PipelineRetried { mr_index, pipeline } => {
+                if let Some(retry) = self.pipeline_retry.as_mut().filter(|retry| retry.pipeline_id == pipeline.id) {
+                    retry.accepted = true;
+                }
+                self.set_transient_status(format!("Retrying the failed jobs of pipeline #{}...", pipeline.iid));
+                self.reload_pipeline(mr_index, pipeline.id)
+            }
+
+            Action::PipelineRetryFailed { pipeline_id, error } => {
+                self.pipeline_retry.take_if(|retry| retry.pipeline_id == pipeline_id);
+                self.set_transient_status(format!("Retry failed: {}", error));
+                None
+            }
+
+            Action::CancelJob { mr_index, pipeline_id, job_id } => {
+                self.set_transient_status("Canceling job...".to_string());
+                Some(Effect::CancelJob { mr_index, project_id: self.project_id, pipeline_id, job_id })
+            }
+
+            Action::CancelPipeline { mr_index, pipeline_id } => {
+                self.set_transient_status("Canceling pipeline...".to_string());
+                Some(Effect::CancelPipeline { mr_index, project_id: self.project_id, pipeline_id })
+            }
+
+            Action::JobCanceled { mr_index, pipeline_id, job } => {
+                let message = if matches!(job.status, JobStatus::Canceled | JobStatus::Canceling) {
+                    format!("Canceled job '{}'", job.name)
+                } else {
+                    // It finished on its own between the key press and the request
+                    let status = format!("{:?}", job.status).to_lowercase();
+                    format!("Job '{}' already finished ({})", job.name, status)
+                };
+                self.set_transient_status(message);
+                self.reload_pipeline(mr_index, pipeline_id)
+            }
+
+            Action::PipelineCanceled { mr_index, pipeline } => {
+                let message = if matches!(pipeline.status, PipelineStatus::Canceled | PipelineStatus::Canceling) {
+                    format!("Canceled pipeline #{}", pipeline.iid)
+                } else {
+                    let status = format!("{:?}", pipeline.status).to_lowercase();
+                    format!("Pipeline #{} already finished ({})", pipeline.iid, status)
+                };
+                self.set_transient_status(message);
+                self.reload_pipeline(mr_index, pipeline.id)
+            }
+
+            Action::JobActionFailed(message) => {
+                self.set_transient_status(message);
+                None
+            }
+
+            Action::Refresh => {
+                // Reset auto-refresh timer on manual refresh
+                self.last_auto_refresh = Instant::now();
+
+                // Set flag to refetch notes after refresh if currently viewing comments
+                self.refetch_notes_after_refresh = self.mode == AppMode::ViewingComments;
+
+                // Save the currently selected note ID if viewing comments
+                if self.refetch_notes_after_refresh {
+                    self.selected_note_id_before_refresh = self.get_selected_note_id();
+                }
+
+                // Clear all cached data including notes and job logs
+                for mr in &mut self.tracked_mrs {
+                    mr.notes_loaded = false;
+                    mr.notes.clear();
+                    mr.job_logs_cache.clear();
+                }
+
+                self.status_message = Some("Refreshing...".to_string());
+                Some(Effect::RefreshAll {
+                    project_id: self.project_id,
+                    source_branch: if self.focus_current_branch {
+                        self.current_branch.clone()
+                    } else {
+                        None
+                    },
+                })
+            }
+
+            Action::RemoveCurrentMr => {
+                if !self.tracked_mrs.is_empty() {
+                    self.tracked_mrs.remove(self.selected_mr_index);
+                    if self.selected_mr_index > 0 {
+                        self.selected_mr_index -= 1;
+                    }
+                    self.selected_job_index = 0;
+                }
+                None
+            }
+
+            Action::MergeRequestsLoaded(mrs) => {
+                // Initialize tracked MRs with the loaded data
+                for mr in mrs {
+                    if !self.tracked_mrs.iter().any(|tmr| tmr.mr.iid == mr.iid) {
+                        let tracked_mr = TrackedMergeRequest {
+                            mr: mr.clone(),
+                            pipelines: Vec::new(),
+                            jobs: HashMap::new(),
+                            job_logs_cache: HashMap::new(),
+                            notes: Vec::new(),
+                            notes_loaded: false,
+                            selected_pipeline_index: 0,
+                            selected_note_index: 0,
+                            loading: true,
+                            error: None,
+                            ..Default::default()
+                        };
+                        self.tracked_mrs.push(tracked_mr);
+                    }
+                }
+
+                self.status_message = Some(format!("Loaded {} merge requests", self.tracked_mrs.len()));
+
+                // Go back to where the last session left off, if its MR is still listed
+                if let Some(mr_iid) = self.restore_location.as_ref().map(|location| location.mr_iid) {
+                    match self.tracked_mrs.iter().position(|tmr| tmr.mr.iid == mr_iid) {
+                        Some(index) => self.selected_mr_index = index,
+                        None => {
+                            self.restore_location = None;
+                            self.set_transient_status(format!(
+                                "Couldn't restore the last location, !{} is no longer listed",
+                                mr_iid
+                            ));
+                        }
+                    }
+                }
+
+                // A pipeline opened from the group view is shown in its MR, or in the browser without one
+                let mut opened = None;
+                if let Some(pipeline) = self.pending_pipeline_focus.clone() {
+                    match self.tracked_mr_of(&pipeline) {
+                        Some(index) => self.selected_mr_index = index,
+                        None => {
+                            self.pending_pipeline_focus = None;
+                            self.set_transient_status(format!(
+                                "No open merge request runs pipeline #{}, opened it in the browser",
+                                pipeline.iid
+                            ));
+                            opened = Some(Effect::OpenUrl(pipeline.web_url));
+                        }
+                    }
+                }
+
+                // Fetch pipelines for each MR
+                let effects: Vec<Effect> = self
+                    .tracked_mrs
+                    .iter()
+                    .enumerate()
+                    .map(|(index, tmr)| Effect::FetchPipelines {
+                        mr_index: index,
+                        project_id: self.project_id,
+                        mr_iid: tmr.mr.iid,
+                        page: 1,
+                        status: self.pipeline_status_filter.clone(),
+                    })
+                    .chain(opened)
+                    .collect();
+
+                Effect::batch(effects)
+            }
+
+            Action::PipelinesLoaded { mr_index, page, status, mut pipelines, next_page } => {
+                if status != self.pipeline_status_filter {
+                    return None; // Fetched before the filter changed
+                }
+                if let Some(status) = &status {
+                    pipelines.retain(|p| &p.status == status);
+                }
+                if page > 1 {
+                    // An older page: append it below what is already shown
+                    if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
+                        let known: HashSet<u64> = mr.pipelines.iter().map(|p| p.id).collect();
+                        mr.pipelines.extend(pipelines.into_iter().filter(|p| !known.contains(&p.id)));
+                        mr.pipelines_next_page = next_page;
+                        mr.loading_more_pipelines = false;
+                        if self.pipeline_sort != PipelineSort::Newest {
+                            sort_pipelines(mr, self.pipeline_sort);
+                        }
+                    }
+                    return None;
+                }
+
+                let effect = 'loaded: {
+                    if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
+                        // Keep older pages fetched earlier, and where to continue after them
+                        let selected = mr.pipelines.get(mr.selected_pipeline_index).map(|p| p.id);
+                        // Lists leave out who started a pipeline, keep the user fetched with it alone
+                        for pipeline in pipelines.iter_mut().filter(|p| p.user.is_none()) {
+                            pipeline.user = mr.pipelines.iter().find(|p| p.id == pipeline.id).and_then(|p| p.user.clone());
+                        }
+                        let fresh: HashSet<u64> = pipelines.iter().map(|p| p.id).collect();
+                        let older: Vec<Pipeline> =
+                            mr.pipelines.drain(..).filter(|p| !fresh.contains(&p.id)).collect();
+                        if older.is_empty() {
+                            mr.pipelines_next_page = next_page;
+                        }
+                        mr.pipelines = pipelines;
+                        mr.pipelines.extend(older);
+                        mr.loading = false;
+                        if self.pipeline_sort != PipelineSort::Newest {
+                            sort_pipelines_selecting(mr, self.pipeline_sort, selected);
+                        }
+
+                        // Check if we need to refetch notes after refresh (only for selected MR)
+                        if self.refetch_notes_after_refresh && mr_index == self.selected_mr_index {
+                            self.refetch_notes_after_refresh = false;
+                            self.status_message = Some("Reloading comments...".to_string());
+                            break 'loaded Some(Effect::FetchNotes {
+                                mr_index,
+                                project_id: self.project_id,
+                                mr_iid: mr.mr.iid,
+                            });
+                        }
+
+                        // Fetch jobs for the latest pipeline, wherever the sort put it
+                        if let Some(pipeline) = mr.pipelines.iter().max_by_key(|p| p.id) {
+                            break 'loaded Some(Effect::FetchJobs {
+                                mr_index,
+                                project_id: self.project_id,
+                                pipeline_id: pipeline.id,
+                            });
+                        }
+                    }
+                    None
+                };
+                // The jobs of a restored pipeline or one opened from the group view are fetched instead
+                let effect = self.restore_pipeline(mr_index).or_else(|| self.focus_pending_pipeline(mr_index)).or(effect);
+                self.with_live_poll(effect)
+            }
+
+            Action::JobsLoaded {
+                mr_index,
+                pipeline_id,
+                mut jobs,
+            } => {
+                let notifications = self.finished_watched_jobs(mr_index, pipeline_id, &jobs);
+                let retry = self.pipeline_retry.take_if(|retry| retry.pipeline_id == pipeline_id && retry.accepted);
+                if let Some(retry) = retry {
+                    self.report_pipeline_retry(retry, &jobs);
+                }
+                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
+                    // Sort jobs: failed first, then running, pending, etc.
+                    jobs.sort_by_key(|job| job_status_rank(&job.status));
+                    mr.jobs.insert(pipeline_id, jobs);
+                }
+                // Statuses may have reordered the jobs, keep the same one selected
+                let mut open_log = None;
+                let mut prefetch = None;
+                let mut fetch_pipeline = None;
+                if mr_index == self.selected_mr_index && self.get_selected_pipeline().is_some_and(|p| p.id == pipeline_id) {
+                    if self.get_selected_pipeline().is_some_and(|p| p.user.is_none())
+                        && self.pipeline_users_requested.insert(pipeline_id)
+                    {
+                        fetch_pipeline = Some(Effect::FetchPipeline { mr_index, project_id: self.project_id, pipeline_id });
+                    }
+                    // Running jobs are timed anew only here, where the selection follows its job
+                    self.job_sort_time = chrono::Utc::now();
+                    open_log = self.restore_job_selection();
+                    let restored = self.restore_job(mr_index, pipeline_id);
+                    open_log = open_log.or(restored);
+                    prefetch = self.prefetch_logs(self.log_prefetch_mode);
+                }
+                self.last_refresh = Some(chrono::Utc::now());
+                let effect = Effect::batch(
+                    open_log.into_iter().chain(prefetch).chain(notifications).chain(fetch_pipeline).collect(),
+                );
+                self.with_live_poll(effect)
+            }
+
+            Action::PipelineLoaded { mr_index, pipeline } => {
+                let restoring = self.restore_location.as_ref().is_some_and(|location| location.pipeline_id == pipeline.id);
+                let mr = self.tracked_mrs.get_mut(mr_index)?;
+                match mr.pipelines.iter_mut().find(|p| p.id == pipeline.id) {
+                    Some(listed) => listed.user = pipeline.user,
+                    None if restoring => {
+                        // Older than the pages loaded so far, listed after them
+                        mr.pipelines.push(pipeline);
+                        if self.pipeline_sort != PipelineSort::Newest {
+                            sort_pipelines(mr, self.pipeline_sort);
+                        }
+                        let effect = self.restore_pipeline(mr_index);
+                        return self.with_live_poll(effect);
+                    }
+                    None => {}
+                }
+                None
+            }
+
+            Action::PipelineFetchFailed { mr_index, pipeline_id, error } => {
+                if self.restore_location.as_ref().is_none_or(|location| location.pipeline_id != pipeline_id) {
+                    return None; // Only who started it is missing
+                }
+                self.restore_location = None;
+                self.set_transient_status(format!("Couldn't restore the last location, pipeline #{}: {}", pipeline_id, error));
+                let pipeline_id = self.tracked_mrs.get(mr_index)?.pipelines.iter().map(|p| p.id).max()?;
+                self.with_live_poll(Some(Effect::FetchJobs { mr_index, project_id: self.project_id, pipeline_id }))
+            }
+
+            Action::ToggleWatchJob => {
+                let job = self.selected_job()?;
+                let (id, name, active) = (job.id, job.name.clone(), job.status.is_active());
+                if self.watched_jobs.remove(&id) {
+                    self.set_transient_status(format!("Stopped watching '{}'", name));
+                    return None;
+                }
+                if !active {
+                    self.set_transient_status(format!("'{}' has already finished", name));
+                    return None;
+                }
+                if self.live_refresh_interval.is_none() {
+                    self.set_transient_status(WATCH_NEEDS_LIVE_REFRESH.to_string());
+                    return None;
+                }
+                self.watched_jobs.insert(id);
+                self.set_transient_status(format!("Watching '{}', you'll be notified when it finishes", name));
+                self.with_live_poll(None)
+            }
+
+            Action::ToggleWatchPipeline => {
+                let pipeline = self.get_selected_pipeline()?;
+                let (id, active) = (pipeline.id, pipeline.status.is_active());
+                if self.watched_pipelines.remove(&id) {
+                    self.set_transient_status(format!("Stopped watching pipeline #{}", id));
+                    return None;
+                }
+                if !active {
+                    self.set_transient_status(format!("Pipeline #{} has already finished", id));
+                    return None;
+                }
+                if self.live_refresh_interval.is_none() {
+                    self.set_transient_status(WATCH_NEEDS_LIVE_REFRESH.to_string());
+                    return None;
+                }
+                self.watched_pipelines.insert(id);
+                self.set_transient_status(format!("Watching pipeline #{}, you'll be notified as its jobs finish", id));
+                self.with_live_poll(None)
+            }
+
+            Action::LivePollDue => {
+                self.live_poll_scheduled = false;
+                if let (Some(resume_at), Some(interval)) = (self.rate_limit_resume_at(), self.live_refresh_interval) {
+                    // Check back once the rate limit is over
+                    self.live_poll_scheduled = true;
+                    let delay = (resume_at - chrono::Utc::now()).to_std().unwrap_or(interval);
+                    return Some(Effect::ScheduleLivePoll { delay });
+                }
+                let mut effects = self.live_poll_effects();
+                if effects.is_empty() {
+                    return None; // Everything finished, polling stops until something runs again
+                }
+                // Keep polling even if one of these requests fails
+                if let Some(delay) = self.live_refresh_interval {
+                    self.live_poll_scheduled = true;
+                    effects.push(Effect::ScheduleLivePoll { delay });
+                }
+                Effect::batch(effects)
+            }
+
+            Action::JobTraceChunkLoaded { job_id, job_name, offset, chunk } => {
+                if offset == 0 {
+                    if !self.log_viewer_open() || self.log_job_id != Some(job_id) {
+                        return None; // Viewer closed or switched job while loading
+                    }
+                    if chunk.complete && chunk.data.is_empty() && self.trace_erased(job_id) {
+                        self.show_log_expired(job_id, job_name);
+                        return None;
+                    }
+                    // First chunk replaces the loading state, the rest streams in behind it
+                    self.log_loading_since = None;
+                    self.set_log_content(job_id, String::new(), job_name.clone());
+                    self.log_fetch_complete = false;
+                } else {
+                    let received = self.log_content.as_ref().map_or(0, |b| b.len_bytes()) as u64;
+                    if !self.log_viewer_open()
+                        || self.log_job_id != Some(job_id)
+                        || self.log_fetch_complete
+                        || received != offset
+                    {
+                        return None; // Viewer closed, switched job or reloaded: stop downloading
+                    }
+                }
+
+                let next_offset = offset + chunk.data.len() as u64;
+                self.append_log_chunk(&chunk);
+
+                if !chunk.complete {
+                    return Some(Effect::FetchJobTrace {
+                        project_id: self.project_id,
+                        job_id,
+                        job_name,
+                        offset: next_offset,
+                    });
+                }
+
+                self.restore_log_view();
+                // Cache the complete log in the current MR, and on disk unless it came from there. A
+                // log too big to be kept in memory is downloaded again next time instead
+                let log = self.log_content.as_ref()?.shared()?;
+                if let Some(mr) = self.tracked_mrs.get_mut(self.selected_mr_index) {
+                    mr.job_logs_cache.insert(job_id, Arc::clone(&log));
+                }
+                let save = self.offline_cached_at.is_none().then_some(Effect::SaveJobLog {
+                    project_id: self.project_id,
+                    job_id,
+                    log,
+                });
+                // Follow mode may have been switched on while downloading
+                Effect::batch(save.into_iter().chain(self.next_log_poll()).collect())
+            }
+
+            Action::JobTraceFailed { job_id, error } => {
+                if self.log_job_id != Some(job_id) {
+                    return None;
+                }
+                self.log_loading_since = None;
+                self.log_fetch_complete = true;
+                self.log_error = Some(error);
+                None
+            }
+
+            Action::JobTraceExpired { job_id } => {
+                if self.log_job_id != Some(job_id) {
+                    return None;
+                }
+                let job_name = self.log_job_name.clone().unwrap_or_default();
+                match &self.log_content {
+                    // Erased while the rest of the log was downloading
+                    Some(_) => {
+                        self.log_fetch_complete = true;
+                        self.log_error = Some("the trace was erased meanwhile".to_string());
+                    }
+                    None => self.show_log_expired(job_id, job_name),
+                }
+                None
+            }
+
+            Action::RetryLogFetch => {
+                self.log_error.as_ref()?;
+                let job_id = self.log_job_id?;
+                let job_name = self.log_job_name.clone().unwrap_or_default();
+                let offset = match &self.log_content {
+                    // Resume a download that broke off part way
+                    Some(buffer) => {
+                        self.log_error = None;
+                        self.log_fetch_complete = false;
+                        buffer.len_bytes() as u64
+                    }
+                    None => {
+                        self.begin_log_fetch(job_id, job_name.clone());
+                        0
+                    }
+                };
+                Some(Effect::FetchJobTrace { project_id: self.project_id, job_id, job_name, offset })
+            }
+
+            Action::CloseLogViewer => {
+                self.save_log_view();
+                if self.log_tabs.len() > 1 {
+                    // The next tab takes the closed one's place, or the previous one at the end
+                    let closed = self.active_log_tab;
+                    self.switch_log_tab(if closed + 1 < self.log_tabs.len() { closed + 1 } else { closed - 1 });
+                    self.log_tabs.remove(closed);
+                    if self.active_log_tab > closed {
+                        self.active_log_tab -= 1;
+                    }
+                    return self.next_log_poll();
+                }
+                self.log_tabs.clear();
+                self.active_log_tab = 0;
+                self.mode = AppMode::Normal;
+                self.log_content = None;
+                self.log_loading_since = None;
+                self.log_error = None;
+                self.log_fetch_complete = true;
+                self.log_total_bytes = None;
+                // Processed lines stay cached with their key so reopening the same log is instant
+                self.log_sections.clear();
+                self.collapsed_sections.clear();
+                self.log_visible_lines.clear();
+                self.log_error_lines.clear();
+                self.log_warning_lines.clear();
+                self.log_row_break_lines.clear();
+                self.log_started_at = None;
+                self.log_outcome = None;
+                self.current_error_line = None;
+                self.pending_key = None;
+                self.pending_count = None;
+                self.log_job_name = None;
+                self.log_job_id = None;
+                self.log_selection = None;
+                self.log_visual_mode = false;
+        self.log_visual_mode = false;
+                self.follow_mode = false;
+                self.log_scroll_offset = 0;
+                self.log_scroll_row = 0;
+                self.search_query.clear();
+                self.search_regex = None;
+                self.search_regex_error = false;
+                self.search_results.clear();
+                self.search_scan = None;
+                self.current_search_result = 0;
+                self.is_searching = false;
+                self.filter_query.clear();
+                self.filter_inverse = false;
+                self.filter_regex = None;
+                self.filtered_line_indices = None;
+                self.is_filtering = false;
+                None
+            }
+
+            Action::HideLogViewer => {
+                if self.log_tabs.is_empty() {
+                    return self.handle_action(Action::CloseLogViewer);
+                }
+                if self.mode == AppMode::ViewingLog {
+                    // Back to the job list, the tabs stay open and keep loading
+                    self.pending_key = None;
+                    self.pending_count = None;
+                    self.mode = AppMode::Normal;
+                }
+                None
+            }
+
+            Action::ShowLogTabs => {
+                if self.mode == AppMode::Normal && !self.log_tabs.is_empty() {
+                    self.mode = AppMode::ViewingLog;
+                }
+                None
+            }
+
+            Action::NextLogTab | Action::PrevLogTab => {
+                let count = self.log_tabs.len();
+                if self.mode == AppMode::ViewingLog && count > 1 {
+                    let step = if matches!(action, Action::NextLogTab) { 1 } else { count - 1 };
+                    self.switch_log_tab((self.active_log_tab + step) % count);
+                }
+                None
+            }
+
+            Action::GoToLogTab(number) => {
+                if self.mode == AppMode::ViewingLog {
+                    match number.checked_sub(1).filter(|&index| index < self.log_tabs.len()) {
+                        Some(index) => self.switch_log_tab(index),
+                        None => self.set_transient_status(format!("No log tab {}", number)),
+                    }
+                }
+                None
+            }
+
+            Action::StartVisualSelection => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                if self.log_visual_mode {
+                    return self.handle_action(Action::CancelVisualSelection);
+                }
+                // From the end of a clicked selection, or the top line on screen
+                let line = match self.log_selection {
+                    Some((_, end)) => end,
+                    None => *self.log_visible_lines.get(self.log_scroll_offset)?,
+                };
+                self.log_selection = Some((line, line));
+                self.log_visual_mode = true;
+                None
+            }
+
+            Action::CancelVisualSelection => {
+                self.log_visual_mode = false;
+                self.log_selection = None;
+                None
+            }
+
+            Action::ScrollLogUp
+            | Action::ScrollLogDown
+            | Action::ScrollLogFastUp
+            | Action::ScrollLogFastDown
+            | Action::ScrollLogPageUp
+            | Action::ScrollLogPageDown
+            | Action::ScrollLogHalfPageUp
+            | Action::ScrollLogHalfPageDown
+            | Action::ScrollLogHome
+            | Action::ScrollLogEnd
+                if self.log_visual_mode && self.mode == AppMode::ViewingLog =>
+            {
+                let page = self.log_viewport_height.max(1) as isize;
+                let delta = match action {
+                    Action::ScrollLogUp => -1,
+                    Action::ScrollLogDown => 1,
+                    Action::ScrollLogFastUp => -(self.fast_scroll_step as isize),
+                    Action::ScrollLogFastDown => self.fast_scroll_step as isize,
+                    Action::ScrollLogPageUp => -page,
+                    Action::ScrollLogPageDown => page,
+                    Action::ScrollLogHalfPageUp => -(page / 2).max(1),
+                    Action::ScrollLogHalfPageDown => (page / 2).max(1),
+                    Action::ScrollLogHome => isize::MIN,
+                    _ => isize::MAX,
+                };
+                self.move_visual_selection(delta);
+                None
+            }
+
+            Action::ScrollLogUp => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_up_by(self.scroll_step);
+                }
+                None
+            }
+
+            Action::ScrollLogDown => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_down_by(self.scroll_step);
+                }
+                None
+            }
+
+            Action::ScrollLogFastUp => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_up_by(self.fast_scroll_step);
+                }
+                None
+            }
+
+            Action::ScrollLogFastDown => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_down_by(self.fast_scroll_step);
+                }
+                None
+            }
+
+            Action::ScrollLogUpBy(rows) => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_up_by(rows);
+                }
+                None
+            }
+
+            Action::ScrollLogDownBy(rows) => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_down_by(rows);
+                }
+                None
+            }
+
+            Action::SelectLogLine { row, extend } => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                let line_number = self.log_line_at_row(row)?;
+                self.log_selection = match self.log_selection {
+                    Some((anchor, _)) if extend => Some((anchor, line_number)),
+                    _ => Some((line_number, line_number)),
+                };
+                None
+            }
+
+            Action::OpenLogUrl => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                let lines = match self.log_selection {
+                    Some((_, end)) => vec![end],
+                    None => self.log_visible_lines[self.log_viewport_lines()].to_vec(),
+                };
+                self.open_log_url(&lines)
+            }
+
+            Action::OpenLogUrlAt { row } => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                let line_number = self.log_line_at_row(row)?;
+                self.log_selection = Some((line_number, line_number));
+                self.open_log_url(&[line_number])
+            }
+
+            Action::ScrollLogPageUp => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_up_by(self.log_viewport_height.max(1));
+                }
+                None
+            }
+
+            Action::ScrollLogPageDown => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_down_by(self.log_viewport_height.max(1));
+                }
+                None
+            }
+
+            Action::ScrollLogHalfPageUp => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_up_by((self.log_viewport_height / 2).max(1));
+                }
+                None
+            }
+
+            Action::ScrollLogHalfPageDown => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_down_by((self.log_viewport_height / 2).max(1));
+                }
+                None
+            }
+
+            Action::JumpToScrollbarRow(row) => {
+                let track = self.log_content_area?.height as usize;
+                let (max_offset, max_row) = self.max_log_scroll_position();
+                // Top and bottom of the track reach the ends of the log
+                let offset = (row * max_offset + track.saturating_sub(1) / 2) / track.saturating_sub(1).max(1);
+                (self.log_scroll_offset, self.log_scroll_row) =
+                    if offset >= max_offset { (max_offset, max_row) } else { (offset, 0) };
+                self.follow_mode = false;
+                None
+            }
+
+            Action::JumpToMinimapRow(row) => {
+                let track = self.log_content_area?.height as usize;
+                let len = self.log_visible_lines.len();
+                // The minimap spans the whole log, center the stretch a row stands for
+                let index = (row * len + len / 2) / track.max(1);
+                self.log_scroll_offset = index.saturating_sub(track / 2).min(self.max_log_scroll_offset());
+                self.log_scroll_row = 0;
+                self.follow_mode = false;
+                None
+            }
+
+            Action::ScrollLogHome => {
+                if self.mode == AppMode::ViewingLog {
+                    self.log_scroll_offset = 0;
+                    self.log_scroll_row = 0;
+                    self.follow_mode = false; // Reading back disables following
+                }
+                None
+            }
+
+            Action::ScrollLogEnd => {
+                if self.mode == AppMode::ViewingLog {
+                    self.scroll_log_to_bottom();
+                }
+                None
+            }
+
+            Action::ToggleFollowMode => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                self.follow_mode = !self.follow_mode;
+                self.follow_by_default = self.follow_mode; // An explicit choice sticks for the next log
+                if self.follow_mode {
+                    self.scroll_log_to_bottom();
+                    if !self.log_job_running {
+                        self.set_transient_status("Job is not running, log will not update".to_string());
+                    }
+                    return self.next_log_poll();
+                }
+                None
+            }
+
+            Action::JobTraceRefreshed { job_id, trace, status } => {
+                self.log_poll_in_flight = false;
+                if !self.log_viewer_open() || self.log_job_id != Some(job_id) {
+                    return None; // Viewer closed or switched to another job
+                }
+
+                let was_running = self.log_job_running;
+                self.log_job_running = matches!(status, JobStatus::Running | JobStatus::Pending);
+                if was_running && !self.log_job_running {
+                    self.set_transient_status(format!("Job finished: {:?}", status).to_lowercase());
+                }
+
+                let trace = Arc::new(trace);
+                if let Some(mr) = self.tracked_mrs.get_mut(self.selected_mr_index) {
+                    if trace.len() <= crate::log_buffer::RESIDENT_LIMIT {
+                        mr.job_logs_cache.insert(job_id, Arc::clone(&trace));
+                    } else {
+                        mr.job_logs_cache.remove(&job_id);
+                    }
+                }
+                if !self.log_content.as_ref().is_some_and(|buffer| buffer.has_text(&trace)) {
+                    self.refresh_log_content(Arc::clone(&trace));
+                }
+                // The final trace replaces whatever part of the log was saved while it ran
+                let save = Effect::SaveJobLog { project_id: self.project_id, job_id, log: trace };
+                Effect::batch(std::iter::once(save).chain(self.next_log_poll()).collect())
+            }
+
+            Action::JobTraceStreamed { job_id, offset, chunk, status } => {
+                self.log_poll_in_flight = false;
+                if !self.log_viewer_open() || self.log_job_id != Some(job_id) {
+                    return None; // Viewer closed or switched to another job
+                }
+                // Polling goes on until the job is over, then fetches the full trace
+                self.log_job_running = status.is_active();
+
+                let received = self.log_content.as_ref().map_or(0, |b| b.len_bytes()) as u64;
+                if received != offset {
+                    return self.next_log_poll(); // Reloaded meanwhile, ask again from the new end
+                }
+                self.append_log_chunk(&chunk);
+                if chunk.complete {
+                    self.next_log_poll()
+                } else {
+                    // More output than one chunk arrived since the last poll, catch up right away
+                    self.log_poll(std::time::Duration::ZERO)
+                }
+            }
+
+            Action::JobTracePollFailed { job_id, error } => {
+                self.log_poll_in_flight = false;
+                if self.log_job_id == Some(job_id) {
+                    self.follow_mode = false;
+                    self.set_transient_status(format!("Follow stopped: {}", error));
+                }
+                None
+            }
+
+            Action::ToggleStatusBar => {
+                self.show_status_bar = !self.show_status_bar;
+                None
+            }
+
+            // While a filter is applied, + / - change how many context lines it keeps instead
+            Action::GrowLogPopup | Action::ShrinkLogPopup if self.filter_regex.is_some() => {
+                self.filter_context = match action {
+                    Action::GrowLogPopup => (self.filter_context + 1).min(MAX_FILTER_CONTEXT),
+                    _ => self.filter_context.saturating_sub(1),
+                };
+                self.refilter_log();
+                let plural = if self.filter_context == 1 { "" } else { "s" };
+                self.set_transient_status(format!("{} line{} of context around matches", self.filter_context, plural));
+                None
+            }
+
+            Action::GrowLogPopup | Action::ShrinkLogPopup => {
+                let percent = match action {
+                    Action::GrowLogPopup => self.log_popup_percent + LOG_POPUP_STEP,
+                    _ => self.log_popup_percent.saturating_sub(LOG_POPUP_STEP),
+                };
+                self.log_popup_percent = percent.clamp(MIN_LOG_POPUP_PERCENT, 100);
+                self.set_transient_status(format!("Log viewer at {}% of the screen", self.log_popup_percent));
+                None
+            }
+
+            Action::CurrentUserLoaded(user) => {
+                self.username = Some(user.username);
+                None
+            }
+
+            Action::ProjectPathLoaded(path) => {
+                self.project_path = Some(path);
+                None
+            }
+
+            Action::ToggleMinimap => {
+                if self.mode == AppMode::ViewingLog {
+                    // The minimap takes a column, which changes where long lines wrap
+                    self.keep_top_log_line(|app| app.show_minimap = !app.show_minimap);
+                }
+                None
+            }
+
+            Action::ToggleLineNumbers => {
+                if self.mode == AppMode::ViewingLog {
+                    self.show_line_numbers = !self.show_line_numbers;
+                }
+                None
+            }
+
+            Action::ToggleWrap => {
+                if self.mode == AppMode::ViewingLog {
+                    self.keep_top_log_line(|app| app.wrap_enabled = !app.wrap_enabled);
+                    self.horizontal_scroll_offset = 0;
+                }
+                None
+            }
+
+            Action::ScrollLogLeft => {
+                if self.mode == AppMode::ViewingLog && !self.wrap_enabled {
+                    self.horizontal_scroll_offset = self.horizontal_scroll_offset.saturating_sub(HORIZONTAL_SCROLL_STEP);
+                }
+                None
+            }
+
+            Action::ScrollLogRight => {
+                if self.mode == AppMode::ViewingLog && !self.wrap_enabled {
+                    let longest = self
+                        .log_processed_lines
+                        .rows()
+                        .iter()
+                        .map(|line| line.width())
+                        .max()
+                        .unwrap_or(0);
+                    self.horizontal_scroll_offset = (self.horizontal_scroll_offset + HORIZONTAL_SCROLL_STEP)
+                        .min(longest.saturating_sub(1));
+                }
+                None
+            }
+
+            Action::ToggleTimestampMode => {
+                if self.mode == AppMode::ViewingLog {
+                    self.timestamp_mode = match self.timestamp_mode {
+                        TimestampDisplayMode::Hidden => TimestampDisplayMode::DateOnly,
+                        TimestampDisplayMode::DateOnly => TimestampDisplayMode::Full,
+                        TimestampDisplayMode::Full => TimestampDisplayMode::Relative,
+                        TimestampDisplayMode::Relative => TimestampDisplayMode::Hidden,
+                    };
+                    // Reprocess lines with new timestamp mode
+                    self.keep_top_log_line(Self::redisplay_log_lines);
+                }
+                None
+            }
+
+            Action::ToggleRawView => {
+                if self.mode == AppMode::ViewingLog {
+                    self.raw_log_view = !self.raw_log_view;
+                    self.keep_top_log_line(Self::redisplay_log_lines);
+                }
+                None
+            }
+
+            Action::PendingKey(key) => {
+                self.pending_key = Some(key);
+                None
+            }
+
+            Action::CancelPendingKey => {
+                self.pending_key = None;
+                None
+            }
+
+            Action::PushCountDigit(digit) => {
+                let count = self.active_count().unwrap_or(0);
+                let count = (count * 10 + digit as usize).min(MAX_COUNT_PREFIX);
+                self.pending_count = Some((count, Instant::now()));
+                None
+            }
+
+            Action::Repeat { count, action } => {
+                self.pending_count = None;
+                // A count before the next-tab key picks the tab, like vim's `{count}gt`
+                if matches!(*action, Action::NextLogTab) {
+                    return self.update(Action::GoToLogTab(count));
+                }
+                if !action.honors_count() {
+                    return self.update(*action);
+                }
+                let mut effect = None;
+                for _ in 0..count {
+                    effect = self.update((*action).clone()).or(effect);
+                }
+                effect
+            }
+
+            Action::ToggleSection => {
+                self.pending_key = None;
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+
+                let (name, start_line) = self
+                    .section_at_cursor()
+                    .map(|section| (section.name.clone(), section.start_line))?;
+
+                if !self.collapsed_sections.remove(&name) {
+                    self.collapsed_sections.insert(name);
+                }
+                self.rebuild_visible_lines();
+
+                // Keep the toggled header in view when folding from inside the section
+                if let Some(header_row) = self.visible_row_of_line(start_line) {
+                    if header_row < self.log_scroll_offset {
+                        self.log_scroll_offset = header_row;
+                        self.log_scroll_row = 0;
+                    }
+                }
+                None
+            }
+
+            Action::NextErrorLine => {
+                if self.mode == AppMode::ViewingLog && !self.log_error_lines.is_empty() {
+                    let next = match self.current_error_line {
+                        Some(idx) => (idx + 1) % self.log_error_lines.len(),
+                        None => {
+                            // Start from the first error at or below the top of the view
+                            let top_line = self.log_visible_lines.get(self.log_scroll_offset).copied().unwrap_or(0);
+                            self.log_error_lines
+                                .iter()
+                                .position(|&line| line >= top_line)
+                                .unwrap_or(0)
+                        }
+                    };
+                    self.current_error_line = Some(next);
+                    self.center_log_line(self.log_error_lines[next]);
+                }
+                None
+            }
+
+            Action::PrevErrorLine => {
+                if self.mode == AppMode::ViewingLog && !self.log_error_lines.is_empty() {
+                    let len = self.log_error_lines.len();
+                    let prev = match self.current_error_line {
+                        Some(idx) => idx.checked_sub(1).unwrap_or(len - 1),
+                        None => {
+                            // Start from the last error above the top of the view
+                            let top_line = self.log_visible_lines.get(self.log_scroll_offset).copied().unwrap_or(0);
+                            self.log_error_lines
+                                .iter()
+                                .rposition(|&line| line < top_line)
+                                .unwrap_or(len - 1)
+                        }
+                    };
+                    self.current_error_line = Some(prev);
+                    self.center_log_line(self.log_error_lines[prev]);
+                }
+                None
+            }
+
+            Action::ToggleBookmark => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                let job_id = self.log_job_id?;
+                let top_line = *self.log_visible_lines.get(self.log_scroll_offset)?;
+                let bookmarks = self.log_bookmarks.entry(job_id).or_default();
+                let message = if bookmarks.remove(&top_line) {
+                    format!("Removed the bookmark on line {}", top_line + 1)
+                } else {
+                    bookmarks.insert(top_line);
+                    format!("Bookmarked line {} ({} in this log)", top_line + 1, bookmarks.len())
+                };
+                self.set_transient_status(message);
+                None
+            }
+
+            Action::NextBookmark => {
+                if self.mode == AppMode::ViewingLog {
+                    let bookmarks = self.reachable_bookmarks();
+                    let top_line = self.log_visible_lines.get(self.log_scroll_offset).copied().unwrap_or(0);
+                    let next = bookmarks.iter().find(|&&line| line > top_line).or(bookmarks.first());
+                    if let Some(&line) = next {
+                        self.jump_to_bookmark(line);
+                    }
+                }
+                None
+            }
+
+            Action::PrevBookmark => {
+                if self.mode == AppMode::ViewingLog {
+                    let bookmarks = self.reachable_bookmarks();
+                    let top_line = self.log_visible_lines.get(self.log_scroll_offset).copied().unwrap_or(0);
+                    let prev = bookmarks.iter().rev().find(|&&line| line < top_line).or(bookmarks.last());
+                    if let Some(&line) = prev {
+                        self.jump_to_bookmark(line);
+                    }
+                }
+                None
+            }
+
+            Action::CopyVisibleLog => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                if self.log_selection.is_some() {
+                    let lines: Vec<usize> = self
+                        .log_visible_lines
+                        .iter()
+                        .copied()
+                        .filter(|&idx| self.is_log_line_selected(idx))
+                        .collect();
+                    // Yanking ends a visual selection, like in vim
+                    if self.log_visual_mode {
+                        self.handle_action(Action::CancelVisualSelection);
+                    }
+                    return self.copy_log_lines(&lines);
+                }
+                let lines = self.log_visible_lines[self.log_viewport_lines()].to_vec();
+                self.copy_log_lines(&lines)
+            }
+
+            Action::CopyFullLog => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                let lines: Vec<usize> = (0..self.log_line_count()).collect();
+                self.copy_log_lines(&lines)
+            }
+
+            Action::ExportLog { raw } => {
+                if self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                let job_id = self.log_job_id?;
+                let job_name = self.log_job_name.clone().unwrap_or_default();
+                let content = if raw {
+                    // Untouched apart from secrets, like everything else that leaves the viewer
+                    crate::log_processor::redact_secrets(&self.log_content.as_ref()?.to_text()).into_owned()
+                } else {
+                    let lines: Vec<usize> = (0..self.log_line_count()).collect();
+                    self.processed_log_text(&lines)
+                };
+
+                let path = crate::export::default_export_filename(job_id, &job_name, raw);
+                self.set_transient_status(format!("Exporting log to {}...", path.display()));
+                self.exports_in_flight += 1;
+                Some(Effect::ExportLog { path, content })
+            }
+
+            Action::LogExported(path) => {
+                self.exports_in_flight = self.exports_in_flight.saturating_sub(1);
+                self.set_transient_status(format!("Log exported to {}", path.display()));
+                None
+            }
+
+            Action::LogExportFailed(reason) => {
+                self.exports_in_flight = self.exports_in_flight.saturating_sub(1);
+                self.set_transient_status(format!("Log export failed: {}", reason));
+                None
+            }
+
+            Action::CopiedToClipboard(line_count) => {
+                self.set_transient_status(format!("Copied {} lines to clipboard", line_count));
+                None
+            }
+
+            Action::ClipboardUnavailable(reason) => {
+                self.set_transient_status(format!("Clipboard unavailable: {}", reason));
+                None
+            }
+
+            Action::StartSearch => {
+                if self.mode == AppMode::ViewingLog {
+                    self.is_searching = true;
+                    self.search_scan = None; // A new query is coming, stop searching for the old one
+                    self.search_query.clear();
+                    self.search_history_index = None;
+                    self.search_origin = None;
+                }
+                None
+            }
+
+            Action::UpdateSearchQuery(query) => {
+                if !self.is_searching {
+                    return None;
+                }
+                self.search_query = query;
+                // Editing a recalled query turns it into a new draft
+                self.search_history_index = None;
+                self.debounce_search()
+            }
+
+            Action::SearchHistoryPrev => {
+                if self.is_searching && !self.search_history.is_empty() {
+                    let index = match self.search_history_index {
+                        Some(index) => index.saturating_sub(1),
+                        None => {
+                            self.search_draft = self.search_query.clone();
+                            self.search_history.len() - 1
+                        }
+                    };
+                    self.search_history_index = Some(index);
+                    self.search_query = self.search_history[index].clone();
+                    return self.debounce_search();
+                }
+                None
+            }
+
+            Action::SearchHistoryNext => {
+                if let (true, Some(index)) = (self.is_searching, self.search_history_index) {
+                    if index + 1 < self.search_history.len() {
+                        self.search_history_index = Some(index + 1);
+                        self.search_query = self.search_history[index + 1].clone();
+                    } else {
+                        self.search_history_index = None;
+                        self.search_query = std::mem::take(&mut self.search_draft);
+                    }
+                    return self.debounce_search();
+                }
+                None
+            }
+
+            Action::CycleSearchMode => {
+                if !self.is_searching {
+                    return None;
+                }
+                self.search_mode = self.search_mode.next();
+                self.debounce_search()
+            }
+
+            Action::ToggleWholeWordSearch => {
+                if !self.is_searching {
+                    return None;
+                }
+                self.search_whole_word = !self.search_whole_word;
+                self.debounce_search()
+            }
+
+            Action::SearchInputSettled { generation } => {
+                if self.is_searching && generation == self.search_input_generation && self.log_content.is_some() {
+                    // Search while still typing, Esc goes back to where the search started
+                    self.search_origin.get_or_insert((self.log_scroll_offset, self.log_scroll_row));
+                    self.search_and_jump();
+                }
+                None
+            }
+
+            Action::ExecuteSearch => {
+                let query = self.search_query.clone();
+                self.push_search_history(&query);
+                self.search_history_index = None;
+                self.search_input_generation += 1; // Drop a pending search while typing
+                self.search_origin = None;
+                if self.log_content.is_some() {
+                    self.is_searching = false;
+                    // Jump to first result if any, centered in viewport, or as soon as one is found
+                    self.search_and_jump();
+                }
+                None
+            }
+
+            Action::ContinueSearch { generation } => {
+                if self.search_scan.as_ref().is_some_and(|scan| scan.generation == generation) {
+                    if let Some(scan) = &mut self.search_scan {
+                        scan.scheduled = false;
+                    }
+                    self.search_step();
+                }
+                None
+            }
+
+            Action::NextSearchResult | Action::PrevSearchResult => {
+                if self.search_results.is_empty() || self.mode != AppMode::ViewingLog {
+                    return None;
+                }
+                if let Some(scan) = &mut self.search_scan {
+                    scan.select_line = None; // Moving on from the match a reopened log returned to
+                }
+                let last = self.search_results.len() - 1;
+                let next = matches!(action, Action::NextSearchResult);
+                let at_end = if next { self.current_search_result == last } else { self.current_search_result == 0 };
+                if at_end && !self.search_wrap {
+                    self.set_transient_status(if next { "Last match" } else { "First match" }.to_string());
+                    return None;
+                }
+                let len = self.search_results.len();
+                let step = if next { 1 } else { len - 1 };
+                self.current_search_result = (self.current_search_result + step) % len;
+                if at_end {
+                    self.set_transient_status(
+                        if next { "Search wrapped to the first match" } else { "Search wrapped to the last match" }
+                            .to_string(),
+                    );
+                }
+                self.center_log_line(self.search_results[self.current_search_result]);
+                None
+            }
+
+            Action::CancelSearch => {
+                self.is_searching = false;
+                self.search_scan = None;
+                self.search_query.clear();
+                self.search_input_generation += 1;
+                if let Some(origin) = self.search_origin.take() {
+                    // Drop the matches of the abandoned query and scroll back
+                    self.search_results.clear();
+                    self.search_regex = None;
+                    self.search_regex_error = false;
+                    (self.log_scroll_offset, self.log_scroll_row) = origin.min(self.max_log_scroll_position());
+                }
+                None
+            }
+
+            Action::StartFilter => {
+                if self.mode == AppMode::ViewingLog {
+                    self.is_filtering = true;
+                    self.is_searching = false;
+                }
+                None
+            }
+
+            Action::UpdateFilterQuery(query) => {
+                if self.is_filtering {
+                    self.filter_query = query;
+                }
+                None
+            }
+
+            Action::ToggleFilterInverse => {
+                if self.is_filtering {
+                    self.filter_inverse = !self.filter_inverse;
+                }
+                None
+            }
+
+            Action::ApplyFilter => {
+                if !self.is_filtering {
+                    return None;
+                }
+                if self.filter_query.is_empty() {
+                    // Submitting an empty pattern removes the filter
+                    self.filter_regex = None;
+                    self.filter_inverse = false;
+                } else {
+                    match Regex::new(&self.filter_query) {
+                        Ok(regex) => self.filter_regex = Some(regex),
+                        Err(_) => {
+                            self.set_transient_status("Invalid filter regex".to_string());
+                            return None; // Stay in input mode to fix the pattern
+                        }
+                    }
+                }
+                self.is_filtering = false;
+                self.refilter_log();
+                None
+            }
+
+            Action::CancelFilter => {
+                self.is_filtering = false;
+                self.filter_query.clear();
+                self.filter_inverse = false;
+                if self.filter_regex.take().is_some() {
+                    self.refilter_log();
+                }
+                None
+            }
+
+            Action::ApiError(error) => {
+                self.error_message = Some(error.clone());
+                self.status_message = None;
+                self.stop_loading_more();
+                None
+            }
+
+            Action::RateLimited { retry_after } => {
+                self.stop_loading_more();
+                let retry_after = retry_after.min(crate::gitlab::client::MAX_RATE_LIMIT_DELAY);
+                let resume_at = chrono::Duration::from_std(retry_after)
+                    .ok()
+                    .and_then(|delay| chrono::Utc::now().checked_add_signed(delay))
+                    .unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC);
+                if self.rate_limit_resume_at().is_some_and(|until| until >= resume_at) {
+                    return None; // Another request already hit the same limit
+                }
+                self.rate_limited_until = Some(resume_at);
+                self.set_transient_status(format!(
+                    "GitLab rate limit reached, polling resumes at {}",
+                    resume_at.with_timezone(&chrono::Local).format("%H:%M:%S")
+                ));
+                None
+            }
+
+            Action::ServedFromCache { saved_at } => {
+                if self.offline_cached_at.is_none() {
+                    self.set_transient_status("GitLab is unreachable, showing cached data".to_string());
+                }
+                self.offline_cached_at = Some(self.offline_cached_at.map_or(saved_at, |at| at.min(saved_at)));
+                None
+            }
+
+            Action::BackOnline => {
+                self.offline_cached_at = None;
+                self.set_transient_status("Back online".to_string());
+                None
+            }
+
+            Action::ShowHelp => {
+                self.mode = AppMode::ShowingHelp;
+                self.help_scroll = 0;
+                None
+            }
+
+            Action::CycleTheme => {
+                self.theme = self.theme.next();
+                self.set_transient_status(format!(
+                    "Theme: {} (set theme = \"{}\" under [ui] to keep it)",
+                    self.theme.name, self.theme.name
+                ));
+                None
+            }
+
+            Action::OpenProfilePicker => {
+                if self.profiles.len() < 2 {
+                    self.set_transient_status("No other GitLab profile, add one under [profiles.<name>] in the config".to_string());
+                    return None;
+                }
+                self.profile_picker = Some(self.active_profile);
+                None
+            }
+
+            Action::ProfilePickerNext | Action::ProfilePickerPrev => {
+                let count = self.profiles.len();
+                if let Some(selected) = &mut self.profile_picker {
+                    let step = if matches!(action, Action::ProfilePickerNext) { 1 } else { count - 1 };
+                    *selected = (*selected + step) % count;
+                }
+                None
+            }
+
+            Action::CloseProfilePicker => {
+                self.profile_picker = None;
+                None
+            }
+
+            Action::SelectProfile => {
+                let index = self.profile_picker.take()?;
+                if index == self.active_profile {
+                    return None;
+                }
+                self.switch_profile(index)
+            }
+
+            Action::ProjectResolved { profile, project_id, project_path } => {
+                if profile != self.active_profile {
+                    return None; // Switched again meanwhile
+                }
+                self.project_id = project_id;
+                let with_project = project_path.is_none();
+                self.project_path = project_path;
+                let session = Effect::FetchSessionInfo { project_id, with_project };
+                Effect::batch(self.handle_action(Action::Refresh).into_iter().chain([session]).collect())
+            }
+
+            Action::OpenCommandPalette => {
+                self.command_palette = Some(CommandPalette::default());
+                None
+            }
+
+            Action::UpdatePaletteQuery(query) => {
+                if let Some(palette) = &mut self.command_palette {
+                    palette.query = query;
+                    palette.selected = 0;
+                }
+                None
+            }
+
+            Action::PaletteSelectNext => {
+                self.move_palette_selection(true);
+                None
+            }
+
+            Action::PaletteSelectPrev => {
+                self.move_palette_selection(false);
+                None
+            }
+
+            Action::ExecutePaletteSelection => {
+                let selected = self.command_palette.as_ref()?.selected;
+                let entry = self.palette_entries().into_iter().nth(selected);
+                self.command_palette = None;
+                let action = bindable_action(entry?.name)?.action();
+                self.update(action)
+            }
+
+            Action::CloseCommandPalette => {
+                self.command_palette = None;
+                None
+            }
+
+            Action::ScrollHelpUp => {
+                self.help_scroll = self.help_scroll.saturating_sub(1);
+                None
+            }
+
+            Action::ScrollHelpDown => {
+                self.help_scroll = (self.help_scroll + 1).min(self.help_max_scroll);
+                None
+            }
+
+            Action::HideHelp => {
+                self.mode = AppMode::Normal;
+                None
+            }
+
+            Action::ToggleCommentsView => {
+                self.mode = match self.mode {
+                    AppMode::ViewingComments => AppMode::Normal,
+                    AppMode::Normal => {
+                        // Check if we need to fetch notes
+                        if let Some(mr) = self.get_selected_mr() {
+                            if !mr.notes_loaded {
+                                let mr_index = self.selected_mr_index;
+                                let project_id = self.project_id;
+                                let mr_iid = mr.mr.iid;
+
+                                self.status_message = Some("Loading comments...".to_string());
+                                self.mode = AppMode::ViewingComments;
+
+                                return Some(Effect::FetchNotes {
+                                    mr_index,
+                                    project_id,
+                                    mr_iid,
+                                });
+                            }
+                        }
+                        AppMode::ViewingComments
+                    }
+                    _ => self.mode.clone(), // Don't toggle in other modes
+                };
+                None
+            }
+
+            Action::NotesLoaded { mr_index, notes } => {
+                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
+                    mr.notes = notes;
+                    mr.notes_loaded = true;
+
+                    // Try to restore the previously selected note
+                    if let Some(selected_note_id) = self.selected_note_id_before_refresh.take() {
+                        // Filter user notes (non-system) and find the index of the previously selected note
+                        let user_notes: Vec<_> = mr.notes.iter().filter(|n| !n.system).collect();
+                        let restored_index = user_notes
+                            .iter()
+                            .position(|note| note.id == selected_note_id)
+                            .unwrap_or(0); // Default to 0 if note not found
+
+                        mr.selected_note_index = restored_index;
+                    } else {
+                        // No note to restore, default to 0
+                        mr.selected_note_index = 0;
+                    }
+
+                    // After notes are loaded following a refresh, continue to fetch jobs
+                    if let Some(pipeline) = mr.pipelines.first() {
+                        self.status_message = None;
+                        return Some(Effect::FetchJobs {
+                            mr_index,
+                            project_id: self.project_id,
+                            pipeline_id: pipeline.id,
+                        });
+                    }
+                }
+                self.status_message = None;
+                None
+            }
+
+            Action::NextNote => {
+                if self.mode == AppMode::ViewingComments {
+                    // Get the length of user notes (excluding system notes)
+                    let user_notes_len = self
+                        .get_selected_notes()
+                        .map(|notes| notes.iter().filter(|n| !n.system).count())
+                        .unwrap_or(0);
+                    if user_notes_len > 0 {
+                        if let Some(mr) = self.tracked_mrs.get_mut(self.selected_mr_index) {
+                            mr.selected_note_index = (mr.selected_note_index + 1) % user_notes_len;
+                        }
+                    }
+                }
+                None
+            }
+
+            Action::PrevNote => {
+                if self.mode == AppMode::ViewingComments {
+                    // Get the length of user notes (excluding system notes)
+                    let user_notes_len = self
+                        .get_selected_notes()
+                        .map(|notes| notes.iter().filter(|n| !n.system).count())
+                        .unwrap_or(0);
+                    if user_notes_len > 0 {
+                        if let Some(mr) = self.tracked_mrs.get_mut(self.selected_mr_index) {
+                            mr.selected_note_index = mr
+                                .selected_note_index
+                                .checked_sub(1)
+                                .unwrap_or(user_notes_len - 1);
+                        }
+                    }
+                }
+                None
+            }
+
+            Action::OpenMrInBrowser => {
+                if let Some(mr) = self.get_selected_mr() {
+                    return Some(Effect::OpenUrl(mr.mr.web_url.clone()));
+                }
+                None
+            }
+
+            Action::OpenJobInBrowser => {
+                let job = match self.mode {
+                    AppMode::ViewingLog => self.log_job(),
+                    _ => self.selected_job(),
+                };
+                match job {
+                    Some(job) => Some(Effect::OpenUrl(job.web_url.clone())),
+                    // No jobs loaded yet, the pipeline page is the next best thing
+                    None => self.update(Action::OpenPipelineInBrowser),
+                }
+            }
+
+            Action::OpenPipelineInBrowser => {
+                let pipeline = self.get_selected_pipeline()?;
+                Some(Effect::OpenUrl(pipeline.web_url.clone()))
+            }
+
+            Action::UrlOpenFailed(reason) => {
+                self.set_transient_status(format!("Could not open browser: {}", reason));
+                None
+            }
+
+            Action::Tick => {
+                if self.rate_limit_resume_at().is_some() {
+                    return None; // The auto-refresh waits for the rate limit to pass
+                }
+
+                // Check if it's time for an auto-refresh
+                let elapsed = self.last_auto_refresh.elapsed();
+                let refresh_interval = std::time::Duration::from_secs(self.auto_refresh_interval_minutes * 60);
+
+                if elapsed >= refresh_interval {
+                    // Trigger auto-refresh
+                    self.last_auto_refresh = Instant::now();
+
+                    // Set flag to refetch notes after refresh if currently viewing comments
+                    self.refetch_notes_after_refresh = self.mode == AppMode::ViewingComments;
+
+                    // Save the currently selected note ID if viewing comments
+                    if self.refetch_notes_after_refresh {
+                        self.selected_note_id_before_refresh = self.get_selected_note_id();
+                    }
+
+                    // Clear all cached data including notes and job logs
+                    for mr in &mut self.tracked_mrs {
+                        mr.notes_loaded = false;
+                        mr.notes.clear();
+                        mr.job_logs_cache.clear();
+                    }
+
+                    self.status_message = Some("Auto-refreshing...".to_string());
+                    Some(Effect::RefreshAll {
+                        project_id: self.project_id,
+                        source_branch: if self.focus_current_branch {
+                            self.current_branch.clone()
+                        } else {
+                            None
+                        },
+                    })
+                } else {
+                    None
+                }
+            }
+
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gitlab::models::User;
+    use chrono::Utc;
+
+    fn create_test_mr(id: u64, iid: u64, title: &str) -> MergeRequest {
+        MergeRequest {
+            id,
+            iid,
+            title: title.to_string(),
+            author: User {
+                id: 1,
+                username: "testuser".to_string(),
+                name: "Test User".to_string(),
+            },
+            state: "opened".to_string(),
+            web_url: format!("https://gitlab.com/test/-/merge_requests/{}", iid),
+            source_branch: format!("feature-{}", iid),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn create_test_pipeline(id: u64, status: PipelineStatus) -> Pipeline {
+        Pipeline {
+            id,
+            iid: id,
+            status,
+            ref_name: "main".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            web_url: format!("https://gitlab.com/test/-/pipelines/{}", id),
+            user: Some(User { id: 1, username: "alice".to_string(), name: "Alice".to_string() }),
+            ..Default::default()
+        }
+    }
+
+    fn create_test_job(id: u64, name: &str, status: JobStatus) -> Job {
+        Job {
+            id,
+            name: name.to_string(),
+            status,
+            stage: "test".to_string(),
+            created_at: Utc::now(),
+            started_at: Some(Utc::now()),
+            finished_at: Some(Utc::now()),
+            duration: Some(120.0),
+            web_url: format!("https://gitlab.com/test/-/jobs/{}", id),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_app_new() {
+        let app = App::new(123, None, false, 1);
+        assert_eq!(app.project_id, 123);
+        assert!(!app.should_quit);
+        assert_eq!(app.selected_mr_index, 0);
+        assert_eq!(app.selected_job_index, 0);
+        assert!(app.tracked_mrs.is_empty());
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_quit_action() {
+        let mut app = App::new(123, None, false, 1);
+        assert!(!app.should_quit);
+
+        app.update(Action::Quit);
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_quit_asks_while_work_is_running() {
+        let mut app = app_with_log("a");
+        app.update(Action::ExportLog { raw: false });
+        app.update(Action::Quit);
+        assert!(!app.should_quit);
+        let confirmation = app.pending_confirmation.as_ref().expect("quit confirmation");
+        assert_eq!(confirmation.prompt, "Still exporting a log. Quit anyway?");
+        app.update(Action::ConfirmPending);
+        assert!(app.should_quit);
+
+        // Once the export is done quitting is immediate again
+        let mut app = app_with_log("a");
+        app.update(Action::ExportLog { raw: false });
+        app.update(Action::LogExported(PathBuf::from("job-1-build.log")));
+        app.update(Action::Quit);
+        assert!(app.pending_confirmation.is_none());
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_next_mr() {
+        let mut app = App::new(123, None, false, 1);
+
+        // Add some MRs
+        let mr1 = create_test_mr(1, 10, "MR 1");
+        let mr2 = create_test_mr(2, 20, "MR 2");
+
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr: mr1,
+            pipelines: vec![],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr: mr2,
+            pipelines: vec![],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        assert_eq!(app.selected_mr_index, 0);
+        app.update(Action::NextMr);
+        assert_eq!(app.selected_mr_index, 1);
+        app.update(Action::NextMr);
+        assert_eq!(app.selected_mr_index, 0); // Wraps around
+    }
+
+    #[test]
+    fn test_prev_mr() {
+        let mut app = App::new(123, None, false, 1);
+
+        let mr1 = create_test_mr(1, 10, "MR 1");
+        let mr2 = create_test_mr(2, 20, "MR 2");
+
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr: mr1,
+            pipelines: vec![],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr: mr2,
+            pipelines: vec![],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        assert_eq!(app.selected_mr_index, 0);
+        app.update(Action::PrevMr);
+        assert_eq!(app.selected_mr_index, 1); // Wraps around to end
+        app.update(Action::PrevMr);
+        assert_eq!(app.selected_mr_index, 0);
+    }
+
+    #[test]
+    fn test_merge_requests_loaded() {
+        let mut app = App::new(123, None, false, 1);
+
+        let mrs = vec![
+            create_test_mr(1, 10, "MR 1"),
+            create_test_mr(2, 20, "MR 2"),
+        ];
+
+        app.update(Action::MergeRequestsLoaded(mrs));
+        assert_eq!(app.tracked_mrs.len(), 2);
+        assert_eq!(app.tracked_mrs[0].mr.title, "MR 1");
+        assert_eq!(app.tracked_mrs[1].mr.title, "MR 2");
+    }
+
+    #[test]
+    fn test_pipelines_loaded() {
+        let mut app = App::new(123, None, false, 1);
+
+        let mr = create_test_mr(1, 10, "Test MR");
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr,
+            pipelines: vec![],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: true,
+            error: None,
+            ..Default::default()
+        });
+
+        let pipelines = vec![
+            create_test_pipeline(100, PipelineStatus::Success),
+            create_test_pipeline(101, PipelineStatus::Failed),
+        ];
+
+        app.update(Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 1,
+            status: None,
+            pipelines,
+            next_page: None,
+        });
+
+        assert_eq!(app.tracked_mrs[0].pipelines.len(), 2);
+        assert!(!app.tracked_mrs[0].loading);
+        assert_eq!(app.tracked_mrs[0].pipelines[0].status, PipelineStatus::Success);
+    }
+
+    #[test]
+    fn test_jobs_loaded() {
+        let mut app = App::new(123, None, false, 1);
+
+        let mr = create_test_mr(1, 10, "Test MR");
+        let pipeline = create_test_pipeline(100, PipelineStatus::Running);
+
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr,
+            pipelines: vec![pipeline],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        let jobs = vec![
+            create_test_job(200, "build", JobStatus::Success),
+            create_test_job(201, "test", JobStatus::Failed),
+        ];
+
+        app.update(Action::JobsLoaded {
+            mr_index: 0,
+            pipeline_id: 100,
+            jobs,
+        });
+
+        assert!(app.tracked_mrs[0].jobs.contains_key(&100));
+        let loaded_jobs = &app.tracked_mrs[0].jobs[&100];
+        assert_eq!(loaded_jobs.len(), 2);
+        // Jobs are sorted by status: Failed jobs come first
+        assert_eq!(loaded_jobs[0].name, "test"); // Failed
+        assert_eq!(loaded_jobs[1].name, "build"); // Success
+    }
+
+    #[test]
+    fn test_selected_pipeline_user_fetched_once() {
+        let mut app = app_with_job(JobStatus::Failed);
+        app.tracked_mrs[0].pipelines[0].user = None; // As pipeline lists give it
+        let effect = app.update(jobs_loaded(vec![create_test_job(200, "test", JobStatus::Failed)]));
+        assert!(matches!(effect, Some(Effect::FetchPipeline { mr_index: 0, pipeline_id: 100, .. })));
+
+        let pipeline = create_test_pipeline(100, PipelineStatus::Failed);
+        app.update(Action::PipelineLoaded { mr_index: 0, pipeline });
+        assert_eq!(app.tracked_mrs[0].pipelines[0].user.as_ref().unwrap().username, "alice");
+
+        // Reloaded lists keep the user, and it isn't fetched again
+        let pipelines = vec![Pipeline { user: None, ..create_test_pipeline(100, PipelineStatus::Failed) }];
+        app.update(Action::PipelinesLoaded { mr_index: 0, page: 1, status: None, pipelines, next_page: None });
+        assert_eq!(app.tracked_mrs[0].pipelines[0].user.as_ref().unwrap().username, "alice");
+        let effect = app.update(jobs_loaded(vec![create_test_job(200, "test", JobStatus::Failed)]));
+        assert!(!matches!(effect, Some(Effect::FetchPipeline { .. })));
+    }
+
+    #[test]
+    fn test_api_error() {
+        let mut app = App::new(123, None, false, 1);
+
+        app.update(Action::ApiError("Test error".to_string()));
+        assert_eq!(app.error_message, Some("Test error".to_string()));
+        assert!(app.status_message.is_none());
+    }
+
+    #[test]
+    fn test_remove_current_mr() {
+        let mut app = App::new(123, None, false, 1);
+
+        let mr1 = create_test_mr(1, 10, "MR 1");
+        let mr2 = create_test_mr(2, 20, "MR 2");
+
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr: mr1,
+            pipelines: vec![],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr: mr2,
+            pipelines: vec![],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        assert_eq!(app.tracked_mrs.len(), 2);
+        app.update(Action::RemoveCurrentMr);
+        assert_eq!(app.tracked_mrs.len(), 1);
+        assert_eq!(app.tracked_mrs[0].mr.title, "MR 2");
+    }
+
+    #[test]
+    fn test_get_selected_mr() {
+        let mut app = App::new(123, None, false, 1);
+
+        assert!(app.get_selected_mr().is_none());
+
+        let mr = create_test_mr(1, 10, "Test MR");
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr,
+            pipelines: vec![],
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        let selected = app.get_selected_mr();
+        assert!(selected.is_some());
+        assert_eq!(selected.unwrap().mr.title, "Test MR");
+    }
+
+    #[test]
+    fn test_get_selected_jobs() {
+        let mut app = App::new(123, None, false, 1);
+
+        let mr = create_test_mr(1, 10, "Test MR");
+        let pipeline = create_test_pipeline(100, PipelineStatus::Running);
+        let job = create_test_job(200, "test-job", JobStatus::Success);
+
+        let mut jobs_map = HashMap::new();
+        jobs_map.insert(100, vec![job]);
+
+        app.tracked_mrs.push(TrackedMergeRequest {
+            mr,
+            pipelines: vec![pipeline],
+            jobs: jobs_map,
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        });
+
+        let jobs = app.get_selected_jobs();
+        assert!(jobs.is_some());
+        assert_eq!(jobs.unwrap().len(), 1);
+        assert_eq!(jobs.unwrap()[0].name, "test-job");
+    }
+
+    fn app_with_log(content: &str) -> App {
+        let mut app = App::new(123, None, false, 1);
+        app.set_log_content(1, content.to_string(), "job".to_string());
+        app.mode = AppMode::ViewingLog;
+        app
+    }
+
+    fn run_search(app: &mut App, query: &str) {
+        app.update(Action::StartSearch);
+        app.update(Action::UpdateSearchQuery(query.to_string()));
+        app.update(Action::ExecuteSearch);
+    }
+
+    fn continue_search_generation(effect: &Option<Effect>) -> Option<u64> {
+        match effect {
+            Some(Effect::ContinueSearch { generation }) => Some(*generation),
+            Some(Effect::Batch(effects)) => effects.iter().find_map(|effect| match effect {
+                Effect::ContinueSearch { generation } => Some(*generation),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+
+    fn huge_log_with_match_at(line: usize) -> App {
+        let content: Vec<String> = (0..SEARCH_BATCH_LINES * 2 + 10)
+            .map(|idx| if idx == line { "needle".to_string() } else { format!("line {}", idx) })
+            .collect();
+        let mut app = app_with_log(&content.join("\n"));
+        app.log_viewport_height = 20;
+        app
+    }
+
+    #[test]
+    fn test_search_on_huge_log_runs_in_steps() {
+        let target = SEARCH_BATCH_LINES + 5;
+        let mut app = huge_log_with_match_at(target);
+        app.update(Action::StartSearch);
+        app.update(Action::UpdateSearchQuery("needle".to_string()));
+        let mut effect = app.update(Action::ExecuteSearch);
+
+        // Only the first step ran: no match yet, the rest is scheduled
+        assert!(app.search_results.is_empty());
+        assert_eq!(app.search_progress(), Some(49));
+        let mut steps = 0;
+        while let Some(generation) = continue_search_generation(&effect) {
+            effect = app.update(Action::ContinueSearch { generation });
+            steps += 1;
+        }
+        assert_eq!(steps, 2);
+        assert_eq!(app.search_results, vec![target]);
+        assert!(app.search_progress().is_none());
+        // The first match found later is still jumped to
+        assert_eq!(app.log_visible_lines[app.log_scroll_offset + app.log_viewport_height / 2], target);
+    }
+
+    #[test]
+    fn test_search_scan_cancelled_by_new_query() {
+        let mut app = huge_log_with_match_at(SEARCH_BATCH_LINES * 2);
+        app.update(Action::StartSearch);
+        app.update(Action::UpdateSearchQuery("needle".to_string()));
+        let effect = app.update(Action::ExecuteSearch);
+        let old = continue_search_generation(&effect).unwrap();
+
+        app.update(Action::StartSearch);
+        assert!(app.search_scan.is_none());
+        assert!(app.update(Action::ContinueSearch { generation: old }).is_none());
+        assert!(app.search_results.is_empty());
+
+        // A new search ignores steps of the old one
+        app.update(Action::UpdateSearchQuery("line 1".to_string()));
+        app.update(Action::ExecuteSearch);
+        let next_line = app.search_scan.as_ref().unwrap().next_line;
+        app.update(Action::ContinueSearch { generation: old });
+        assert_eq!(app.search_scan.as_ref().unwrap().next_line, next_line);
+    }
+
+    #[test]
+    fn test_search_while_typing_after_a_pause() {
+        let mut app = app_with_log("a\nneedle\nb\nneedle 2");
+        app.update(Action::StartSearch);
+        let debounce = |effect: Option<Effect>| match effect {
+            Some(Effect::DebounceSearch { generation, delay }) => {
+                assert_eq!(delay, DEFAULT_SEARCH_DEBOUNCE);
+                generation
+            }
+            _ => panic!("expected a debounced search"),
+        };
+        let stale = debounce(app.update(Action::UpdateSearchQuery("nee".to_string())));
+        let latest = debounce(app.update(Action::UpdateSearchQuery("needle 2".to_string())));
+
+        // Only the last edit searches, without closing the search bar
+        app.update(Action::SearchInputSettled { generation: stale });
+        assert!(app.search_results.is_empty());
+        app.update(Action::SearchInputSettled { generation: latest });
+        assert_eq!(app.search_results, vec![3]);
+        assert!(app.is_searching);
+
+        // Esc drops the matches, Enter keeps them
+        app.update(Action::CancelSearch);
+        assert!(app.search_results.is_empty());
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (0, 0));
+        app.update(Action::StartSearch);
+        let generation = debounce(app.update(Action::UpdateSearchQuery("needle".to_string())));
+        app.update(Action::SearchInputSettled { generation });
+        app.update(Action::ExecuteSearch);
+        assert!(!app.is_searching);
+        assert_eq!(app.search_results, vec![1, 3]);
+        assert!(app.update(Action::SearchInputSettled { generation }).is_none());
+        assert_eq!(app.search_history, vec!["needle".to_string()]);
+    }
+
+    #[test]
+    fn test_search_on_enter_only_without_debounce() {
+        let mut app = app_with_log("needle");
+        app.search_debounce = None;
+        app.update(Action::StartSearch);
+        assert!(app.update(Action::UpdateSearchQuery("needle".to_string())).is_none());
+        assert!(app.search_results.is_empty());
+        app.update(Action::ExecuteSearch);
+        assert_eq!(app.search_results, vec![0]);
+    }
+
+    #[test]
+    fn test_search_defaults_to_case_insensitive() {
+        let mut app = app_with_log("Error here\nno match\nerror again");
+        run_search(&mut app, "ERROR");
+        assert_eq!(app.search_mode, SearchMode::CaseInsensitive);
+        assert_eq!(app.search_results, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_search_literal_mode_is_case_sensitive() {
+        let mut app = app_with_log("Error here\nno match\nerror again");
+        app.update(Action::StartSearch);
+        app.update(Action::CycleSearchMode); // CaseInsensitive -> Regex
+        app.update(Action::CycleSearchMode); // Regex -> Literal
+        assert_eq!(app.search_mode, SearchMode::Literal);
+        app.update(Action::UpdateSearchQuery("error".to_string()));
+        app.update(Action::ExecuteSearch);
+        assert_eq!(app.search_results, vec![2]);
+    }
+
+    #[test]
+    fn test_search_regex_mode() {
+        let mut app = app_with_log("exit code 0\nexit code 1\nexit code 137");
+        app.update(Action::StartSearch);
+        app.update(Action::CycleSearchMode);
+        assert_eq!(app.search_mode, SearchMode::Regex);
+        app.update(Action::UpdateSearchQuery(r"exit code [1-9]\d*".to_string()));
+        app.update(Action::ExecuteSearch);
+        assert_eq!(app.search_results, vec![1, 2]);
+        assert!(app.search_regex.is_some());
+        assert!(!app.search_regex_error);
+    }
+
+    #[test]
+    fn test_whole_word_search() {
+        let mut app = app_with_log("run test\nlatest build\ntesting\nTest: ok");
+        app.update(Action::StartSearch);
+        app.update(Action::ToggleWholeWordSearch);
+        app.update(Action::UpdateSearchQuery("test".to_string()));
+        app.update(Action::ExecuteSearch);
+        assert_eq!(app.search_results, vec![0, 3]);
+
+        // The option stays on for the next search, also in regex mode
+        app.update(Action::StartSearch);
+        assert!(app.search_whole_word);
+        app.update(Action::CycleSearchMode);
+        app.update(Action::UpdateSearchQuery("test|build".to_string()));
+        app.update(Action::ExecuteSearch);
+        assert_eq!(app.search_results, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_search_invalid_regex_is_reported() {
+        let mut app = app_with_log("some (log)");
+        app.search_mode = SearchMode::Regex;
+        run_search(&mut app, "(unclosed");
+        assert!(app.search_regex_error);
+        assert!(app.search_regex.is_none());
+        assert!(app.search_results.is_empty());
+    }
+
+    #[test]
+    fn test_literal_mode_escapes_special_characters() {
+        let mut app = app_with_log("some (log)\nsome log");
+        app.search_mode = SearchMode::Literal;
+        run_search(&mut app, "(log)");
+        assert!(!app.search_regex_error);
+        assert_eq!(app.search_results, vec![0]);
+    }
+
+    const SECTIONED_LOG: &str = "intro\n\
+section_start:1700000000:install[collapsed=true]\r\x1b[0KInstalling deps\n\
+npm install\n\
+added 100 packages\n\
+section_end:1700000010:install\r\x1b[0K\n\
+section_start:1700000010:test\r\x1b[0KRunning tests\n\
+cargo test\n\
+section_end:1700000020:test\r\x1b[0K\n\
+done";
+
+    fn app_with_sectioned_log() -> App {
+        app_with_log(SECTIONED_LOG)
+    }
+
+    #[test]
+    fn test_log_of_only_section_markers_has_no_displayable_output() {
+        let app = app_with_log(
+            "section_start:1700000000:build\r\x1b[0K\n\
+             section_end:1700000005:build\r\x1b[0K\n\
+             \x1b[0K   \n",
+        );
+        assert_eq!(app.log_empty_message(), Some("(no displayable output)"));
+        assert_eq!(app_with_log("").log_empty_message(), Some("(empty log)"));
+        assert_eq!(app_with_sectioned_log().log_empty_message(), None);
+    }
+
+    #[test]
+    fn test_sections_parsed_and_collapsed_by_default() {
+        let app = app_with_sectioned_log();
+        assert_eq!(app.log_sections.len(), 2);
+        assert_eq!(app.log_sections[0].name, "install");
+        assert_eq!(app.log_sections[0].header, "Installing deps");
+        assert_eq!(app.log_sections[0].end_line, Some(4));
+        assert!(app.collapsed_sections.contains("install"));
+        // install body and both end markers are hidden
+        assert_eq!(app.log_visible_lines, vec![0, 1, 5, 6, 8]);
+    }
+
+    #[test]
+    fn test_raw_view_shows_markers_and_copies_verbatim() {
+        let mut app = app_with_sectioned_log();
+        app.log_scroll_offset = 2; // "test" header at top
+        app.update(Action::ToggleRawView);
+        assert!(app.raw_log_view);
+        // Nothing is folded or hidden, the top line stays in place
+        assert_eq!(app.log_visible_lines, (0..9).collect::<Vec<_>>());
+        assert_eq!(app.log_visible_lines[app.log_scroll_offset], 5);
+        assert_eq!(app.processed_log_text(&[4, 5]), "section_end:1700000010:install␍\nsection_start:1700000010:test␍Running tests");
+        assert!(app.update(Action::ToggleSection).is_none());
+        assert!(app.collapsed_sections.contains("install"));
+
+        // Folds are kept for the processed view
+        app.update(Action::ToggleRawView);
+        assert_eq!(app.log_visible_lines, vec![0, 1, 5, 6, 8]);
+        assert_eq!(app.processed_log_text(&[4, 5]), "");
+    }
+
+    #[test]
+    fn test_toggle_section_at_top_line() {
+        let mut app = app_with_sectioned_log();
+        app.log_scroll_offset = 1; // "install" header at top
+        app.update(Action::ToggleSection);
+        assert!(!app.collapsed_sections.contains("install"));
+        assert_eq!(app.log_visible_lines, vec![0, 1, 2, 3, 5, 6, 8]);
+
+        app.update(Action::ToggleSection);
+        assert!(app.collapsed_sections.contains("install"));
+        assert_eq!(app.log_visible_lines, vec![0, 1, 5, 6, 8]);
+    }
+
+    #[test]
+    fn test_toggle_section_from_inside_keeps_header_visible() {
+        let mut app = app_with_sectioned_log();
+        app.log_scroll_offset = 3; // "cargo test", inside the "test" section
+        app.update(Action::ToggleSection);
+        assert!(app.collapsed_sections.contains("test"));
+        assert_eq!(app.log_visible_lines, vec![0, 1, 5, 8]);
+        assert_eq!(app.log_scroll_offset, 2);
+    }
+
+    #[test]
+    fn test_search_reveals_folded_section() {
+        let mut app = app_with_sectioned_log();
+        run_search(&mut app, "packages");
+        assert_eq!(app.search_results, vec![3]);
+        assert!(!app.collapsed_sections.contains("install"));
+        assert!(app.visible_row_of_line(3).is_some());
+    }
+
+    #[test]
+    fn test_unclosed_section_folds_to_end() {
+        let mut app = App::new(123, None, false, 1);
+        app.set_log_content(
+            1,
+            "section_start:1700000000:deploy\r\x1b[0KDeploying\nstep 1\nstep 2".to_string(),
+            "job".to_string(),
+        );
+        app.mode = AppMode::ViewingLog;
+        assert_eq!(app.log_sections[0].end_line, None);
+        app.update(Action::ToggleSection);
+        assert_eq!(app.log_visible_lines, vec![0]);
+    }
+
+    #[test]
+    fn test_section_durations() {
+        let app = app_with_sectioned_log();
+        assert_eq!(app.log_sections[0].duration_secs(), Some(10));
+        assert_eq!(app.log_sections[0].duration_label(false), "(10s)");
+        assert_eq!(app.log_sections[1].duration_label(false), "(10s)");
+    }
+
+    #[test]
+    fn test_unclosed_section_duration_label() {
+        let mut app = App::new(123, None, false, 1);
+        app.set_log_content(
+            1,
+            "section_start:1700000000:deploy\r\x1b[0KDeploying\nstep 1".to_string(),
+            "job".to_string(),
+        );
+        let section = &app.log_sections[0];
+        assert_eq!(section.duration_secs(), None);
+        assert_eq!(section.duration_label(true), "(running...)");
+        assert_eq!(section.duration_label(false), "(incomplete)");
+    }
+
+    #[test]
+    fn test_section_duration_formatting() {
+        use crate::log_processor::format_section_duration;
+        assert_eq!(format_section_duration(0), "0s");
+        assert_eq!(format_section_duration(45), "45s");
+        assert_eq!(format_section_duration(83), "1m23s");
+        assert_eq!(format_section_duration(7500), "2h05m");
+    }
+
+    #[test]
+    fn test_section_end_before_start_is_clamped() {
+        let mut app = App::new(123, None, false, 1);
+        app.set_log_content(
+            1,
+            "section_start:1700000010:skewed\r\x1b[0K\nwork\nsection_end:1700000000:skewed\r\x1b[0K".to_string(),
+            "job".to_string(),
+        );
+        assert_eq!(app.log_sections[0].duration_secs(), Some(0));
+    }
+
+    #[test]
+    fn test_horizontal_scroll_only_without_wrap() {
+        let mut app = app_with_log("a fairly long line of log output\nshort");
+        app.update(Action::ScrollLogRight);
+        assert_eq!(app.horizontal_scroll_offset, 0);
+
+        app.update(Action::ToggleWrap);
+        assert!(!app.wrap_enabled);
+        app.update(Action::ScrollLogRight);
+        app.update(Action::ScrollLogRight);
+        assert_eq!(app.horizontal_scroll_offset, 8);
+        app.update(Action::ScrollLogLeft);
+        assert_eq!(app.horizontal_scroll_offset, 4);
+
+        // Re-enabling wrap resets the pan
+        app.update(Action::ToggleWrap);
+        assert_eq!(app.horizontal_scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_horizontal_scroll_clamped_to_longest_line() {
+        let mut app = app_with_log("12345\n123");
+        app.update(Action::ToggleWrap);
+        for _ in 0..10 {
+            app.update(Action::ScrollLogRight);
+        }
+        assert_eq!(app.horizontal_scroll_offset, 4);
+    }
+
+    #[test]
+    fn test_error_lines_detected_on_load() {
+        let app = app_with_log(
+            "ok\nERROR: boom\nerror: bad thing\nexit code 0\nexit code 2\nFAILED tests\n\x1b[31mred text\x1b[0m\n\x1b[32mgreen\x1b[0m",
+        );
+        assert_eq!(app.log_error_lines, vec![1, 2, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_warning_lines_detected_on_load() {
+        let app = app_with_log("ok\nWARNING: deprecated\nwarn: slow\nforewarned\n\x1b[33myellow\x1b[0m\n\x1b[1;93mbright\x1b[0m");
+        assert_eq!(app.log_warning_lines, vec![1, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_next_and_prev_error_line() {
+        let mut app = app_with_log("ok\nERROR one\nok\nok\nERROR two");
+        app.update(Action::NextErrorLine);
+        assert_eq!(app.current_error_line, Some(0));
+        app.update(Action::NextErrorLine);
+        assert_eq!(app.current_error_line, Some(1));
+        app.update(Action::NextErrorLine);
+        assert_eq!(app.current_error_line, Some(0)); // Wraps around
+        app.update(Action::PrevErrorLine);
+        assert_eq!(app.current_error_line, Some(1));
+    }
+
+    #[test]
+    fn test_copy_full_log_uses_processed_text() {
+        let mut app = app_with_log(
+            "2024-01-15T10:30:45.123Z 00O first\nsection_start:1700000000:s\r\x1b[0KHeader\nsecond\nsection_end:1700000001:s\r\x1b[0K",
+        );
+        match app.update(Action::CopyFullLog) {
+            Some(Effect::CopyToClipboard { text, line_count }) => {
+                assert_eq!(text, "first\nsecond");
+                assert_eq!(line_count, 2);
+            }
+            _ => panic!("Expected CopyToClipboard effect"),
+        }
+    }
+
+    #[test]
+    fn test_copy_visible_log_limited_to_viewport() {
+        let mut app = app_with_log("a\nb\nc\nd");
+        app.log_viewport_height = 2;
+        app.log_scroll_offset = 1;
+        match app.update(Action::CopyVisibleLog) {
+            Some(Effect::CopyToClipboard { text, .. }) => assert_eq!(text, "b\nc"),
+            _ => panic!("Expected CopyToClipboard effect"),
+        }
+    }
+
+    #[test]
+    fn test_clipboard_unavailable_sets_transient_status() {
+        let mut app = app_with_log("a");
+        app.update(Action::ClipboardUnavailable("no display".to_string()));
+        assert_eq!(app.visible_status(), Some("Clipboard unavailable: no display"));
+        app.status_expires_at = Some(Instant::now() - std::time::Duration::from_secs(1));
+        assert_eq!(app.visible_status(), None);
+    }
+
+    #[test]
+    fn test_export_log_cleaned_and_raw() {
+        let raw_log = "2024-01-15T10:30:45.123Z 00O hello";
+        let mut app = app_with_log(raw_log);
+        app.log_job_name = Some("build".to_string());
+
+        match app.update(Action::ExportLog { raw: false }) {
+            Some(Effect::ExportLog { path, content }) => {
+                assert_eq!(path, std::path::PathBuf::from("job-1-build.log"));
+                assert_eq!(content, "hello");
+            }
+            _ => panic!("Expected ExportLog effect"),
+        }
+
+        match app.update(Action::ExportLog { raw: true }) {
+            Some(Effect::ExportLog { path, content }) => {
+                assert_eq!(path, std::path::PathBuf::from("job-1-build.raw.log"));
+                assert_eq!(content, raw_log);
+            }
+            _ => panic!("Expected ExportLog effect"),
+        }
+    }
+
+    #[test]
+    fn test_export_result_surfaces_as_status() {
+        let mut app = app_with_log("a");
+        app.update(Action::LogExportFailed("permission denied".to_string()));
+        assert_eq!(app.visible_status(), Some("Log export failed: permission denied"));
+    }
+
+    #[test]
+    fn test_follow_mode_polls_running_job_and_snaps_to_bottom() {
+        let mut app = app_with_log("1\n2\n3");
+        app.log_viewport_height = 2;
+        app.log_job_running = true;
+
+        let effect = app.update(Action::ToggleFollowMode);
+        assert!(app.follow_mode);
+        assert!(matches!(effect, Some(Effect::PollJobTrace { job_id: 1, offset: 5, .. })));
+        assert_eq!(app.log_scroll_offset, 1);
+
+        // Toggling again while a poll is in flight doesn't schedule a second one
+        app.update(Action::ToggleFollowMode);
+        assert!(app.update(Action::ToggleFollowMode).is_none());
+
+        let effect = app.update(streamed(5, "\n4\n5", true, JobStatus::Running));
+        assert!(matches!(effect, Some(Effect::PollJobTrace { offset: 9, .. })));
+        assert_eq!(app.log_content.as_ref().unwrap().to_text(), "1\n2\n3\n4\n5");
+        assert_eq!(app.log_scroll_offset, 3);
+    }
+
+    fn streamed(offset: u64, data: &str, complete: bool, status: JobStatus) -> Action {
+        Action::JobTraceStreamed {
+            job_id: 1,
+            offset,
+            chunk: TraceChunk { data: data.as_bytes().to_vec(), total_size: None, complete },
+            status,
+        }
+    }
+
+    #[test]
+    fn test_streamed_log_structure_matches_a_full_parse() {
+        let log = "section_start:1:build[collapsed=true]\r\x1b[0KBuild\nerror: one\nwarning: two\n\
+                   section_end:9:build\r\x1b[0K\nsection_start:10:test\r\x1b[0KTest\nok\nerror: three\n\
+                   section_end:20:test\r\x1b[0K\nERROR: Job failed: exit code 1\n";
+        let full = app_with_log(log);
+
+        // Chunk boundaries fall in the middle of lines and sections
+        let mut app = app_with_log("");
+        app.log_job_running = true;
+        app.update(Action::ToggleFollowMode);
+        let mut offset = 0;
+        for piece in log.as_bytes().chunks(7) {
+            let piece = std::str::from_utf8(piece).unwrap();
+            app.update(streamed(offset, piece, true, JobStatus::Running));
+            offset += piece.len() as u64;
+        }
+        assert_eq!(app.log_sections, full.log_sections);
+        assert_eq!(app.log_error_lines, full.log_error_lines);
+        assert_eq!(app.log_warning_lines, full.log_warning_lines);
+        assert_eq!(app.log_outcome, full.log_outcome);
+        assert_eq!(app.collapsed_sections, full.collapsed_sections);
+    }
+
+    #[test]
+    fn test_follow_mode_keeps_a_character_split_between_polls() {
+        let mut app = app_with_log("");
+        app.log_job_running = true;
+        app.update(Action::ToggleFollowMode);
+        let check = "✓".as_bytes();
+        let poll = |offset: u64, data: &[u8]| Action::JobTraceStreamed {
+            job_id: 1,
+            offset,
+            chunk: TraceChunk { data: data.to_vec(), total_size: None, complete: true },
+            status: JobStatus::Running,
+        };
+        app.update(poll(0, &[b"ok ", &check[..1]].concat()));
+        assert_eq!(app.log_content.as_ref().unwrap().to_text(), "ok ");
+        app.update(poll(4, &check[1..]));
+        assert_eq!(app.log_content.as_ref().unwrap().to_text(), "ok ✓");
+    }
+
+    #[test]
+    fn test_follow_mode_streams_new_output() {
+        let mut app = app_with_log("1\n2");
+        app.log_job_running = true;
+        app.update(Action::ToggleFollowMode);
+
+        // Bytes that don't continue the log (it was reloaded meanwhile) are dropped
+        let effect = app.update(streamed(2, "x", true, JobStatus::Running));
+        assert!(matches!(effect, Some(Effect::PollJobTrace { offset: 3, .. })));
+        assert_eq!(app.log_line_count(), 2);
+
+        // A burst bigger than one chunk is fetched without waiting for the next poll
+        match app.update(streamed(3, "\n3", false, JobStatus::Running)) {
+            Some(Effect::PollJobTrace { offset: 5, delay, .. }) => assert!(delay.is_zero()),
+            _ => panic!("Expected an immediate poll"),
+        }
+        assert_eq!(app.log_line_count(), 3);
+
+        // Once the job ends the poll brings the full trace, and polling stops
+        let effect = app.update(Action::JobTraceRefreshed {
+            job_id: 1,
+            trace: "1\n2\n3\n4".to_string(),
+            status: JobStatus::Success,
+        });
+        assert!(matches!(effect, Some(Effect::SaveJobLog { job_id: 1, .. })));
+        assert!(!app.log_job_running);
+        assert_eq!(app.log_line_count(), 4);
+    }
+
+    #[test]
+    fn test_scrolling_up_disables_follow_mode() {
+        let mut app = app_with_log("1\n2\n3");
+        app.log_job_running = true;
+        app.update(Action::ToggleFollowMode);
+        app.update(Action::ScrollLogUp);
+        assert!(!app.follow_mode);
+
+        // New content no longer moves the view
+        let offset = app.log_scroll_offset;
+        app.update(Action::JobTraceRefreshed {
+            job_id: 1,
+            trace: "1\n2\n3\n4".to_string(),
+            status: JobStatus::Running,
+        });
+        assert_eq!(app.log_scroll_offset, offset);
+        assert_eq!(app.log_line_count(), 4);
+    }
+
+    #[test]
+    fn test_follow_mode_stops_polling_when_job_finishes() {
+        let mut app = app_with_log("1");
+        app.log_job_running = true;
+        app.update(Action::ToggleFollowMode);
+        let effect = app.update(Action::JobTraceRefreshed {
+            job_id: 1,
+            trace: "1\n2".to_string(),
+            status: JobStatus::Success,
+        });
+        // The final trace goes to the offline cache, and no poll follows
+        match effect {
+            Some(Effect::SaveJobLog { job_id: 1, log, .. }) => assert_eq!(log.as_str(), "1\n2"),
+            _ => panic!("Expected the final trace to be saved"),
+        }
+        assert!(!app.log_job_running);
+        assert!(!app.log_poll_in_flight);
+    }
+
+    fn chunk(data: &str, total_size: u64, complete: bool) -> Action {
+        Action::JobTraceChunkLoaded {
+            job_id: 7,
+            job_name: "build".to_string(),
+            offset: 0,
+            chunk: TraceChunk {
+                data: data.as_bytes().to_vec(),
+                total_size: Some(total_size),
+                complete,
+            },
+        }
+    }
+
+    #[test]
+    fn test_chunked_log_loading() {
+        let mut app = App::new(123, None, false, 1);
+        app.begin_log_fetch(7, "build".to_string());
+        assert!(app.log_loading_since.is_some());
+        assert!(app.log_content.is_none());
+        let full = "first\nsecond line\nthird";
+
+        // First chunk ends mid-line and opens the viewer right away
+        let effect = app.update(chunk("first\nsec", full.len() as u64, false));
+        assert_eq!(app.mode, AppMode::ViewingLog);
+        assert!(app.log_loading_since.is_none());
+        assert_eq!(app.log_line_count(), 2);
+        assert_eq!(app.log_load_progress(), Some(39)); // 9 of 23 bytes
+        assert!(matches!(effect, Some(Effect::FetchJobTrace { job_id: 7, offset: 9, .. })));
+
+        let mut last = chunk("ond line\nthird", full.len() as u64, true);
+        if let Action::JobTraceChunkLoaded { offset, .. } = &mut last {
+            *offset = 9;
+        }
+        assert!(matches!(app.update(last), Some(Effect::SaveJobLog { job_id: 7, .. })));
+        assert_eq!(app.log_line_count(), 3);
+        assert_eq!(app.processed_log_text(&[1]), "second line");
+        assert_eq!(app.log_load_progress(), None);
+    }
+
+    #[test]
+    fn test_chunked_log_with_invalid_utf8() {
+        let mut app = App::new(123, None, false, 1);
+        app.begin_log_fetch(7, "build".to_string());
+        let data = b"2024-01-15T10:30:45Z 00O dump \xFF\xFE end\n2024-01-15T10:30:46Z 00O ok \xE2\x9C\x93\n";
+        let split = data.len() - 2; // Inside the check mark
+        let mut first = chunk("", data.len() as u64, false);
+        let mut last = chunk("", data.len() as u64, true);
+        if let (
+            Action::JobTraceChunkLoaded { chunk: first, .. },
+            Action::JobTraceChunkLoaded { offset, chunk: last, .. },
+        ) = (&mut first, &mut last)
+        {
+            first.data = data[..split].to_vec();
+            last.data = data[split..].to_vec();
+            *offset = split as u64;
+        }
+        app.update(first);
+        app.update(last);
+        assert_eq!(app.processed_log_text(&[0, 1]), "dump \u{FFFD}\u{FFFD} end\nok ✓");
+    }
+
+    #[test]
+    fn test_stale_log_chunk_is_ignored() {
+        let mut app = App::new(123, None, false, 1);
+        app.begin_log_fetch(7, "build".to_string());
+        app.update(chunk("first\n", 100, false));
+        app.update(Action::CloseLogViewer);
+
+        let mut late = chunk("second\n", 100, false);
+        if let Action::JobTraceChunkLoaded { offset, .. } = &mut late {
+            *offset = 6;
+        }
+        assert!(app.update(late).is_none());
+        assert!(app.log_content.is_none());
+    }
+
+    #[test]
+    fn test_log_closed_while_loading_stays_closed() {
+        let mut app = App::new(123, None, false, 1);
+        app.begin_log_fetch(7, "build".to_string());
+        app.update(Action::CloseLogViewer);
+        assert!(app.log_loading_since.is_none());
+        assert!(app.update(chunk("first\n", 6, true)).is_none());
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.log_content.is_none());
+    }
+
+    #[test]
+    fn test_log_fetch_error_shown_in_viewer_and_retried() {
+        let mut app = App::new(123, None, false, 1);
+        assert!(app.update(Action::RetryLogFetch).is_none());
+
+        app.begin_log_fetch(7, "build".to_string());
+        app.update(Action::JobTraceFailed { job_id: 7, error: "Job trace not found (HTTP 404)".to_string() });
+        assert!(app.log_loading_since.is_none());
+        assert_eq!(app.mode, AppMode::ViewingLog);
+        assert_eq!(app.log_error.as_deref(), Some("Job trace not found (HTTP 404)"));
+
+        let effect = app.update(Action::RetryLogFetch);
+        assert!(matches!(effect, Some(Effect::FetchJobTrace { job_id: 7, offset: 0, .. })));
+        assert!(app.log_error.is_none());
+        assert!(app.log_loading_since.is_some());
+    }
+
+    #[test]
+    fn test_expired_log_shown_without_retry() {
+        let mut app = app_with_job(JobStatus::Failed);
+        let effect = app.update(Action::OpenSelectedJobLog);
+        assert!(matches!(effect, Some(Effect::FetchJobTrace { job_id: 200, .. })));
+
+        // The trace endpoint answered 404
+        app.update(Action::JobTraceExpired { job_id: 200 });
+        assert!(app.log_expired);
+        assert!(app.log_error.is_none());
+        assert!(app.log_loading_since.is_none());
+        assert_eq!(app.mode, AppMode::ViewingLog);
+        assert!(app.update(Action::RetryLogFetch).is_none());
+    }
+
+    #[test]
+    fn test_empty_trace_of_finished_job_is_expired() {
+        let mut app = app_with_job(JobStatus::Success);
+        app.update(Action::OpenSelectedJobLog);
+        let chunk = TraceChunk { data: Vec::new(), total_size: Some(0), complete: true };
+        app.update(Action::JobTraceChunkLoaded { job_id: 200, job_name: "test".to_string(), offset: 0, chunk });
+        assert!(app.log_expired);
+        assert!(app.log_content.is_none());
+
+        // A job that never ran has no log to lose
+        let mut app = app_with_job(JobStatus::Canceled);
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap()[0].started_at = None;
+        app.update(Action::OpenSelectedJobLog);
+        let chunk = TraceChunk { data: Vec::new(), total_size: Some(0), complete: true };
+        app.update(Action::JobTraceChunkLoaded { job_id: 200, job_name: "test".to_string(), offset: 0, chunk });
+        assert!(!app.log_expired);
+        assert!(app.log_content.is_some());
+    }
+
+    #[test]
+    fn test_erased_job_log_is_not_fetched() {
+        let mut app = app_with_job(JobStatus::Failed);
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap()[0].erased_at = Some(Utc::now());
+        assert!(app.update(Action::OpenSelectedJobLog).is_none());
+        assert!(app.log_expired);
+        assert_eq!(app.mode, AppMode::ViewingLog);
+        assert_eq!(app.log_job_id, Some(200));
+    }
+
+    #[test]
+    fn test_log_retry_resumes_broken_download() {
+        let mut app = App::new(123, None, false, 1);
+        app.begin_log_fetch(7, "build".to_string());
+        app.update(chunk("first\n", 100, false));
+        app.update(Action::JobTraceFailed { job_id: 7, error: "Network error".to_string() });
+        assert!(app.log_fetch_complete);
+
+        let effect = app.update(Action::RetryLogFetch);
+        assert!(matches!(effect, Some(Effect::FetchJobTrace { job_id: 7, offset: 6, .. })));
+        assert!(!app.log_fetch_complete);
+    }
+
+    #[test]
+    fn test_job_outcome_found_once_the_log_ends() {
+        let mut app = App::new(123, None, false, 1);
+        app.begin_log_fetch(7, "build".to_string());
+        let (head, tail) = ("$ make\nmake: *** Error 1\n", "ERROR: Job failed: exit code 1\n");
+        let total = (head.len() + tail.len()) as u64;
+        app.update(chunk(head, total, false));
+        assert!(app.log_outcome.is_none());
+
+        let mut last = chunk(tail, total, true);
+        if let Action::JobTraceChunkLoaded { offset, .. } = &mut last {
+            *offset = head.len() as u64;
+        }
+        app.update(last);
+        let outcome = app.log_outcome.clone().unwrap();
+        assert_eq!((outcome.line, outcome.succeeded, outcome.exit_code), (2, false, Some(1)));
+
+        app.update(Action::CloseLogViewer);
+        assert!(app.log_outcome.is_none());
+    }
+
+    #[test]
+    fn test_processed_lines_cached_by_content_and_mode() {
+        let mut app = app_with_log("2024-01-15T10:30:45Z 00O hello");
+        let key = app.log_lines_key.clone();
+        assert!(key.is_some());
+
+        // Reopening the same log after closing reuses the cached lines
+        app.update(Action::CloseLogViewer);
+        app.log_processed_lines.push(vec![ratatui::text::Line::from("marker")]);
+        app.set_log_content(1, "2024-01-15T10:30:45Z 00O hello".to_string(), "job".to_string());
+        assert_eq!(app.log_lines_key, key);
+        assert_eq!(app.log_processed_lines.lines().len(), 2);
+
+        // A different timestamp mode invalidates the cache
+        app.mode = AppMode::ViewingLog;
+        app.update(Action::ToggleTimestampMode);
+        assert_ne!(app.log_lines_key, key);
+        assert_eq!(app.log_processed_lines.lines().len(), 1);
+        assert_eq!(app.processed_log_text(&[0]), "2024-01-15 hello");
+    }
+
+    fn apply_filter(app: &mut App, pattern: &str, inverse: bool) {
+        app.update(Action::StartFilter);
+        app.update(Action::UpdateFilterQuery(pattern.to_string()));
+        if inverse {
+            app.update(Action::ToggleFilterInverse);
+        }
+        app.update(Action::ApplyFilter);
+    }
+
+    #[test]
+    fn test_filter_keeps_matching_lines() {
+        let mut app = app_with_log("compile a\nprogress 10%\ncompile b\nprogress 90%");
+        apply_filter(&mut app, "compile", false);
+        assert!(!app.is_filtering);
+        assert_eq!(app.filtered_line_indices, Some(vec![0, 2]));
+        assert_eq!(app.log_visible_lines, vec![0, 2]);
+
+        // Search only looks at what the filter shows
+        run_search(&mut app, "b");
+        assert_eq!(app.search_results, vec![2]);
+    }
+
+    #[test]
+    fn test_inverse_filter_hides_matching_lines() {
+        let mut app = app_with_log("compile a\nprogress 10%\ncompile b\nprogress 90%");
+        apply_filter(&mut app, "^progress", true);
+        assert_eq!(app.log_visible_lines, vec![0, 2]);
+
+        // Clearing restores the full log
+        app.update(Action::StartFilter);
+        app.update(Action::CancelFilter);
+        assert_eq!(app.filtered_line_indices, None);
+        assert_eq!(app.log_visible_lines, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_invalid_filter_stays_in_input_mode() {
+        let mut app = app_with_log("a\nb");
+        apply_filter(&mut app, "(", false);
+        assert!(app.is_filtering);
+        assert!(app.filter_regex.is_none());
+        assert_eq!(app.log_visible_lines, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_filter_context_lines() {
+        let mut app = app_with_log("a\nb\nmatch 1\nc\nd\ne\nf\nmatch 2\nmatch 3\ng");
+        app.filter_context = 1;
+        apply_filter(&mut app, "match", false);
+        assert_eq!(app.log_visible_lines, vec![1, 2, 3, 6, 7, 8, 9]);
+        // A `--` row separates the groups and counts when scrolling
+        let separators: Vec<usize> = (0..7).filter(|&offset| app.starts_filter_group(offset)).collect();
+        assert_eq!(separators, vec![3]);
+        app.log_viewport_height = 4;
+        assert_eq!(app.max_log_scroll_position(), (3, 1));
+
+        // + / - adjust the context of an applied filter
+        app.update(Action::ShrinkLogPopup);
+        assert_eq!(app.filter_context, 0);
+        assert_eq!(app.log_visible_lines, vec![2, 7, 8]);
+        assert!(!app.starts_filter_group(1));
+        app.update(Action::GrowLogPopup);
+        app.update(Action::GrowLogPopup);
+        assert_eq!(app.log_visible_lines, (0..10).collect::<Vec<_>>());
+        assert_eq!(app.log_popup_percent, DEFAULT_LOG_POPUP_PERCENT);
+    }
+
+    #[test]
+    fn test_lines_moving_the_cursor_down_take_several_rows() {
+        let mut app = app_with_log(
+            "section_start:1700000000:build\r\x1b[0KBuilding\n\
+             step 1\x0bdone 1\n\
+             step 2\x1b[Edone 2\n\
+             section_end:1700000005:build\r\x1b[0K\n\
+             tail\n",
+        );
+        assert_eq!(app.log_line_count(), 5);
+        assert_eq!(app.log_visible_lines, vec![0, 1, 2, 4]);
+        assert_eq!((app.log_line_rows(0), app.log_line_rows(1), app.log_line_rows(2)), (1, 2, 2));
+        assert_eq!(app.processed_log_text(&[1, 2]), "step 1\ndone 1\nstep 2\ndone 2");
+
+        // Scrolling counts rows: the last four show the second row of line 1 down to the tail
+        app.log_viewport_height = 4;
+        assert_eq!(app.max_log_scroll_position(), (1, 1));
+        app.update(Action::ScrollLogEnd);
+        assert_eq!((app.log_scroll_offset, app.log_top_rows_skipped()), (1, 1));
+
+        // Search and filter match text on any row of a line
+        run_search(&mut app, "done 2");
+        assert_eq!(app.search_results, vec![2]);
+        apply_filter(&mut app, "done", false);
+        assert_eq!(app.log_visible_lines, vec![1, 2]);
+        assert_eq!(app.max_log_scroll_position(), (0, 0));
+        app.update(Action::CancelFilter);
+
+        // Folding the section hides every row of its lines
+        app.update(Action::ScrollLogHome);
+        app.update(Action::ToggleSection);
+        assert_eq!(app.log_visible_lines, vec![0, 4]);
+        assert_eq!(app.max_log_scroll_position(), (0, 0));
+    }
+
+    #[test]
+    fn test_only_lines_around_viewport_are_processed_and_in_memory() {
+        let mut buffer = LogBuffer::with_resident_limit(64 * 1024);
+        for i in 0..20_000 {
+            buffer.append_str(&format!("line {}\n", i));
+        }
+        buffer.append_str("section_start:1700000000:deploy\r\x1b[0KDeploying\nlast\n");
+        let mut app = App::new(123, None, false, 1);
+        app.set_log_content(1, buffer, "job".to_string());
+        app.mode = AppMode::ViewingLog;
+        app.log_viewport_height = 20;
+        app.update(Action::ScrollLogHome);
+
+        let window = app.log_processed_lines.lines();
+        assert_eq!(window.start, 0);
+        assert!(window.end < 2 * LOG_WINDOW_MARGIN + 20);
+        assert!(app.log_content.as_ref().unwrap().resident_len() <= 64 * 1024);
+        assert_eq!(app.log_sections.len(), 1);
+        assert_eq!(app.log_sections[0].start_line, 20_000);
+
+        // Lines away from the viewport are processed when asked for
+        assert_eq!(app.processed_log_text(&[15_000]), "line 15000");
+        run_search(&mut app, "line 12345");
+        assert_eq!(app.search_results, vec![12_345]);
+        apply_filter(&mut app, "line 1500[0-2]$", false);
+        assert_eq!(app.log_visible_lines, vec![15_000, 15_001, 15_002]);
+        app.update(Action::CancelFilter);
+
+        // The window follows the viewport to the end
+        app.update(Action::ScrollLogEnd);
+        let window = app.log_processed_lines.lines();
+        assert_eq!(window.end, 20_002);
+        assert!(window.start > 20_000 - 2 * LOG_WINDOW_MARGIN);
+        assert!(app.log_content.as_ref().unwrap().resident_len() <= 64 * 1024);
+    }
+
+    #[test]
+    fn test_filter_flattens_folded_sections() {
+        let mut app = app_with_sectioned_log();
+        let folded_visible = app.log_visible_lines.len();
+        apply_filter(&mut app, ".", false);
+        assert!(app.log_visible_lines.len() > folded_visible);
+    }
+
+    #[test]
+    fn test_search_and_filter_match_displayed_text() {
+        let mut app = app_with_log("2024-01-15T10:30:45Z 00O \x1b[32mall good\x1b[0m\n2024-01-15T10:30:46Z 01E failed\n");
+        assert_eq!(app.timestamp_mode, TimestampDisplayMode::Hidden);
+
+        // Prefixes, timestamps and ANSI codes aren't displayed, so they don't match
+        for hidden in ["00O", "10:30:46", "32m"] {
+            run_search(&mut app, hidden);
+            assert!(app.search_results.is_empty(), "{:?}", hidden);
+        }
+        run_search(&mut app, "failed");
+        assert_eq!(app.search_results, vec![1]);
+
+        // Matches follow what the timestamp mode shows
+        run_search(&mut app, "10:30:46");
+        app.timestamp_mode = TimestampDisplayMode::DateOnly;
+        app.update(Action::ToggleTimestampMode);
+        assert_eq!(app.search_results, vec![1]);
+
+        apply_filter(&mut app, "^2024-01-15 10:30:45 all good$", false);
+        assert_eq!(app.log_visible_lines, vec![0]);
+    }
+
+    #[test]
+    fn test_search_matches_section_headers() {
+        let mut app = app_with_sectioned_log();
+        run_search(&mut app, "Installing deps");
+        assert_eq!(app.search_results, vec![1]);
+        run_search(&mut app, "section_start");
+        assert!(app.search_results.is_empty());
+    }
+
+    #[test]
+    fn test_toggle_line_numbers() {
+        let mut app = app_with_log("a\nb");
+        assert!(!app.show_line_numbers);
+        app.update(Action::ToggleLineNumbers);
+        assert!(app.show_line_numbers);
+        app.update(Action::ToggleLineNumbers);
+        assert!(!app.show_line_numbers);
+    }
+
+    #[test]
+    fn test_page_navigation_is_clamped() {
+        let content: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+        let mut app = app_with_log(&content.join("\n"));
+        app.log_viewport_height = 20;
+
+        app.update(Action::ScrollLogHalfPageDown);
+        assert_eq!(app.log_scroll_offset, 10);
+        app.update(Action::ScrollLogPageDown);
+        assert_eq!(app.log_scroll_offset, 30);
+        app.update(Action::ScrollLogHalfPageUp);
+        assert_eq!(app.log_scroll_offset, 20);
+
+        app.update(Action::ScrollLogEnd);
+        assert_eq!(app.log_scroll_offset, 80);
+        app.update(Action::ScrollLogPageDown);
+        assert_eq!(app.log_scroll_offset, 80);
+        app.update(Action::ScrollLogDown);
+        assert_eq!(app.log_scroll_offset, 80);
+
+        app.update(Action::ScrollLogPageUp);
+        assert_eq!(app.log_scroll_offset, 60);
+        app.update(Action::ScrollLogHome);
+        assert_eq!(app.log_scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_click_selects_lines_for_copy() {
+        let mut app = app_with_log("a\nb\nc\nd");
+        app.update(Action::SelectLogLine { row: 1, extend: false });
+        assert_eq!(app.log_selection, Some((1, 1)));
+        app.update(Action::SelectLogLine { row: 3, extend: true });
+        assert!(app.is_log_line_selected(2));
+
+        let effect = app.update(Action::CopyVisibleLog);
+        assert!(matches!(
+            effect,
+            Some(Effect::CopyToClipboard { ref text, line_count: 3 }) if text == "b\nc\nd"
+        ));
+
+        // Clicking below the last line does nothing
+        app.update(Action::SelectLogLine { row: 10, extend: false });
+        assert_eq!(app.log_selection, Some((1, 3)));
+    }
+
+    #[test]
+    fn test_open_link_in_log() {
+        let mut app = app_with_log("$ make coverage
+Report: https://example.com/cov/index.html.
+Deployed to https://review.example.com
+done");
+        let opened = |effect: Option<Effect>| match effect {
+            Some(Effect::OpenUrl(url)) => Some(url),
+            _ => None,
+        };
+
+        // Without a selection the first link in view opens
+        assert_eq!(opened(app.update(Action::OpenLogUrl)).as_deref(), Some("https://example.com/cov/index.html"));
+
+        // Otherwise the one on the selected line
+        app.update(Action::SelectLogLine { row: 2, extend: false });
+        assert_eq!(opened(app.update(Action::OpenLogUrl)).as_deref(), Some("https://review.example.com"));
+        app.update(Action::SelectLogLine { row: 3, extend: false });
+        assert!(app.update(Action::OpenLogUrl).is_none());
+        assert_eq!(app.visible_status(), Some("No link found"));
+
+        // Ctrl+click opens the clicked line's link and selects it
+        let effect = app.update(Action::OpenLogUrlAt { row: 1 });
+        assert_eq!(opened(effect).as_deref(), Some("https://example.com/cov/index.html"));
+        assert_eq!(app.log_selection, Some((1, 1)));
+    }
+
+    #[test]
+    fn test_visual_selection_from_keyboard() {
+        let content: Vec<String> = (0..20).map(|i| format!("line {}", i)).collect();
+        let mut app = app_with_log(&content.join("\n"));
+        app.log_viewport_height = 5;
+        app.update(Action::ScrollLogDown);
+
+        app.update(Action::StartVisualSelection);
+        assert!(app.log_visual_mode);
+        assert_eq!(app.log_selection, Some((1, 1)));
+
+        // j extends the selection and scrolls to keep its end on screen
+        app.update(Action::Repeat { count: 6, action: Box::new(Action::ScrollLogDown) });
+        assert_eq!(app.log_selection, Some((1, 7)));
+        assert!(app.log_viewport_lines().contains(&7));
+        app.update(Action::ScrollLogUp);
+        assert_eq!(app.log_selection, Some((1, 6)));
+
+        let effect = app.update(Action::CopyVisibleLog);
+        assert!(matches!(effect, Some(Effect::CopyToClipboard { line_count: 6, .. })));
+        assert!(!app.log_visual_mode);
+        assert!(app.log_selection.is_none());
+
+        // Esc drops the selection, moving keys scroll again
+        app.update(Action::StartVisualSelection);
+        app.update(Action::ScrollLogEnd);
+        assert_eq!(app.log_selection.map(|(_, end)| end), Some(19));
+        app.update(Action::CancelVisualSelection);
+        assert!(app.log_selection.is_none());
+        let offset = app.log_scroll_offset;
+        app.update(Action::ScrollLogUp);
+        assert_eq!(app.log_scroll_offset, offset - 1);
+    }
+
+    #[test]
+    fn test_wheel_scroll_is_clamped() {
+        let content: Vec<String> = (0..40).map(|i| i.to_string()).collect();
+        let mut app = app_with_log(&content.join("\n"));
+        app.log_viewport_height = 30;
+        app.update(Action::ScrollLogDownBy(3));
+        assert_eq!(app.log_scroll_offset, 3);
+        app.update(Action::ScrollLogDownBy(30));
+        assert_eq!(app.log_scroll_offset, 10);
+        app.update(Action::ScrollLogUpBy(30));
+        assert_eq!(app.log_scroll_offset, 0);
+    }
+
+    #[test]
+    fn test_configured_scroll_steps() {
+        let mut app = app_with_log(&numbered_lines(100));
+        app.log_viewport_height = 10;
+        app.scroll_step = 3;
+        app.fast_scroll_step = 20;
+        app.update(Action::ScrollLogDown);
+        assert_eq!(app.log_scroll_offset, 3);
+        app.update(Action::ScrollLogFastDown);
+        assert_eq!(app.log_scroll_offset, 23);
+        app.update(Action::ScrollLogUp);
+        assert_eq!(app.log_scroll_offset, 20);
+        // Pages still move by the viewport height
+        app.update(Action::ScrollLogPageDown);
+        assert_eq!(app.log_scroll_offset, 30);
+        app.update(Action::ScrollLogFastUp);
+        assert_eq!(app.log_scroll_offset, 10);
+    }
+
+    #[test]
+    fn test_log_popup_size_is_clamped() {
+        let mut app = App::new(123, None, false, 1);
+        assert_eq!(app.log_popup_percent, DEFAULT_LOG_POPUP_PERCENT);
+        for _ in 0..5 {
+            app.update(Action::GrowLogPopup);
+        }
+        assert_eq!(app.log_popup_percent, 100);
+        for _ in 0..20 {
+            app.update(Action::ShrinkLogPopup);
+        }
+        assert_eq!(app.log_popup_percent, MIN_LOG_POPUP_PERCENT);
+        assert_eq!(app.visible_status(), Some("Log viewer at 50% of the screen"));
+
+        // Hand-edited preferences can't make the viewer unusable either
+        let preferences = crate::config::Preferences { log_popup_percent: 5, ..Default::default() };
+        app.apply_preferences(&preferences);
+        assert_eq!(app.log_popup_percent, MIN_LOG_POPUP_PERCENT);
+    }
+
+    #[test]
+    fn test_preferences_round_trip_through_app() {
+        let mut app = App::new(123, None, false, 1);
+        let preferences = crate::config::Preferences {
+            timestamp_mode: TimestampDisplayMode::Full,
+            wrap_enabled: false,
+            follow_running_jobs: true,
+            search_history: vec!["error".to_string()],
+            show_status_bar: false,
+            log_popup_percent: 100,
+            last_location: None,
+        };
+        app.apply_preferences(&preferences);
+        assert_eq!(app.preferences(), preferences);
+    }
+
+    #[test]
+    fn test_follow_preference_applies_to_running_jobs() {
+        let mut app = App::new(123, None, false, 1);
+        app.follow_by_default = true;
+        app.log_job_running = true;
+        app.set_log_content(1, "a\nb".to_string(), "job".to_string());
+        assert!(app.follow_mode);
+
+        app.log_job_running = false;
+        app.set_log_content(2, "a\nb".to_string(), "job".to_string());
+        assert!(!app.follow_mode);
+    }
+
+    fn type_count(app: &mut App, digits: &str) {
+        for c in digits.chars() {
+            app.update(Action::PushCountDigit(c.to_digit(10).unwrap()));
+        }
+    }
+
+    fn numbered_lines(count: usize) -> String {
+        (0..count).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn test_count_prefix_repeats_scroll() {
+        let mut app = app_with_log(&numbered_lines(50));
+        app.log_viewport_height = 10;
+        type_count(&mut app, "12");
+        assert_eq!(app.active_count(), Some(12));
+
+        let count = app.active_count().unwrap();
+        app.update(Action::Repeat { count, action: Box::new(Action::ScrollLogDown) });
+        assert_eq!(app.log_scroll_offset, 12);
+        assert_eq!(app.active_count(), None);
+    }
+
+    #[test]
+    fn test_count_prefix_repeats_next_match() {
+        let mut app = app_with_log(&numbered_lines(50));
+        run_search(&mut app, "line 1");
+        assert_eq!(app.current_search_result, 0);
+        app.update(Action::Repeat { count: 3, action: Box::new(Action::NextSearchResult) });
+        assert_eq!(app.current_search_result, 3);
+    }
+
+    #[test]
+    fn test_count_prefix_ignored_by_other_actions() {
+        let mut app = app_with_log("a\nb");
+        type_count(&mut app, "3");
+        app.update(Action::Repeat { count: 3, action: Box::new(Action::ToggleWrap) });
+        assert!(!app.wrap_enabled); // Toggled once, not three times
+        assert_eq!(app.active_count(), None);
+    }
+
+    #[test]
+    fn test_count_prefix_times_out() {
+        let mut app = App::new(123, None, false, 1);
+        app.pending_count = Some((5, Instant::now() - COUNT_PREFIX_TIMEOUT));
+        assert_eq!(app.active_count(), None);
+        type_count(&mut app, "7");
+        assert_eq!(app.active_count(), Some(7));
+    }
+
+    #[test]
+    fn test_count_prefix_is_capped() {
+        let mut app = App::new(123, None, false, 1);
+        type_count(&mut app, "123456");
+        assert_eq!(app.active_count(), Some(MAX_COUNT_PREFIX));
+    }
+
+    #[test]
+    fn test_search_history_records_submitted_queries() {
+        let mut app = app_with_log("error\nwarning");
+        run_search(&mut app, "error");
+        run_search(&mut app, "warning");
+        run_search(&mut app, "error"); // Moves to the most recent position
+        run_search(&mut app, "");
+        assert_eq!(app.search_history, vec!["warning", "error"]);
+    }
+
+    #[test]
+    fn test_search_history_is_capped() {
+        let mut app = app_with_log("a");
+        for i in 0..SEARCH_HISTORY_LIMIT + 5 {
+            run_search(&mut app, &format!("query {}", i));
+        }
+        assert_eq!(app.search_history.len(), SEARCH_HISTORY_LIMIT);
+        assert_eq!(app.search_history[0], "query 5");
+    }
+
+    #[test]
+    fn test_search_history_recall() {
+        let mut app = app_with_log("a");
+        run_search(&mut app, "first");
+        run_search(&mut app, "second");
+
+        app.update(Action::StartSearch);
+        app.update(Action::UpdateSearchQuery("dra".to_string()));
+        app.update(Action::SearchHistoryPrev);
+        assert_eq!(app.search_query, "second");
+        app.update(Action::SearchHistoryPrev);
+        assert_eq!(app.search_query, "first");
+        app.update(Action::SearchHistoryPrev); // Stays on the oldest entry
+        assert_eq!(app.search_query, "first");
+        app.update(Action::SearchHistoryNext);
+        assert_eq!(app.search_query, "second");
+        app.update(Action::SearchHistoryNext); // Back to the draft
+        assert_eq!(app.search_query, "dra");
+        assert_eq!(app.search_history_index, None);
+    }
+
+    fn tracked_mr_with(pipelines: Vec<Pipeline>) -> TrackedMergeRequest {
+        TrackedMergeRequest {
+            mr: create_test_mr(1, 10, "Test MR"),
+            pipelines,
+            jobs: HashMap::new(),
+            job_logs_cache: HashMap::new(),
+            notes: Vec::new(),
+            notes_loaded: false,
+            selected_pipeline_index: 0,
+            selected_note_index: 0,
+            loading: false,
+            error: None,
+            ..Default::default()
+        }
+    }
+
+    fn is_live_poll(effect: &Effect) -> bool {
+        matches!(effect, Effect::ScheduleLivePoll { .. })
+    }
+
+    #[test]
+    fn test_merge_requests_loaded_fetches_all_pipelines() {
+        let mut app = App::new(123, None, false, 1);
+        let effect = app.update(Action::MergeRequestsLoaded(vec![
+            create_test_mr(1, 10, "MR 1"),
+            create_test_mr(2, 20, "MR 2"),
+        ]));
+        match effect {
+            Some(Effect::Batch(effects)) => assert_eq!(effects.len(), 2),
+            other => panic!("expected a batch, got {:?}", other),
+        }
+    }
+
+    fn restoring(pipeline_id: u64, job_id: Option<u64>, log_open: bool) -> App {
+        let mut app = App::new(123, None, false, 1);
+        app.live_refresh_interval = None;
+        app.restore_location =
+            Some(crate::config::LastLocation { project_id: 123, mr_iid: 20, pipeline_id, job_id, log_open });
+        app.update(Action::MergeRequestsLoaded(vec![create_test_mr(1, 10, "MR 1"), create_test_mr(2, 20, "MR 2")]));
+        assert_eq!(app.selected_mr_index, 1);
+        app
+    }
+
+    fn pipelines_loaded(app: &mut App) -> Option<Effect> {
+        app.update(Action::PipelinesLoaded {
+            mr_index: 1,
+            page: 1,
+            status: None,
+            pipelines: vec![
+                create_test_pipeline(101, PipelineStatus::Success),
+                create_test_pipeline(100, PipelineStatus::Failed),
+            ],
+            next_page: None,
+        })
+    }
+
+    #[test]
+    fn test_restore_last_location() {
+        let mut app = restoring(100, Some(201), true);
+        // The restored pipeline's jobs are fetched, not the latest pipeline's
+        let effect = pipelines_loaded(&mut app);
+        assert!(matches!(effect, Some(Effect::FetchJobs { mr_index: 1, pipeline_id: 100, .. })));
+        assert_eq!(app.get_selected_pipeline().map(|p| p.id), Some(100));
+
+        let jobs = vec![create_test_job(200, "lint", JobStatus::Failed), create_test_job(201, "test", JobStatus::Success)];
+        let effect = app.update(Action::JobsLoaded { mr_index: 1, pipeline_id: 100, jobs });
+        assert_eq!(app.selected_job().map(|job| job.id), Some(201));
+        assert_eq!(app.mode, AppMode::ViewingLog);
+        assert!(matches!(effect, Some(Effect::FetchJobTrace { job_id: 201, .. })));
+        assert!(app.restore_location.is_none());
+
+        // Saved again on quitting
+        let location = app.preferences().last_location.unwrap();
+        assert_eq!((location.mr_iid, location.pipeline_id, location.job_id, location.log_open), (20, 100, Some(201), true));
+    }
+
+    #[test]
+    fn test_restore_pipeline_past_the_first_page() {
+        let mut app = restoring(50, None, false);
+        assert!(matches!(pipelines_loaded(&mut app), Some(Effect::FetchPipeline { mr_index: 1, pipeline_id: 50, .. })));
+        // A refresh meanwhile doesn't ask again
+        assert!(!matches!(pipelines_loaded(&mut app), Some(Effect::FetchPipeline { .. })));
+
+        let pipeline = create_test_pipeline(50, PipelineStatus::Failed);
+        let effect = app.update(Action::PipelineLoaded { mr_index: 1, pipeline });
+        assert!(matches!(effect, Some(Effect::FetchJobs { mr_index: 1, pipeline_id: 50, .. })));
+        assert_eq!(app.get_selected_pipeline().map(|p| p.id), Some(50));
+        assert_eq!(app.tracked_mrs[1].pipelines.len(), 3);
+    }
+
+    #[test]
+    fn test_restore_gives_up_on_gone_locations() {
+        let mut app = restoring(999, None, false);
+        assert!(matches!(pipelines_loaded(&mut app), Some(Effect::FetchPipeline { mr_index: 1, pipeline_id: 999, .. })));
+        let error = "Resource not found: 404 Not found".to_string();
+        let effect = app.update(Action::PipelineFetchFailed { mr_index: 1, pipeline_id: 999, error });
+        assert!(matches!(effect, Some(Effect::FetchJobs { pipeline_id: 101, .. })));
+        assert!(app.restore_location.is_none());
+        assert_eq!(
+            app.visible_status(),
+            Some("Couldn't restore the last location, pipeline #999: Resource not found: 404 Not found")
+        );
+
+        let mut app = restoring(100, Some(999), true);
+        pipelines_loaded(&mut app);
+        let jobs = vec![create_test_job(200, "lint", JobStatus::Failed)];
+        assert!(app.update(Action::JobsLoaded { mr_index: 1, pipeline_id: 100, jobs }).is_none());
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.visible_status(), Some("Couldn't restore the last location, job #999 is gone"));
+
+        let mut app = App::new(123, None, false, 1);
+        app.restore_location =
+            Some(crate::config::LastLocation { project_id: 123, mr_iid: 5, pipeline_id: 1, job_id: None, log_open: false });
+        app.update(Action::MergeRequestsLoaded(vec![create_test_mr(1, 10, "MR 1")]));
+        assert!(app.restore_location.is_none());
+        assert_eq!(app.visible_status(), Some("Couldn't restore the last location, !5 is no longer listed"));
+    }
+
+    #[test]
+    fn test_running_pipeline_starts_live_polling() {
+        let mut app = App::new(123, None, false, 1);
+        app.tracked_mrs.push(tracked_mr_with(vec![]));
 
-                // Process all lines upfront for fast rendering
-                self.log_processed_lines = crate::log_processor::process_log_content(&trace, &self.timestamp_mode);
-                self.log_content = Some(trace);
-                self.log_job_name = Some(job_name);
-                self.log_scroll_offset = 0;
-                self.mode = AppMode::ViewingLog;
-                None
+        let effect = app.update(Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 1,
+            status: None,
+            pipelines: vec![create_test_pipeline(100, PipelineStatus::Running)],
+            next_page: None,
+        });
+        match effect {
+            Some(Effect::Batch(effects)) => {
+                assert!(matches!(effects[0], Effect::FetchJobs { pipeline_id: 100, .. }));
+                assert!(is_live_poll(&effects[1]));
             }
+            other => panic!("expected jobs fetch and live poll, got {:?}", other),
+        }
 
-            Action::CloseLogViewer => {
-                self.mode = AppMode::Normal;
-                self.log_content = None;
-                self.log_processed_lines.clear();
-                self.log_job_name = None;
-                self.log_scroll_offset = 0;
-                self.search_query.clear();
-                self.search_results.clear();
-                self.current_search_result = 0;
-                self.is_searching = false;
-                None
-            }
+        // Only one poll loop at a time
+        let effect = app.update(Action::JobsLoaded {
+            mr_index: 0,
+            pipeline_id: 100,
+            jobs: vec![create_test_job(200, "build", JobStatus::Running)],
+        });
+        assert!(effect.is_none());
+    }
 
-            Action::ScrollLogUp => {
-                if self.mode == AppMode::ViewingLog {
-                    self.log_scroll_offset = self.log_scroll_offset.saturating_sub(1);
-                }
-                None
-            }
+    #[test]
+    fn test_finished_pipelines_are_not_polled() {
+        let mut app = App::new(123, None, false, 1);
+        app.tracked_mrs.push(tracked_mr_with(vec![]));
+        let effect = app.update(Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 1,
+            status: None,
+            pipelines: vec![create_test_pipeline(100, PipelineStatus::Success)],
+            next_page: None,
+        });
+        assert!(matches!(effect, Some(Effect::FetchJobs { .. })));
+    }
 
-            Action::ScrollLogDown => {
-                if self.mode == AppMode::ViewingLog {
-                    self.log_scroll_offset = self.log_scroll_offset.saturating_add(1);
-                }
-                None
+    #[test]
+    fn test_live_poll_refetches_active_items_until_done() {
+        let mut app = App::new(123, None, false, 1);
+        let mut mr = tracked_mr_with(vec![
+            create_test_pipeline(101, PipelineStatus::Running),
+            create_test_pipeline(100, PipelineStatus::Failed),
+        ]);
+        mr.jobs.insert(100, vec![create_test_job(200, "retry", JobStatus::Pending)]);
+        app.tracked_mrs.push(mr);
+
+        match app.update(Action::LivePollDue) {
+            Some(Effect::Batch(effects)) => {
+                assert!(matches!(effects[0], Effect::FetchPipelines { mr_iid: 10, .. }));
+                assert!(matches!(effects[1], Effect::FetchJobs { pipeline_id: 100, .. }));
+                assert!(is_live_poll(&effects[2]));
             }
+            other => panic!("expected fetches and next poll, got {:?}", other),
+        }
 
-            Action::ScrollLogPageUp => {
-                if self.mode == AppMode::ViewingLog {
-                    self.log_scroll_offset = self.log_scroll_offset.saturating_sub(10);
-                }
-                None
-            }
+        // Once everything finished the loop stops
+        app.tracked_mrs[0].pipelines[0].status = PipelineStatus::Success;
+        app.tracked_mrs[0].jobs.insert(100, vec![create_test_job(200, "retry", JobStatus::Success)]);
+        assert!(app.update(Action::LivePollDue).is_none());
+        assert!(!app.has_active_pipelines());
+    }
 
-            Action::ScrollLogPageDown => {
-                if self.mode == AppMode::ViewingLog {
-                    self.log_scroll_offset = self.log_scroll_offset.saturating_add(10);
-                }
-                None
-            }
+    #[test]
+    fn test_live_polling_can_be_disabled() {
+        let mut app = App::new(123, None, false, 1);
+        app.live_refresh_interval = None;
+        app.tracked_mrs.push(tracked_mr_with(vec![]));
+        let effect = app.update(Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 1,
+            status: None,
+            pipelines: vec![create_test_pipeline(100, PipelineStatus::Running)],
+            next_page: None,
+        });
+        assert!(matches!(effect, Some(Effect::FetchJobs { .. })));
+    }
 
-            Action::ScrollLogHome => {
-                if self.mode == AppMode::ViewingLog {
-                    self.log_scroll_offset = 0;
-                }
-                None
-            }
+    fn app_with_job(status: JobStatus) -> App {
+        let mut app = App::new(123, None, false, 1);
+        let mut mr = tracked_mr_with(vec![create_test_pipeline(100, PipelineStatus::Failed)]);
+        mr.jobs.insert(100, vec![create_test_job(200, "test", status)]);
+        app.tracked_mrs.push(mr);
+        app
+    }
 
-            Action::ScrollLogEnd => {
-                if self.mode == AppMode::ViewingLog {
-                    if let Some(content) = &self.log_content {
-                        let total_lines = content.lines().count();
-                        self.log_scroll_offset = total_lines.saturating_sub(1);
-                    }
-                }
-                None
-            }
+    fn jobs_loaded(jobs: Vec<Job>) -> Action {
+        Action::JobsLoaded { mr_index: 0, pipeline_id: 100, jobs }
+    }
 
-            Action::ToggleTimestampMode => {
-                if self.mode == AppMode::ViewingLog {
-                    self.timestamp_mode = match self.timestamp_mode {
-                        TimestampDisplayMode::Hidden => TimestampDisplayMode::DateOnly,
-                        TimestampDisplayMode::DateOnly => TimestampDisplayMode::Full,
-                        TimestampDisplayMode::Full => TimestampDisplayMode::Hidden,
-                    };
-                    // Reprocess lines with new timestamp mode
-                    if let Some(ref content) = self.log_content {
-                        self.log_processed_lines = crate::log_processor::process_log_content(content, &self.timestamp_mode);
-                    }
-                }
-                None
-            }
+    #[test]
+    fn test_live_job_times_only_for_running_or_queued_jobs() {
+        let app = app_with_job(JobStatus::Success);
+        assert!(!app.shows_live_job_times());
+        let mut app = app_with_job(JobStatus::Pending);
+        assert!(app.shows_live_job_times());
+        app.mode = AppMode::ViewingLog;
+        assert!(!app.shows_live_job_times());
+    }
 
-            Action::StartSearch => {
-                if self.mode == AppMode::ViewingLog {
-                    self.is_searching = true;
-                    self.search_query.clear();
-                }
-                None
+    #[test]
+    fn test_watched_job_notifies_when_it_finishes() {
+        let mut app = app_with_job(JobStatus::Success);
+        app.update(Action::ToggleWatchJob);
+        assert!(app.watched_jobs.is_empty()); // Already over
+        assert_eq!(app.visible_status(), Some("'test' has already finished"));
+
+        // Nothing would notice the job finishing without live refresh
+        let mut app = app_with_job(JobStatus::Running);
+        app.live_refresh_interval = None;
+        app.update(Action::ToggleWatchJob);
+        assert!(app.watched_jobs.is_empty());
+        assert_eq!(app.visible_status(), Some(WATCH_NEEDS_LIVE_REFRESH));
+
+        // The first watch schedules polling, which later updates don't add to
+        let mut app = app_with_job(JobStatus::Running);
+        assert!(matches!(app.update(Action::ToggleWatchJob), Some(Effect::ScheduleLivePoll { .. })));
+        assert!(app.watched_jobs.contains(&200));
+
+        assert!(app.update(jobs_loaded(vec![create_test_job(200, "test", JobStatus::Running)])).is_none());
+        match app.update(jobs_loaded(vec![create_test_job(200, "test", JobStatus::Failed)])) {
+            Some(Effect::Notify { mode: NotifyMode::Both, summary, body, urgent }) => {
+                assert!(summary.contains("test"));
+                assert_eq!(body, "Job 'test' finished: failed");
+                assert!(urgent);
             }
+            _ => panic!("Expected a notification"),
+        }
+        assert!(app.watched_jobs.is_empty());
+        assert!(app.update(jobs_loaded(vec![create_test_job(200, "test", JobStatus::Failed)])).is_none());
+    }
 
-            Action::UpdateSearchQuery(query) => {
-                if self.is_searching {
-                    self.search_query = query;
-                }
-                None
-            }
+    #[test]
+    fn test_watched_pipeline_notifies_for_each_job() {
+        let mut app = App::new(123, None, false, 1);
+        let mut mr = tracked_mr_with(vec![create_test_pipeline(100, PipelineStatus::Running)]);
+        mr.jobs.insert(
+            100,
+            vec![create_test_job(200, "build", JobStatus::Running), create_test_job(201, "test", JobStatus::Pending)],
+        );
+        app.tracked_mrs.push(mr);
+        app.update(Action::ToggleWatchPipeline);
+        assert!(app.watched_pipelines.contains(&100));
+
+        let effect = app.update(jobs_loaded(vec![
+            create_test_job(200, "build", JobStatus::Success),
+            create_test_job(201, "test", JobStatus::Running),
+        ]));
+        assert!(matches!(effect, Some(Effect::Notify { urgent: false, .. })));
+        assert!(app.watched_pipelines.contains(&100));
+
+        // Canceled jobs aren't announced, and the watch ends with the last running job
+        app.notify_mode = NotifyMode::Bell;
+        let effect = app.update(jobs_loaded(vec![
+            create_test_job(200, "build", JobStatus::Success),
+            create_test_job(201, "test", JobStatus::Canceled),
+        ]));
+        assert!(effect.is_none());
+        assert!(app.watched_pipelines.is_empty());
+    }
 
-            Action::ExecuteSearch => {
-                if let Some(content) = &self.log_content {
-                    self.search_results.clear();
+    #[test]
+    fn test_retry_job_asks_for_confirmation() {
+        let mut app = app_with_job(JobStatus::Failed);
+        assert!(app.update(Action::RetrySelectedJob).is_none());
+        let confirmation = app.pending_confirmation.as_ref().expect("confirmation prompt");
+        assert!(confirmation.prompt.contains("test"));
+
+        let effect = app.update(Action::ConfirmPending);
+        assert!(matches!(
+            effect,
+            Some(Effect::RetryJob { mr_index: 0, project_id: 123, pipeline_id: 100, job_id: 200 })
+        ));
+        assert!(app.pending_confirmation.is_none());
+    }
 
-                    if !self.search_query.is_empty() {
-                        // Find all lines containing the search query (case-insensitive)
-                        let query_lower = self.search_query.to_lowercase();
-                        for (idx, line) in content.lines().enumerate() {
-                            if line.to_lowercase().contains(&query_lower) {
-                                self.search_results.push(idx);
-                            }
-                        }
-                    }
+    #[test]
+    fn test_cancelled_retry_does_nothing() {
+        let mut app = app_with_job(JobStatus::Failed);
+        app.update(Action::RetrySelectedJob);
+        assert!(app.update(Action::CancelConfirmation).is_none());
+        assert!(app.pending_confirmation.is_none());
+        assert!(app.update(Action::ConfirmPending).is_none());
+    }
 
-                    self.is_searching = false;
-                    self.current_search_result = 0;
+    #[test]
+    fn test_running_job_cannot_be_retried() {
+        let mut app = app_with_job(JobStatus::Running);
+        app.update(Action::RetrySelectedJob);
+        assert!(app.pending_confirmation.is_none());
+        assert_eq!(app.visible_status(), Some("Job 'test' is running and can't be retried"));
+    }
 
-                    // Jump to first result if any, centered in viewport
-                    if !self.search_results.is_empty() {
-                        self.center_log_line(self.search_results[0]);
-                    }
-                }
-                None
+    #[test]
+    fn test_play_manual_job_with_variables() {
+        let mut app = app_with_job(JobStatus::Failed);
+        app.update(Action::PlaySelectedJob);
+        assert!(app.play_prompt.is_none());
+        assert_eq!(app.visible_status(), Some("Job 'test' is not a manual job"));
+
+        let mut app = app_with_job(JobStatus::Manual);
+        assert!(app.update(Action::PlaySelectedJob).is_none());
+        assert_eq!(app.play_prompt.as_ref().map(|prompt| prompt.job_id), Some(200));
+
+        // A malformed variable keeps the prompt open
+        app.update(Action::UpdatePlayInput("TARGET".to_string()));
+        assert!(app.update(Action::SubmitPlayJob).is_none());
+        assert!(app.play_prompt.is_some());
+
+        app.update(Action::UpdatePlayInput("TARGET=prod".to_string()));
+        match app.update(Action::SubmitPlayJob) {
+            Some(Effect::PlayJob { mr_index: 0, project_id: 123, pipeline_id: 100, job_id: 200, variables }) => {
+                assert_eq!(variables, vec![("TARGET".to_string(), "prod".to_string())]);
             }
+            other => panic!("Expected PlayJob, got {:?}", other),
+        }
+        assert!(app.play_prompt.is_none());
+    }
 
-            Action::NextSearchResult => {
-                if !self.search_results.is_empty() && self.mode == AppMode::ViewingLog {
-                    self.current_search_result = (self.current_search_result + 1) % self.search_results.len();
-                    self.center_log_line(self.search_results[self.current_search_result]);
-                }
-                None
-            }
+    #[test]
+    fn test_cancelled_play_does_nothing() {
+        let mut app = app_with_job(JobStatus::Manual);
+        app.update(Action::PlaySelectedJob);
+        assert!(app.update(Action::CancelPlayJob).is_none());
+        assert!(app.play_prompt.is_none());
+        assert!(app.update(Action::SubmitPlayJob).is_none());
+    }
 
-            Action::PrevSearchResult => {
-                if !self.search_results.is_empty() && self.mode == AppMode::ViewingLog {
-                    self.current_search_result = if self.current_search_result == 0 {
-                        self.search_results.len() - 1
-                    } else {
-                        self.current_search_result - 1
-                    };
-                    self.center_log_line(self.search_results[self.current_search_result]);
-                }
-                None
-            }
+    #[test]
+    fn test_job_played_refreshes_pipeline() {
+        let mut app = app_with_job(JobStatus::Manual);
+        let effect = app.update(Action::JobPlayed {
+            mr_index: 0,
+            pipeline_id: 100,
+            job: create_test_job(200, "test", JobStatus::Pending),
+        });
+        assert!(matches!(effect, Some(Effect::Batch(_))));
+        assert_eq!(app.visible_status(), Some("Started manual job 'test'"));
+    }
 
-            Action::CancelSearch => {
-                self.is_searching = false;
-                self.search_query.clear();
-                None
+    #[test]
+    fn test_job_retried_refreshes_pipeline() {
+        let mut app = app_with_job(JobStatus::Failed);
+        let effect = app.update(Action::JobRetried {
+            mr_index: 0,
+            pipeline_id: 100,
+            job: create_test_job(201, "test", JobStatus::Pending),
+        });
+        match effect {
+            Some(Effect::Batch(effects)) => {
+                assert!(matches!(effects[0], Effect::FetchPipelines { mr_iid: 10, .. }));
+                assert!(matches!(effects[1], Effect::FetchJobs { pipeline_id: 100, .. }));
             }
+            other => panic!("expected pipeline refresh, got {:?}", other),
+        }
+        assert_eq!(app.visible_status(), Some("Retried job 'test' as #201"));
+    }
 
-            Action::ApiError(error) => {
-                self.error_message = Some(error.clone());
-                self.status_message = None;
-                None
+    #[test]
+    fn test_retry_failure_is_shown() {
+        let mut app = app_with_job(JobStatus::Failed);
+        app.update(Action::JobActionFailed("Retry failed: Insufficient permissions".to_string()));
+        assert_eq!(app.visible_status(), Some("Retry failed: Insufficient permissions"));
+    }
+
+    #[test]
+    fn test_cancel_running_job_after_confirmation() {
+        let mut app = app_with_job(JobStatus::Running);
+        app.update(Action::CancelSelectedJob);
+        let effect = app.update(Action::ConfirmPending);
+        assert!(matches!(
+            effect,
+            Some(Effect::CancelJob { mr_index: 0, pipeline_id: 100, job_id: 200, .. })
+        ));
+    }
+
+    #[test]
+    fn test_finished_job_cannot_be_canceled() {
+        let mut app = app_with_job(JobStatus::Success);
+        app.update(Action::CancelSelectedJob);
+        assert!(app.pending_confirmation.is_none());
+        assert_eq!(app.visible_status(), Some("Job 'test' is not running"));
+    }
+
+    #[test]
+    fn test_cancel_pipeline_after_confirmation() {
+        let mut app = app_with_job(JobStatus::Running);
+        app.tracked_mrs[0].pipelines[0].status = PipelineStatus::Running;
+        app.update(Action::CancelSelectedPipeline);
+        assert!(app.pending_confirmation.is_some());
+        let effect = app.update(Action::ConfirmPending);
+        assert!(matches!(effect, Some(Effect::CancelPipeline { mr_index: 0, pipeline_id: 100, .. })));
+    }
+
+    #[test]
+    fn test_retry_pipeline_reports_jobs_left_alone() {
+        let mut app = app_with_stages();
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap().push(create_test_job(206, "e2e", JobStatus::Canceled));
+        app.update(Action::RetrySelectedPipeline);
+        assert_eq!(
+            app.pending_confirmation.as_ref().map(|c| c.prompt.as_str()),
+            Some("Retry 2 failed or canceled jobs of pipeline #100?")
+        );
+        let effect = app.update(Action::ConfirmPending);
+        assert!(matches!(effect, Some(Effect::RetryPipeline { mr_index: 0, pipeline_id: 100, .. })));
+
+        // Jobs from a poll sent before GitLab answered don't tell anything yet
+        let before = app.tracked_mrs[0].jobs[&100].clone();
+        app.update(jobs_loaded(before.clone()));
+        assert_eq!(app.visible_status(), Some("Retrying pipeline..."));
+
+        let pipeline = create_test_pipeline(100, PipelineStatus::Pending);
+        assert!(app.update(Action::PipelineRetried { mr_index: 0, pipeline }).is_some());
+        // unit was replaced by a new job, e2e is still listed as it was
+        let mut after: Vec<Job> = before.into_iter().filter(|job| job.id != 205).collect();
+        after.push(Job { stage: "test".to_string(), ..create_test_job(305, "unit", JobStatus::Pending) });
+        app.update(jobs_loaded(after.clone()));
+        assert_eq!(app.visible_status(), Some("Retried 1 of 2 jobs of pipeline #100, GitLab did not retry: e2e"));
+
+        // Reported once
+        app.set_transient_status(String::new());
+        app.update(jobs_loaded(after));
+        assert_eq!(app.visible_status(), Some(""));
+    }
+
+    #[test]
+    fn test_retry_pipeline_without_failed_jobs() {
+        let mut app = app_with_job(JobStatus::Success);
+        app.update(Action::RetrySelectedPipeline);
+        assert!(app.pending_confirmation.is_none());
+        assert_eq!(app.visible_status(), Some("Pipeline #100 has no failed or canceled jobs"));
+
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap()[0].status = JobStatus::Failed;
+        app.update(Action::RetrySelectedPipeline);
+        app.update(Action::ConfirmPending);
+        app.update(Action::PipelineRetryFailed { pipeline_id: 100, error: "Forbidden".to_string() });
+        assert_eq!(app.visible_status(), Some("Retry failed: Forbidden"));
+        assert!(app.pipeline_retry.is_none());
+    }
+
+    #[test]
+    fn test_cancel_of_job_that_finished_meanwhile() {
+        let mut app = app_with_job(JobStatus::Running);
+        let effect = app.update(Action::JobCanceled {
+            mr_index: 0,
+            pipeline_id: 100,
+            job: create_test_job(200, "test", JobStatus::Success),
+        });
+        assert_eq!(app.visible_status(), Some("Job 'test' already finished (success)"));
+        assert!(matches!(effect, Some(Effect::Batch(_))));
+
+        app.update(Action::JobCanceled {
+            mr_index: 0,
+            pipeline_id: 100,
+            job: create_test_job(200, "test", JobStatus::Canceled),
+        });
+        assert_eq!(app.visible_status(), Some("Canceled job 'test'"));
+    }
+
+    #[test]
+    fn test_download_requires_artifacts() {
+        let mut app = app_with_job(JobStatus::Success);
+        assert!(app.update(Action::DownloadSelectedArtifacts).is_none());
+        assert!(app.artifact_download.is_none());
+        assert_eq!(app.visible_status(), Some("Job 'test' has no artifacts"));
+    }
+
+    #[test]
+    fn test_browse_and_view_artifact_file() {
+        let mut app = app_with_job(JobStatus::Success);
+        assert!(app.update(Action::BrowseArtifacts).is_none());
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap()[0].artifacts_file = Some(crate::gitlab::ArtifactsFile {
+            filename: "artifacts.zip".to_string(),
+            size: 2048,
+        });
+
+        let effect = app.update(Action::BrowseArtifacts);
+        assert!(matches!(effect, Some(Effect::FetchArtifactList { job_id: 200, .. })));
+        let entries = vec![
+            ArtifactEntry { path: "coverage/dump.bin".to_string(), size: MAX_VIEWED_FILE_SIZE + 1 },
+            ArtifactEntry { path: "report.xml".to_string(), size: 10 },
+        ];
+        app.update(Action::ArtifactListLoaded { job_id: 200, entries });
+
+        // Too large to show inline
+        assert!(app.update(Action::OpenArtifactFile).is_none());
+        assert!(app.artifact_browser.as_ref().unwrap().file.is_none());
+
+        app.update(Action::ArtifactBrowserNext);
+        let effect = app.update(Action::OpenArtifactFile);
+        assert!(matches!(effect, Some(Effect::FetchArtifactFile { job_id: 200, ref path, .. }) if path == "report.xml"));
+        let text = (1..=10).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        app.update(Action::ArtifactFileLoaded { job_id: 200, path: "report.xml".to_string(), text });
+
+        let file = app.artifact_browser.as_mut().unwrap().file.as_mut().unwrap();
+        file.height = 4;
+        app.update(Action::ScrollArtifactFile(isize::MAX));
+        assert_eq!(app.artifact_browser.as_ref().unwrap().file.as_ref().unwrap().scroll, 6);
+        app.update(Action::ScrollArtifactFile(-2));
+        assert_eq!(app.artifact_browser.as_ref().unwrap().file.as_ref().unwrap().scroll, 4);
+
+        // Back in the list, a late answer for the closed file is dropped
+        app.update(Action::CloseArtifactFile);
+        let error = "Binary file".to_string();
+        app.update(Action::ArtifactFileFailed { job_id: 200, path: "report.xml".to_string(), error });
+        assert!(app.artifact_browser.as_ref().unwrap().file.is_none());
+        app.update(Action::CloseArtifactBrowser);
+        assert!(app.artifact_browser.is_none());
+    }
+
+    #[test]
+    fn test_test_report_lists_failures_first() {
+        let mut app = app_with_job(JobStatus::Failed);
+        assert!(app.update(Action::ShowTestReport).is_none());
+        assert_eq!(app.visible_status(), Some("Job 'test' uploaded no JUnit report"));
+
+        let job = &mut app.tracked_mrs[0].jobs.get_mut(&100).unwrap()[0];
+        job.artifacts = vec![crate::gitlab::JobArtifact { file_type: "junit".to_string(), size: 100 }];
+        assert!(app.update(Action::ShowTestReport).is_none());
+        assert_eq!(app.visible_status(), Some("Job 'test' kept no artifacts archive to read its report from"));
+
+        let job = &mut app.tracked_mrs[0].jobs.get_mut(&100).unwrap()[0];
+        job.artifacts_file = Some(crate::gitlab::ArtifactsFile { filename: "artifacts.zip".to_string(), size: 1024 });
+        let effect = app.update(Action::ShowTestReport);
+        assert!(matches!(effect, Some(Effect::FetchTestReport { job_id: 200, .. })));
+
+        let case = |status: &str, name: &str| crate::gitlab::TestCase {
+            status: status.to_string(),
+            name: name.to_string(),
+            classname: None,
+            execution_time: 0.0,
+            system_output: None,
+            stack_trace: None,
+        };
+        let suite = TestSuite {
+            test_cases: vec![case("failed", "a"), case("error", "b"), case("success", "c")],
+            ..Default::default()
+        };
+        app.update(Action::TestReportLoaded { job_id: 200, suite });
+        assert_eq!(app.test_report.as_ref().unwrap().visible_cases(), vec![0, 1]);
+
+        // Failures start expanded
+        app.update(Action::TestReportNext);
+        app.update(Action::ToggleTestCase);
+        assert!(app.test_report.as_ref().unwrap().collapsed.contains(&1));
+
+        app.update(Action::ToggleAllTests);
+        let report = app.test_report.as_ref().unwrap();
+        assert_eq!(report.visible_cases(), vec![0, 1, 2]);
+        assert_eq!(report.selected, 1);
+        app.update(Action::CloseTestReport);
+        assert!(app.test_report.is_none());
+    }
+
+    #[test]
+    fn test_download_artifacts_lifecycle() {
+        let mut app = app_with_job(JobStatus::Success);
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap()[0].artifacts_file = Some(crate::gitlab::ArtifactsFile {
+            filename: "artifacts.zip".to_string(),
+            size: 2048,
+        });
+
+        let cancel = match app.update(Action::DownloadSelectedArtifacts) {
+            Some(Effect::DownloadArtifacts { job_id: 200, path, cancel, .. }) => {
+                assert_eq!(path, PathBuf::from("job-200-test-artifacts.zip"));
+                cancel
             }
+            other => panic!("expected a download, got {:?}", other),
+        };
+        assert_eq!(app.artifact_download.as_ref().unwrap().total, Some(2048));
 
-            Action::ShowHelp => {
-                self.mode = AppMode::ShowingHelp;
-                None
+        // A second request while downloading is refused
+        assert!(app.update(Action::DownloadSelectedArtifacts).is_none());
+
+        app.update(Action::ArtifactDownloadProgress { job_id: 200, received: 1024, total: None });
+        assert_eq!(app.artifact_download.as_ref().unwrap().received, 1024);
+
+        app.update(Action::CancelArtifactDownload);
+        assert!(cancel.load(Ordering::Relaxed));
+        app.update(Action::ArtifactDownloadCanceled { job_id: 200 });
+        assert!(app.artifact_download.is_none());
+        assert_eq!(app.visible_status(), Some("Artifacts download canceled"));
+    }
+
+    #[test]
+    fn test_parse_trigger_input() {
+        assert_eq!(parse_trigger_input("main"), Ok(("main".to_string(), vec![])));
+        assert_eq!(
+            parse_trigger_input("  feature/x DEPLOY=staging EMPTY= "),
+            Ok((
+                "feature/x".to_string(),
+                vec![
+                    ("DEPLOY".to_string(), "staging".to_string()),
+                    ("EMPTY".to_string(), String::new())
+                ]
+            ))
+        );
+        assert!(parse_trigger_input("   ").is_err());
+        assert!(parse_trigger_input("main =oops").is_err());
+        assert!(parse_trigger_input("main novalue").is_err());
+    }
+
+    #[test]
+    fn test_trigger_prompt_defaults_to_current_branch() {
+        let mut app = App::new(123, Some("my-branch".to_string()), false, 1);
+        app.update(Action::StartTriggerPipeline);
+        assert_eq!(app.trigger_input.as_deref(), Some("my-branch"));
+
+        app.update(Action::UpdateTriggerInput("my-branch X=1".to_string()));
+        let effect = app.update(Action::SubmitTriggerPipeline);
+        match effect {
+            Some(Effect::TriggerPipeline { ref_name, variables, .. }) => {
+                assert_eq!(ref_name, "my-branch");
+                assert_eq!(variables, vec![("X".to_string(), "1".to_string())]);
             }
+            other => panic!("expected a trigger, got {:?}", other),
+        }
+        assert!(app.trigger_input.is_none());
+    }
 
-            Action::HideHelp => {
-                self.mode = AppMode::Normal;
-                None
+    #[test]
+    fn test_head_pipelines_poll_then_open() {
+        let mut app = App::new(123, None, false, 1);
+        let mut other = tracked_mr_with(vec![create_test_pipeline(200, PipelineStatus::Success)]);
+        other.mr = create_test_mr(2, 20, "Other MR");
+        app.tracked_mrs.push(tracked_mr_with(vec![create_test_pipeline(100, PipelineStatus::Success)]));
+        app.tracked_mrs.push(other);
+
+        let effect = app.update(Action::ShowHeadPipelines);
+        assert!(matches!(effect, Some(Effect::FetchHeadPipelines { project_id: 123, delay }) if delay.is_zero()));
+        assert!(app.update(Action::HeadPipelinesLoaded { sha: "abc".to_string(), pipelines: vec![] }).is_none());
+
+        // Nothing for HEAD yet: poll until a pipeline shows up
+        let effect = app.update(Action::ToggleHeadPipelinePolling);
+        assert!(matches!(effect, Some(Effect::FetchHeadPipelines { delay, .. }) if !delay.is_zero()));
+        let effect = app.update(Action::HeadPipelinesLoaded { sha: "abc".to_string(), pipelines: vec![] });
+        assert!(matches!(effect, Some(Effect::FetchHeadPipelines { .. })));
+        let mut pipeline = create_test_pipeline(201, PipelineStatus::Running);
+        pipeline.ref_name = "feature-20".to_string();
+        let pipelines = vec![pipeline, create_test_pipeline(300, PipelineStatus::Failed)];
+        assert!(app.update(Action::HeadPipelinesLoaded { sha: "abc".to_string(), pipelines }).is_none());
+        assert!(!app.head_pipelines.as_ref().unwrap().polling);
+
+        // One of a tracked MR's branch is shown there, others open in the browser
+        app.update(Action::OpenHeadPipeline);
+        assert!(app.head_pipelines.is_none());
+        assert_eq!(app.selected_mr_index, 1);
+        assert_eq!(app.tracked_mrs[1].pipelines[0].id, 201);
+
+        app.update(Action::ShowHeadPipelines);
+        let pipelines = vec![create_test_pipeline(201, PipelineStatus::Running), create_test_pipeline(300, PipelineStatus::Failed)];
+        app.update(Action::HeadPipelinesLoaded { sha: "abc".to_string(), pipelines });
+        app.update(Action::HeadPipelinesNext);
+        assert!(matches!(app.update(Action::OpenHeadPipeline), Some(Effect::OpenUrl(_))));
+
+        // Answers arriving after the popup closed are dropped
+        assert!(app.update(Action::HeadPipelinesLoaded { sha: "abc".to_string(), pipelines: vec![] }).is_none());
+    }
+
+    #[test]
+    fn test_group_pipelines_filter_sort_and_paginate() {
+        let mut app = App::new(123, None, false, 1);
+        assert!(app.update(Action::ShowGroupPipelines).is_none());
+        assert_eq!(app.visible_status(), Some("Set group under [gitlab] to browse a group's pipelines"));
+
+        app.project_path = Some("acme/platform/api".to_string());
+        let effect = app.update(Action::ShowGroupPipelines);
+        assert!(matches!(effect, Some(Effect::FetchGroupPipelines { ref group, page: 1 }) if group == "acme/platform"));
+
+        let group = "acme/platform".to_string();
+        app.update(Action::GroupProjectsLoaded { group: group.clone(), project_count: 2, next_page: Some(2) });
+        let pipelines = vec![create_test_pipeline(10, PipelineStatus::Success), create_test_pipeline(30, PipelineStatus::Failed)];
+        let project_path = "acme/platform/api".to_string();
+        app.update(Action::GroupPipelinesLoaded { group: group.clone(), project_id: 1, project_path, pipelines });
+        let pipelines = vec![create_test_pipeline(20, PipelineStatus::Running)];
+        let project_path = "acme/platform/web".to_string();
+        app.update(Action::GroupPipelinesLoaded { group: group.clone(), project_id: 2, project_path, pipelines });
+        // Answers for a group no longer shown are dropped
+        let project_path = "other/app".to_string();
+        app.update(Action::GroupPipelinesLoaded { group: "other".to_string(), project_id: 3, project_path, pipelines: vec![] });
+
+        let view = app.group_pipelines.as_ref().unwrap();
+        assert_eq!(view.projects_loading, 0);
+        let ids: Vec<u64> = view.visible().iter().map(|row| row.pipeline.id).collect();
+        assert_eq!(ids, vec![30, 20, 10]);
+
+        app.update(Action::ToggleGroupStatusFilter(PipelineStatus::Running));
+        let view = app.group_pipelines.as_ref().unwrap();
+        assert_eq!(view.visible().len(), 1);
+        assert_eq!(view.visible()[0].project_path, "acme/platform/web");
+        app.update(Action::ClearGroupStatusFilter);
+
+        // The selection follows its pipeline when the order changes
+        app.update(Action::GroupPipelinesNext);
+        app.update(Action::CycleGroupSort);
+        let view = app.group_pipelines.as_ref().unwrap();
+        assert_eq!(view.sort, PipelineSort::Oldest);
+        assert_eq!(view.visible()[view.selected].pipeline.id, 20);
+
+        // Moving past the last row lists the next projects
+        app.update(Action::GroupPipelinesNext);
+        let effect = app.update(Action::GroupPipelinesNext);
+        assert!(matches!(effect, Some(Effect::FetchGroupPipelines { page: 2, .. })));
+        assert!(app.update(Action::GroupPipelinesNext).is_none());
+
+        app.update(Action::GroupProjectsLoaded { group: group.clone(), project_count: 1, next_page: None });
+        app.update(Action::GroupProjectFailed { group, project_path: "acme/platform/db".to_string(), error: "403".to_string() });
+        let view = app.group_pipelines.as_ref().unwrap();
+        assert_eq!(view.failed_projects, vec!["acme/platform/db".to_string()]);
+        assert_eq!(view.projects_loading, 0);
+    }
+
+    #[test]
+    fn test_open_group_pipeline_of_another_project() {
+        let mut app = App::new(123, None, true, 1);
+        app.profiles = vec![profile("default", "https://gitlab.com", Some(123))];
+        app.group = Some("acme".to_string());
+        app.tracked_mrs.push(tracked_mr_with(vec![create_test_pipeline(100, PipelineStatus::Success)]));
+        app.update(Action::ShowGroupPipelines);
+        let group = "acme".to_string();
+        let mut pipeline = create_test_pipeline(500, PipelineStatus::Failed);
+        pipeline.ref_name = "feature-20".to_string();
+        let project_path = "acme/web".to_string();
+        app.update(Action::GroupPipelinesLoaded { group, project_id: 7, project_path, pipelines: vec![pipeline] });
+
+        // The other project is loaded, then the pipeline's MR and the pipeline are selected
+        let effect = app.update(Action::OpenGroupPipeline);
+        assert!(app.group_pipelines.is_none());
+        assert_eq!(app.project_id, 7);
+        assert_eq!(app.project_path.as_deref(), Some("acme/web"));
+        assert!(app.tracked_mrs.is_empty());
+        assert!(!app.focus_current_branch);
+        assert!(matches!(effect, Some(Effect::Batch(ref effects)) if matches!(effects[0], Effect::RefreshAll { project_id: 7, .. })));
+
+        app.update(Action::MergeRequestsLoaded(vec![create_test_mr(1, 10, "First"), create_test_mr(2, 20, "Second")]));
+        assert_eq!(app.selected_mr_index, 1);
+        let pipelines = vec![create_test_pipeline(400, PipelineStatus::Success)];
+        let effect = app.update(Action::PipelinesLoaded { mr_index: 1, page: 1, status: None, pipelines, next_page: None });
+        assert!(app.pending_pipeline_focus.is_none());
+        assert_eq!(app.tracked_mrs[1].pipelines[app.tracked_mrs[1].selected_pipeline_index].id, 500);
+        assert!(matches!(effect, Some(Effect::FetchJobs { mr_index: 1, pipeline_id: 500, .. })));
+    }
+
+    #[test]
+    fn test_invalid_trigger_input_keeps_prompt_open() {
+        let mut app = App::new(123, None, false, 1);
+        app.update(Action::StartTriggerPipeline);
+        app.update(Action::UpdateTriggerInput("main bad".to_string()));
+        assert!(app.update(Action::SubmitTriggerPipeline).is_none());
+        assert!(app.trigger_input.is_some());
+        assert_eq!(app.visible_status(), Some("Invalid variable 'bad', expected KEY=value"));
+    }
+
+    #[test]
+    fn test_triggered_pipeline_is_selected() {
+        let mut app = App::new(123, None, false, 1);
+        app.tracked_mrs.push(tracked_mr_with(vec![create_test_pipeline(100, PipelineStatus::Success)]));
+        let mut other = tracked_mr_with(vec![create_test_pipeline(200, PipelineStatus::Success)]);
+        other.mr = create_test_mr(2, 20, "Other MR");
+        app.tracked_mrs.push(other);
+
+        let mut pipeline = create_test_pipeline(201, PipelineStatus::Created);
+        pipeline.ref_name = "feature-20".to_string();
+        let effect = app.update(Action::PipelineTriggered(pipeline));
+
+        assert_eq!(app.selected_mr_index, 1);
+        assert_eq!(app.tracked_mrs[1].pipelines[0].id, 201);
+        assert_eq!(app.tracked_mrs[1].selected_pipeline_index, 0);
+        match effect {
+            Some(Effect::Batch(effects)) => {
+                assert!(matches!(effects[0], Effect::FetchJobs { mr_index: 1, pipeline_id: 201, .. }))
             }
+            other => panic!("expected jobs fetch and live poll, got {:?}", other),
+        }
+    }
 
-            Action::ToggleCommentsView => {
-                self.mode = match self.mode {
-                    AppMode::ViewingComments => AppMode::Normal,
-                    AppMode::Normal => {
-                        // Check if we need to fetch notes
-                        if let Some(mr) = self.get_selected_mr() {
-                            if !mr.notes_loaded {
-                                let mr_index = self.selected_mr_index;
-                                let project_id = self.project_id;
-                                let mr_iid = mr.mr.iid;
+    #[test]
+    fn test_open_job_and_pipeline_in_browser() {
+        let mut app = app_with_job(JobStatus::Failed);
+        assert!(matches!(
+            app.update(Action::OpenJobInBrowser),
+            Some(Effect::OpenUrl(url)) if url == "https://gitlab.com/test/-/jobs/200"
+        ));
+        assert!(matches!(
+            app.update(Action::OpenPipelineInBrowser),
+            Some(Effect::OpenUrl(url)) if url == "https://gitlab.com/test/-/pipelines/100"
+        ));
+    }
+
+    #[test]
+    fn test_open_job_in_browser_from_log_viewer() {
+        let mut app = app_with_job(JobStatus::Failed);
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap().push(create_test_job(201, "lint", JobStatus::Success));
+        app.set_log_content(201, "log".to_string(), "lint".to_string());
+        app.mode = AppMode::ViewingLog;
+        assert!(matches!(
+            app.update(Action::OpenJobInBrowser),
+            Some(Effect::OpenUrl(url)) if url == "https://gitlab.com/test/-/jobs/201"
+        ));
+    }
+
+    #[test]
+    fn test_open_job_falls_back_to_pipeline() {
+        let mut app = App::new(123, None, false, 1);
+        app.tracked_mrs.push(tracked_mr_with(vec![create_test_pipeline(100, PipelineStatus::Running)]));
+        assert!(matches!(
+            app.update(Action::OpenJobInBrowser),
+            Some(Effect::OpenUrl(url)) if url == "https://gitlab.com/test/-/pipelines/100"
+        ));
+    }
 
-                                self.status_message = Some("Loading comments...".to_string());
-                                self.mode = AppMode::ViewingComments;
+    #[test]
+    fn test_rate_limit_pauses_polling() {
+        let mut app = App::new(123, None, false, 1);
+        app.tracked_mrs.push(tracked_mr_with(vec![create_test_pipeline(100, PipelineStatus::Running)]));
+        app.last_auto_refresh = Instant::now() - std::time::Duration::from_secs(120);
+
+        assert!(app.update(Action::RateLimited { retry_after: std::time::Duration::from_secs(30) }).is_none());
+        assert!(app.rate_limit_resume_at().is_some());
+        assert!(app.visible_status().unwrap().contains("polling resumes at"));
+
+        // No auto-refresh, and the live poll just waits for the limit to end
+        assert!(app.update(Action::Tick).is_none());
+        match app.update(Action::LivePollDue) {
+            Some(Effect::ScheduleLivePoll { delay }) => assert!(delay <= std::time::Duration::from_secs(30)),
+            other => panic!("expected a delayed live poll, got {:?}", other.is_some()),
+        }
 
-                                return Some(Effect::FetchNotes {
-                                    mr_index,
-                                    project_id,
-                                    mr_iid,
-                                });
-                            }
-                        }
-                        AppMode::ViewingComments
-                    }
-                    _ => self.mode.clone(), // Don't toggle in other modes
-                };
-                None
-            }
+        app.rate_limited_until = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        assert!(matches!(app.update(Action::Tick), Some(Effect::RefreshAll { .. })));
 
-            Action::NotesLoaded { mr_index, notes } => {
-                if let Some(mr) = self.tracked_mrs.get_mut(mr_index) {
-                    mr.notes = notes;
-                    mr.notes_loaded = true;
+        // However long a limit claims to last, polling resumes within the hour
+        app.update(Action::RateLimited { retry_after: std::time::Duration::MAX });
+        let resume_at = app.rate_limit_resume_at().unwrap();
+        assert!(resume_at <= chrono::Utc::now() + chrono::Duration::hours(1));
+    }
 
-                    // Try to restore the previously selected note
-                    if let Some(selected_note_id) = self.selected_note_id_before_refresh.take() {
-                        // Filter user notes (non-system) and find the index of the previously selected note
-                        let user_notes: Vec<_> = mr.notes.iter().filter(|n| !n.system).collect();
-                        let restored_index = user_notes
-                            .iter()
-                            .position(|note| note.id == selected_note_id)
-                            .unwrap_or(0); // Default to 0 if note not found
+    #[test]
+    fn test_offline_indicator_keeps_oldest_cache_time() {
+        let mut app = App::new(123, None, false, 1);
+        let now = chrono::Utc::now();
+        let earlier = now - chrono::Duration::minutes(10);
+
+        app.update(Action::ServedFromCache { saved_at: now });
+        assert_eq!(app.visible_status(), Some("GitLab is unreachable, showing cached data"));
+        app.update(Action::ServedFromCache { saved_at: earlier });
+        app.update(Action::ServedFromCache { saved_at: now });
+        assert_eq!(app.offline_cached_at, Some(earlier));
+        let time = earlier.with_timezone(&chrono::Local).format("%H:%M").to_string();
+        assert!(app.offline_label().unwrap().ends_with(&time));
+
+        app.update(Action::BackOnline);
+        assert!(app.offline_label().is_none());
+        assert_eq!(app.visible_status(), Some("Back online"));
+    }
 
-                        mr.selected_note_index = restored_index;
-                    } else {
-                        // No note to restore, default to 0
-                        mr.selected_note_index = 0;
-                    }
+    #[test]
+    fn test_older_pipelines_load_past_the_end() {
+        let mut app = App::new(123, None, false, 1);
+        app.tracked_mrs.push(tracked_mr_with(vec![]));
+        app.update(Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 1,
+            status: None,
+            pipelines: vec![create_test_pipeline(102, PipelineStatus::Success), create_test_pipeline(101, PipelineStatus::Success)],
+            next_page: Some(2),
+        });
+        app.update(Action::NextPipeline);
+        assert_eq!(app.tracked_mrs[0].selected_pipeline_index, 1);
 
-                    // After notes are loaded following a refresh, continue to fetch jobs
-                    if let Some(pipeline) = mr.pipelines.first() {
-                        self.status_message = None;
-                        return Some(Effect::FetchJobs {
-                            mr_index,
-                            project_id: self.project_id,
-                            pipeline_id: pipeline.id,
-                        });
-                    }
-                }
-                self.status_message = None;
-                None
-            }
+        // At the last pipeline the next page is requested instead of wrapping around, once
+        assert!(matches!(app.update(Action::NextPipeline), Some(Effect::FetchPipelines { page: 2, .. })));
+        assert!(app.tracked_mrs[0].loading_more_pipelines);
+        assert!(app.update(Action::NextPipeline).is_none());
 
-            Action::NextNote => {
-                if self.mode == AppMode::ViewingComments {
-                    // Get the length of user notes (excluding system notes)
-                    let user_notes_len = self
-                        .get_selected_notes()
-                        .map(|notes| notes.iter().filter(|n| !n.system).count())
-                        .unwrap_or(0);
-                    if user_notes_len > 0 {
-                        if let Some(mr) = self.tracked_mrs.get_mut(self.selected_mr_index) {
-                            mr.selected_note_index = (mr.selected_note_index + 1) % user_notes_len;
-                        }
-                    }
-                }
-                None
-            }
+        app.update(Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 2,
+            status: None,
+            pipelines: vec![create_test_pipeline(101, PipelineStatus::Success), create_test_pipeline(100, PipelineStatus::Failed)],
+            next_page: None,
+        });
+        let ids: Vec<u64> = app.tracked_mrs[0].pipelines.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![102, 101, 100]);
+        assert!(!app.tracked_mrs[0].loading_more_pipelines);
 
-            Action::PrevNote => {
-                if self.mode == AppMode::ViewingComments {
-                    // Get the length of user notes (excluding system notes)
-                    let user_notes_len = self
-                        .get_selected_notes()
-                        .map(|notes| notes.iter().filter(|n| !n.system).count())
-                        .unwrap_or(0);
-                    if user_notes_len > 0 {
-                        if let Some(mr) = self.tracked_mrs.get_mut(self.selected_mr_index) {
-                            mr.selected_note_index = mr
-                                .selected_note_index
-                                .checked_sub(1)
-                                .unwrap_or(user_notes_len - 1);
-                        }
-                    }
-                }
-                None
-            }
+        // Refreshing the first page keeps the older pages
+        app.update(Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 1,
+            status: None,
+            pipelines: vec![create_test_pipeline(103, PipelineStatus::Running), create_test_pipeline(102, PipelineStatus::Success)],
+            next_page: Some(2),
+        });
+        let ids: Vec<u64> = app.tracked_mrs[0].pipelines.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![103, 102, 101, 100]);
+        assert_eq!(app.tracked_mrs[0].pipelines_next_page, None);
+    }
 
-            Action::OpenMrInBrowser => {
-                if let Some(mr) = self.get_selected_mr() {
-                    return Some(Effect::OpenUrl(mr.mr.web_url.clone()));
-                }
-                None
-            }
+    #[test]
+    fn test_pipeline_status_filter() {
+        let mut app = App::new(123, None, false, 1);
+        app.tracked_mrs.push(tracked_mr_with(vec![
+            create_test_pipeline(101, PipelineStatus::Success),
+            create_test_pipeline(100, PipelineStatus::Failed),
+        ]));
+
+        // Setting a filter drops what is shown and refetches it from the API
+        match app.update(Action::TogglePipelineStatusFilter(PipelineStatus::Failed)) {
+            Some(Effect::FetchPipelines { page: 1, status: Some(PipelineStatus::Failed), .. }) => {}
+            other => panic!("expected a filtered fetch, got {:?}", other.is_some()),
+        }
+        assert!(app.tracked_mrs[0].pipelines.is_empty());
 
-            Action::Tick => {
-                // Check if it's time for an auto-refresh
-                let elapsed = self.last_auto_refresh.elapsed();
-                let refresh_interval = std::time::Duration::from_secs(self.auto_refresh_interval_minutes * 60);
+        // Unfiltered responses still in flight are ignored, and statuses the API didn't filter are hidden
+        let loaded = |status| Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 1,
+            status,
+            pipelines: vec![
+                create_test_pipeline(101, PipelineStatus::Success),
+                create_test_pipeline(100, PipelineStatus::Failed),
+            ],
+            next_page: None,
+        };
+        app.update(loaded(None));
+        assert!(app.tracked_mrs[0].pipelines.is_empty());
+        app.update(loaded(Some(PipelineStatus::Failed)));
+        let ids: Vec<u64> = app.tracked_mrs[0].pipelines.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![100]);
+
+        // Toggling the same status again shows everything
+        assert!(matches!(
+            app.update(Action::TogglePipelineStatusFilter(PipelineStatus::Failed)),
+            Some(Effect::FetchPipelines { status: None, .. })
+        ));
+        assert!(app.update(Action::ClearPipelineStatusFilter).is_none());
+    }
 
-                if elapsed >= refresh_interval {
-                    // Trigger auto-refresh
-                    self.last_auto_refresh = Instant::now();
+    fn app_with_stages() -> App {
+        let mut app = App::new(123, None, false, 1);
+        let mut mr = tracked_mr_with(vec![create_test_pipeline(100, PipelineStatus::Failed)]);
+        let job = |id, name, stage: &str, status| Job { stage: stage.to_string(), ..create_test_job(id, name, status) };
+        mr.jobs.insert(
+            100,
+            vec![
+                job(205, "unit", "test", JobStatus::Failed),
+                job(201, "compile", "build", JobStatus::Success),
+                job(204, "lint", "test", JobStatus::Success),
+                job(202, "docs", "build", JobStatus::Success),
+            ],
+        );
+        app.tracked_mrs.push(mr);
+        app
+    }
 
-                    // Set flag to refetch notes after refresh if currently viewing comments
-                    self.refetch_notes_after_refresh = self.mode == AppMode::ViewingComments;
+    #[test]
+    fn test_pipeline_summary_counts_jobs_by_status() {
+        let mut app = app_with_stages();
+        let now = Utc::now();
+        let jobs = app.tracked_mrs[0].jobs.get_mut(&100).unwrap();
+        for (job, minutes) in jobs.iter_mut().zip([10, 0, 8, 3]) {
+            job.started_at = Some(now - chrono::Duration::minutes(12));
+            job.finished_at = Some(now - chrono::Duration::minutes(minutes));
+        }
+        jobs[0].started_at = Some(now - chrono::Duration::minutes(15));
+        let not_started = |id, name, status| Job { started_at: None, finished_at: None, ..create_test_job(id, name, status) };
+        jobs.push(not_started(206, "deploy", JobStatus::Manual));
+        jobs.push(not_started(207, "e2e", JobStatus::Created));
+
+        let summary = app.pipeline_summary(now).unwrap();
+        assert_eq!(summary.status, PipelineStatus::Failed);
+        assert_eq!(
+            summary.counts,
+            vec![(JobStatus::Success, 3), (JobStatus::Failed, 1), (JobStatus::Pending, 1), (JobStatus::Manual, 1)]
+        );
+        // From the earliest start to the latest finish
+        assert_eq!(summary.seconds, Some(15.0 * 60.0));
+
+        // A running pipeline counts up to now, and the counts follow refreshed statuses
+        app.tracked_mrs[0].pipelines[0].status = PipelineStatus::Running;
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap()[0].status = JobStatus::Running;
+        let summary = app.pipeline_summary(now + chrono::Duration::minutes(5)).unwrap();
+        assert_eq!(summary.seconds, Some(20.0 * 60.0));
+        assert_eq!(summary.counts[1], (JobStatus::Running, 1));
+
+        // Nothing to summarize before the jobs load
+        app.tracked_mrs[0].jobs.clear();
+        assert_eq!(app.pipeline_summary(now), None);
+    }
 
-                    // Save the currently selected note ID if viewing comments
-                    if self.refetch_notes_after_refresh {
-                        self.selected_note_id_before_refresh = self.get_selected_note_id();
-                    }
+    #[test]
+    fn test_job_graph_rows_follow_needs() {
+        let job = |id, name, status| create_test_job(id, name, status);
+        let jobs = vec![
+            job(1, "build", JobStatus::Success),
+            job(2, "rspec 1/2", JobStatus::Failed),
+            job(3, "rspec 2/2", JobStatus::Success),
+            job(4, "lint", JobStatus::Success),
+            job(5, "deploy", JobStatus::Skipped),
+        ];
+        let needs: HashMap<String, Vec<String>> = [
+            ("build", vec![]),
+            ("rspec 1/2", vec!["build"]),
+            ("rspec 2/2", vec!["build"]),
+            ("lint", vec![]),
+            ("deploy", vec!["rspec", "lint"]),
+        ]
+        .into_iter()
+        .map(|(name, needs)| (name.to_string(), needs.into_iter().map(String::from).collect()))
+        .collect();
+
+        let rows = job_graph_rows(&jobs, &needs);
+        let tree: Vec<(usize, &str, bool)> =
+            rows.iter().map(|row| (row.depth, row.job.name.as_str(), row.repeated)).collect();
+        assert_eq!(
+            tree,
+            vec![
+                (0, "build", false),
+                (1, "rspec 1/2", false),
+                (2, "deploy", false),
+                (1, "rspec 2/2", false),
+                (2, "deploy", true),
+                (0, "lint", false),
+                (1, "deploy", true),
+            ]
+        );
+        // The skipped deploy was held back by the failed instance of the parallel job
+        let blockers: Vec<u64> = rows[2].blocked_by.iter().map(|job| job.id).collect();
+        assert_eq!(blockers, vec![2]);
+        assert!(rows[1].blocked_by.is_empty());
+    }
 
-                    // Clear all cached data including notes and job logs
-                    for mr in &mut self.tracked_mrs {
-                        mr.notes_loaded = false;
-                        mr.notes.clear();
-                        mr.job_logs_cache.clear();
-                    }
+    #[test]
+    fn test_job_graph_opens_selected_job_log() {
+        let mut app = app_with_stages();
+        assert!(app.update(Action::ShowJobGraph).is_none());
+        assert!(app.job_graph.is_none());
+
+        app.project_path = Some("acme/api".to_string());
+        let effect = app.update(Action::ShowJobGraph);
+        assert!(matches!(
+            effect,
+            Some(Effect::FetchJobNeeds { ref project_path, pipeline_id: 100, .. }) if project_path == "acme/api"
+        ));
+        let needs = HashMap::from([("unit".to_string(), vec!["compile".to_string()])]);
+        app.update(Action::JobNeedsLoaded { pipeline_id: 999, needs: HashMap::new() }); // Another pipeline's
+        assert!(app.job_graph_rows().is_empty());
+        app.update(Action::JobNeedsLoaded { pipeline_id: 100, needs });
+        let names: Vec<&str> = app.job_graph_rows().iter().map(|row| row.job.name.as_str()).collect();
+        assert_eq!(names, vec!["compile", "unit", "docs", "lint"]);
+
+        // Enter on unit opens its log, even with its stage folded in the job list
+        app.collapsed_stages.insert("test".to_string());
+        app.update(Action::JobGraphNext);
+        app.update(Action::OpenJobGraphLog);
+        assert!(app.job_graph.is_none());
+        assert_eq!(app.selected_job().unwrap().id, 205);
+        assert_eq!(app.mode, AppMode::ViewingLog);
+    }
 
-                    self.status_message = Some("Auto-refreshing...".to_string());
-                    Some(Effect::RefreshAll {
-                        project_id: self.project_id,
-                        source_branch: if self.focus_current_branch {
-                            self.current_branch.clone()
-                        } else {
-                            None
-                        },
-                    })
-                } else {
-                    None
-                }
+    #[test]
+    fn test_failed_logs_prefetched_until_pipeline_left() {
+        let mut app = app_with_stages();
+        app.log_prefetch_mode = LogPrefetch::Failed;
+        let mut other = tracked_mr_with(vec![create_test_pipeline(300, PipelineStatus::Failed)]);
+        other.mr = create_test_mr(2, 20, "Other MR");
+        app.tracked_mrs.push(other);
+
+        let jobs = app.tracked_mrs[0].jobs[&100].clone();
+        let effect = app.update(jobs_loaded(jobs.clone()));
+        let cancel = match effect {
+            Some(Effect::PrefetchJobLogs { pipeline_id: 100, job_ids, cancel, .. }) => {
+                assert_eq!(job_ids, vec![205]);
+                cancel
             }
+            other => panic!("expected a log prefetch, got {:?}", other),
+        };
+        // The next poll of the jobs doesn't ask again
+        assert!(app.update(jobs_loaded(jobs)).is_none());
+
+        app.update(Action::JobLogPrefetched { pipeline_id: 100, job_id: 205, trace: "boom".to_string() });
+        assert_eq!(app.tracked_mrs[0].job_logs_cache.get(&205).map(|log| log.as_str()), Some("boom"));
+        assert_eq!(app.visible_status(), Some("Prefetched 1 job logs"));
+
+        // Asking by hand for every finished log, then moving to another MR cancels the rest
+        app.log_prefetch_mode = LogPrefetch::Finished;
+        assert!(matches!(
+            app.update(Action::PrefetchJobLogs),
+            Some(Effect::PrefetchJobLogs { ref job_ids, .. }) if job_ids.len() == 3
+        ));
+        app.update(Action::NextMr);
+        assert!(cancel.load(Ordering::Relaxed));
+        assert!(app.log_prefetch.is_none());
+        assert!(app.update(Action::JobLogPrefetched { pipeline_id: 100, job_id: 201, trace: "late".to_string() }).is_none());
+        assert!(!app.tracked_mrs[0].job_logs_cache.contains_key(&201));
+    }
 
-            _ => None,
-        }
+    #[test]
+    fn test_prefetch_by_hand_defaults_to_failed_jobs() {
+        let mut app = app_with_stages();
+        assert!(matches!(
+            app.update(Action::PrefetchJobLogs),
+            Some(Effect::PrefetchJobLogs { ref job_ids, .. }) if job_ids == &vec![205]
+        ));
+        assert_eq!(app.visible_status(), Some("Prefetching the logs of 1 failed jobs"));
+        app.update(Action::JobLogPrefetchFailed { pipeline_id: 100, job_id: 205, error: "500".to_string() });
+        assert!(app.update(Action::PrefetchJobLogs).is_none());
+        assert_eq!(app.visible_status(), Some("No failed job logs left to prefetch"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::gitlab::{JobStatus, PipelineStatus};
-    use crate::gitlab::models::User;
-    use chrono::Utc;
+    #[test]
+    fn test_jobs_grouped_by_stage() {
+        let app = app_with_stages();
+        let groups = app.job_stage_groups();
+        let names: Vec<&str> = groups.iter().map(|g| g.name).collect();
+        assert_eq!(names, vec!["build", "test"]);
+        assert_eq!(groups[0].status, JobStatus::Success);
+        assert_eq!(groups[1].status, JobStatus::Failed);
+
+        let ids: Vec<u64> = app.visible_jobs().iter().map(|j| j.id).collect();
+        assert_eq!(ids, vec![201, 202, 205, 204]);
+        assert_eq!(app.selected_job().unwrap().id, 201);
+    }
 
-    fn create_test_mr(id: u64, iid: u64, title: &str) -> MergeRequest {
-        MergeRequest {
-            id,
-            iid,
-            title: title.to_string(),
-            author: User {
-                id: 1,
-                username: "testuser".to_string(),
-                name: "Test User".to_string(),
-            },
-            state: "opened".to_string(),
-            web_url: format!("https://gitlab.com/test/-/merge_requests/{}", iid),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-        }
+    #[test]
+    fn test_cycle_job_sort() {
+        let mut app = app_with_stages();
+        app.collapsed_stages.insert("test".to_string());
+        let ids = |app: &App| app.visible_jobs().iter().map(|j| j.id).collect::<Vec<u64>>();
+        let set_duration = |app: &mut App, id, seconds| {
+            let jobs = app.tracked_mrs[0].jobs.get_mut(&100).unwrap();
+            jobs.iter_mut().find(|job| job.id == id).unwrap().duration = Some(seconds);
+        };
+        set_duration(&mut app, 201, 30.0);
+        set_duration(&mut app, 204, 300.0);
+        app.selected_job_index = 1; // docs
+
+        // Sorted jobs are listed together, folds left aside, keeping the selected job
+        app.update(Action::CycleJobSort);
+        assert_eq!(app.job_sort, JobSort::NameAscending);
+        assert_eq!(app.job_stage_groups().len(), 1);
+        assert_eq!(ids(&app), vec![201, 202, 204, 205]);
+        assert_eq!(app.selected_job().unwrap().id, 202);
+        assert_eq!(app.visible_status(), Some("Jobs sorted by name A-Z"));
+
+        app.update(Action::CycleJobSort);
+        assert_eq!(ids(&app), vec![205, 204, 202, 201]);
+        app.update(Action::CycleJobSort);
+        assert_eq!(ids(&app), vec![204, 202, 205, 201]);
+        app.update(Action::CycleJobSort);
+        assert_eq!(ids(&app), vec![201, 202, 205, 204]);
+        app.update(Action::CycleJobSort);
+        assert_eq!(app.job_sort, JobSort::Stage);
+        assert_eq!(ids(&app), vec![201, 202]);
     }
 
-    fn create_test_pipeline(id: u64, status: PipelineStatus) -> Pipeline {
-        Pipeline {
-            id,
-            iid: id,
-            status,
-            ref_name: "main".to_string(),
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            web_url: format!("https://gitlab.com/test/-/pipelines/{}", id),
+    #[test]
+    fn test_cycle_pipeline_sort() {
+        let mut app = App::new(123, None, false, 1);
+        let pipeline = |id, status, minutes| {
+            let mut pipeline = create_test_pipeline(id, status);
+            pipeline.updated_at = pipeline.created_at + chrono::Duration::minutes(minutes);
+            pipeline
+        };
+        app.tracked_mrs.push(tracked_mr_with(vec![
+            pipeline(103, PipelineStatus::Success, 5),
+            pipeline(102, PipelineStatus::Failed, 20),
+            pipeline(101, PipelineStatus::Success, 10),
+        ]));
+        let ids = |app: &App| app.tracked_mrs[0].pipelines.iter().map(|p| p.id).collect::<Vec<u64>>();
+
+        app.update(Action::CyclePipelineSort);
+        assert_eq!(ids(&app), vec![101, 102, 103]);
+        // The selection follows the pipeline
+        assert_eq!(app.get_selected_pipeline().unwrap().id, 103);
+        app.update(Action::CyclePipelineSort);
+        assert_eq!(ids(&app), vec![102, 103, 101]);
+        app.update(Action::CyclePipelineSort);
+        assert_eq!(ids(&app), vec![102, 101, 103]);
+
+        // Older pages keep the order
+        app.update(Action::PipelinesLoaded {
+            mr_index: 0,
+            page: 2,
+            status: None,
+            pipelines: vec![pipeline(100, PipelineStatus::Success, 15)],
+            next_page: None,
+        });
+        assert_eq!(ids(&app), vec![102, 100, 101, 103]);
+        app.update(Action::CyclePipelineSort);
+        app.update(Action::CyclePipelineSort);
+        assert_eq!(app.pipeline_sort, PipelineSort::Newest);
+        assert_eq!(ids(&app), vec![103, 102, 101, 100]);
+    }
+
+    #[test]
+    fn test_sorted_pipelines_refresh_keeps_selection_and_latest() {
+        let mut app = App::new(123, None, false, 1);
+        let pipelines = |ids: &[u64]| ids.iter().map(|&id| create_test_pipeline(id, PipelineStatus::Success)).collect();
+        app.tracked_mrs.push(tracked_mr_with(pipelines(&[103, 102, 101])));
+        app.update(Action::CyclePipelineSort); // Oldest first
+        app.tracked_mrs[0].selected_pipeline_index = 1;
+
+        // A new pipeline appears on refresh: the selection stays, the new one's jobs are fetched
+        let mut fresh: Vec<Pipeline> = pipelines(&[104, 103, 102, 101]);
+        fresh[0].status = PipelineStatus::Running;
+        let loaded = Action::PipelinesLoaded { mr_index: 0, page: 1, status: None, pipelines: fresh, next_page: None };
+        let effect = app.update(loaded);
+        let ids: Vec<u64> = app.tracked_mrs[0].pipelines.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![101, 102, 103, 104]);
+        assert_eq!(app.get_selected_pipeline().unwrap().id, 102);
+        fn fetches_jobs_of(effect: &Effect, id: u64) -> bool {
+            match effect {
+                Effect::FetchJobs { pipeline_id, .. } => *pipeline_id == id,
+                Effect::Batch(effects) => effects.iter().any(|effect| fetches_jobs_of(effect, id)),
+                _ => false,
+            }
         }
+        assert!(fetches_jobs_of(effect.as_ref().unwrap(), 104));
+
+        // Re-fetching the list reloads the latest pipeline's jobs, it isn't polled on its own
+        app.tracked_mrs[0].jobs.insert(104, vec![create_test_job(1, "build", JobStatus::Running)]);
+        let effects = app.live_poll_effects();
+        assert!(matches!(effects[0], Effect::FetchPipelines { .. }));
+        assert!(!effects.iter().any(|effect| fetches_jobs_of(effect, 104)));
     }
 
-    fn create_test_job(id: u64, name: &str, status: JobStatus) -> Job {
-        Job {
-            id,
-            name: name.to_string(),
-            status,
-            stage: "test".to_string(),
-            created_at: Utc::now(),
-            started_at: Some(Utc::now()),
-            finished_at: Some(Utc::now()),
-            duration: Some(120.0),
-            web_url: format!("https://gitlab.com/test/-/jobs/{}", id),
+    #[test]
+    fn test_jobs_sorted_by_duration_keep_their_order_until_reloaded() {
+        let mut app = app_with_stages();
+        let now = Utc::now();
+        app.job_sort = JobSort::Slowest;
+        app.job_sort_time = now - chrono::Duration::seconds(100);
+        let jobs = app.tracked_mrs[0].jobs.get_mut(&100).unwrap();
+        for job in jobs.iter_mut() {
+            job.duration = Some(1.0);
         }
+        // lint has run 10 seconds when the list was sorted, and 110 by now
+        jobs[2].status = JobStatus::Running;
+        jobs[2].started_at = Some(app.job_sort_time - chrono::Duration::seconds(10));
+        jobs[3].duration = Some(50.0);
+        let ids = |app: &App| app.visible_jobs().iter().map(|j| j.id).collect::<Vec<u64>>();
+        assert_eq!(ids(&app)[..2], [202, 204]);
+        app.selected_job_index = 1;
+        app.update(Action::Tick);
+        assert_eq!(app.selected_job().unwrap().id, 204);
+
+        // A reload times running jobs again, and the selection follows its job
+        let jobs = app.tracked_mrs[0].jobs[&100].clone();
+        app.update(Action::JobsLoaded { mr_index: 0, pipeline_id: 100, jobs });
+        assert_eq!(ids(&app)[..2], [204, 202]);
+        assert_eq!(app.selected_job().unwrap().id, 204);
     }
 
     #[test]
-    fn test_app_new() {
-        let app = App::new(123, None, false, 1);
-        assert_eq!(app.project_id, 123);
-        assert!(!app.should_quit);
-        assert_eq!(app.selected_mr_index, 0);
-        assert_eq!(app.selected_job_index, 0);
-        assert!(app.tracked_mrs.is_empty());
-        assert_eq!(app.mode, AppMode::Normal);
+    fn test_fold_stages() {
+        let mut app = app_with_stages();
+        app.selected_job_index = 3; // lint, in "test"
+        app.update(Action::FoldSelectedStage);
+        let ids: Vec<u64> = app.visible_jobs().iter().map(|j| j.id).collect();
+        assert_eq!(ids, vec![201, 202]);
+        assert_eq!(app.selected_job().unwrap().id, 202);
+
+        // With a stage folded, the toggle unfolds everything; then it folds everything
+        app.update(Action::ToggleAllStagesFold);
+        assert_eq!(app.visible_jobs().len(), 4);
+        app.update(Action::ToggleAllStagesFold);
+        assert!(app.visible_jobs().is_empty());
+        assert!(app.selected_job().is_none());
+        assert!(app.update(Action::RetrySelectedJob).is_none());
     }
 
     #[test]
-    fn test_quit_action() {
-        let mut app = App::new(123, None, false, 1);
-        assert!(!app.should_quit);
+    fn test_job_selection_remembered_per_pipeline() {
+        let mut app = app_with_stages();
+        let mr = &mut app.tracked_mrs[0];
+        mr.pipelines.push(create_test_pipeline(101, PipelineStatus::Success));
+        mr.jobs.insert(
+            101,
+            vec![create_test_job(301, "compile", JobStatus::Success), create_test_job(302, "unit", JobStatus::Success)],
+        );
+
+        app.update(Action::NextJob);
+        app.update(Action::NextJob);
+        assert_eq!(app.selected_job().unwrap().id, 205);
+
+        app.update(Action::NextPipeline);
+        assert_eq!(app.selected_job().unwrap().id, 301);
+        app.update(Action::NextJob);
+        app.update(Action::PrevPipeline);
+        assert_eq!(app.selected_job().unwrap().id, 205);
+        app.update(Action::NextPipeline);
+        assert_eq!(app.selected_job().unwrap().id, 302);
+
+        // A remembered job that is gone falls back to the first one
+        app.update(Action::PrevPipeline);
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap().retain(|job| job.id != 205);
+        app.update(Action::NextPipeline);
+        app.update(Action::PrevPipeline);
+        assert_eq!(app.selected_job().unwrap().id, 201);
+    }
 
-        app.update(Action::Quit);
-        assert!(app.should_quit);
+    #[test]
+    fn test_failed_pipeline_selects_failed_job() {
+        let mut app = app_with_stages();
+        let jobs = app.tracked_mrs[0].jobs.remove(&100).unwrap();
+        app.failed_pipeline_jump = FailedPipelineJump::SelectJob;
+        app.collapsed_stages.insert("test".to_string());
+
+        let effect = app.update(Action::JobsLoaded { mr_index: 0, pipeline_id: 100, jobs: jobs.clone() });
+        assert!(!matches!(effect, Some(Effect::FetchJobTrace { .. })));
+        assert_eq!(app.selected_job().unwrap().id, 205);
+        assert!(!app.collapsed_stages.contains("test"));
+
+        // Only the first visit jumps, later reloads keep the user's selection
+        app.update(Action::PrevJob);
+        app.update(Action::JobsLoaded { mr_index: 0, pipeline_id: 100, jobs });
+        assert_ne!(app.selected_job().unwrap().id, 205);
     }
 
     #[test]
-    fn test_next_mr() {
-        let mut app = App::new(123, None, false, 1);
+    fn test_failed_pipeline_opens_failed_log() {
+        let mut app = app_with_stages();
+        let jobs = app.tracked_mrs[0].jobs.remove(&100).unwrap();
+        app.failed_pipeline_jump = FailedPipelineJump::OpenLog;
+
+        let effect = app.update(Action::JobsLoaded { mr_index: 0, pipeline_id: 100, jobs });
+        assert!(matches!(effect, Some(Effect::FetchJobTrace { job_id: 205, .. })));
+        assert_eq!(app.mode, AppMode::ViewingLog);
+    }
 
-        // Add some MRs
-        let mr1 = create_test_mr(1, 10, "MR 1");
-        let mr2 = create_test_mr(2, 20, "MR 2");
+    #[test]
+    fn test_select_first_failed_job_key() {
+        let mut app = app_with_stages();
+        app.update(Action::SelectFirstFailedJob);
+        assert_eq!(app.selected_job().unwrap().id, 205);
+
+        app.tracked_mrs[0].jobs.get_mut(&100).unwrap().retain(|job| job.status != JobStatus::Failed);
+        app.update(Action::SelectFirstFailedJob);
+        assert_eq!(app.visible_status(), Some("No failed job in this pipeline"));
+    }
 
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr: mr1,
-            pipelines: vec![],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
-        });
+    #[test]
+    fn test_reloaded_jobs_keep_the_selected_job() {
+        let mut app = app_with_stages();
+        app.update(Action::NextJob); // docs
+        let mut jobs = app.tracked_mrs[0].jobs[&100].clone();
+        jobs.insert(0, Job { stage: "build".to_string(), ..create_test_job(200, "setup", JobStatus::Success) });
+        app.update(Action::JobsLoaded { mr_index: 0, pipeline_id: 100, jobs });
+        assert_eq!(app.selected_job().unwrap().id, 202);
+    }
 
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr: mr2,
-            pipelines: vec![],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
-        });
+    #[test]
+    fn test_fuzzy_job_filter() {
+        let mut app = app_with_stages();
+        app.collapsed_stages.insert("test".to_string());
+        app.update(Action::StartJobFilter);
+        app.update(Action::UpdateJobFilter("lt".to_string()));
+
+        // Folded stages are searched too, the best match is selected
+        let names: Vec<&str> = app.visible_jobs().iter().map(|j| j.name.as_str()).collect();
+        assert_eq!(names, vec!["lint"]);
+        assert_eq!(app.selected_job().unwrap().id, 204);
+        assert_eq!(app.job_filter_match("lint").unwrap().1, vec![0, 3]);
+
+        app.update(Action::UpdateJobFilter("c".to_string()));
+        assert_eq!(app.visible_jobs().len(), 2); // compile, docs
+        app.update(Action::UpdateJobFilter("co".to_string()));
+        assert_eq!(app.selected_job().unwrap().id, 201);
+
+        app.update(Action::ApplyJobFilter);
+        assert!(!app.job_filter.as_ref().unwrap().editing);
+
+        // Clearing restores the full list and keeps the selection
+        app.update(Action::ClearJobFilter);
+        assert!(app.job_filter.is_none());
+        assert_eq!(app.visible_jobs().len(), 2);
+        assert_eq!(app.selected_job().unwrap().id, 201);
+    }
 
-        assert_eq!(app.selected_mr_index, 0);
-        app.update(Action::NextMr);
-        assert_eq!(app.selected_mr_index, 1);
-        app.update(Action::NextMr);
-        assert_eq!(app.selected_mr_index, 0); // Wraps around
+    #[test]
+    fn test_job_search_across_columns() {
+        let mut app = app_with_stages();
+        app.update(Action::StartJobSearch);
+        let names = |app: &App| app.visible_jobs().iter().map(|j| j.name.clone()).collect::<Vec<_>>();
+
+        // A stage name lists the whole stage, a status every job in it
+        app.update(Action::UpdateJobFilter("BUILD".to_string()));
+        assert_eq!(names(&app), vec!["compile", "docs"]);
+        app.update(Action::UpdateJobFilter("fail".to_string()));
+        assert_eq!(names(&app), vec!["unit"]);
+
+        // Every word must match some column, unlike a fuzzy match letters must be together
+        app.update(Action::UpdateJobFilter("test succ".to_string()));
+        assert_eq!(names(&app), vec!["lint"]);
+        app.update(Action::UpdateJobFilter("lt".to_string()));
+        assert!(names(&app).is_empty());
+
+        app.update(Action::UpdateJobFilter("in test".to_string()));
+        assert_eq!(app.job_search_positions("lint"), vec![1, 2]);
+        assert_eq!(app.job_search_positions("test"), vec![0, 1, 2, 3]);
+        assert_eq!(app.job_filter_match("lint").unwrap().1, vec![1, 2]);
+
+        // Starting the fuzzy filter replaces the search
+        app.update(Action::ApplyJobFilter);
+        app.update(Action::StartJobFilter);
+        let filter = app.job_filter.as_ref().unwrap();
+        assert!(!filter.columns && filter.query.is_empty());
+        assert!(app.job_search_positions("test").is_empty());
     }
 
     #[test]
-    fn test_prev_mr() {
-        let mut app = App::new(123, None, false, 1);
+    fn test_empty_job_filter_is_dropped() {
+        let mut app = app_with_stages();
+        app.update(Action::StartJobFilter);
+        assert_eq!(app.visible_jobs().len(), 4);
+        app.update(Action::ApplyJobFilter);
+        assert!(app.job_filter.is_none());
+    }
 
-        let mr1 = create_test_mr(1, 10, "MR 1");
-        let mr2 = create_test_mr(2, 20, "MR 2");
+    #[test]
+    fn test_compare_two_jobs() {
+        let mut app = app_with_stages();
+        assert!(app.update(Action::CompareSelectedJob).is_none());
+        assert_eq!(app.compare_job, Some((201, "compile".to_string())));
+
+        // The same job again clears the mark
+        app.update(Action::CompareSelectedJob);
+        assert!(app.compare_job.is_none());
+
+        app.update(Action::CompareSelectedJob);
+        app.selected_job_index = 2; // unit
+        let effect = app.update(Action::CompareSelectedJob);
+        assert!(matches!(effect, Some(Effect::FetchLogDiff { left_id: 201, right_id: 205, .. })));
+        assert_eq!(app.mode, AppMode::ComparingLogs);
+        assert_eq!(app.key_context(), Some(KeyContext::Diff));
+        assert!(app.compare_job.is_none());
+
+        // Results for another comparison are dropped
+        let content = crate::log_diff::DiffContent::compute("a\nb\n", "a\nc\n");
+        app.update(Action::LogDiffLoaded { left_id: 1, right_id: 2, content: content.clone() });
+        assert!(app.log_diff.as_ref().unwrap().content.is_none());
+        app.update(Action::LogDiffLoaded { left_id: 201, right_id: 205, content });
+        assert_eq!(app.log_diff.as_ref().unwrap().row_count(), 2);
+
+        app.update(Action::CloseLogDiff);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.log_diff.is_none());
+    }
 
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr: mr1,
-            pipelines: vec![],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
-        });
+    #[test]
+    fn test_diff_scrolling_stays_in_bounds() {
+        let mut app = app_with_stages();
+        app.log_viewport_height = 10;
+        let mut diff = LogDiff::new((1, "a".to_string()), (2, "b".to_string()));
+        let log: String = (0..25).map(|i| format!("line {}\n", i)).collect();
+        diff.content = Some(crate::log_diff::DiffContent::compute(&log, &log));
+        app.log_diff = Some(diff);
+        app.mode = AppMode::ComparingLogs;
+
+        app.update(Action::DiffPageDown);
+        assert_eq!(app.log_diff.as_ref().unwrap().scroll, 10);
+        app.update(Action::DiffBottom);
+        assert_eq!(app.log_diff.as_ref().unwrap().scroll, 15);
+        app.update(Action::DiffScrollDown);
+        assert_eq!(app.log_diff.as_ref().unwrap().scroll, 15);
+        app.update(Action::DiffTop);
+        app.update(Action::DiffScrollUp);
+        assert_eq!(app.log_diff.as_ref().unwrap().scroll, 0);
+
+        app.update(Action::NextDiffChange);
+        assert_eq!(app.visible_status(), Some("No more differences"));
+    }
 
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr: mr2,
-            pipelines: vec![],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
-        });
+    #[test]
+    fn test_stage_filter() {
+        let mut app = app_with_stages();
+        app.selected_job_index = 2; // unit, in "test"
+        app.update(Action::ToggleStageFilter);
+        assert_eq!(app.stage_filter.as_deref(), Some("test"));
+        let ids: Vec<u64> = app.visible_jobs().iter().map(|j| j.id).collect();
+        assert_eq!(ids, vec![205, 204]);
+        assert_eq!(app.selected_job_index, 0);
 
-        assert_eq!(app.selected_mr_index, 0);
-        app.update(Action::PrevMr);
-        assert_eq!(app.selected_mr_index, 1); // Wraps around to end
-        app.update(Action::PrevMr);
-        assert_eq!(app.selected_mr_index, 0);
+        app.update(Action::ToggleStageFilter);
+        assert!(app.stage_filter.is_none());
+        assert_eq!(app.visible_jobs().len(), 4);
     }
 
     #[test]
-    fn test_merge_requests_loaded() {
+    fn test_help_scroll_is_clamped() {
         let mut app = App::new(123, None, false, 1);
+        app.update(Action::ShowHelp);
+        app.help_max_scroll = 2;
+        for _ in 0..5 {
+            app.update(Action::ScrollHelpDown);
+        }
+        assert_eq!(app.help_scroll, 2);
+        app.update(Action::ScrollHelpUp);
+        assert_eq!(app.help_scroll, 1);
 
-        let mrs = vec![
-            create_test_mr(1, 10, "MR 1"),
-            create_test_mr(2, 20, "MR 2"),
-        ];
+        app.update(Action::HideHelp);
+        app.update(Action::ShowHelp);
+        assert_eq!(app.help_scroll, 0);
+    }
 
-        app.update(Action::MergeRequestsLoaded(mrs));
-        assert_eq!(app.tracked_mrs.len(), 2);
-        assert_eq!(app.tracked_mrs[0].mr.title, "MR 1");
-        assert_eq!(app.tracked_mrs[1].mr.title, "MR 2");
+    #[test]
+    fn test_command_palette_filters_and_runs_actions() {
+        let mut app = app_with_job(JobStatus::Failed);
+        app.update(Action::OpenCommandPalette);
+
+        // Only actions of the current view are offered, with their keys
+        let entries = app.palette_entries();
+        assert!(entries.iter().any(|e| e.name == "retry_job" && e.keys == "R"));
+        assert!(entries.iter().all(|e| e.name != "toggle_wrap" && e.name != "command_palette"));
+
+        app.update(Action::UpdatePaletteQuery("retry".to_string()));
+        assert_eq!(app.palette_entries()[0].name, "retry_job");
+
+        assert!(app.update(Action::ExecutePaletteSelection).is_none());
+        assert!(app.command_palette.is_none());
+        assert!(app.pending_confirmation.is_some());
     }
 
     #[test]
-    fn test_pipelines_loaded() {
+    fn test_command_palette_selection_wraps() {
         let mut app = App::new(123, None, false, 1);
+        app.update(Action::OpenCommandPalette);
+        app.update(Action::UpdatePaletteQuery("zzzzzz".to_string()));
+        assert!(app.palette_entries().is_empty());
+        app.update(Action::PaletteSelectNext);
+        assert!(app.update(Action::ExecutePaletteSelection).is_none());
+        assert!(app.command_palette.is_none());
+
+        app.update(Action::OpenCommandPalette);
+        app.update(Action::PaletteSelectPrev);
+        let last = app.palette_entries().len() - 1;
+        assert_eq!(app.command_palette.as_ref().unwrap().selected, last);
+        app.update(Action::PaletteSelectNext);
+        assert_eq!(app.command_palette.as_ref().unwrap().selected, 0);
+    }
 
-        let mr = create_test_mr(1, 10, "Test MR");
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr,
-            pipelines: vec![],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: true,
-            error: None,
-        });
+    #[test]
+    fn test_cycle_theme_wraps_around_builtin_themes() {
+        let mut app = App::new(123, None, false, 1);
+        assert_eq!(app.theme.name, "dark");
+        app.update(Action::CycleTheme);
+        assert_eq!(app.theme.name, "light");
+        assert!(app.visible_status().unwrap().contains("theme = \"light\""));
+        app.update(Action::CycleTheme);
+        app.update(Action::CycleTheme);
+        assert_eq!(app.theme.name, "dark");
+    }
 
-        let pipelines = vec![
-            create_test_pipeline(100, PipelineStatus::Success),
-            create_test_pipeline(101, PipelineStatus::Failed),
-        ];
+    #[test]
+    fn test_theme_lookup_by_name() {
+        assert_eq!(Theme::by_name("Solarized").map(|theme| theme.name), Some("solarized"));
+        assert!(Theme::by_name("neon").is_none());
+    }
 
-        app.update(Action::PipelinesLoaded {
-            mr_index: 0,
-            pipelines,
-        });
+    fn app_with_wrapped_log(content: &str, width: u16, height: usize) -> App {
+        let mut app = app_with_log(content);
+        app.wrap_enabled = true;
+        app.log_content_area = Some(ratatui::layout::Rect::new(0, 0, width, height as u16));
+        app.log_viewport_height = height;
+        app
+    }
 
-        assert_eq!(app.tracked_mrs[0].pipelines.len(), 2);
-        assert!(!app.tracked_mrs[0].loading);
-        assert_eq!(app.tracked_mrs[0].pipelines[0].status, PipelineStatus::Success);
+    #[test]
+    fn test_wrapped_line_scrolls_row_by_row() {
+        let long = "x".repeat(35); // 4 rows at width 10
+        let mut app = app_with_wrapped_log(&format!("a\n{}\nb\nc", long), 10, 3);
+        assert_eq!(app.log_line_rows(1), 4);
+        // 7 rows in total, the last 3 being the long line's last row, b and c
+        assert_eq!(app.max_log_scroll_position(), (1, 3));
+        assert_eq!(app.log_viewport_lines(), 0..2);
+
+        app.update(Action::ScrollLogDown);
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (1, 0));
+        app.update(Action::ScrollLogDown);
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (1, 1));
+        app.update(Action::ScrollLogDownBy(10));
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (1, 3));
+        assert_eq!(app.log_viewport_lines(), 1..4);
+
+        // The last row of the long line is on top, so the second screen row is "b"
+        app.update(Action::SelectLogLine { row: 1, extend: false });
+        assert_eq!(app.log_selection, Some((2, 2)));
+
+        app.update(Action::ScrollLogUpBy(4));
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (0, 0));
     }
 
     #[test]
-    fn test_jobs_loaded() {
-        let mut app = App::new(123, None, false, 1);
+    fn test_wrapped_rows_depend_on_width() {
+        let long = "y".repeat(100);
+        for (width, rows) in [(10, 10), (33, 4), (100, 1), (200, 1)] {
+            let app = app_with_wrapped_log(&long, width, 5);
+            assert_eq!(app.log_line_rows(0), rows, "width {}", width);
+            assert_eq!(app.max_log_scroll_position(), (0, rows.saturating_sub(5)), "width {}", width);
+        }
 
-        let mr = create_test_mr(1, 10, "Test MR");
-        let pipeline = create_test_pipeline(100, PipelineStatus::Running);
+        // Without wrapping every line is one row
+        let mut app = app_with_wrapped_log(&long, 10, 5);
+        app.wrap_enabled = false;
+        assert_eq!(app.log_line_rows(0), 1);
+    }
 
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr,
-            pipelines: vec![pipeline],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
-        });
+    #[test]
+    fn test_timestamp_toggle_keeps_top_line() {
+        let content: Vec<String> = (0..20)
+            .map(|idx| format!("2024-01-15T10:30:{:02}Z 00O step {} {}", idx, idx, "z".repeat(25)))
+            .collect();
+        let mut app = app_with_wrapped_log(&content.join("\n"), 20, 5);
+        app.log_scroll_offset = 6;
+        app.log_scroll_row = 1;
+
+        for _ in 0..4 {
+            app.update(Action::ToggleTimestampMode);
+            assert_eq!(app.log_visible_lines[app.log_scroll_offset], 6);
+            assert_eq!(app.log_scroll_row, 0);
+            assert!(app.log_scroll_offset <= app.max_log_scroll_offset());
+        }
 
-        let jobs = vec![
-            create_test_job(200, "build", JobStatus::Success),
-            create_test_job(201, "test", JobStatus::Failed),
-        ];
+        // While following, the view stays pinned to the end instead
+        app.follow_mode = true;
+        app.update(Action::ToggleTimestampMode);
+        assert_eq!(
+            (app.log_scroll_offset, app.log_scroll_row),
+            app.max_log_scroll_position()
+        );
+    }
 
-        app.update(Action::JobsLoaded {
-            mr_index: 0,
-            pipeline_id: 100,
-            jobs,
-        });
+    #[test]
+    fn test_bookmarks_cycle_and_survive_reopening() {
+        let content: Vec<String> = (0..50).map(|idx| format!("line {}", idx)).collect();
+        let mut app = app_with_log(&content.join("\n"));
+        app.log_viewport_height = 10;
+
+        app.log_scroll_offset = 5;
+        app.update(Action::ToggleBookmark);
+        app.log_scroll_offset = 30;
+        app.update(Action::ToggleBookmark);
+        assert!(app.is_bookmarked(5) && app.is_bookmarked(30));
+        assert_eq!(app.log_gutter_width(), 1);
+
+        app.log_scroll_offset = 0;
+        app.update(Action::NextBookmark);
+        assert_eq!(app.log_scroll_offset, 5);
+        app.update(Action::NextBookmark);
+        assert_eq!(app.log_scroll_offset, 30);
+        app.update(Action::NextBookmark);
+        assert_eq!(app.log_scroll_offset, 5); // Wraps around
+        app.update(Action::PrevBookmark);
+        assert_eq!(app.log_scroll_offset, 30);
+
+        // Anchored to source lines, so other timestamp modes keep them
+        app.update(Action::ToggleTimestampMode);
+        assert!(app.is_bookmarked(30));
+
+        // Closing and reopening the same job's log keeps them, another job has its own
+        app.update(Action::CloseLogViewer);
+        app.set_log_content(1, content.join("\n"), "job".to_string());
+        app.mode = AppMode::ViewingLog;
+        assert!(app.is_bookmarked(5));
+        app.set_log_content(2, content.join("\n"), "other".to_string());
+        assert!(app.current_bookmarks().is_none());
+    }
 
-        assert!(app.tracked_mrs[0].jobs.contains_key(&100));
-        let loaded_jobs = &app.tracked_mrs[0].jobs[&100];
-        assert_eq!(loaded_jobs.len(), 2);
-        // Jobs are sorted by status: Failed jobs come first
-        assert_eq!(loaded_jobs[0].name, "test"); // Failed
-        assert_eq!(loaded_jobs[1].name, "build"); // Success
+    #[test]
+    fn test_toggle_bookmark_removes_it() {
+        let mut app = app_with_log("a\nb\nc");
+        app.update(Action::ToggleBookmark);
+        assert!(app.is_bookmarked(0));
+        app.update(Action::ToggleBookmark);
+        assert!(app.current_bookmarks().is_none());
+        assert_eq!(app.log_gutter_width(), 0);
     }
 
     #[test]
-    fn test_api_error() {
-        let mut app = App::new(123, None, false, 1);
+    fn test_scrollbar_marks() {
+        let log: String = (0..100).map(|i| if i % 25 == 0 { "match\n".to_string() } else { format!("line {}\n", i) }).collect();
+        let mut app = app_with_log(&log);
+        run_search(&mut app, "match");
+        app.log_bookmarks.insert(1, BTreeSet::from([50, 99]));
+
+        assert_eq!(
+            app.scrollbar_marks(10),
+            vec![
+                (0, ScrollbarMark::SearchMatch),
+                (2, ScrollbarMark::SearchMatch),
+                (5, ScrollbarMark::Bookmark),
+                (7, ScrollbarMark::SearchMatch),
+                (9, ScrollbarMark::Bookmark),
+            ]
+        );
+    }
 
-        app.update(Action::ApiError("Test error".to_string()));
-        assert_eq!(app.error_message, Some("Test error".to_string()));
-        assert!(app.status_message.is_none());
+    #[test]
+    fn test_scrollbar_click_jumps() {
+        let log: String = (0..110).map(|i| format!("line {}\n", i)).collect();
+        let mut app = app_with_log(&log);
+        app.wrap_enabled = false;
+        app.log_viewport_height = 10;
+        app.log_content_area = Some(ratatui::layout::Rect::new(1, 1, 40, 11));
+        app.follow_mode = true;
+
+        app.update(Action::JumpToScrollbarRow(5));
+        assert_eq!(app.log_scroll_offset, 50);
+        assert!(!app.follow_mode);
+        app.update(Action::JumpToScrollbarRow(10));
+        assert_eq!(app.log_scroll_offset, 100);
+        app.update(Action::JumpToScrollbarRow(0));
+        assert_eq!(app.log_scroll_offset, 0);
     }
 
     #[test]
-    fn test_remove_current_mr() {
-        let mut app = App::new(123, None, false, 1);
+    fn test_minimap_marks() {
+        let log: String = (0..100)
+            .map(|i| match i {
+                0 => "section_start:1700000000:build\r\x1b[0KBuilding\n".to_string(),
+                30 => "warning: unused\n".to_string(),
+                50 => "section_start:1700000010:test\r\x1b[0KTesting\n".to_string(),
+                52 => "ERROR: failed\n".to_string(),
+                i => format!("line {}\n", i),
+            })
+            .collect();
+        let mut app = app_with_log(&log);
+        app.log_viewport_height = 10;
+        assert!(app.minimap_visible(10));
+
+        // The error outranks the section start sharing its row
+        assert_eq!(
+            app.minimap_marks(10),
+            vec![(0, MinimapMark::Section), (3, MinimapMark::Warning), (5, MinimapMark::Error)]
+        );
+
+        app.update(Action::ToggleMinimap);
+        assert!(!app.minimap_visible(10));
+    }
 
-        let mr1 = create_test_mr(1, 10, "MR 1");
-        let mr2 = create_test_mr(2, 20, "MR 2");
+    #[test]
+    fn test_minimap_click_centers_its_stretch() {
+        let log: String = (0..110).map(|i| format!("line {}\n", i)).collect();
+        let mut app = app_with_log(&log);
+        app.wrap_enabled = false;
+        app.log_viewport_height = 10;
+        app.log_content_area = Some(ratatui::layout::Rect::new(1, 1, 40, 10));
+        app.follow_mode = true;
+
+        app.update(Action::JumpToMinimapRow(5));
+        assert_eq!(app.log_scroll_offset, 55);
+        assert!(!app.follow_mode);
+        app.update(Action::JumpToMinimapRow(0));
+        assert_eq!(app.log_scroll_offset, 0);
+        app.update(Action::JumpToMinimapRow(9));
+        assert_eq!(app.log_scroll_offset, 99);
+    }
 
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr: mr1,
-            pipelines: vec![],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
-        });
+    #[test]
+    fn test_resize_keeps_top_line_and_clamps() {
+        let log: String = (0..100).map(|i| format!("line number {}\n", i)).collect();
+        let mut app = app_with_wrapped_log(&log, 40, 10);
+        app.log_scroll_offset = 50;
+
+        // Narrower: lines wrap onto more rows, the same line stays at the top
+        app.set_log_layout(ratatui::layout::Rect::new(0, 0, 6, 10));
+        assert_eq!(app.log_scroll_offset, 50);
+        assert_eq!(app.log_viewport_height, 10);
+
+        // Taller than the rest of the log: pulled back so the last line sits at the bottom
+        app.set_log_layout(ratatui::layout::Rect::new(0, 0, 40, 80));
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (20, 0));
+
+        // Following stays at the bottom
+        app.follow_mode = true;
+        app.set_log_layout(ratatui::layout::Rect::new(0, 0, 40, 30));
+        assert_eq!(app.log_scroll_offset, 70);
+    }
 
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr: mr2,
-            pipelines: vec![],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
+    #[test]
+    fn test_log_tabs_keep_their_own_state() {
+        let mut app = app_with_stages();
+        let log: String = (0..50).map(|i| format!("line {}\n", i)).collect();
+        app.tracked_mrs[0].job_logs_cache.insert(201, Arc::new(log.clone()));
+        app.tracked_mrs[0].job_logs_cache.insert(202, Arc::new(log));
+        app.log_viewport_height = 10;
+
+        app.update(Action::OpenSelectedJobLog);
+        run_search(&mut app, "line 3");
+        app.log_scroll_offset = 20;
+
+        // Esc goes back to the list with the tab kept, opening another job adds a tab
+        app.update(Action::HideLogViewer);
+        assert_eq!(app.mode, AppMode::Normal);
+        app.update(Action::NextJob);
+        app.update(Action::OpenSelectedJobLog);
+        assert_eq!(app.log_tab_names(), vec!["compile", "docs"]);
+        assert_eq!(app.active_log_tab, 1);
+        assert_eq!(app.log_scroll_offset, 0);
+        assert!(app.search_results.is_empty());
+
+        app.update(Action::NextLogTab);
+        assert_eq!(app.log_job_id, Some(201));
+        assert_eq!(app.log_scroll_offset, 20);
+        assert_eq!(app.search_query, "line 3");
+
+        // A count before Tab picks the tab, opening a job with a tab switches to it
+        app.update(Action::Repeat { count: 2, action: Box::new(Action::NextLogTab) });
+        assert_eq!(app.log_job_id, Some(202));
+        app.update(Action::HideLogViewer);
+        app.update(Action::PrevJob);
+        app.update(Action::OpenSelectedJobLog);
+        assert_eq!((app.active_log_tab, app.log_tabs.len()), (0, 2));
+
+        // q closes the active tab, the last one closes the viewer
+        app.update(Action::CloseLogViewer);
+        assert_eq!(app.log_tab_names(), vec!["docs"]);
+        assert_eq!(app.log_job_id, Some(202));
+        assert_eq!(app.mode, AppMode::ViewingLog);
+        app.update(Action::CloseLogViewer);
+        assert!(app.log_tabs.is_empty());
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_reopened_log_restores_its_view() {
+        let mut app = app_with_stages();
+        let log: String = (0..50).map(|i| format!("line {}\n", i)).collect();
+        app.tracked_mrs[0].job_logs_cache.insert(201, Arc::new(log.clone()));
+        app.log_viewport_height = 10;
+
+        app.update(Action::OpenSelectedJobLog);
+        apply_filter(&mut app, "line [1-3]", false);
+        run_search(&mut app, "line 2");
+        app.update(Action::NextSearchResult);
+        app.update(Action::ToggleTimestampMode);
+        let mode = app.timestamp_mode.clone();
+        app.log_scroll_offset = 12;
+        let (top_line, match_line) = (app.log_visible_lines[12], app.search_results[app.current_search_result]);
+        app.update(Action::CloseLogViewer);
+        assert!(app.search_query.is_empty() && app.filter_regex.is_none());
+
+        // Another job's log starts out fresh
+        app.update(Action::NextJob);
+        app.update(Action::OpenSelectedJobLog);
+        app.update(Action::JobTraceChunkLoaded {
+            job_id: 202,
+            job_name: "docs".to_string(),
+            offset: 0,
+            chunk: TraceChunk { data: log.into_bytes(), total_size: None, complete: true },
         });
+        assert!(app.search_query.is_empty());
+        assert_eq!(app.log_scroll_offset, 0);
+        app.update(Action::CloseLogViewer);
+
+        app.update(Action::PrevJob);
+        app.update(Action::OpenSelectedJobLog);
+        assert_eq!(app.log_job_id, Some(201));
+        assert_eq!((app.filter_query.as_str(), app.search_query.as_str()), ("line [1-3]", "line 2"));
+        assert_eq!(app.timestamp_mode, mode);
+        assert_eq!(app.search_results[app.current_search_result], match_line);
+        assert_eq!(app.log_visible_lines[app.log_scroll_offset], top_line);
+
+        // Only the most recently closed logs are remembered
+        for job_id in 1000..1000 + SAVED_LOG_VIEWS_LIMIT as u64 {
+            app.set_log_content(job_id, "x".to_string(), "job".to_string());
+            app.save_log_view();
+        }
+        assert_eq!(app.saved_log_views.len(), SAVED_LOG_VIEWS_LIMIT);
+        assert!(!app.saved_log_views.contains_key(&201));
+    }
 
-        assert_eq!(app.tracked_mrs.len(), 2);
-        app.update(Action::RemoveCurrentMr);
-        assert_eq!(app.tracked_mrs.len(), 1);
-        assert_eq!(app.tracked_mrs[0].mr.title, "MR 2");
+    #[test]
+    fn test_reopened_log_returns_to_match_as_search_reaches_it() {
+        let mut app = app_with_stages();
+        let log: String = (0..SEARCH_BATCH_LINES + 100).map(|i| format!("step {}\n", i)).collect();
+        app.tracked_mrs[0].job_logs_cache.insert(201, Arc::new(log));
+        app.update(Action::OpenSelectedJobLog);
+        app.search_mode = SearchMode::Regex;
+        app.search_whole_word = true;
+        run_search(&mut app, r"step \d+0");
+        while app.search_scan.is_some() {
+            app.update(Action::ContinueSearch { generation: app.search_scan.as_ref().unwrap().generation });
+        }
+        app.update(Action::PrevSearchResult);
+        let match_line = app.search_results[app.current_search_result];
+        assert!(match_line >= SEARCH_BATCH_LINES);
+        app.update(Action::CloseLogViewer);
+        app.search_mode = SearchMode::Literal;
+        app.search_whole_word = false;
+
+        // The query is searched as it was, in steps, and the match selected once the scan gets there
+        app.update(Action::OpenSelectedJobLog);
+        assert_eq!((app.search_mode, app.search_whole_word), (SearchMode::Regex, true));
+        let scan = app.search_scan.as_ref().unwrap();
+        let generation = scan.generation;
+        assert!(scan.scheduled);
+        assert_eq!(app.current_search_result, 0);
+        app.update(Action::ContinueSearch { generation });
+        assert!(app.search_scan.is_none());
+        assert_eq!(app.search_results[app.current_search_result], match_line);
     }
 
     #[test]
-    fn test_get_selected_mr() {
-        let mut app = App::new(123, None, false, 1);
+    fn test_log_tab_in_background_keeps_downloading() {
+        let mut app = app_with_stages();
+        let effect = app.update(Action::OpenSelectedJobLog);
+        assert!(matches!(effect, Some(Effect::FetchJobTrace { job_id: 201, offset: 0, .. })));
+        app.update(Action::HideLogViewer);
+        app.update(Action::NextJob);
+        app.update(Action::OpenSelectedJobLog);
+
+        let effect = app.update(Action::JobTraceChunkLoaded {
+            job_id: 201,
+            job_name: "compile".to_string(),
+            offset: 0,
+            chunk: TraceChunk { data: b"first\n".to_vec(), total_size: Some(12), complete: false },
+        });
+        assert!(matches!(effect, Some(Effect::FetchJobTrace { job_id: 201, offset: 6, .. })));
+        assert_eq!(app.log_job_id, Some(202));
+        assert!(app.log_content.is_none());
 
-        assert!(app.get_selected_mr().is_none());
+        app.update(Action::PrevLogTab);
+        assert_eq!(app.log_content.as_ref().map(|buffer| buffer.to_text().into_owned()).as_deref(), Some("first\n"));
+    }
 
-        let mr = create_test_mr(1, 10, "Test MR");
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr,
-            pipelines: vec![],
-            jobs: HashMap::new(),
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
-        });
+    #[test]
+    fn test_match_lands_at_configured_position_when_lines_wrap() {
+        // Lines above the match wrap onto two rows each at width 10
+        let log: String = (0..40).map(|i| if i == 30 { "needle\n".to_string() } else { format!("long line {:02}\n", i) }).collect();
+        let mut app = app_with_wrapped_log(&log, 10, 10);
+        run_search(&mut app, "needle");
+
+        // Rows above the match: the last row of line 27, then lines 28 and 29
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (27, 1));
+        assert_eq!(app.search_results, vec![30]);
+
+        app.match_position = 0.0;
+        app.update(Action::NextSearchResult);
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (30, 0));
+
+        app.match_position = 1.0;
+        app.update(Action::NextSearchResult);
+        assert_eq!((app.log_scroll_offset, app.log_scroll_row), (25, 1));
+    }
 
-        let selected = app.get_selected_mr();
-        assert!(selected.is_some());
-        assert_eq!(selected.unwrap().mr.title, "Test MR");
+    #[test]
+    fn test_search_navigation_wraps_or_stops() {
+        let mut app = app_with_log("match a\nx\nmatch b\ny\nmatch c");
+        run_search(&mut app, "match");
+        app.update(Action::NextSearchResult);
+        app.update(Action::NextSearchResult);
+        assert_eq!(app.current_search_result, 2);
+        assert!(!app.visible_status().unwrap_or_default().contains("wrapped"));
+
+        app.update(Action::NextSearchResult);
+        assert_eq!(app.current_search_result, 0);
+        assert_eq!(app.visible_status(), Some("Search wrapped to the first match"));
+        app.update(Action::PrevSearchResult);
+        assert_eq!(app.current_search_result, 2);
+        assert_eq!(app.visible_status(), Some("Search wrapped to the last match"));
+
+        app.search_wrap = false;
+        app.update(Action::NextSearchResult);
+        assert_eq!(app.current_search_result, 2);
+        assert_eq!(app.visible_status(), Some("Last match"));
     }
 
     #[test]
-    fn test_get_selected_jobs() {
+    fn test_status_bar_session_info() {
         let mut app = App::new(123, None, false, 1);
+        assert!(app.show_status_bar);
+        app.update(Action::CurrentUserLoaded(crate::gitlab::User {
+            id: 7,
+            username: "jdoe".to_string(),
+            name: "Jane Doe".to_string(),
+        }));
+        app.update(Action::ProjectPathLoaded("group/project".to_string()));
+        assert_eq!(app.username.as_deref(), Some("jdoe"));
+        assert_eq!(app.project_path.as_deref(), Some("group/project"));
+
+        app.update(Action::ToggleStatusBar);
+        assert!(!app.preferences().show_status_bar);
+    }
 
-        let mr = create_test_mr(1, 10, "Test MR");
-        let pipeline = create_test_pipeline(100, PipelineStatus::Running);
-        let job = create_test_job(200, "test-job", JobStatus::Success);
-
-        let mut jobs_map = HashMap::new();
-        jobs_map.insert(100, vec![job]);
+    fn profile(name: &str, instance_url: &str, default_project_id: Option<u64>) -> GitLabProfile {
+        GitLabProfile {
+            name: name.to_string(),
+            instance_url: instance_url.to_string(),
+            api_url: format!("{}/api/v4", instance_url),
+            token: format!("token-{}", name),
+            default_project_id,
+        }
+    }
 
-        app.tracked_mrs.push(TrackedMergeRequest {
-            mr,
-            pipelines: vec![pipeline],
-            jobs: jobs_map,
-            job_logs_cache: HashMap::new(),
-            notes: Vec::new(),
-            notes_loaded: false,
-            selected_pipeline_index: 0,
-            selected_note_index: 0,
-            loading: false,
-            error: None,
+    #[test]
+    fn test_switch_profile_resets_project_state() {
+        let mut app = app_with_stages();
+        app.profiles = vec![
+            profile("default", "https://gitlab.com", Some(123)),
+            profile("work", "https://gitlab.example.com", Some(7)),
+            profile("client", "https://git.client.org", None),
+        ];
+        app.username = Some("jdoe".to_string());
+        app.set_log_content(201, "log".to_string(), "compile".to_string());
+        app.mode = AppMode::ViewingLog;
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        app.artifact_download = Some(ArtifactDownload {
+            job_id: 201,
+            job_name: "compile".to_string(),
+            path: PathBuf::from("job-201-compile-artifacts.zip"),
+            received: 0,
+            total: None,
+            cancel: cancel.clone(),
         });
 
-        let jobs = app.get_selected_jobs();
-        assert!(jobs.is_some());
-        assert_eq!(jobs.unwrap().len(), 1);
-        assert_eq!(jobs.unwrap()[0].name, "test-job");
+        app.update(Action::OpenProfilePicker);
+        app.update(Action::ProfilePickerNext);
+        let effect = app.update(Action::SelectProfile);
+        assert!(app.profile_picker.is_none());
+        assert_eq!((app.active_profile, app.profile_generation), (1, 1));
+        assert!(app.artifact_download.is_none() && cancel.load(Ordering::Relaxed));
+        assert_eq!(app.project_id, 7);
+        assert_eq!(app.instance_host, "gitlab.example.com");
+        assert!(app.tracked_mrs.is_empty());
+        assert!(app.log_content.is_none());
+        assert!(app.username.is_none());
+        assert_eq!(app.mode, AppMode::Normal);
+        let Some(Effect::Batch(effects)) = effect else {
+            panic!("expected a refresh and session info, got {:?}", effect);
+        };
+        assert!(matches!(effects[0], Effect::RefreshAll { project_id: 7, .. }));
+        assert!(matches!(effects[1], Effect::FetchSessionInfo { project_id: 7, with_project: true }));
+
+        // Without a project id it is looked up from the git remote on the new instance
+        app.update(Action::OpenProfilePicker);
+        app.update(Action::ProfilePickerNext);
+        let effect = app.update(Action::SelectProfile);
+        assert!(matches!(effect, Some(Effect::ResolveProject { profile: 2 })));
+        assert!(app.update(Action::ProjectResolved { profile: 1, project_id: 9, project_path: None }).is_none());
+        app.update(Action::ProjectResolved { profile: 2, project_id: 42, project_path: Some("team/app".to_string()) });
+        assert_eq!(app.project_id, 42);
+        assert_eq!(app.project_path.as_deref(), Some("team/app"));
     }
 }
@@ -0,0 +1,184 @@
+/// Bytes requested from the end of the archive, enough for the end record with the longest comment
+pub const ARCHIVE_TAIL_LEN: u64 = 22 + u16::MAX as u64;
+
+/// Largest artifact file shown inline
+pub const MAX_VIEWED_FILE_SIZE: u64 = 16 * 1024 * 1024;
+
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const CENTRAL_DIRECTORY_ENTRY: u32 = 0x0201_4b50;
+const ZIP64_EXTRA_FIELD: u16 = 0x0001;
+
+/// A file inside an artifacts archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactEntry {
+    pub path: String,
+    pub size: u64, // Uncompressed
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+}
+
+/// Offset and size of the central directory, from the end record found in the archive's last bytes
+pub fn central_directory_location(tail: &[u8]) -> Result<(u64, u64), String> {
+    let end = (0..=tail.len().saturating_sub(22))
+        .rev()
+        .find(|&at| u32_at(tail, at) == Some(END_OF_CENTRAL_DIRECTORY))
+        .ok_or_else(|| "the archive is not a zip file".to_string())?;
+
+    let entries = u16_at(tail, end + 10).unwrap_or_default();
+    let size = u32_at(tail, end + 12).unwrap_or_default();
+    let offset = u32_at(tail, end + 16).unwrap_or_default();
+    if entries == u16::MAX || size == u32::MAX || offset == u32::MAX {
+        return Err("archives over 4 GiB (ZIP64) are not supported".to_string());
+    }
+    Ok((offset as u64, size as u64))
+}
+
+/// Files listed in a central directory, sorted by path; directories are left out
+pub fn parse_central_directory(directory: &[u8]) -> Result<Vec<ArtifactEntry>, String> {
+    let corrupt = || "the archive's file list is corrupt".to_string();
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while at < directory.len() {
+        if u32_at(directory, at) != Some(CENTRAL_DIRECTORY_ENTRY) {
+            return Err(corrupt());
+        }
+        let size = u32_at(directory, at + 24).ok_or_else(corrupt)?;
+        let name_len = u16_at(directory, at + 28).ok_or_else(corrupt)? as usize;
+        let extra_len = u16_at(directory, at + 30).ok_or_else(corrupt)? as usize;
+        let comment_len = u16_at(directory, at + 32).ok_or_else(corrupt)? as usize;
+        let name_start = at + 46;
+        let extra_start = name_start + name_len;
+        let name = directory.get(name_start..extra_start).ok_or_else(corrupt)?;
+        let extra = directory.get(extra_start..extra_start + extra_len).ok_or_else(corrupt)?;
+
+        let path = String::from_utf8_lossy(name).into_owned();
+        let size = if size == u32::MAX { zip64_size(extra).unwrap_or(size as u64) } else { size as u64 };
+        if !path.ends_with('/') {
+            entries.push(ArtifactEntry { path, size });
+        }
+        at = extra_start + extra_len + comment_len;
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Uncompressed size from the ZIP64 extra field, which comes first in it when present
+fn zip64_size(mut extra: &[u8]) -> Option<u64> {
+    while extra.len() >= 4 {
+        let id = u16_at(extra, 0)?;
+        let len = u16_at(extra, 2)? as usize;
+        if id == ZIP64_EXTRA_FIELD {
+            return u64_at(extra, 4);
+        }
+        extra = extra.get(4 + len..)?;
+    }
+    None
+}
+
+/// Text of a fetched file, binary files (NUL bytes or invalid UTF-8) are refused
+pub fn decode_text(bytes: Vec<u8>) -> Result<String, String> {
+    if bytes.contains(&0) {
+        return Err("Binary file, download the artifacts to open it".to_string());
+    }
+    String::from_utf8(bytes).map_err(|_| "Binary file, download the artifacts to open it".to_string())
+}
+
+/// Percent-encode a path inside the archive for the single-file artifacts URL, keeping its slashes
+pub fn encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Central directory entry for a file of this name and size, optionally with its size in ZIP64 form
+    fn entry(name: &str, size: u64, zip64: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(CENTRAL_DIRECTORY_ENTRY.to_le_bytes());
+        bytes.extend([0u8; 16]); // Versions, flags, method, time, CRC
+        bytes.extend(0u32.to_le_bytes()); // Compressed size
+        let size32 = if zip64 { u32::MAX } else { size as u32 };
+        bytes.extend(size32.to_le_bytes());
+        bytes.extend((name.len() as u16).to_le_bytes());
+        bytes.extend((if zip64 { 12u16 } else { 0 }).to_le_bytes());
+        bytes.extend(0u16.to_le_bytes()); // Comment
+        bytes.extend([0u8; 12]); // Disk, attributes, local header offset
+        bytes.extend(name.as_bytes());
+        if zip64 {
+            bytes.extend(ZIP64_EXTRA_FIELD.to_le_bytes());
+            bytes.extend(8u16.to_le_bytes());
+            bytes.extend(size.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn end_record(entries: u16, size: u32, offset: u32, comment: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        bytes.extend([0u8; 4]);
+        bytes.extend(entries.to_le_bytes());
+        bytes.extend(entries.to_le_bytes());
+        bytes.extend(size.to_le_bytes());
+        bytes.extend(offset.to_le_bytes());
+        bytes.extend((comment.len() as u16).to_le_bytes());
+        bytes.extend(comment);
+        bytes
+    }
+
+    #[test]
+    fn test_list_archive_files() {
+        let mut directory = entry("reports/junit.xml", 1234, false);
+        directory.extend(entry("reports/", 0, false));
+        directory.extend(entry("coverage.txt", 5_000_000_000, true));
+
+        let mut tail = b"file data".to_vec();
+        tail.extend(&directory);
+        tail.extend(end_record(3, directory.len() as u32, 9, b"comment"));
+        assert_eq!(central_directory_location(&tail), Ok((9, directory.len() as u64)));
+
+        let entries = parse_central_directory(&directory).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ArtifactEntry { path: "coverage.txt".to_string(), size: 5_000_000_000 },
+                ArtifactEntry { path: "reports/junit.xml".to_string(), size: 1234 },
+            ]
+        );
+        assert!(parse_central_directory(&directory[..50]).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_archives() {
+        assert!(central_directory_location(b"not a zip").is_err());
+        let tail = end_record(u16::MAX, u32::MAX, u32::MAX, b"");
+        assert!(central_directory_location(&tail).unwrap_err().contains("ZIP64"));
+    }
+
+    #[test]
+    fn test_binary_files_are_refused() {
+        assert_eq!(decode_text(b"ok\n".to_vec()), Ok("ok\n".to_string()));
+        assert!(decode_text(vec![0x89, b'P', b'N', b'G', 0, 1]).is_err());
+        assert!(decode_text(vec![0xff, 0xfe, b'a']).is_err());
+    }
+
+    #[test]
+    fn test_encode_path() {
+        assert_eq!(encode_path("reports/unit tests/a+b.xml"), "reports/unit%20tests/a%2Bb.xml");
+    }
+}
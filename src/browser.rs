@@ -0,0 +1,21 @@
+use crate::error::{PeeplabError, Result};
+
+/// Open a URL in the default browser without waiting for it
+pub fn open_url(url: &str) -> Result<()> {
+    if is_headless() {
+        return Err(PeeplabError::Browser("no graphical session (DISPLAY/WAYLAND_DISPLAY unset)".to_string()));
+    }
+    open::that_detached(url).map_err(|e| PeeplabError::Browser(e.to_string()))
+}
+
+/// Over SSH or on a server there is no browser to hand the URL to
+#[cfg(all(unix, not(target_os = "macos")))]
+fn is_headless() -> bool {
+    let unset = |name| std::env::var_os(name).is_none_or(|value| value.is_empty());
+    unset("DISPLAY") && unset("WAYLAND_DISPLAY")
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn is_headless() -> bool {
+    false
+}
@@ -0,0 +1,26 @@
+use crate::error::{PeeplabError, Result};
+use std::sync::Mutex;
+
+// On X11 the copied text is served by the process owning the clipboard handle,
+// so keep it alive instead of dropping it right after copying
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// Copy text to the system clipboard
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut guard = CLIPBOARD
+        .lock()
+        .map_err(|_| PeeplabError::Clipboard("clipboard lock poisoned".to_string()))?;
+
+    if guard.is_none() {
+        // Fails on headless systems (no X11/Wayland display), which the caller reports
+        let clipboard = arboard::Clipboard::new()
+            .map_err(|e| PeeplabError::Clipboard(e.to_string()))?;
+        *guard = Some(clipboard);
+    }
+
+    guard
+        .as_mut()
+        .expect("clipboard initialized above")
+        .set_text(text.to_string())
+        .map_err(|e| PeeplabError::Clipboard(e.to_string()))
+}
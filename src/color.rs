@@ -0,0 +1,199 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+
+/// How many colors the terminal can show, everything drawn is reduced to fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    TrueColor,  // 24-bit RGB
+    Ansi256,    // xterm 256-color palette
+    Ansi16,     // The 16 basic ANSI colors
+    Monochrome, // No colors, selections and matches are shown reversed
+}
+
+/// RGB values of the 16 basic colors (xterm defaults), used to find the nearest one
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel values of the 6x6x6 color cube of the 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorSupport {
+    /// Parse a `--color` / `[ui] color` value, None for "auto" or anything unknown
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "truecolor" | "24bit" => Some(ColorSupport::TrueColor),
+            "256" => Some(ColorSupport::Ansi256),
+            "16" => Some(ColorSupport::Ansi16),
+            "none" | "mono" | "monochrome" => Some(ColorSupport::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// Guess the capability from `NO_COLOR`, `COLORTERM` and `TERM`
+    pub fn detect() -> Self {
+        let var = |name| std::env::var(name).ok();
+        Self::from_env(var("NO_COLOR").as_deref(), var("COLORTERM").as_deref(), var("TERM").as_deref())
+    }
+
+    fn from_env(no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if no_color.is_some_and(|value| !value.is_empty()) {
+            return ColorSupport::Monochrome;
+        }
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorSupport::TrueColor;
+        }
+        match term.unwrap_or_default() {
+            "dumb" => ColorSupport::Monochrome,
+            term if term.ends_with("-direct") => ColorSupport::TrueColor,
+            term if term.contains("256color") => ColorSupport::Ansi256,
+            _ => ColorSupport::Ansi16,
+        }
+    }
+
+    /// The closest color this terminal can show
+    pub fn reduce(self, color: Color) -> Color {
+        match (self, color) {
+            (_, Color::Reset) | (ColorSupport::TrueColor, _) => color,
+            (ColorSupport::Monochrome, _) => Color::Reset,
+            (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_indexed(r, g, b)),
+            (ColorSupport::Ansi256, _) => color,
+            (ColorSupport::Ansi16, Color::Rgb(r, g, b)) => nearest_ansi16((r, g, b)),
+            (ColorSupport::Ansi16, Color::Indexed(index)) => nearest_ansi16(indexed_rgb(index)),
+            (ColorSupport::Ansi16, _) => color,
+        }
+    }
+
+    /// Reduce every cell of a drawn frame, covering theme colors and ANSI colors from logs alike
+    pub fn apply(self, buffer: &mut Buffer) {
+        if self == ColorSupport::TrueColor {
+            return;
+        }
+        for cell in buffer.content.iter_mut() {
+            if self == ColorSupport::Monochrome && cell.bg != Color::Reset {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+            cell.fg = self.reduce(cell.fg);
+            cell.bg = self.reduce(cell.bg);
+        }
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// RGB value of an entry of the 256-color palette
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (CUBE_LEVELS[(i / 36) as usize], CUBE_LEVELS[(i / 6 % 6) as usize], CUBE_LEVELS[(i % 6) as usize])
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Closest entry of the color cube or grayscale ramp of the 256-color palette
+fn nearest_indexed(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &l)| (l as i32 - v as i32).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or(0)
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+    let average = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+    if distance((r, g, b), indexed_rgb(gray)) < distance((r, g, b), indexed_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_override() {
+        assert_eq!(ColorSupport::parse("TrueColor"), Some(ColorSupport::TrueColor));
+        assert_eq!(ColorSupport::parse("256"), Some(ColorSupport::Ansi256));
+        assert_eq!(ColorSupport::parse("16"), Some(ColorSupport::Ansi16));
+        assert_eq!(ColorSupport::parse("none"), Some(ColorSupport::Monochrome));
+        assert_eq!(ColorSupport::parse("auto"), None);
+    }
+
+    #[test]
+    fn test_detect_from_env() {
+        assert_eq!(ColorSupport::from_env(None, Some("truecolor"), Some("screen")), ColorSupport::TrueColor);
+        assert_eq!(ColorSupport::from_env(None, None, Some("xterm-256color")), ColorSupport::Ansi256);
+        assert_eq!(ColorSupport::from_env(None, None, Some("screen")), ColorSupport::Ansi16);
+        assert_eq!(ColorSupport::from_env(None, None, None), ColorSupport::Ansi16);
+        assert_eq!(ColorSupport::from_env(None, None, Some("dumb")), ColorSupport::Monochrome);
+        assert_eq!(ColorSupport::from_env(Some("1"), Some("truecolor"), None), ColorSupport::Monochrome);
+        assert_eq!(ColorSupport::from_env(Some(""), None, Some("xterm-direct")), ColorSupport::TrueColor);
+    }
+
+    #[test]
+    fn test_truecolor_to_ansi16() {
+        let support = ColorSupport::Ansi16;
+        assert_eq!(support.reduce(Color::Rgb(255, 128, 0)), Color::Yellow);
+        assert_eq!(support.reduce(Color::Rgb(220, 50, 47)), Color::Red);
+        assert_eq!(support.reduce(Color::Rgb(7, 54, 66)), Color::Black);
+        assert_eq!(support.reduce(Color::Indexed(200)), Color::LightMagenta);
+        assert_eq!(support.reduce(Color::Indexed(9)), Color::LightRed);
+        assert_eq!(support.reduce(Color::Cyan), Color::Cyan);
+    }
+
+    #[test]
+    fn test_truecolor_to_256() {
+        let support = ColorSupport::Ansi256;
+        assert_eq!(support.reduce(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(support.reduce(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(support.reduce(Color::Indexed(42)), Color::Indexed(42));
+    }
+
+    #[test]
+    fn test_monochrome_reverses_backgrounds() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 2, 1));
+        buffer[(0, 0)].set_fg(Color::Yellow).set_bg(Color::DarkGray);
+        buffer[(1, 0)].set_fg(Color::Cyan);
+        ColorSupport::Monochrome.apply(&mut buffer);
+        assert_eq!(buffer[(0, 0)].bg, Color::Reset);
+        assert!(buffer[(0, 0)].modifier.contains(Modifier::REVERSED));
+        assert_eq!(buffer[(1, 0)].fg, Color::Reset);
+        assert!(!buffer[(1, 0)].modifier.contains(Modifier::REVERSED));
+    }
+}
@@ -2,6 +2,11 @@ use crate::error::{PeeplabError, Result};
 use super::settings::Settings;
 use dirs::config_dir;
 use std::path::PathBuf;
+use std::process::Command;
+
+/// Environment variables checked for the `[gitlab]` token, in order. CI_JOB_TOKEN isn't one: job
+/// tokens go in another header and can't read merge requests
+const TOKEN_ENV_VARS: [&str; 1] = ["GITLAB_TOKEN"];
 
 pub fn get_config_path() -> Result<PathBuf> {
     let config_dir = config_dir()
@@ -24,12 +29,54 @@ pub fn load_config() -> Result<Settings> {
     }
 
     let content = std::fs::read_to_string(&config_path)?;
-    let settings: Settings = toml::from_str(&content)?;
+    let mut settings: Settings = toml::from_str(&content)?;
+    resolve_tokens(&mut settings, |name| std::env::var(name).ok())?;
 
     settings.validate().map_err(|e| PeeplabError::Config(e.to_string()))?;
     Ok(settings)
 }
 
+/// Fill in the tokens to use: for `[gitlab]` the first of GITLAB_TOKEN, token_command and
+/// token; for profiles their token_command, then token
+pub fn resolve_tokens(settings: &mut Settings, env: impl Fn(&str) -> Option<String>) -> Result<()> {
+    let env_token = TOKEN_ENV_VARS.iter().filter_map(|name| env(name)).find(|token| !token.trim().is_empty());
+    if let Some(token) = env_token {
+        settings.gitlab.token = token.trim().to_string();
+    } else if let Some(command) = &settings.gitlab.token_command {
+        settings.gitlab.token = run_token_command(command)?;
+    }
+    for profile in settings.profiles.values_mut() {
+        if let Some(command) = &profile.token_command {
+            profile.token = run_token_command(command)?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a secret helper through the shell, its output without surrounding whitespace is the token
+fn run_token_command(command: &str) -> Result<String> {
+    let output = if cfg!(windows) {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| PeeplabError::Config(format!("Could not run token_command '{}': {}", command, e)))?;
+
+    if !output.status.success() {
+        return Err(PeeplabError::Config(format!(
+            "token_command '{}' failed ({}): {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if token.is_empty() {
+        return Err(PeeplabError::Config(format!("token_command '{}' printed no token", command)));
+    }
+    Ok(token)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,4 +145,47 @@ mod tests {
         assert!(path.to_string_lossy().contains("peeplab"));
         assert!(path.to_string_lossy().contains("config.toml"));
     }
+
+    fn settings_with(gitlab: &str) -> Settings {
+        toml::from_str(&format!("[gitlab]\n{}\n[profiles.work]\ntoken = \"work-token\"\n", gitlab)).unwrap()
+    }
+
+    #[test]
+    fn test_token_resolution_order() {
+        let no_env = |_: &str| None;
+
+        let mut settings = settings_with(r#"token = "from-file""#);
+        resolve_tokens(&mut settings, no_env).unwrap();
+        assert_eq!(settings.gitlab.token, "from-file");
+
+        let mut settings = settings_with(r#"token = "from-file""#);
+        resolve_tokens(&mut settings, |name| (name == "GITLAB_TOKEN").then(|| "from-env\n".to_string())).unwrap();
+        assert_eq!(settings.gitlab.token, "from-env");
+
+        // A CI job token can't be used with the API calls peeplab makes
+        let mut settings = settings_with(r#"token = "from-file""#);
+        resolve_tokens(&mut settings, |name| (name == "CI_JOB_TOKEN").then(|| "from-ci".to_string())).unwrap();
+        assert_eq!(settings.gitlab.token, "from-file");
+
+        // GITLAB_TOKEN wins over the command, which isn't run
+        let mut settings = settings_with("token_command = \"exit 1\"");
+        resolve_tokens(&mut settings, |name| Some(format!("from-{}", name))).unwrap();
+        assert_eq!(settings.gitlab.token, "from-GITLAB_TOKEN");
+        assert_eq!(settings.profiles["work"].token, "work-token");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_token_command_output_is_trimmed() {
+        let mut settings = settings_with("token = \"from-file\"\ntoken_command = \"printf 'secret \\\\t\\\\r\\\\n\\\\n'\"");
+        resolve_tokens(&mut settings, |_| None).unwrap();
+        assert_eq!(settings.gitlab.token, "secret");
+
+        let mut settings = settings_with("token_command = \"echo oops >&2; exit 3\"");
+        let error = resolve_tokens(&mut settings, |_| None).unwrap_err().to_string();
+        assert!(error.contains("oops"), "{}", error);
+
+        let mut settings = settings_with("token_command = \"true\"");
+        assert!(resolve_tokens(&mut settings, |_| None).is_err());
+    }
 }
@@ -1,4 +1,6 @@
 pub mod loader;
+pub mod preferences;
 pub mod settings;
 
 pub use loader::{get_config_path, load_config};
+pub use preferences::{load_preferences, save_preferences, LastLocation, Preferences};
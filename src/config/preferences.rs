@@ -0,0 +1,135 @@
+use crate::app::TimestampDisplayMode;
+use crate::error::{PeeplabError, Result};
+use dirs::config_dir;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Log viewer choices remembered between sessions, kept apart from the hand-written config
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub timestamp_mode: TimestampDisplayMode,
+    pub wrap_enabled: bool,
+    pub follow_running_jobs: bool, // Start following when opening a running job's log
+    pub search_history: Vec<String>, // Submitted log searches, oldest first
+    pub show_status_bar: bool,
+    pub log_popup_percent: u16, // Size of the log viewer, grown and shrunk with + / -
+    pub last_location: Option<LastLocation>, // Where the last session left off, reselected on startup
+}
+
+/// The merge request, pipeline and job selected when quitting
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LastLocation {
+    pub project_id: u64,
+    pub mr_iid: u64,
+    pub pipeline_id: u64,
+    pub job_id: Option<u64>,
+    #[serde(default)]
+    pub log_open: bool, // The job's log was open, reopen it too
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            timestamp_mode: TimestampDisplayMode::Hidden,
+            wrap_enabled: true,
+            follow_running_jobs: false,
+            search_history: Vec::new(),
+            show_status_bar: true,
+            log_popup_percent: crate::app::DEFAULT_LOG_POPUP_PERCENT,
+            last_location: None,
+        }
+    }
+}
+
+pub fn get_preferences_path() -> Result<PathBuf> {
+    let config_dir = config_dir()
+        .ok_or_else(|| PeeplabError::Config("Could not determine config directory".to_string()))?;
+
+    Ok(config_dir.join("peeplab").join("preferences.toml"))
+}
+
+/// Load saved preferences, falling back to defaults if the file is missing or unreadable
+pub fn load_preferences() -> Preferences {
+    get_preferences_path()
+        .map(|path| load_preferences_from(&path))
+        .unwrap_or_default()
+}
+
+pub fn load_preferences_from(path: &Path) -> Preferences {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_preferences(preferences: &Preferences) -> Result<()> {
+    save_preferences_to(&get_preferences_path()?, preferences)
+}
+
+pub fn save_preferences_to(path: &Path, preferences: &Preferences) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = toml::to_string(preferences)
+        .map_err(|e| PeeplabError::Config(format!("Could not serialize preferences: {}", e)))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_preferences_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("preferences.toml");
+
+        let preferences = Preferences {
+            timestamp_mode: TimestampDisplayMode::Relative,
+            wrap_enabled: false,
+            follow_running_jobs: true,
+            search_history: vec!["error".to_string(), "exit code".to_string()],
+            show_status_bar: false,
+            log_popup_percent: 75,
+            last_location: Some(LastLocation {
+                project_id: 42,
+                mr_iid: 7,
+                pipeline_id: 1001,
+                job_id: Some(5),
+                log_open: true,
+            }),
+        };
+        save_preferences_to(&path, &preferences).unwrap();
+
+        assert_eq!(load_preferences_from(&path), preferences);
+    }
+
+    #[test]
+    fn test_missing_preferences_use_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let preferences = load_preferences_from(&temp_dir.path().join("preferences.toml"));
+        assert_eq!(preferences, Preferences::default());
+    }
+
+    #[test]
+    fn test_corrupt_preferences_use_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("preferences.toml");
+        std::fs::write(&path, "timestamp_mode = \"Sometimes\"\nwrap_enabled = [").unwrap();
+        assert_eq!(load_preferences_from(&path), Preferences::default());
+    }
+
+    #[test]
+    fn test_partial_preferences_keep_other_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("preferences.toml");
+        std::fs::write(&path, "timestamp_mode = \"Full\"\n").unwrap();
+
+        let preferences = load_preferences_from(&path);
+        assert_eq!(preferences.timestamp_mode, TimestampDisplayMode::Full);
+        assert!(preferences.wrap_enabled);
+    }
+}
@@ -1,4 +1,7 @@
+use crate::app::{FailedPipelineJump, LogPrefetch, NotifyMode};
+use crate::log_processor::{DEFAULT_PREFIX_PATTERNS, DEFAULT_REDACT_PATTERNS};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Settings {
@@ -9,14 +12,85 @@ pub struct Settings {
     pub ui: UiConfig,
     #[serde(default)]
     pub editor: EditorConfig,
+    /// Key binding overrides per context, e.g. `[keys.log]` with `"ctrl+e" = "scroll_down"`
+    #[serde(default)]
+    pub keys: BTreeMap<String, BTreeMap<String, String>>,
+    /// More GitLab instances to switch to at runtime, e.g. `[profiles.work]`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct GitLabConfig {
-    pub token: String,
+    #[serde(default)]
+    pub token: String, // Used when neither GITLAB_TOKEN nor token_command give one
+    pub token_command: Option<String>, // Shell command printing the token, e.g. "pass show gitlab"
     pub default_project_id: Option<u64>,
     #[serde(default = "default_instance_url")]
     pub instance_url: String,
+    pub api_url: Option<String>, // Full API base behind a proxy prefix, instance_url + "/api/v4" when unset
+    pub group: Option<String>, // Group whose pipelines G lists, the project's namespace when unset
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+/// A GitLab instance and token besides the `[gitlab]` one
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub token: String,
+    pub token_command: Option<String>, // Takes precedence over token
+    #[serde(default = "default_instance_url")]
+    pub instance_url: String,
+    pub api_url: Option<String>,
+    pub default_project_id: Option<u64>, // Detected from the git remote when unset
+}
+
+/// A GitLab instance the app can talk to, the `[gitlab]` section being the "default" one
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitLabProfile {
+    pub name: String,
+    pub instance_url: String,
+    pub api_url: String, // Where API requests go, e.g. "https://host/gitlab/api/v4"
+    pub token: String,
+    pub default_project_id: Option<u64>,
+}
+
+impl GitLabProfile {
+    /// Host part of the instance URL, e.g. "gitlab.com"
+    pub fn host(&self) -> String {
+        self.instance_url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/')
+            .to_string()
+    }
+}
+
+/// The configured API base, or the standard `/api/v4` under the instance URL
+fn resolve_api_url(instance_url: &str, api_url: Option<&str>) -> String {
+    match api_url {
+        Some(api_url) => api_url.trim_end_matches('/').to_string(),
+        None => format!("{}/api/v4", instance_url.trim_end_matches('/')),
+    }
+}
+
+/// An http(s) URL with a host and nothing after the path
+fn validate_url(setting: &str, value: &str) -> anyhow::Result<()> {
+    let url = url::Url::parse(value)
+        .map_err(|e| anyhow::anyhow!("Invalid {} '{}': {}, expected e.g. https://gitlab.example.com", setting, value, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!("Invalid {} '{}': it must start with http:// or https://", setting, value);
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        anyhow::bail!("Invalid {} '{}': it has no host", setting, value);
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        anyhow::bail!("Invalid {} '{}': it can't have a query or fragment", setting, value);
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -29,6 +103,20 @@ pub struct AppConfig {
     pub focus_current_branch: bool,
     #[serde(default = "default_auto_refresh_interval_minutes")]
     pub auto_refresh_interval_minutes: u64,
+    #[serde(default = "default_live_refresh_interval_secs")]
+    pub live_refresh_interval_secs: u64, // Re-poll of running pipelines and jobs, 0 disables
+    #[serde(default)]
+    pub failed_pipeline: FailedPipelineJump, // "off", "select_job" or "open_log"
+    #[serde(default)]
+    pub notify: NotifyMode, // How watched jobs finishing are announced: "off", "bell", "desktop" or "both"
+    #[serde(default)]
+    pub prefetch_logs: LogPrefetch, // Logs fetched ahead on opening a pipeline: "off", "failed" or "finished"
+    #[serde(default = "default_restore_last_location")]
+    pub restore_last_location: bool, // Reselect the last session's pipeline and job, `--no-restore` skips it once
+    #[serde(default = "default_cache_max_age_days")]
+    pub cache_max_age_days: u64, // Cached data older than this is dropped on startup
+    #[serde(default = "default_cache_max_size_mb")]
+    pub cache_max_size_mb: u64, // Offline cache size cap, 0 disables the cache
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -37,9 +125,31 @@ pub struct UiConfig {
     pub relative_timestamps: bool,
     #[serde(default = "default_theme")]
     pub theme: String,
+    #[serde(default = "default_color")]
+    pub color: String, // "auto", "truecolor", "256", "16" or "none"
+    #[serde(default = "default_mouse_scroll_lines")]
+    pub mouse_scroll_lines: usize, // Log lines scrolled per mouse wheel tick
+    #[serde(default = "default_scroll_step")]
+    pub scroll_step: usize, // Log lines scrolled per j / k
+    #[serde(default = "default_fast_scroll_step")]
+    pub fast_scroll_step: usize, // Log lines scrolled per J / K
+    #[serde(default = "default_match_position")]
+    pub match_position: f64, // Where jumping to a search match or error puts it, 0.0 top to 1.0 bottom
+    #[serde(default = "default_search_wrap")]
+    pub search_wrap: bool, // Jumping past the last search match goes back to the first
+    #[serde(default)]
+    pub filter_context: usize, // Lines shown before and after each filter match, + / - change it
+    #[serde(default = "default_search_debounce_ms")]
+    pub search_debounce_ms: u64, // Pause in typing before searching, 0 searches on Enter only
+    #[serde(default = "default_redact_patterns")]
+    pub redact_patterns: Vec<String>, // Regexes of secrets replaced by *** in logs, an empty list turns redaction off
+    #[serde(default = "default_prefix_patterns")]
+    pub prefix_patterns: Vec<String>, // Regexes of runner prefixes stripped from the start of lines and after timestamps, an empty list keeps them
+    #[serde(default = "default_timezone")]
+    pub timezone: String, // Zone of full and date timestamps: "utc", "local", a zone name such as "Europe/Paris" or an offset
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct EditorConfig {
     pub custom_editor: Option<String>,
 }
@@ -49,6 +159,14 @@ fn default_instance_url() -> String {
     "https://gitlab.com".to_string()
 }
 
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
 fn default_refresh_interval() -> u64 {
     30
 }
@@ -57,6 +175,18 @@ fn default_max_tracked_mrs() -> usize {
     5
 }
 
+fn default_restore_last_location() -> bool {
+    true
+}
+
+fn default_cache_max_age_days() -> u64 {
+    7
+}
+
+fn default_cache_max_size_mb() -> u64 {
+    100
+}
+
 fn default_focus_current_branch() -> bool {
     true
 }
@@ -65,6 +195,10 @@ fn default_auto_refresh_interval_minutes() -> u64 {
     1
 }
 
+fn default_live_refresh_interval_secs() -> u64 {
+    5
+}
+
 fn default_relative_timestamps() -> bool {
     true
 }
@@ -73,6 +207,46 @@ fn default_theme() -> String {
     "dark".to_string()
 }
 
+fn default_color() -> String {
+    "auto".to_string()
+}
+
+fn default_mouse_scroll_lines() -> usize {
+    3
+}
+
+fn default_scroll_step() -> usize {
+    1
+}
+
+fn default_fast_scroll_step() -> usize {
+    10
+}
+
+fn default_search_debounce_ms() -> u64 {
+    150
+}
+
+fn default_match_position() -> f64 {
+    0.5
+}
+
+fn default_search_wrap() -> bool {
+    true
+}
+
+fn default_redact_patterns() -> Vec<String> {
+    DEFAULT_REDACT_PATTERNS.iter().map(|pattern| pattern.to_string()).collect()
+}
+
+fn default_prefix_patterns() -> Vec<String> {
+    DEFAULT_PREFIX_PATTERNS.iter().map(|pattern| pattern.to_string()).collect()
+}
+
+fn default_timezone() -> String {
+    "utc".to_string()
+}
+
 // Defaults for the configs
 impl Default for AppConfig {
     fn default() -> Self {
@@ -81,6 +255,13 @@ impl Default for AppConfig {
             max_tracked_mrs: default_max_tracked_mrs(),
             focus_current_branch: default_focus_current_branch(),
             auto_refresh_interval_minutes: default_auto_refresh_interval_minutes(),
+            live_refresh_interval_secs: default_live_refresh_interval_secs(),
+            failed_pipeline: FailedPipelineJump::default(),
+            notify: NotifyMode::default(),
+            prefetch_logs: LogPrefetch::default(),
+            restore_last_location: default_restore_last_location(),
+            cache_max_age_days: default_cache_max_age_days(),
+            cache_max_size_mb: default_cache_max_size_mb(),
         }
     }
 }
@@ -90,14 +271,17 @@ impl Default for UiConfig {
         Self {
             relative_timestamps: default_relative_timestamps(),
             theme: default_theme(),
-        }
-    }
-}
-
-impl Default for EditorConfig {
-    fn default() -> Self {
-        Self {
-            custom_editor: None,
+            color: default_color(),
+            mouse_scroll_lines: default_mouse_scroll_lines(),
+            scroll_step: default_scroll_step(),
+            fast_scroll_step: default_fast_scroll_step(),
+            match_position: default_match_position(),
+            search_wrap: default_search_wrap(),
+            search_debounce_ms: default_search_debounce_ms(),
+            filter_context: 0,
+            redact_patterns: default_redact_patterns(),
+            prefix_patterns: default_prefix_patterns(),
+            timezone: default_timezone(),
         }
     }
 }
@@ -105,10 +289,42 @@ impl Default for EditorConfig {
 impl Settings {
     pub fn validate(&self) -> anyhow::Result<()> {
         if self.gitlab.token.is_empty() {
-            anyhow::bail!("GitLab token cannot be empty");
+            anyhow::bail!("GitLab token cannot be empty, set GITLAB_TOKEN, token_command or token");
+        }
+        if let Some(name) = self.profiles.iter().find(|(_, profile)| profile.token.is_empty()).map(|(name, _)| name) {
+            anyhow::bail!("GitLab token of profile '{}' cannot be empty", name);
+        }
+        validate_url("instance_url", &self.gitlab.instance_url)?;
+        if let Some(api_url) = &self.gitlab.api_url {
+            validate_url("api_url", api_url)?;
+        }
+        for (name, profile) in &self.profiles {
+            validate_url(&format!("instance_url of profile '{}'", name), &profile.instance_url)?;
+            if let Some(api_url) = &profile.api_url {
+                validate_url(&format!("api_url of profile '{}'", name), api_url)?;
+            }
         }
         Ok(())
     }
+
+    /// Every configured instance, `[gitlab]` first as "default", then the profiles by name
+    pub fn gitlab_profiles(&self) -> Vec<GitLabProfile> {
+        let default = GitLabProfile {
+            name: "default".to_string(),
+            instance_url: self.gitlab.instance_url.clone(),
+            api_url: resolve_api_url(&self.gitlab.instance_url, self.gitlab.api_url.as_deref()),
+            token: self.gitlab.token.clone(),
+            default_project_id: self.gitlab.default_project_id,
+        };
+        let profiles = self.profiles.iter().map(|(name, profile)| GitLabProfile {
+            name: name.clone(),
+            instance_url: profile.instance_url.clone(),
+            api_url: resolve_api_url(&profile.instance_url, profile.api_url.as_deref()),
+            token: profile.token.clone(),
+            default_project_id: profile.default_project_id,
+        });
+        std::iter::once(default).chain(profiles).collect()
+    }
 }
 
 #[cfg(test)]
@@ -125,6 +341,8 @@ mod tests {
         let settings: Settings = toml::from_str(toml).unwrap();
         assert_eq!(settings.gitlab.token, "test-token");
         assert_eq!(settings.gitlab.instance_url, "https://gitlab.com");
+        assert_eq!(settings.gitlab.connect_timeout_secs, 10);
+        assert_eq!(settings.gitlab.request_timeout_secs, 30);
         assert_eq!(settings.app.refresh_interval, 30);
         assert_eq!(settings.app.max_tracked_mrs, 5);
         assert_eq!(settings.app.auto_refresh_interval_minutes, 1);
@@ -145,10 +363,21 @@ mod tests {
             refresh_interval = 60
             max_tracked_mrs = 10
             auto_refresh_interval_minutes = 5
+            live_refresh_interval_secs = 0
+            failed_pipeline = "open_log"
+            notify = "bell"
+            prefetch_logs = "failed"
 
             [ui]
             relative_timestamps = false
             theme = "light"
+            color = "16"
+            mouse_scroll_lines = 5
+            scroll_step = 3
+            match_position = 0.25
+            search_wrap = false
+            search_debounce_ms = 0
+            filter_context = 2
 
             [editor]
             custom_editor = "nvim"
@@ -161,22 +390,58 @@ mod tests {
         assert_eq!(settings.app.refresh_interval, 60);
         assert_eq!(settings.app.max_tracked_mrs, 10);
         assert_eq!(settings.app.auto_refresh_interval_minutes, 5);
+        assert_eq!(settings.app.live_refresh_interval_secs, 0);
+        assert_eq!(settings.app.failed_pipeline, FailedPipelineJump::OpenLog);
+        assert_eq!(settings.app.notify, NotifyMode::Bell);
+        assert_eq!(settings.app.prefetch_logs, LogPrefetch::Failed);
         assert!(!settings.ui.relative_timestamps);
         assert_eq!(settings.ui.theme, "light");
+        assert_eq!(settings.ui.color, "16");
+        assert_eq!(settings.ui.mouse_scroll_lines, 5);
+        assert_eq!(settings.ui.scroll_step, 3);
+        assert_eq!(settings.ui.fast_scroll_step, 10);
+        assert_eq!(settings.ui.match_position, 0.25);
+        assert!(!settings.ui.search_wrap);
+        assert_eq!(settings.ui.search_debounce_ms, 0);
+        assert_eq!(settings.ui.filter_context, 2);
         assert_eq!(settings.editor.custom_editor, Some("nvim".to_string()));
     }
 
+    #[test]
+    fn test_key_overrides() {
+        let toml = r#"
+            [gitlab]
+            token = "test-token"
+
+            [keys.log]
+            "ctrl+e" = "scroll_down"
+            "z c" = "toggle_section"
+        "#;
+
+        let settings: Settings = toml::from_str(toml).unwrap();
+        let log_keys = &settings.keys["log"];
+        assert_eq!(log_keys["ctrl+e"], "scroll_down");
+        assert_eq!(log_keys["z c"], "toggle_section");
+    }
+
     #[test]
     fn test_validation_empty_token() {
         let settings = Settings {
             gitlab: GitLabConfig {
                 token: String::new(),
                 default_project_id: Some(1),
+                token_command: None,
                 instance_url: "https://gitlab.com".to_string(),
+                api_url: None,
+                group: None,
+                connect_timeout_secs: 10,
+                request_timeout_secs: 30,
             },
             app: AppConfig::default(),
             ui: UiConfig::default(),
             editor: EditorConfig::default(),
+            keys: BTreeMap::new(),
+            profiles: BTreeMap::new(),
         };
 
         assert!(settings.validate().is_err());
@@ -188,22 +453,101 @@ mod tests {
             gitlab: GitLabConfig {
                 token: "valid-token".to_string(),
                 default_project_id: Some(1),
+                token_command: None,
                 instance_url: "https://gitlab.com".to_string(),
+                api_url: None,
+                group: None,
+                connect_timeout_secs: 10,
+                request_timeout_secs: 30,
             },
             app: AppConfig::default(),
             ui: UiConfig::default(),
             editor: EditorConfig::default(),
+            keys: BTreeMap::new(),
+            profiles: BTreeMap::new(),
         };
 
         assert!(settings.validate().is_ok());
     }
 
+    #[test]
+    fn test_profiles() {
+        let toml = r#"
+            [gitlab]
+            token = "glpat-public"
+
+            [profiles.work]
+            token = "glpat-work"
+            instance_url = "https://gitlab.example.com/"
+            default_project_id = 7
+        "#;
+
+        let settings: Settings = toml::from_str(toml).unwrap();
+        let profiles = settings.gitlab_profiles();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].name, "default");
+        assert_eq!(profiles[0].host(), "gitlab.com");
+        assert_eq!(profiles[1].name, "work");
+        assert_eq!(profiles[1].token, "glpat-work");
+        assert_eq!(profiles[1].host(), "gitlab.example.com");
+        assert_eq!(profiles[1].default_project_id, Some(7));
+        assert_eq!(profiles[0].api_url, "https://gitlab.com/api/v4");
+        assert_eq!(profiles[1].api_url, "https://gitlab.example.com/api/v4");
+        assert!(settings.validate().is_ok());
+
+        let toml = r#"
+            [gitlab]
+            token = "glpat-public"
+
+            [profiles.work]
+            token = ""
+        "#;
+        let settings: Settings = toml::from_str(toml).unwrap();
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_api_url_behind_a_proxy_prefix() {
+        let toml = r#"
+            [gitlab]
+            token = "glpat-public"
+            instance_url = "https://host/gitlab"
+            api_url = "https://host/gitlab/api/v4/"
+        "#;
+        let settings: Settings = toml::from_str(toml).unwrap();
+        assert!(settings.validate().is_ok());
+        let profile = &settings.gitlab_profiles()[0];
+        assert_eq!(profile.api_url, "https://host/gitlab/api/v4");
+        assert_eq!(profile.host(), "host/gitlab");
+    }
+
+    #[test]
+    fn test_malformed_urls_are_rejected() {
+        let with_urls = |instance_url: &str, api_url: Option<&str>| {
+            let mut settings: Settings = toml::from_str("[gitlab]\ntoken = \"t\"").unwrap();
+            settings.gitlab.instance_url = instance_url.to_string();
+            settings.gitlab.api_url = api_url.map(str::to_string);
+            settings.validate().map_err(|e| e.to_string())
+        };
+        assert!(with_urls("https://gitlab.example.com", None).is_ok());
+        assert!(with_urls("gitlab.example.com", None).unwrap_err().contains("instance_url 'gitlab.example.com'"));
+        assert!(with_urls("ftp://gitlab.example.com", None).unwrap_err().contains("http://"));
+        assert!(with_urls("https://gitlab.example.com", Some("https://host/api/v4?x=1")).unwrap_err().contains("api_url"));
+    }
+
     #[test]
     fn test_app_config_defaults() {
         let config = AppConfig::default();
         assert_eq!(config.refresh_interval, 30);
         assert_eq!(config.max_tracked_mrs, 5);
         assert_eq!(config.auto_refresh_interval_minutes, 1);
+        assert_eq!(config.live_refresh_interval_secs, 5);
+        assert_eq!(config.failed_pipeline, FailedPipelineJump::Off);
+        assert_eq!(config.notify, NotifyMode::Both);
+        assert_eq!(config.prefetch_logs, LogPrefetch::Off);
+        assert!(config.restore_last_location);
+        assert_eq!(config.cache_max_age_days, 7);
+        assert_eq!(config.cache_max_size_mb, 100);
     }
 
     #[test]
@@ -211,6 +555,16 @@ mod tests {
         let config = UiConfig::default();
         assert!(config.relative_timestamps);
         assert_eq!(config.theme, "dark");
+        assert_eq!(config.color, "auto");
+        assert_eq!(config.mouse_scroll_lines, 3);
+        assert_eq!(config.scroll_step, 1);
+        assert_eq!(config.fast_scroll_step, 10);
+        assert_eq!(config.match_position, 0.5);
+        assert!(config.search_wrap);
+        assert_eq!(config.search_debounce_ms, 150);
+        assert_eq!(config.redact_patterns.len(), DEFAULT_REDACT_PATTERNS.len());
+        assert_eq!(config.prefix_patterns.len(), DEFAULT_PREFIX_PATTERNS.len());
+        assert_eq!(config.timezone, "utc");
     }
 
     #[test]
@@ -0,0 +1,73 @@
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::Subscriber;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// A log file past this size is moved aside on startup, keeping one previous session around
+const MAX_LOG_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Level written when `RUST_LOG` doesn't say otherwise: debug with `--verbose`, warnings and errors without
+fn default_level(verbose: bool) -> LevelFilter {
+    if verbose {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::WARN
+    }
+}
+
+/// Plain-text lines for the events `filter` lets through, written to `writer`
+fn subscriber<W>(filter: EnvFilter, writer: W) -> impl Subscriber + Send + Sync
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt().with_env_filter(filter).with_writer(writer).with_ansi(false).finish()
+}
+
+/// Where diagnostics are written: `peeplab.log` in the user's cache directory
+pub fn log_path() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("peeplab").join("peeplab.log"))
+}
+
+/// Send `tracing` events to the log file, never to the terminal the TUI draws on
+pub fn init(verbose: bool) -> std::io::Result<PathBuf> {
+    let path = log_path().ok_or_else(|| std::io::Error::other("no cache directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if std::fs::metadata(&path).is_ok_and(|metadata| metadata.len() > MAX_LOG_SIZE) {
+        std::fs::rename(&path, path.with_extension("log.old"))?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+    // Invalid `RUST_LOG` directives are skipped rather than keeping peeplab from starting
+    let filter = EnvFilter::builder().with_default_directive(default_level(verbose).into()).from_env_lossy();
+    tracing::subscriber::set_global_default(subscriber(filter, Mutex::new(file))).map_err(std::io::Error::other)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_written_by_directive() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let filter = EnvFilter::builder()
+            .with_default_directive(default_level(false).into())
+            .parse_lossy("warn,peeplab::gitlab=debug,hyper=off");
+        tracing::subscriber::with_default(subscriber(filter, Mutex::new(file.reopen().unwrap())), || {
+            tracing::debug!(target: "peeplab::gitlab::client", status = 403, "Request failed");
+            tracing::debug!(target: "peeplab::app", "not verbose");
+            tracing::warn!(target: "peeplab::app", "Shown by default");
+            tracing::error!(target: "hyper::proto", "turned off");
+        });
+
+        let written = std::fs::read_to_string(file.path()).unwrap();
+        assert!(written.contains("DEBUG peeplab::gitlab::client: Request failed status=403\n"), "{}", written);
+        assert!(written.contains(" WARN peeplab::app: Shown by default\n"), "{}", written);
+        assert!(!written.contains("not verbose") && !written.contains("turned off"), "{}", written);
+        assert!(!written.contains('\x1b'), "{}", written);
+    }
+}
@@ -1,9 +1,10 @@
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum PeeplabError {
     #[error("GitLab API error: {0}")]
-    GitLabApi(#[from] reqwest::Error),
+    GitLabApi(reqwest::Error),
 
     #[error("Configuration error: {0}")]
     Config(String),
@@ -17,6 +18,15 @@ pub enum PeeplabError {
     #[error("TOML parsing error: {0}")]
     TomlParse(#[from] toml::de::Error),
 
+    #[error("Clipboard unavailable: {0}")]
+    Clipboard(String),
+
+    #[error("Could not open browser: {0}")]
+    Browser(String),
+
+    #[error("Desktop notification failed: {0}")]
+    Notification(String),
+
     #[error("Editor launch failed: {0}")]
     EditorLaunch(String),
 
@@ -26,8 +36,44 @@ pub enum PeeplabError {
     #[error("Network error: {0}")]
     Network(String),
 
+    #[error("GitLab rate limit reached, retry in {}s", retry_after.as_secs())]
+    RateLimited { retry_after: Duration },
+
     #[error("Resource not found: {0}")]
     NotFound(String),
+
+    #[error("Log expired (job trace was erased)")]
+    LogExpired,
+
+    #[error("GitLab rejected the request: {0}")]
+    Rejected(String),
+
+    #[error("Artifacts can't be browsed: {0}")]
+    Artifacts(String),
+}
+
+impl From<reqwest::Error> for PeeplabError {
+    fn from(e: reqwest::Error) -> Self {
+        // Timeouts and unreachable hosts are network trouble, not API errors
+        if e.is_timeout() {
+            PeeplabError::Network("GitLab did not respond in time".to_string())
+        } else if e.is_connect() {
+            PeeplabError::Network(format!("Could not connect to GitLab: {}", e))
+        } else {
+            PeeplabError::GitLabApi(e)
+        }
+    }
+}
+
+impl PeeplabError {
+    /// GitLab couldn't be reached or failed on its side, as opposed to refusing the request
+    pub fn is_unreachable(&self) -> bool {
+        match self {
+            PeeplabError::Network(_) => true,
+            PeeplabError::GitLabApi(e) => e.status().is_none_or(|status| status.is_server_error()),
+            _ => false,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, PeeplabError>;
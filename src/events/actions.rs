@@ -1,81 +1,382 @@
-use crate::gitlab::{Job, MergeRequest, Note, Pipeline};
+use crate::app::NotifyMode;
+use crate::artifacts::ArtifactEntry;
+use crate::gitlab::{Job, JobStatus, MergeRequest, Note, Pipeline, PipelineStatus, TestSuite, TraceChunk, User};
+use crate::log_diff::DiffContent;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub enum Action {
     // User Input Actions
-    Quit,
+    Quit,      // Asks first if downloads or exports are running
+    ForceQuit, // Quit without asking
     NextMr,
     PrevMr,
     NextJob,
     PrevJob,
+    FoldSelectedStage,   // Hide the jobs of the selected job's stage
+    ToggleAllStagesFold, // Fold every stage, or unfold them all if any is folded
+    ToggleStageFilter,   // List only the selected job's stage, or every stage again
+    SelectFirstFailedJob, // Failed job of the earliest stage
+    StartJobFilter,      // Type a fuzzy filter on job names
+    StartJobSearch,      // Type words to find in job names, stages or statuses
+    UpdateJobFilter(String),
+    ApplyJobFilter,      // Stop typing, the filter stays until cleared
+    ClearJobFilter,
     NextPipeline,
     PrevPipeline,
+    TogglePipelineStatusFilter(PipelineStatus), // Show only this status, or everything again
+    ClearPipelineStatusFilter,
     SelectMr,
     OpenSelectedJobLog,
+    CompareSelectedJob, // Mark the selected job, or diff its log against the marked one
+    CloseLogDiff,
+    DiffScrollUp,
+    DiffScrollDown,
+    DiffPageUp,
+    DiffPageDown,
+    DiffTop,
+    DiffBottom,
+    NextDiffChange,
+    PrevDiffChange,
     Refresh,
     RemoveCurrentMr,
     AddMr(u64), // Add MR by IID
     ShowHelp,
     HideHelp,
+    ScrollHelpUp,
+    ScrollHelpDown,
+    CycleTheme,
+    ToggleStatusBar,
+    GrowLogPopup,   // Up to the full screen
+    ShrinkLogPopup, // Down to `MIN_LOG_POPUP_PERCENT`
+    OpenProfilePicker,
+    ProfilePickerNext,
+    ProfilePickerPrev,
+    SelectProfile,
+    CloseProfilePicker,
+    ShowHeadPipelines, // Pipelines of the commit checked out locally
+    HeadPipelinesNext,
+    HeadPipelinesPrev,
+    RefreshHeadPipelines,
+    ToggleHeadPipelinePolling, // Look again periodically until HEAD has a pipeline
+    OpenHeadPipeline,
+    CloseHeadPipelines,
+    ShowGroupPipelines, // Recent pipelines across the projects of the group
+    GroupPipelinesNext, // Moving past the last one lists more projects
+    GroupPipelinesPrev,
+    ToggleGroupStatusFilter(PipelineStatus),
+    ClearGroupStatusFilter,
+    CycleGroupSort,
+    RefreshGroupPipelines,
+    OpenGroupPipeline, // In its project's view, switching project if needed
+    CloseGroupPipelines,
+    OpenCommandPalette,
+    UpdatePaletteQuery(String),
+    PaletteSelectNext,
+    PaletteSelectPrev,
+    ExecutePaletteSelection,
+    CloseCommandPalette,
     ToggleCommentsView,
     NextNote,
     PrevNote,
-    CloseLogViewer,
-    ScrollLogUp,
+    CloseLogViewer, // Closes the active log tab
+    HideLogViewer,  // Back to the job list, keeping the log tabs open
+    ShowLogTabs,
+    NextLogTab,
+    PrevLogTab,
+    GoToLogTab(usize), // 1-based, as typed in a count prefix
+    ScrollLogUp,   // By `[ui] scroll_step` lines
     ScrollLogDown,
+    ScrollLogFastUp, // By `[ui] fast_scroll_step` lines
+    ScrollLogFastDown,
     ScrollLogPageUp,
     ScrollLogPageDown,
+    ScrollLogUpBy(usize),   // Mouse wheel
+    ScrollLogDownBy(usize), // Mouse wheel
+    SelectLogLine { row: usize, extend: bool }, // Click on a row of the log viewport
+    OpenLogUrl,                  // Link on the selected line, else the first one in view
+    OpenLogUrlAt { row: usize }, // Ctrl+click on a row of the log viewport
+    StartVisualSelection,  // Select lines from the keyboard, moving keys extend the selection
+    CancelVisualSelection,
+    JumpToScrollbarRow(usize), // Click or drag on the scrollbar, row within its track
+    JumpToMinimapRow(usize), // Row of the minimap clicked, its stretch of the log is centered
+    ScrollLogHalfPageUp,
+    ScrollLogHalfPageDown,
     ScrollLogHome,
     ScrollLogEnd,
+    ScrollLogLeft,
+    ScrollLogRight,
+    ToggleWrap,
+    ToggleLineNumbers,
+    ToggleMinimap,
+    ToggleFollowMode,
+    NextErrorLine,
+    PrevErrorLine,
+    ToggleBookmark, // On the top visible log line
+    NextBookmark,
+    PrevBookmark,
+    CopyVisibleLog,
+    CopyFullLog,
+    ExportLog { raw: bool }, // Raw trace or the cleaned text shown in the viewer
     ToggleTimestampMode,
+    ToggleRawView, // Show the log exactly as received, without prefix or timestamp processing
+    ToggleSection,
+    PendingKey(char), // First key of a two-key sequence
+    CancelPendingKey,
+    PushCountDigit(u32), // Digit of a vim-style count prefix, e.g. the "1" and "0" of "10j"
+    Repeat { count: usize, action: Box<Action> }, // Key pressed after a count prefix
     StartSearch,
     UpdateSearchQuery(String),
+    SearchHistoryPrev, // Recall an older query into the search bar
+    SearchHistoryNext, // Recall a newer query, or back to what was being typed
+    CycleSearchMode,
+    ToggleWholeWordSearch,
+    SearchInputSettled { generation: u64 }, // Typing paused, search for the query being typed
     ExecuteSearch,
+    ContinueSearch { generation: u64 }, // Next step of a search through a large log
     NextSearchResult,
     PrevSearchResult,
     CancelSearch,
+    StartFilter,
+    UpdateFilterQuery(String),
+    ToggleFilterInverse,
+    ApplyFilter,
+    CancelFilter,
     OpenMrInBrowser,
+    OpenJobInBrowser,      // Selected job, or the job whose log is open
+    OpenPipelineInBrowser, // Selected pipeline
+    RetrySelectedJob, // Asks for confirmation first
+    RetryJob { mr_index: usize, pipeline_id: u64, job_id: u64 },
+    CancelSelectedJob,      // Asks for confirmation first
+    CancelSelectedPipeline, // Asks for confirmation first
+    CancelJob { mr_index: usize, pipeline_id: u64, job_id: u64 },
+    CancelPipeline { mr_index: usize, pipeline_id: u64 },
+    RetrySelectedPipeline, // Its failed and canceled jobs, asks for confirmation first
+    RetryPipeline { mr_index: usize, pipeline_id: u64 },
+    PlaySelectedJob, // Opens the variables prompt, which doubles as the confirmation
+    PrefetchJobLogs, // Of the selected pipeline's failed jobs, or those `[app] prefetch_logs` picks
+    UpdatePlayInput(String),
+    SubmitPlayJob,
+    CancelPlayJob,
+    PlayJob { mr_index: usize, pipeline_id: u64, job_id: u64, variables: Vec<(String, String)> },
+    StartTriggerPipeline, // Opens the ref/variables prompt
+    UpdateTriggerInput(String),
+    SubmitTriggerPipeline,
+    CancelTriggerPipeline,
+    DownloadSelectedArtifacts,
+    BrowseArtifacts, // List the files of the selected job's artifacts
+    ArtifactBrowserNext,
+    ArtifactBrowserPrev,
+    OpenArtifactFile,
+    ScrollArtifactFile(isize), // Lines, negative scrolls up
+    CloseArtifactFile,         // Back to the file list
+    CloseArtifactBrowser,
+    ShowTestReport, // Results of the selected job's JUnit report
+    TestReportNext,
+    TestReportPrev,
+    ToggleTestCase, // Show or hide the selected failure's message
+    ToggleAllTests, // Every test, or only the failing ones
+    CloseTestReport,
+    ShowJobGraph, // What the selected pipeline's jobs need, as a tree
+    JobGraphNext,
+    JobGraphPrev,
+    OpenJobGraphLog, // Log of the job selected in the tree
+    CloseJobGraph,
+    CancelArtifactDownload,
+    ConfirmPending,
+    CancelConfirmation,
 
     // API Response Actions
     MergeRequestsLoaded(Vec<MergeRequest>),
     PipelinesLoaded {
         mr_index: usize,
+        page: u32,
+        status: Option<PipelineStatus>, // Status filter the page was fetched with
         pipelines: Vec<Pipeline>,
+        next_page: Option<u32>,
     },
     JobsLoaded {
         mr_index: usize,
         pipeline_id: u64,
         jobs: Vec<Job>,
     },
-    JobTraceLoaded {
+    PipelineLoaded { mr_index: usize, pipeline: Pipeline },
+    PipelineFetchFailed { mr_index: usize, pipeline_id: u64, error: String },
+    JobTraceChunkLoaded {
         job_id: u64,
         job_name: String,
+        offset: u64, // Byte offset of this chunk within the trace
+        chunk: TraceChunk,
+    },
+    JobTraceFailed {
+        job_id: u64,
+        error: String,
+    },
+    JobTraceExpired { job_id: u64 }, // Erased by GitLab's retention policy
+    JobLogPrefetched { pipeline_id: u64, job_id: u64, trace: String },
+    JobLogPrefetchFailed { pipeline_id: u64, job_id: u64, error: String },
+    RetryLogFetch,
+    LogDiffLoaded {
+        left_id: u64,
+        right_id: u64,
+        content: DiffContent,
+    },
+    LogDiffFailed {
+        left_id: u64,
+        right_id: u64,
+        error: String,
+    },
+    JobTraceRefreshed {
+        job_id: u64,
         trace: String,
+        status: JobStatus,
+    },
+    JobTraceStreamed {
+        job_id: u64,
+        offset: u64, // Where `chunk` starts in the trace
+        chunk: TraceChunk,
+        status: JobStatus,
+    },
+    JobTracePollFailed {
+        job_id: u64,
+        error: String,
     },
     NotesLoaded {
         mr_index: usize,
         notes: Vec<Note>,
     },
+    CurrentUserLoaded(User),
+    ProjectResolved { profile: usize, project_id: u64, project_path: Option<String> }, // After switching profiles
+    ProjectPathLoaded(String),
+
+    JobRetried { mr_index: usize, pipeline_id: u64, job: Job }, // The newly created job
+    JobCanceled { mr_index: usize, pipeline_id: u64, job: Job }, // Job as returned by the cancel request
+    JobPlayed { mr_index: usize, pipeline_id: u64, job: Job },
+    PipelineCanceled { mr_index: usize, pipeline: Pipeline },
+    PipelineRetried { mr_index: usize, pipeline: Pipeline },
+    PipelineRetryFailed { pipeline_id: u64, error: String },
+    JobActionFailed(String),
+    PipelineTriggered(Pipeline),
+    PipelineTriggerFailed(String),
+    HeadPipelinesLoaded { sha: String, pipelines: Vec<Pipeline> },
+    HeadPipelinesFailed(String),
+    GroupProjectsLoaded { group: String, project_count: usize, next_page: Option<u32> },
+    GroupPipelinesLoaded { group: String, project_id: u64, project_path: String, pipelines: Vec<Pipeline> },
+    GroupProjectFailed { group: String, project_path: String, error: String },
+    GroupPipelinesFailed { group: String, error: String }, // The group's projects couldn't be listed
+    ArtifactDownloadProgress { job_id: u64, received: u64, total: Option<u64> },
+    ArtifactsDownloaded { job_id: u64, path: PathBuf },
+    ArtifactDownloadCanceled { job_id: u64 },
+    ArtifactDownloadFailed { job_id: u64, error: String },
+    TestReportLoaded { job_id: u64, suite: TestSuite },
+    TestReportFailed { job_id: u64, error: String },
+    JobNeedsLoaded { pipeline_id: u64, needs: HashMap<String, Vec<String>> }, // Job name -> names it needs
+    JobNeedsFailed { pipeline_id: u64, error: String },
+    ArtifactListLoaded { job_id: u64, entries: Vec<ArtifactEntry> },
+    ArtifactListFailed { job_id: u64, error: String },
+    ArtifactFileLoaded { job_id: u64, path: String, text: String },
+    ArtifactFileFailed { job_id: u64, path: String, error: String },
+
+    CopiedToClipboard(usize), // Number of lines copied
+    ClipboardUnavailable(String),
+    LogExported(PathBuf),
+    LogExportFailed(String),
+    UrlOpenFailed(String),
+    ToggleWatchJob,      // Notify when the selected job finishes
+    ToggleWatchPipeline, // Notify as each job of the selected pipeline finishes
+    CyclePipelineSort,   // Newest, oldest, by status, slowest, fastest
+    CycleJobSort,        // Stage order, by name either way, slowest, fastest
 
     // Error Actions
     ApiError(String),
+    RateLimited { retry_after: std::time::Duration }, // Polling pauses until GitLab accepts requests again
+    ServedFromCache { saved_at: chrono::DateTime<chrono::Utc> }, // GitLab is unreachable, cached data follows
+    BackOnline, // GitLab answered again after data was served from the cache
 
     // Tick for auto-refresh
     Tick,
+    LivePollDue, // Time to re-poll pipelines and jobs that are still running
 
     // No-op
     None,
 }
 
+impl Action {
+    /// Whether a count prefix repeats this action; every other action runs once and drops the count
+    pub fn honors_count(&self) -> bool {
+        matches!(
+            self,
+            Action::ScrollLogUp
+                | Action::ScrollLogDown
+                | Action::ScrollLogPageUp
+                | Action::ScrollLogPageDown
+                | Action::NextSearchResult
+                | Action::PrevSearchResult
+                | Action::NextErrorLine
+                | Action::PrevErrorLine
+                | Action::NextBookmark
+                | Action::PrevBookmark
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Effect {
     FetchMergeRequests { project_id: u64 },
     FetchMergeRequestsByBranch { project_id: u64, source_branch: String },
-    FetchPipelines { mr_index: usize, project_id: u64, mr_iid: u64 },
+    FetchPipelines {
+        mr_index: usize,
+        project_id: u64,
+        mr_iid: u64,
+        page: u32,
+        status: Option<PipelineStatus>, // Only pipelines with this status
+    },
     FetchJobs { mr_index: usize, project_id: u64, pipeline_id: u64 },
-    FetchJobTrace { project_id: u64, job_id: u64, job_name: String },
+    FetchPipeline { mr_index: usize, project_id: u64, pipeline_id: u64 }, // For who started it, or to restore it
+    FetchJobTrace { project_id: u64, job_id: u64, job_name: String, offset: u64 },
+    PrefetchJobLogs { project_id: u64, pipeline_id: u64, job_ids: Vec<u64>, cancel: Arc<AtomicBool> }, // A few at a time
+    PollJobTrace { project_id: u64, job_id: u64, offset: u64, delay: Duration }, // Trace bytes from `offset` while running, then the full trace
+    FetchLogDiff { project_id: u64, left_id: u64, right_id: u64 }, // Both full traces, then their diff
     FetchNotes { mr_index: usize, project_id: u64, mr_iid: u64 },
+    FetchSessionInfo { project_id: u64, with_project: bool }, // Token's user, and the project path unless known
+    ResolveProject { profile: usize }, // Find the git remote's project on a profile's instance
     OpenInEditor(String),
     RefreshAll { project_id: u64, source_branch: Option<String> },
     OpenUrl(String),
+    Notify { mode: NotifyMode, summary: String, body: String, urgent: bool }, // A watched job finished
+    CopyToClipboard { text: String, line_count: usize },
+    ExportLog { path: PathBuf, content: String },
+    SaveJobLog { project_id: u64, job_id: u64, log: Arc<String> }, // A complete trace, to the offline cache
+    RetryJob { mr_index: usize, project_id: u64, pipeline_id: u64, job_id: u64 },
+    CancelJob { mr_index: usize, project_id: u64, pipeline_id: u64, job_id: u64 },
+    CancelPipeline { mr_index: usize, project_id: u64, pipeline_id: u64 },
+    RetryPipeline { mr_index: usize, project_id: u64, pipeline_id: u64 },
+    PlayJob { mr_index: usize, project_id: u64, pipeline_id: u64, job_id: u64, variables: Vec<(String, String)> },
+    FetchHeadPipelines { project_id: u64, delay: Duration }, // Read HEAD's SHA, then its pipelines
+    FetchGroupPipelines { group: String, page: u32 }, // A page of the group's projects, then their pipelines
+    TriggerPipeline { project_id: u64, ref_name: String, variables: Vec<(String, String)> },
+    FetchTestReport { project_id: u64, job_id: u64, job_name: String },
+    FetchJobNeeds { project_path: String, pipeline_id: u64, pipeline_iid: u64 },
+    FetchArtifactList { project_id: u64, job_id: u64 },
+    FetchArtifactFile { project_id: u64, job_id: u64, path: String }, // Refused if it isn't text
+    DownloadArtifacts { project_id: u64, job_id: u64, path: PathBuf, cancel: Arc<AtomicBool> },
+    ScheduleLivePoll { delay: Duration },
+    ContinueSearch { generation: u64 }, // Yield to the event loop, then search the next part of the log
+    DebounceSearch { generation: u64, delay: Duration }, // Search for the typed query unless it changes before the delay
+    Batch(Vec<Effect>), // Several independent effects from one action
+}
+
+impl Effect {
+    /// Combine effects, avoiding a batch for zero or one of them
+    pub fn batch(mut effects: Vec<Effect>) -> Option<Effect> {
+        match effects.len() {
+            0 => None,
+            1 => effects.pop(),
+            _ => Some(Effect::Batch(effects)),
+        }
+    }
 }
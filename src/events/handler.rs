@@ -1,14 +1,20 @@
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use std::time::Duration;
 use tokio::sync::mpsc;
 
 #[derive(Debug)]
 pub enum AppEvent {
     Input(KeyEvent),
+    Mouse(MouseEvent),
     Tick,
     Resize,
 }
 
+/// Threads reading keys and sending ticks: peeplab can't be used or quit without them, so a
+/// panic on one of them ends it like a panic on the main thread
+pub const INPUT_THREAD: &str = "peeplab-input";
+pub const TICK_THREAD: &str = "peeplab-tick";
+
 pub struct EventHandler {
     rx: mpsc::UnboundedReceiver<AppEvent>,
     _tx: mpsc::UnboundedSender<AppEvent>, // Keep alive for senders
@@ -18,40 +24,35 @@ impl EventHandler {
     pub fn new(tick_rate: Duration) -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
 
-        // Spawn input handler
+        // Spawn input handler, on its own thread as reading blocks
         let input_tx = tx.clone();
-        tokio::spawn(async move {
+        let input = std::thread::Builder::new().name(INPUT_THREAD.to_string()).spawn(move || {
             loop {
                 // Poll with very short timeout for responsive input
                 if let Ok(true) = event::poll(Duration::from_millis(16)) {
-                    match event::read() {
-                        Ok(Event::Key(key)) => {
-                            if input_tx.send(AppEvent::Input(key)).is_err() {
-                                break;
-                            }
-                        }
-                        Ok(Event::Resize(_, _)) => {
-                            if input_tx.send(AppEvent::Resize).is_err() {
-                                break;
-                            }
-                        }
-                        _ => {}
+                    let event = match event::read() {
+                        Ok(Event::Key(key)) => AppEvent::Input(key),
+                        Ok(Event::Mouse(mouse)) => AppEvent::Mouse(mouse),
+                        Ok(Event::Resize(_, _)) => AppEvent::Resize,
+                        _ => continue,
+                    };
+                    if input_tx.send(event).is_err() {
+                        break;
                     }
                 }
             }
         });
+        input.expect("failed to spawn the input thread");
 
         // Spawn tick handler
         let tick_tx = tx.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tick_rate);
-            loop {
-                interval.tick().await;
-                if tick_tx.send(AppEvent::Tick).is_err() {
-                    break;
-                }
+        let tick = std::thread::Builder::new().name(TICK_THREAD.to_string()).spawn(move || loop {
+            std::thread::sleep(tick_rate);
+            if tick_tx.send(AppEvent::Tick).is_err() {
+                break;
             }
         });
+        tick.expect("failed to spawn the tick thread");
 
         Self { rx, _tx: tx }
     }
@@ -63,52 +64,183 @@ impl EventHandler {
 
 use crate::app::{App, AppMode};
 use crate::events::actions::Action;
+use crate::events::keymap::KeyContext;
+use crate::gitlab::PipelineStatus;
 
 pub fn map_event_to_action(event: AppEvent, app: &App) -> Action {
     match event {
-        AppEvent::Input(key) => match app.mode {
-            AppMode::Normal => match key.code {
-                KeyCode::Char('q') => Action::Quit,
-                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    Action::Quit
+        // A confirmation prompt takes every key until answered
+        AppEvent::Input(key) if app.pending_confirmation.is_some() => match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Action::ConfirmPending,
+            // A second q confirms quitting
+            KeyCode::Char('q')
+                if app.pending_confirmation.as_ref().is_some_and(|c| matches!(c.action, Action::ForceQuit)) =>
+            {
+                Action::ConfirmPending
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Char('q') | KeyCode::Esc => {
+                Action::CancelConfirmation
+            }
+            _ => Action::None,
+        },
+        // The run-pipeline prompt is a text field
+        AppEvent::Input(key) if app.trigger_input.is_some() => {
+            let input = app.trigger_input.as_deref().unwrap_or_default();
+            match key.code {
+                KeyCode::Esc => Action::CancelTriggerPipeline,
+                KeyCode::Enter => Action::SubmitTriggerPipeline,
+                KeyCode::Char(c) => Action::UpdateTriggerInput(format!("{}{}", input, c)),
+                KeyCode::Backspace => {
+                    let mut input = input.to_string();
+                    input.pop();
+                    Action::UpdateTriggerInput(input)
                 }
-                KeyCode::Char('?') => Action::ShowHelp,
-                KeyCode::Char('c') => Action::ToggleCommentsView,
-                KeyCode::Left | KeyCode::Char('h') => Action::PrevMr,
-                KeyCode::Right | KeyCode::Char('l') => Action::NextMr,
-                KeyCode::Up | KeyCode::Char('k') => Action::PrevJob,
-                KeyCode::Down | KeyCode::Char('j') => Action::NextJob,
-                KeyCode::Char('[') => Action::PrevPipeline,
-                KeyCode::Char(']') => Action::NextPipeline,
-                KeyCode::Enter => Action::OpenSelectedJobLog,
-                KeyCode::Char('r') => Action::Refresh,
-                KeyCode::Char('d') => Action::RemoveCurrentMr,
-                KeyCode::Char('o') => Action::OpenMrInBrowser,
                 _ => Action::None,
-            },
-            AppMode::ViewingComments => match key.code {
-                KeyCode::Char('q') => Action::Quit,
-                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    Action::Quit
+            }
+        }
+        // So is the manual job prompt, where variables can be passed
+        AppEvent::Input(key) if app.play_prompt.is_some() => {
+            let input = app.play_prompt.as_ref().map(|prompt| prompt.input.as_str()).unwrap_or_default();
+            match key.code {
+                KeyCode::Esc => Action::CancelPlayJob,
+                KeyCode::Enter => Action::SubmitPlayJob,
+                KeyCode::Char(c) => Action::UpdatePlayInput(format!("{}{}", input, c)),
+                KeyCode::Backspace => {
+                    let mut input = input.to_string();
+                    input.pop();
+                    Action::UpdatePlayInput(input)
                 }
-                KeyCode::Char('?') => Action::ShowHelp,
-                KeyCode::Char('c') => Action::ToggleCommentsView,
-                KeyCode::Left | KeyCode::Char('h') => Action::PrevMr,
-                KeyCode::Right | KeyCode::Char('l') => Action::NextMr,
-                KeyCode::Up | KeyCode::Char('k') => Action::PrevNote,
-                KeyCode::Down | KeyCode::Char('j') => Action::NextNote,
-                KeyCode::Char('[') => Action::PrevPipeline,
-                KeyCode::Char(']') => Action::NextPipeline,
-                KeyCode::Char('r') => Action::Refresh,
-                KeyCode::Char('d') => Action::RemoveCurrentMr,
                 _ => Action::None,
-            },
+            }
+        }
+        // The profile picker is a plain list
+        AppEvent::Input(key) if app.profile_picker.is_some() => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::CloseProfilePicker,
+            KeyCode::Enter => Action::SelectProfile,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => Action::ProfilePickerNext,
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => Action::ProfilePickerPrev,
+            _ => Action::None,
+        },
+        // The group pipelines popup also filters and sorts
+        AppEvent::Input(key) if app.group_pipelines.is_some() => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::CloseGroupPipelines,
+            KeyCode::Enter => Action::OpenGroupPipeline,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => Action::GroupPipelinesNext,
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => Action::GroupPipelinesPrev,
+            KeyCode::Char('1') => Action::ToggleGroupStatusFilter(PipelineStatus::Success),
+            KeyCode::Char('2') => Action::ToggleGroupStatusFilter(PipelineStatus::Failed),
+            KeyCode::Char('3') => Action::ToggleGroupStatusFilter(PipelineStatus::Running),
+            KeyCode::Char('4') => Action::ToggleGroupStatusFilter(PipelineStatus::Canceled),
+            KeyCode::Char('0') => Action::ClearGroupStatusFilter,
+            KeyCode::Char('s') => Action::CycleGroupSort,
+            KeyCode::Char('r') => Action::RefreshGroupPipelines,
+            _ => Action::None,
+        },
+        // So is the HEAD pipelines popup
+        AppEvent::Input(key) if app.head_pipelines.is_some() => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::CloseHeadPipelines,
+            KeyCode::Enter => Action::OpenHeadPipeline,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => Action::HeadPipelinesNext,
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => Action::HeadPipelinesPrev,
+            KeyCode::Char('r') => Action::RefreshHeadPipelines,
+            KeyCode::Char('p') => Action::ToggleHeadPipelinePolling,
+            _ => Action::None,
+        },
+        AppEvent::Input(key) if app.test_report.is_some() => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::CloseTestReport,
+            KeyCode::Enter | KeyCode::Char(' ') => Action::ToggleTestCase,
+            KeyCode::Char('a') => Action::ToggleAllTests,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => Action::TestReportNext,
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => Action::TestReportPrev,
+            _ => Action::None,
+        },
+        AppEvent::Input(key) if app.job_graph.is_some() => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::CloseJobGraph,
+            KeyCode::Enter => Action::OpenJobGraphLog,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => Action::JobGraphNext,
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => Action::JobGraphPrev,
+            _ => Action::None,
+        },
+        // Artifact files are read in a pager, listed in a plain list before that
+        AppEvent::Input(key) if app.artifact_browser.as_ref().is_some_and(|b| b.file.is_some()) => {
+            let page = app.artifact_browser.as_ref().and_then(|b| b.file.as_ref()).map_or(1, |f| f.height.max(1));
+            let page = page as isize;
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace => Action::CloseArtifactFile,
+                KeyCode::Down | KeyCode::Char('j') => Action::ScrollArtifactFile(1),
+                KeyCode::Up | KeyCode::Char('k') => Action::ScrollArtifactFile(-1),
+                KeyCode::PageDown | KeyCode::Char(' ') => Action::ScrollArtifactFile(page),
+                KeyCode::PageUp => Action::ScrollArtifactFile(-page),
+                KeyCode::Home | KeyCode::Char('g') => Action::ScrollArtifactFile(isize::MIN),
+                KeyCode::End | KeyCode::Char('G') => Action::ScrollArtifactFile(isize::MAX),
+                _ => Action::None,
+            }
+        }
+        AppEvent::Input(key) if app.artifact_browser.is_some() => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::CloseArtifactBrowser,
+            KeyCode::Enter => Action::OpenArtifactFile,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => Action::ArtifactBrowserNext,
+            KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => Action::ArtifactBrowserPrev,
+            _ => Action::None,
+        },
+        // The command palette filters as you type
+        AppEvent::Input(key) if app.command_palette.is_some() => {
+            let query = app.command_palette.as_ref().map(|p| p.query.as_str()).unwrap_or_default();
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Esc => Action::CloseCommandPalette,
+                KeyCode::Enter => Action::ExecutePaletteSelection,
+                KeyCode::Down | KeyCode::Tab => Action::PaletteSelectNext,
+                KeyCode::Up | KeyCode::BackTab => Action::PaletteSelectPrev,
+                KeyCode::Char('n') if ctrl => Action::PaletteSelectNext,
+                KeyCode::Char('p') if ctrl => Action::PaletteSelectPrev,
+                KeyCode::Char(c) if !ctrl => Action::UpdatePaletteQuery(format!("{}{}", query, c)),
+                KeyCode::Backspace => {
+                    let mut query = query.to_string();
+                    query.pop();
+                    Action::UpdatePaletteQuery(query)
+                }
+                _ => Action::None,
+            }
+        }
+        // The job name filter takes text while it is being typed
+        AppEvent::Input(key) if app.mode == AppMode::Normal && app.job_filter.as_ref().is_some_and(|f| f.editing) => {
+            let query = app.job_filter.as_ref().map(|f| f.query.as_str()).unwrap_or_default();
+            match key.code {
+                KeyCode::Esc => Action::ClearJobFilter,
+                KeyCode::Enter => Action::ApplyJobFilter,
+                KeyCode::Down => Action::NextJob,
+                KeyCode::Up => Action::PrevJob,
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    Action::UpdateJobFilter(format!("{}{}", query, c))
+                }
+                KeyCode::Backspace => {
+                    let mut query = query.to_string();
+                    query.pop();
+                    Action::UpdateJobFilter(query)
+                }
+                _ => Action::None,
+            }
+        }
+        AppEvent::Input(key) => match app.mode {
+            // Esc drops an applied job filter before doing anything else
+            AppMode::Normal if key.code == KeyCode::Esc && app.job_filter.is_some() => Action::ClearJobFilter,
+            AppMode::Normal => app.keymap.resolve(KeyContext::Jobs, None, &key),
+            AppMode::ViewingComments => app.keymap.resolve(KeyContext::Comments, None, &key),
             AppMode::ViewingLog => {
                 // Handle search input mode
                 if app.is_searching {
                     match key.code {
                         KeyCode::Esc => Action::CancelSearch,
                         KeyCode::Enter => Action::ExecuteSearch,
+                        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            Action::CycleSearchMode
+                        }
+                        KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            Action::ToggleWholeWordSearch
+                        }
+                        KeyCode::Up => Action::SearchHistoryPrev,
+                        KeyCode::Down => Action::SearchHistoryNext,
                         KeyCode::Char(c) => {
                             let mut query = app.search_query.clone();
                             query.push(c);
@@ -121,25 +253,31 @@ pub fn map_event_to_action(event: AppEvent, app: &App) -> Action {
                         }
                         _ => Action::None,
                     }
-                } else {
-                    // Normal log viewing mode
+                } else if app.is_filtering {
                     match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => Action::CloseLogViewer,
-                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            Action::Quit
+                        KeyCode::Esc => Action::CancelFilter,
+                        KeyCode::Enter => Action::ApplyFilter,
+                        KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            Action::ToggleFilterInverse
+                        }
+                        KeyCode::Char(c) => {
+                            let mut query = app.filter_query.clone();
+                            query.push(c);
+                            Action::UpdateFilterQuery(query)
+                        }
+                        KeyCode::Backspace => {
+                            let mut query = app.filter_query.clone();
+                            query.pop();
+                            Action::UpdateFilterQuery(query)
                         }
-                        KeyCode::Up | KeyCode::Char('k') => Action::ScrollLogUp,
-                        KeyCode::Down | KeyCode::Char('j') => Action::ScrollLogDown,
-                        KeyCode::PageUp => Action::ScrollLogPageUp,
-                        KeyCode::PageDown => Action::ScrollLogPageDown,
-                        KeyCode::Home => Action::ScrollLogHome,
-                        KeyCode::End => Action::ScrollLogEnd,
-                        KeyCode::Char('t') => Action::ToggleTimestampMode,
-                        KeyCode::Char('/') => Action::StartSearch,
-                        KeyCode::Char('n') => Action::NextSearchResult,
-                        KeyCode::Char('N') => Action::PrevSearchResult,
                         _ => Action::None,
                     }
+                } else if app.log_visual_mode && key.code == KeyCode::Esc {
+                    Action::CancelVisualSelection
+                } else {
+                    // Normal log viewing mode, possibly in the middle of a two-key sequence
+                    let action = app.keymap.resolve(KeyContext::Log, app.pending_key, &key);
+                    with_count_prefix(action, &key, app)
                 }
             },
             AppMode::SelectingMr => match key.code {
@@ -147,12 +285,88 @@ pub fn map_event_to_action(event: AppEvent, app: &App) -> Action {
                 KeyCode::Char('q') => Action::Quit,
                 _ => Action::None,
             },
-            AppMode::ShowingHelp => match key.code {
-                KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => Action::HideHelp,
-                _ => Action::None,
-            },
+            AppMode::ShowingHelp => app.keymap.resolve(KeyContext::Help, None, &key),
+            AppMode::ComparingLogs => app.keymap.resolve(KeyContext::Diff, None, &key),
         },
+        AppEvent::Mouse(mouse) => map_mouse_to_action(mouse, app),
         AppEvent::Tick => Action::Tick,
+        // The loop lays the viewer out again for the new size before the next draw
         AppEvent::Resize => Action::None,
     }
 }
+
+/// Turn unbound digits into a count prefix and hand a pending count to the next key's action
+fn with_count_prefix(action: Action, key: &KeyEvent, app: &App) -> Action {
+    let count = app.active_count();
+    if let (Action::None, KeyCode::Char(c)) = (&action, key.code) {
+        let plain = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        // A leading 0 is not a count, like in vim
+        if let Some(digit) = c.to_digit(10).filter(|&d| plain && (d != 0 || count.is_some())) {
+            return Action::PushCountDigit(digit);
+        }
+    }
+
+    match count {
+        // Esc only drops the count instead of also closing the viewer
+        Some(count) if key.code == KeyCode::Esc => Action::Repeat { count, action: Box::new(Action::None) },
+        Some(count) => Action::Repeat { count, action: Box::new(action) },
+        None => action,
+    }
+}
+
+/// Mouse input only drives the log viewer, and only inside its log area
+fn map_mouse_to_action(mouse: MouseEvent, app: &App) -> Action {
+    if app.mode != AppMode::ViewingLog
+        || app.is_searching
+        || app.is_filtering
+        || app.pending_confirmation.is_some()
+        || app.command_palette.is_some()
+    {
+        return Action::None;
+    }
+    let Some(area) = app.log_content_area else {
+        return Action::None;
+    };
+    // The right border next to the log lines holds the scrollbar, with the minimap just before it
+    let minimap = app.minimap_visible(area.height as usize);
+    let scrollbar_column = area.x + area.width + u16::from(minimap);
+    let on_rows = mouse.row >= area.y && mouse.row < area.y + area.height;
+    let on_track = on_rows && (mouse.column == scrollbar_column || (minimap && mouse.column == area.x + area.width));
+    if on_track {
+        let row = (mouse.row - area.y) as usize;
+        return match mouse.kind {
+            MouseEventKind::Down(event::MouseButton::Left) | MouseEventKind::Drag(event::MouseButton::Left) => {
+                if mouse.column == scrollbar_column {
+                    Action::JumpToScrollbarRow(row)
+                } else {
+                    Action::JumpToMinimapRow(row)
+                }
+            }
+            _ => Action::None,
+        };
+    }
+    let inside = mouse.column >= area.x
+        && mouse.column < area.x + area.width
+        && mouse.row >= area.y
+        && mouse.row < area.y + area.height;
+    if !inside {
+        return Action::None;
+    }
+
+    match mouse.kind {
+        MouseEventKind::ScrollUp => Action::ScrollLogUpBy(app.mouse_scroll_lines),
+        MouseEventKind::ScrollDown => Action::ScrollLogDownBy(app.mouse_scroll_lines),
+        MouseEventKind::Down(event::MouseButton::Left) if mouse.modifiers.contains(KeyModifiers::CONTROL) => {
+            Action::OpenLogUrlAt { row: (mouse.row - area.y) as usize }
+        }
+        MouseEventKind::Down(event::MouseButton::Left) => Action::SelectLogLine {
+            row: (mouse.row - area.y) as usize,
+            extend: mouse.modifiers.contains(KeyModifiers::SHIFT),
+        },
+        MouseEventKind::Drag(event::MouseButton::Left) => Action::SelectLogLine {
+            row: (mouse.row - area.y) as usize,
+            extend: true,
+        },
+        _ => Action::None,
+    }
+}
@@ -0,0 +1,759 @@
+use crate::error::{PeeplabError, Result};
+use crate::events::actions::Action;
+use crate::gitlab::PipelineStatus;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Screen a set of key bindings applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    Jobs,     // MR tabs with the job list
+    Comments, // MR tabs with the comment list
+    Log,      // Log viewer
+    Help,     // Help popup
+    Diff,     // Side-by-side comparison of two job logs
+}
+
+impl KeyContext {
+    pub const ALL: [KeyContext; 5] = [
+        KeyContext::Jobs,
+        KeyContext::Comments,
+        KeyContext::Log,
+        KeyContext::Help,
+        KeyContext::Diff,
+    ];
+
+    /// Section name under `[keys]` in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyContext::Jobs => "jobs",
+            KeyContext::Comments => "comments",
+            KeyContext::Log => "log",
+            KeyContext::Help => "help",
+            KeyContext::Diff => "diff",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|context| context.name() == name)
+    }
+}
+
+/// A key with its modifiers, written `ctrl+f`, `G`, `pagedown`, ...
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(c) => {
+                // The case of a character already tells whether shift was held
+                modifiers.remove(KeyModifiers::SHIFT);
+                if modifiers.contains(KeyModifiers::CONTROL) {
+                    KeyCode::Char(c.to_ascii_lowercase())
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            other => other,
+        };
+        Self { code, modifiers }
+    }
+
+    pub fn from_event(key: &KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        // A lone "+" is the plus key, not a separator
+        let (modifier_part, key_part) = match text.strip_suffix("++") {
+            Some(prefix) => (format!("{}+", prefix), "+"),
+            None => match text.rsplit_once('+') {
+                Some((modifiers, key)) if !key.is_empty() => (format!("{}+", modifiers), key),
+                _ => (String::new(), text),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in modifier_part.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let mut chars = key_part.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key_part.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                name => {
+                    let number: u8 = name.strip_prefix('f')?.parse().ok()?;
+                    if !(1..=12).contains(&number) {
+                        return None;
+                    }
+                    KeyCode::F(number)
+                }
+            },
+        };
+        Some(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "BackTab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Insert => write!(f, "Insert"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// An action that can be bound to keys by name
+pub struct BindableAction {
+    pub name: &'static str,
+    pub description: &'static str,
+    build: fn() -> Action,
+}
+
+impl BindableAction {
+    pub fn action(&self) -> Action {
+        (self.build)()
+    }
+}
+
+macro_rules! bindable {
+    ($name:literal, $description:literal, $action:expr) => {
+        BindableAction { name: $name, description: $description, build: || $action }
+    };
+}
+
+/// Every action a key can be bound to, in the order they are listed in help
+pub const BINDABLE_ACTIONS: &[BindableAction] = &[
+    bindable!("quit", "Quit", Action::Quit),
+    bindable!("show_help", "Show help", Action::ShowHelp),
+    bindable!("hide_help", "Close help", Action::HideHelp),
+    bindable!("command_palette", "Search and run any action", Action::OpenCommandPalette),
+    bindable!("cycle_theme", "Switch to the next color theme", Action::CycleTheme),
+    bindable!("toggle_status_bar", "Show or hide the status bar", Action::ToggleStatusBar),
+    bindable!("grow_log_viewer", "Enlarge the log viewer, or one more line of context around filter matches", Action::GrowLogPopup),
+    bindable!("shrink_log_viewer", "Shrink the log viewer, or one less line of context around filter matches", Action::ShrinkLogPopup),
+    bindable!("switch_profile", "Switch to another GitLab profile", Action::OpenProfilePicker),
+    bindable!("head_pipelines", "Pipelines of the local HEAD commit", Action::ShowHeadPipelines),
+    bindable!("group_pipelines", "Recent pipelines across the group's projects", Action::ShowGroupPipelines),
+    bindable!("scroll_help_up", "Scroll help up", Action::ScrollHelpUp),
+    bindable!("scroll_help_down", "Scroll help down", Action::ScrollHelpDown),
+    bindable!("prev_mr", "Previous MR", Action::PrevMr),
+    bindable!("next_mr", "Next MR", Action::NextMr),
+    bindable!("prev_job", "Previous job", Action::PrevJob),
+    bindable!("next_job", "Next job", Action::NextJob),
+    bindable!("prev_note", "Previous comment", Action::PrevNote),
+    bindable!("next_note", "Next comment", Action::NextNote),
+    bindable!("prev_pipeline", "Previous pipeline", Action::PrevPipeline),
+    bindable!("next_pipeline", "Next pipeline", Action::NextPipeline),
+    bindable!("fold_stage", "Fold the selected job's stage", Action::FoldSelectedStage),
+    bindable!("toggle_all_stages", "Fold/unfold all stages", Action::ToggleAllStagesFold),
+    bindable!("filter_stage", "Show only the selected job's stage / all stages", Action::ToggleStageFilter),
+    bindable!("filter_success_pipelines", "Show only successful pipelines", Action::TogglePipelineStatusFilter(PipelineStatus::Success)),
+    bindable!("filter_failed_pipelines", "Show only failed pipelines", Action::TogglePipelineStatusFilter(PipelineStatus::Failed)),
+    bindable!("filter_running_pipelines", "Show only running pipelines", Action::TogglePipelineStatusFilter(PipelineStatus::Running)),
+    bindable!("filter_canceled_pipelines", "Show only canceled pipelines", Action::TogglePipelineStatusFilter(PipelineStatus::Canceled)),
+    bindable!("clear_pipeline_filter", "Show pipelines of any status", Action::ClearPipelineStatusFilter),
+    bindable!("select_failed_job", "Select the first failed job", Action::SelectFirstFailedJob),
+    bindable!("filter_jobs", "Fuzzy-filter jobs by name", Action::StartJobFilter),
+    bindable!("search_jobs", "Filter jobs by words in their name, stage or status", Action::StartJobSearch),
+    bindable!("toggle_comments", "Toggle jobs/comments view", Action::ToggleCommentsView),
+    bindable!("open_job_log", "Open job log", Action::OpenSelectedJobLog),
+    bindable!("compare_job", "Mark job to compare / compare with the marked job", Action::CompareSelectedJob),
+    bindable!("watch_job", "Notify when the selected job finishes", Action::ToggleWatchJob),
+    bindable!("watch_pipeline", "Notify as the selected pipeline's jobs finish", Action::ToggleWatchPipeline),
+    bindable!("sort_pipelines", "Cycle the pipeline order", Action::CyclePipelineSort),
+    bindable!("sort_jobs", "Cycle the job order", Action::CycleJobSort),
+    bindable!("refresh", "Refresh all data", Action::Refresh),
+    bindable!("remove_mr", "Stop tracking the current MR", Action::RemoveCurrentMr),
+    bindable!("open_mr_in_browser", "Open MR in browser", Action::OpenMrInBrowser),
+    bindable!("open_job_in_browser", "Open job in browser", Action::OpenJobInBrowser),
+    bindable!("open_pipeline_in_browser", "Open pipeline in browser", Action::OpenPipelineInBrowser),
+    bindable!("retry_job", "Retry selected job", Action::RetrySelectedJob),
+    bindable!("cancel_job", "Cancel selected job", Action::CancelSelectedJob),
+    bindable!("cancel_pipeline", "Cancel selected pipeline", Action::CancelSelectedPipeline),
+    bindable!("retry_pipeline", "Retry the selected pipeline's failed and canceled jobs", Action::RetrySelectedPipeline),
+    bindable!("play_job", "Play selected manual job", Action::PlaySelectedJob),
+    bindable!("prefetch_logs", "Prefetch the logs of the pipeline's failed jobs", Action::PrefetchJobLogs),
+    bindable!("trigger_pipeline", "Run a new pipeline", Action::StartTriggerPipeline),
+    bindable!("download_artifacts", "Download selected job's artifacts", Action::DownloadSelectedArtifacts),
+    bindable!("test_report", "Show the selected job's test results", Action::ShowTestReport),
+    bindable!("job_graph", "Show what the pipeline's jobs need, as a tree", Action::ShowJobGraph),
+    bindable!("browse_artifacts", "Browse and view the selected job's artifact files", Action::BrowseArtifacts),
+    bindable!("cancel_download", "Cancel artifacts download", Action::CancelArtifactDownload),
+    bindable!("close_log", "Close the log tab, the viewer with the last one", Action::CloseLogViewer),
+    bindable!("hide_log", "Back to the job list, keeping log tabs open", Action::HideLogViewer),
+    bindable!("show_log_tabs", "Back to the open log tabs", Action::ShowLogTabs),
+    bindable!("next_log_tab", "Next log tab (a count picks the tab, e.g. 2 Tab)", Action::NextLogTab),
+    bindable!("prev_log_tab", "Previous log tab", Action::PrevLogTab),
+    bindable!("scroll_up", "Scroll up", Action::ScrollLogUp),
+    bindable!("scroll_down", "Scroll down", Action::ScrollLogDown),
+    bindable!("fast_scroll_up", "Scroll up several lines", Action::ScrollLogFastUp),
+    bindable!("fast_scroll_down", "Scroll down several lines", Action::ScrollLogFastDown),
+    bindable!("page_up", "Scroll a page up", Action::ScrollLogPageUp),
+    bindable!("page_down", "Scroll a page down", Action::ScrollLogPageDown),
+    bindable!("half_page_up", "Scroll half a page up", Action::ScrollLogHalfPageUp),
+    bindable!("half_page_down", "Scroll half a page down", Action::ScrollLogHalfPageDown),
+    bindable!("scroll_top", "Jump to top of log", Action::ScrollLogHome),
+    bindable!("scroll_bottom", "Jump to bottom of log", Action::ScrollLogEnd),
+    bindable!("scroll_left", "Pan left (wrap off)", Action::ScrollLogLeft),
+    bindable!("scroll_right", "Pan right (wrap off)", Action::ScrollLogRight),
+    bindable!("toggle_wrap", "Toggle line wrap", Action::ToggleWrap),
+    bindable!("toggle_line_numbers", "Toggle line numbers", Action::ToggleLineNumbers),
+    bindable!("toggle_minimap", "Toggle the error/warning minimap", Action::ToggleMinimap),
+    bindable!("toggle_timestamps", "Cycle timestamp display", Action::ToggleTimestampMode),
+    bindable!("toggle_raw", "Toggle the raw log, exactly as received", Action::ToggleRawView),
+    bindable!("toggle_follow", "Follow running job", Action::ToggleFollowMode),
+    bindable!("toggle_section", "Fold/unfold CI section", Action::ToggleSection),
+    bindable!("next_error", "Next error line", Action::NextErrorLine),
+    bindable!("prev_error", "Previous error line", Action::PrevErrorLine),
+    bindable!("toggle_bookmark", "Bookmark the top line / remove its bookmark", Action::ToggleBookmark),
+    bindable!("next_bookmark", "Next bookmark", Action::NextBookmark),
+    bindable!("prev_bookmark", "Previous bookmark", Action::PrevBookmark),
+    bindable!("start_search", "Search", Action::StartSearch),
+    bindable!("next_match", "Next search match", Action::NextSearchResult),
+    bindable!("prev_match", "Previous search match", Action::PrevSearchResult),
+    bindable!("start_filter", "Filter lines", Action::StartFilter),
+    bindable!("visual_select", "Select lines with j/k (y copies, Esc cancels)", Action::StartVisualSelection),
+    bindable!("copy_visible", "Copy visible/selected lines", Action::CopyVisibleLog),
+    bindable!("copy_full", "Copy whole log", Action::CopyFullLog),
+    bindable!("open_log_url", "Open the link on the selected line, else the first one in view", Action::OpenLogUrl),
+    bindable!("export_log", "Export cleaned log", Action::ExportLog { raw: false }),
+    bindable!("export_raw_log", "Export raw log", Action::ExportLog { raw: true }),
+    bindable!("retry_log_fetch", "Retry loading a log that failed", Action::RetryLogFetch),
+    bindable!("close_diff", "Close comparison", Action::CloseLogDiff),
+    bindable!("diff_scroll_up", "Scroll both logs up", Action::DiffScrollUp),
+    bindable!("diff_scroll_down", "Scroll both logs down", Action::DiffScrollDown),
+    bindable!("diff_page_up", "Scroll both logs a page up", Action::DiffPageUp),
+    bindable!("diff_page_down", "Scroll both logs a page down", Action::DiffPageDown),
+    bindable!("diff_top", "Jump to the first lines", Action::DiffTop),
+    bindable!("diff_bottom", "Jump to the last lines", Action::DiffBottom),
+    bindable!("next_change", "Next difference", Action::NextDiffChange),
+    bindable!("prev_change", "Previous difference", Action::PrevDiffChange),
+];
+
+/// Binding a key to this name removes its default binding
+pub const UNBIND: &str = "none";
+
+pub fn bindable_action(name: &str) -> Option<&'static BindableAction> {
+    BINDABLE_ACTIONS.iter().find(|action| action.name == name)
+}
+
+fn default_bindings(context: KeyContext) -> &'static [(&'static str, &'static str)] {
+    match context {
+        KeyContext::Jobs => &[
+            ("q", "quit"),
+            ("ctrl+c", "quit"),
+            ("?", "show_help"),
+            (":", "command_palette"),
+            ("ctrl+p", "command_palette"),
+            ("T", "cycle_theme"),
+            ("c", "toggle_comments"),
+            ("b", "toggle_status_bar"),
+            ("P", "switch_profile"),
+            ("H", "head_pipelines"),
+            ("G", "group_pipelines"),
+            ("left", "prev_mr"),
+            ("h", "prev_mr"),
+            ("right", "next_mr"),
+            ("l", "next_mr"),
+            ("up", "prev_job"),
+            ("k", "prev_job"),
+            ("down", "next_job"),
+            ("j", "next_job"),
+            ("[", "prev_pipeline"),
+            ("]", "next_pipeline"),
+            ("1", "filter_success_pipelines"),
+            ("2", "filter_failed_pipelines"),
+            ("3", "filter_running_pipelines"),
+            ("4", "filter_canceled_pipelines"),
+            ("0", "clear_pipeline_filter"),
+            ("z", "fold_stage"),
+            ("Z", "toggle_all_stages"),
+            ("s", "filter_stage"),
+            ("/", "filter_jobs"),
+            ("ctrl+f", "search_jobs"),
+            ("F", "select_failed_job"),
+            ("enter", "open_job_log"),
+            ("tab", "show_log_tabs"),
+            ("C", "compare_job"),
+            ("w", "watch_job"),
+            ("W", "watch_pipeline"),
+            ("ctrl+s", "sort_pipelines"),
+            ("S", "sort_jobs"),
+            ("r", "refresh"),
+            ("d", "remove_mr"),
+            ("o", "open_mr_in_browser"),
+            ("B", "open_job_in_browser"),
+            ("O", "open_pipeline_in_browser"),
+            ("R", "retry_job"),
+            ("x", "cancel_job"),
+            ("X", "cancel_pipeline"),
+            ("ctrl+r", "retry_pipeline"),
+            ("m", "play_job"),
+            ("L", "prefetch_logs"),
+            ("p", "trigger_pipeline"),
+            ("a", "download_artifacts"),
+            ("A", "browse_artifacts"),
+            ("t", "test_report"),
+            ("D", "job_graph"),
+            ("esc", "cancel_download"),
+        ],
+        KeyContext::Comments => &[
+            ("q", "quit"),
+            ("ctrl+c", "quit"),
+            ("?", "show_help"),
+            (":", "command_palette"),
+            ("ctrl+p", "command_palette"),
+            ("T", "cycle_theme"),
+            ("c", "toggle_comments"),
+            ("b", "toggle_status_bar"),
+            ("P", "switch_profile"),
+            ("H", "head_pipelines"),
+            ("left", "prev_mr"),
+            ("h", "prev_mr"),
+            ("right", "next_mr"),
+            ("l", "next_mr"),
+            ("up", "prev_note"),
+            ("k", "prev_note"),
+            ("down", "next_note"),
+            ("j", "next_note"),
+            ("[", "prev_pipeline"),
+            ("]", "next_pipeline"),
+            ("r", "refresh"),
+            ("d", "remove_mr"),
+            ("o", "open_mr_in_browser"),
+            ("esc", "cancel_download"),
+        ],
+        KeyContext::Log => &[
+            ("q", "close_log"),
+            ("esc", "hide_log"),
+            ("tab", "next_log_tab"),
+            ("ctrl+tab", "next_log_tab"),
+            ("backtab", "prev_log_tab"),
+            ("ctrl+c", "quit"),
+            (":", "command_palette"),
+            ("ctrl+p", "command_palette"),
+            ("T", "cycle_theme"),
+            ("up", "scroll_up"),
+            ("k", "scroll_up"),
+            ("down", "scroll_down"),
+            ("j", "scroll_down"),
+            ("K", "fast_scroll_up"),
+            ("J", "fast_scroll_down"),
+            ("pageup", "page_up"),
+            ("pagedown", "page_down"),
+            ("ctrl+u", "half_page_up"),
+            ("ctrl+d", "half_page_down"),
+            ("home", "scroll_top"),
+            ("g", "scroll_top"),
+            ("end", "scroll_bottom"),
+            ("G", "scroll_bottom"),
+            ("left", "scroll_left"),
+            ("h", "scroll_left"),
+            ("right", "scroll_right"),
+            ("l", "scroll_right"),
+            ("w", "toggle_wrap"),
+            ("#", "toggle_line_numbers"),
+            ("M", "toggle_minimap"),
+            ("+", "grow_log_viewer"),
+            ("=", "grow_log_viewer"),
+            ("-", "shrink_log_viewer"),
+            ("t", "toggle_timestamps"),
+            ("R", "toggle_raw"),
+            ("ctrl+f", "start_filter"),
+            ("f", "toggle_follow"),
+            ("e", "next_error"),
+            ("E", "prev_error"),
+            ("m", "toggle_bookmark"),
+            ("'", "next_bookmark"),
+            ("\"", "prev_bookmark"),
+            ("V", "visual_select"),
+            ("y", "copy_visible"),
+            ("Y", "copy_full"),
+            ("s", "export_log"),
+            ("S", "export_raw_log"),
+            ("r", "retry_log_fetch"),
+            ("o", "open_job_in_browser"),
+            ("u", "open_log_url"),
+            ("/", "start_search"),
+            ("n", "next_match"),
+            ("N", "prev_match"),
+            ("enter", "toggle_section"),
+            ("z a", "toggle_section"),
+        ],
+        KeyContext::Diff => &[
+            ("q", "close_diff"),
+            ("esc", "close_diff"),
+            ("ctrl+c", "quit"),
+            (":", "command_palette"),
+            ("ctrl+p", "command_palette"),
+            ("T", "cycle_theme"),
+            ("up", "diff_scroll_up"),
+            ("k", "diff_scroll_up"),
+            ("down", "diff_scroll_down"),
+            ("j", "diff_scroll_down"),
+            ("pageup", "diff_page_up"),
+            ("pagedown", "diff_page_down"),
+            ("home", "diff_top"),
+            ("g", "diff_top"),
+            ("end", "diff_bottom"),
+            ("G", "diff_bottom"),
+            ("n", "next_change"),
+            ("N", "prev_change"),
+        ],
+        KeyContext::Help => &[
+            ("esc", "hide_help"),
+            ("?", "hide_help"),
+            ("q", "hide_help"),
+            ("up", "scroll_help_up"),
+            ("k", "scroll_help_up"),
+            ("down", "scroll_help_down"),
+            ("j", "scroll_help_down"),
+        ],
+    }
+}
+
+/// One key sequence (a single chord, or two like `z a`) bound to an action
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub keys: Vec<KeyChord>,
+    pub action: &'static str,
+}
+
+impl Binding {
+    /// Keys as shown to the user, e.g. "z a" or "Ctrl+F"
+    pub fn keys_label(&self) -> String {
+        self.keys.iter().map(|chord| chord.to_string()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// One line of the help overlay: every key bound to an action in a context
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelpEntry {
+    pub keys: Vec<String>,
+    pub description: &'static str,
+    action: &'static str,
+}
+
+impl HelpEntry {
+    pub fn keys_label(&self) -> String {
+        self.keys.join(" / ")
+    }
+}
+
+/// Key bindings per context: the defaults, with the user's `[keys.*]` config applied on top
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    contexts: Vec<(KeyContext, Vec<Binding>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&BTreeMap::new()).expect("default key bindings are valid")
+    }
+}
+
+impl Keymap {
+    /// Build the keymap from `[keys.<context>]` tables mapping key sequences to action names
+    pub fn from_config(overrides: &BTreeMap<String, BTreeMap<String, String>>) -> Result<Self> {
+        let mut contexts = Vec::new();
+        for context in KeyContext::ALL {
+            let mut bindings = Vec::new();
+            for (keys, action) in default_bindings(context) {
+                bind(&mut bindings, context, keys, action)?;
+            }
+            contexts.push((context, bindings));
+        }
+
+        for (context_name, entries) in overrides {
+            let context = KeyContext::from_name(context_name).ok_or_else(|| {
+                PeeplabError::Config(format!(
+                    "Unknown key context [keys.{}], expected one of: {}",
+                    context_name,
+                    KeyContext::ALL.map(|c| c.name()).join(", ")
+                ))
+            })?;
+            let bindings = &mut contexts
+                .iter_mut()
+                .find(|(c, _)| *c == context)
+                .expect("every context has bindings")
+                .1;
+            for (keys, action) in entries {
+                bind(bindings, context, keys, action)?;
+            }
+        }
+
+        Ok(Self { contexts })
+    }
+
+    /// Bindings of a context, in the order they were defined
+    pub fn bindings(&self, context: KeyContext) -> &[Binding] {
+        self.contexts
+            .iter()
+            .find(|(c, _)| *c == context)
+            .map(|(_, bindings)| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Whether an action makes sense in a context: bound there by default or by the user
+    pub fn is_available(&self, context: KeyContext, action: &str) -> bool {
+        default_bindings(context).iter().any(|&(_, name)| name == action)
+            || self.bindings(context).iter().any(|binding| binding.action == action)
+    }
+
+    /// Help lines of a context, one per bound action, in binding order
+    pub fn help_entries(&self, context: KeyContext) -> Vec<HelpEntry> {
+        let mut entries: Vec<HelpEntry> = Vec::new();
+        for binding in self.bindings(context) {
+            let Some(bindable) = bindable_action(binding.action) else {
+                continue;
+            };
+            match entries.iter_mut().find(|entry| entry.action == binding.action) {
+                Some(entry) => entry.keys.push(binding.keys_label()),
+                None => entries.push(HelpEntry {
+                    keys: vec![binding.keys_label()],
+                    description: bindable.description,
+                    action: binding.action,
+                }),
+            }
+        }
+        entries
+    }
+
+    /// Help overlay sections: what works the same in both MR views, then each view's own keys
+    pub fn help_sections(&self) -> Vec<(&'static str, Vec<HelpEntry>)> {
+        let jobs = self.help_entries(KeyContext::Jobs);
+        let comments = self.help_entries(KeyContext::Comments);
+        let (global, jobs): (Vec<_>, Vec<_>) = jobs.into_iter().partition(|entry| comments.contains(entry));
+        let comments = comments.into_iter().filter(|entry| !global.contains(entry)).collect();
+        vec![
+            ("Global", global),
+            ("Job List", jobs),
+            ("Comments", comments),
+            ("Log Viewer", self.help_entries(KeyContext::Log)),
+            ("Log Comparison", self.help_entries(KeyContext::Diff)),
+            ("Help", self.help_entries(KeyContext::Help)),
+        ]
+    }
+
+    /// Action for a key press, given the first key of a pending two-key sequence if any
+    pub fn resolve(&self, context: KeyContext, pending: Option<char>, key: &KeyEvent) -> Action {
+        let chord = KeyChord::from_event(key);
+        let bindings = self.bindings(context);
+
+        if let Some(first) = pending {
+            let first = KeyChord::new(KeyCode::Char(first), KeyModifiers::NONE);
+            return bindings
+                .iter()
+                .find(|binding| binding.keys == [first, chord])
+                .and_then(|binding| bindable_action(binding.action))
+                .map(BindableAction::action)
+                .unwrap_or(Action::CancelPendingKey);
+        }
+
+        if let Some(action) = bindings
+            .iter()
+            .find(|binding| binding.keys == [chord])
+            .and_then(|binding| bindable_action(binding.action))
+        {
+            return action.action();
+        }
+
+        // Start of a two-key sequence
+        match chord.code {
+            KeyCode::Char(c)
+                if chord.modifiers.is_empty()
+                    && bindings.iter().any(|binding| binding.keys.len() == 2 && binding.keys[0] == chord) =>
+            {
+                Action::PendingKey(c)
+            }
+            _ => Action::None,
+        }
+    }
+}
+
+/// Add (or with `none`, remove) a binding, replacing whatever the same keys did before
+fn bind(bindings: &mut Vec<Binding>, context: KeyContext, keys: &str, action: &str) -> Result<()> {
+    let invalid = |reason: String| {
+        PeeplabError::Config(format!("Invalid binding \"{}\" in [keys.{}]: {}", keys, context.name(), reason))
+    };
+
+    let chords = keys
+        .split_whitespace()
+        .map(|part| KeyChord::parse(part).ok_or_else(|| invalid(format!("unknown key \"{}\"", part))))
+        .collect::<Result<Vec<_>>>()?;
+    match chords.as_slice() {
+        [] => return Err(invalid("no key given".to_string())),
+        [_] => {}
+        [first, _] if matches!(first.code, KeyCode::Char(_)) && first.modifiers.is_empty() => {}
+        [_, _] => return Err(invalid("sequences must start with a plain character".to_string())),
+        _ => return Err(invalid("at most two keys per sequence".to_string())),
+    }
+
+    bindings.retain(|binding| binding.keys != chords);
+    if action == UNBIND {
+        return Ok(());
+    }
+    let bindable = bindable_action(action)
+        .ok_or_else(|| invalid(format!("unknown action \"{}\"", action)))?;
+    bindings.push(Binding { keys: chords, action: bindable.name });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn overrides(context: &str, entries: &[(&str, &str)]) -> BTreeMap<String, BTreeMap<String, String>> {
+        let entries = entries.iter().map(|(k, a)| (k.to_string(), a.to_string())).collect();
+        BTreeMap::from([(context.to_string(), entries)])
+    }
+
+    #[test]
+    fn test_default_keymap_is_valid() {
+        let keymap = Keymap::default();
+        for context in KeyContext::ALL {
+            assert!(!keymap.bindings(context).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_chords() {
+        assert_eq!(KeyChord::parse("q"), Some(KeyChord::new(KeyCode::Char('q'), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("ctrl+F"), Some(KeyChord::new(KeyCode::Char('f'), KeyModifiers::CONTROL)));
+        assert_eq!(KeyChord::parse("PageDown"), Some(KeyChord::new(KeyCode::PageDown, KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("+"), Some(KeyChord::new(KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("alt++"), Some(KeyChord::new(KeyCode::Char('+'), KeyModifiers::ALT)));
+        assert_eq!(KeyChord::parse("f5"), Some(KeyChord::new(KeyCode::F(5), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("hyper+x"), None);
+        assert_eq!(KeyChord::parse("nonsense"), None);
+    }
+
+    #[test]
+    fn test_shifted_characters_match_by_case() {
+        let keymap = Keymap::default();
+        let action = keymap.resolve(KeyContext::Log, None, &key(KeyCode::Char('G'), KeyModifiers::SHIFT));
+        assert!(matches!(action, Action::ScrollLogEnd));
+    }
+
+    #[test]
+    fn test_two_key_sequence() {
+        let keymap = Keymap::default();
+        let z = key(KeyCode::Char('z'), KeyModifiers::NONE);
+        assert!(matches!(keymap.resolve(KeyContext::Log, None, &z), Action::PendingKey('z')));
+
+        let a = key(KeyCode::Char('a'), KeyModifiers::NONE);
+        assert!(matches!(keymap.resolve(KeyContext::Log, Some('z'), &a), Action::ToggleSection));
+        assert!(matches!(keymap.resolve(KeyContext::Log, Some('z'), &z), Action::CancelPendingKey));
+    }
+
+    #[test]
+    fn test_user_bindings_override_defaults() {
+        let keymap = Keymap::from_config(&overrides(
+            "log",
+            &[("ctrl+e", "scroll_down"), ("j", "none"), ("q", "quit")],
+        ))
+        .unwrap();
+
+        let resolve = |code, modifiers| keymap.resolve(KeyContext::Log, None, &key(code, modifiers));
+        assert!(matches!(resolve(KeyCode::Char('e'), KeyModifiers::CONTROL), Action::ScrollLogDown));
+        assert!(matches!(resolve(KeyCode::Char('j'), KeyModifiers::NONE), Action::None));
+        assert!(matches!(resolve(KeyCode::Char('q'), KeyModifiers::NONE), Action::Quit));
+        // Other defaults are untouched
+        assert!(matches!(resolve(KeyCode::Down, KeyModifiers::NONE), Action::ScrollLogDown));
+    }
+
+    #[test]
+    fn test_unknown_action_is_an_error() {
+        let err = Keymap::from_config(&overrides("log", &[("x", "explode")])).unwrap_err();
+        assert!(err.to_string().contains("unknown action \"explode\""));
+    }
+
+    #[test]
+    fn test_unknown_context_and_key_are_errors() {
+        assert!(Keymap::from_config(&overrides("logs", &[("x", "quit")])).is_err());
+        assert!(Keymap::from_config(&overrides("log", &[("ctrl+nope", "quit")])).is_err());
+        assert!(Keymap::from_config(&overrides("log", &[("ctrl+x a", "quit")])).is_err());
+    }
+
+    #[test]
+    fn test_help_sections_follow_bindings() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert("jobs".to_string(), BTreeMap::from([("J".to_string(), "next_job".to_string())]));
+        let keymap = Keymap::from_config(&overrides).unwrap();
+        let sections = keymap.help_sections();
+        let section = |name| &sections.iter().find(|(title, _)| *title == name).unwrap().1;
+
+        // Same keys for the same action in both MR views: listed once as global
+        let quit = section("Global").iter().find(|e| e.description == "Quit").unwrap();
+        assert_eq!(quit.keys_label(), "q / Ctrl+C");
+        assert!(section("Job List").iter().all(|e| e.description != "Quit"));
+
+        let next_job = section("Job List").iter().find(|e| e.action == "next_job").unwrap();
+        assert_eq!(next_job.keys, vec!["↓", "j", "J"]);
+        let fold = section("Log Viewer").iter().find(|e| e.action == "toggle_section").unwrap();
+        assert_eq!(fold.keys_label(), "Enter / z a");
+    }
+}
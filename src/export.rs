@@ -0,0 +1,120 @@
+use crate::error::Result;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Default file name for an exported job log, e.g. `job-12345-build.log`
+pub fn default_export_filename(job_id: u64, job_name: &str, raw: bool) -> PathBuf {
+    job_file_name(job_id, job_name, if raw { ".raw.log" } else { ".log" })
+}
+
+/// Default file name for a job's downloaded artifacts, e.g. `job-12345-build-artifacts.zip`
+pub fn default_artifacts_filename(job_id: u64, job_name: &str) -> PathBuf {
+    job_file_name(job_id, job_name, "-artifacts.zip")
+}
+
+/// `path`, or the first of `<stem>-1.<ext>`, `<stem>-2.<ext>`, ... next to it that doesn't exist
+/// yet, so a download never replaces a file
+pub fn unused_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{}-{}{}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(path)
+}
+
+fn job_file_name(job_id: u64, job_name: &str, suffix: &str) -> PathBuf {
+    // Job names can contain spaces, slashes and colons (e.g. "test: [linux, 1/2]")
+    let safe_name: String = job_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+        .collect();
+    let safe_name = safe_name.trim_matches('-');
+
+    if safe_name.is_empty() {
+        PathBuf::from(format!("job-{}{}", job_id, suffix))
+    } else {
+        PathBuf::from(format!("job-{}-{}{}", job_id, safe_name, suffix))
+    }
+}
+
+/// Write log content to a file
+pub fn write_log_file(path: &Path, content: &str) -> Result<()> {
+    let file = File::create(path)?;
+    let mut writer = BufWriter::with_capacity(8192, file);
+    writer.write_all(content.as_bytes())?;
+    if !content.is_empty() && !content.ends_with('\n') {
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_export_filename() {
+        assert_eq!(
+            default_export_filename(12345, "build", false),
+            PathBuf::from("job-12345-build.log")
+        );
+        assert_eq!(
+            default_export_filename(12345, "build", true),
+            PathBuf::from("job-12345-build.raw.log")
+        );
+    }
+
+    #[test]
+    fn test_default_export_filename_sanitizes_job_name() {
+        assert_eq!(
+            default_export_filename(7, "test: [linux, 1/2]", false),
+            PathBuf::from("job-7-test---linux--1-2.log")
+        );
+        assert_eq!(default_export_filename(7, "///", false), PathBuf::from("job-7.log"));
+    }
+
+    #[test]
+    fn test_default_artifacts_filename() {
+        assert_eq!(
+            default_artifacts_filename(12345, "build: linux"),
+            PathBuf::from("job-12345-build--linux-artifacts.zip")
+        );
+    }
+
+    #[test]
+    fn test_unused_path_adds_a_suffix() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("job-1-build-artifacts.zip");
+        assert_eq!(unused_path(path.clone()), path);
+
+        fs::write(&path, "old").unwrap();
+        let next = temp_dir.path().join("job-1-build-artifacts-1.zip");
+        assert_eq!(unused_path(path.clone()), next);
+        fs::write(&next, "older").unwrap();
+        assert_eq!(unused_path(path), temp_dir.path().join("job-1-build-artifacts-2.zip"));
+    }
+
+    #[test]
+    fn test_write_log_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("job.log");
+
+        write_log_file(&path, "line 1\nline 2").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 1\nline 2\n");
+    }
+
+    #[test]
+    fn test_write_log_file_to_missing_directory_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("missing").join("job.log");
+        assert!(write_log_file(&path, "content").is_err());
+    }
+}
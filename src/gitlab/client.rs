@@ -1,15 +1,86 @@
+use crate::artifacts::{self, ArtifactEntry};
 use crate::error::{PeeplabError, Result};
-use super::models::{Job, MergeRequest, Note, Pipeline, Project};
-use reqwest::{Client, StatusCode, header};
+use crate::junit;
+use super::models::{
+    GraphQlResponse, Job, MergeRequest, Note, Page, Pipeline, PipelineNeedsData, PipelineStatus, Project, TestSuite,
+    TraceChunk, User,
+};
+use reqwest::{Client, RequestBuilder, StatusCode, header};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
 
+/// Minimum delay between two download progress reports
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Pipelines fetched per page, older ones load when scrolling past the end
+const PIPELINES_PER_PAGE: u32 = 20;
+
+/// Projects of a group fetched per page, most recently active first
+const GROUP_PROJECTS_PER_PAGE: u32 = 20;
+
+/// Names and `needs:` of a pipeline's current jobs, a page at a time
+const PIPELINE_NEEDS_QUERY: &str = "query($project: ID!, $iid: ID!, $after: String) {
+  project(fullPath: $project) {
+    pipeline(iid: $iid) {
+      jobs(first: 100, after: $after, retried: false) {
+        pageInfo { hasNextPage endCursor }
+        nodes { name needs { nodes { name } } }
+      }
+    }
+  }
+}";
+
+/// Back-off when GitLab rate limits us without saying for how long
+const DEFAULT_RATE_LIMIT_DELAY: Duration = Duration::from_secs(60);
+
+/// Longest back-off, whatever GitLab (or a proxy in front of it) asks for
+pub const MAX_RATE_LIMIT_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Idle connections are kept this long for the next poll to reuse
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Timeouts applied to every GitLab request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClientTimeouts {
+    /// Establishing the TCP/TLS connection
+    pub connect: Duration,
+    /// Whole API request, and the longest stall while streaming a download
+    pub request: Duration,
+}
+
+impl Default for ClientTimeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(10),
+            request: Duration::from_secs(30),
+        }
+    }
+}
+
+/// One pooled HTTP client per session, cloned cheaply into every effect task
 #[derive(Clone)]
 pub struct GitLabClient {
     client: Client,
     base_url: String,
+    timeouts: ClientTimeouts,
+    paused_until: Arc<Mutex<Option<Instant>>>, // Rate limit back-off shared by all clones
 }
 
 impl GitLabClient {
     pub fn new(instance_url: &str, token: &str) -> Result<Self> {
+        Self::with_timeouts(instance_url, token, ClientTimeouts::default())
+    }
+
+    pub fn with_timeouts(instance_url: &str, token: &str, timeouts: ClientTimeouts) -> Result<Self> {
+        Self::for_api(&format!("{}/api/v4", instance_url.trim_end_matches('/')), token, timeouts)
+    }
+
+    /// Client for an API base that isn't at the standard `/api/v4`, e.g. behind a proxy prefix
+    pub fn for_api(api_url: &str, token: &str, timeouts: ClientTimeouts) -> Result<Self> {
         let mut headers = header::HeaderMap::new();
         headers.insert(
             "PRIVATE-TOKEN",
@@ -17,16 +88,89 @@ impl GitLabClient {
                 .map_err(|e| PeeplabError::Config(format!("Invalid token format: {}", e)))?,
         );
 
+        // The total request timeout is set per request so artifact downloads
+        // are only bounded by stalls, not by their size
         let client = Client::builder()
             .default_headers(headers)
+            .connect_timeout(timeouts.connect)
+            .read_timeout(timeouts.request)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(POOL_IDLE_TIMEOUT)
             .build()?;
 
         Ok(Self {
             client,
-            base_url: format!("{}/api/v4", instance_url.trim_end_matches('/')),
+            base_url: api_url.trim_end_matches('/').to_string(),
+            timeouts,
+            paused_until: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Host, with its port if not the default, of the GitLab instance this client talks to
+    pub fn host(&self) -> String {
+        let url = url::Url::parse(&self.base_url).ok();
+        let host = url.as_ref().and_then(|url| Some((url.host_str()?, url.port())));
+        match host {
+            Some((host, Some(port))) => format!("{}:{}", host, port),
+            Some((host, None)) => host.to_string(),
+            None => self.base_url.clone(),
+        }
+    }
+
+    /// Send an API request bounded by the request timeout, holding off while rate limited
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        if let Some(retry_after) = self.rate_limit_remaining() {
+            return Err(PeeplabError::RateLimited { retry_after });
+        }
+        // Method and URL only, the token travels in a header and is never logged
+        let (client, request) = request.timeout(self.timeouts.request).build_split();
+        let request = request?;
+        let (method, url) = (request.method().clone(), request.url().clone());
+        let started = Instant::now();
+        let response = match client.execute(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!(%method, %url, error = %e, "Request failed");
+                return Err(e.into());
+            }
+        };
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        if response.status().is_success() {
+            tracing::debug!(%method, %url, status = response.status().as_u16(), elapsed_ms, "Request done");
+        } else {
+            tracing::warn!(%method, %url, status = response.status().as_u16(), elapsed_ms, "Request failed");
+        }
+        self.note_rate_limit(&response)?;
+        Ok(response)
+    }
+
+    /// Time left before GitLab accepts requests again, if we are backing off
+    fn rate_limit_remaining(&self) -> Option<Duration> {
+        let paused_until = (*self.paused_until.lock().unwrap())?;
+        Some(paused_until.saturating_duration_since(Instant::now())).filter(|d| !d.is_zero())
+    }
+
+    /// Back off on a 429, or pre-emptively once the request budget is used up
+    fn note_rate_limit(&self, response: &reqwest::Response) -> Result<()> {
+        let headers = response.headers();
+        let limited = response.status() == StatusCode::TOO_MANY_REQUESTS;
+        let exhausted = header_u64(headers, "ratelimit-remaining") == Some(0);
+        if !limited && !exhausted {
+            return Ok(());
+        }
+
+        let retry_after = rate_limit_delay(headers, SystemTime::now());
+        tracing::warn!(retry_after_secs = retry_after.as_secs(), "Rate limited, pausing requests");
+        if let Some(until) = Instant::now().checked_add(retry_after) {
+            *self.paused_until.lock().unwrap() = Some(until);
+        }
+        if limited {
+            Err(PeeplabError::RateLimited { retry_after })
+        } else {
+            Ok(())
+        }
+    }
+
     async fn handle_response<T>(&self, response: reqwest::Response) -> Result<T>
     where
         T: serde::de::DeserializeOwned,
@@ -37,29 +181,65 @@ impl GitLabClient {
                     "Invalid GitLab token or insufficient permissions".to_string()
                 ))
             }
+            StatusCode::FORBIDDEN => {
+                Err(PeeplabError::Authentication(
+                    "Insufficient permissions for this action".to_string()
+                ))
+            }
             StatusCode::NOT_FOUND => {
                 Err(PeeplabError::NotFound(
                     "Resource not found".to_string()
                 ))
             }
-            StatusCode::TOO_MANY_REQUESTS => {
-                Err(PeeplabError::Network(
-                    "API rate limit exceeded. Please try again later.".to_string()
-                ))
-            }
             _ => {
                 let response = response.error_for_status()?;
-                Ok(response.json().await?)
+                let url = response.url().clone();
+                response.json().await.map_err(|e| {
+                    tracing::warn!(%url, error = %e, "Unexpected response body");
+                    e.into()
+                })
             }
         }
     }
 
+    /// Body of a response that isn't JSON, with the same status handling as `handle_response`
+    async fn handle_bytes(&self, response: reqwest::Response) -> Result<Vec<u8>> {
+        match response.status() {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(PeeplabError::Authentication(
+                "Insufficient permissions to read artifacts".to_string()
+            )),
+            _ => Ok(response.error_for_status()?.bytes().await?.to_vec()),
+        }
+    }
+
+    async fn handle_page<T>(&self, response: reqwest::Response) -> Result<Page<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let next_page = next_page(response.headers());
+        let items = self.handle_response(response).await?;
+        Ok(Page { items, next_page })
+    }
+
+    /// The user the token belongs to
+    pub async fn get_current_user(&self) -> Result<User> {
+        let url = format!("{}/user", self.base_url);
+        let response = self.send(self.client.get(&url)).await?;
+        self.handle_response(response).await
+    }
+
+    pub async fn get_project(&self, project_id: u64) -> Result<Project> {
+        let url = format!("{}/projects/{}", self.base_url, project_id);
+        let response = self.send(self.client.get(&url)).await?;
+        self.handle_response(response).await
+    }
+
     pub async fn get_project_by_path(&self, project_path: &str) -> Result<Project> {
         // URL encode the project path (namespace/project becomes namespace%2Fproject)
         let encoded_path = project_path.replace('/', "%2F");
         let url = format!("{}/projects/{}", self.base_url, encoded_path);
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         self.handle_response(response).await
     }
 
@@ -69,7 +249,7 @@ impl GitLabClient {
             self.base_url, project_id
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         self.handle_response(response).await
     }
 
@@ -83,27 +263,90 @@ impl GitLabClient {
             self.base_url, project_id, source_branch
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         self.handle_response(response).await
     }
 
-    pub async fn get_mr_pipelines(&self, project_id: u64, mr_iid: u64) -> Result<Vec<Pipeline>> {
+    /// One page of an MR's pipelines, newest first, optionally only those with the given status
+    pub async fn get_mr_pipelines(
+        &self,
+        project_id: u64,
+        mr_iid: u64,
+        page: u32,
+        status: Option<&PipelineStatus>,
+    ) -> Result<Page<Pipeline>> {
+        let mut url = format!(
+            "{}/projects/{}/merge_requests/{}/pipelines?per_page={}&page={}",
+            self.base_url, project_id, mr_iid, PIPELINES_PER_PAGE, page
+        );
+        if let Some(status) = status {
+            url.push_str(&format!("&status={}", status.api_name()));
+        }
+
+        let response = self.send(self.client.get(&url)).await?;
+        self.handle_page(response).await
+    }
+
+    /// One page of the unarchived projects of a group and its subgroups, most recently active first
+    pub async fn get_group_projects(&self, group: &str, page: u32) -> Result<Page<Project>> {
         let url = format!(
-            "{}/projects/{}/merge_requests/{}/pipelines?per_page=10",
-            self.base_url, project_id, mr_iid
+            "{}/groups/{}/projects?include_subgroups=true&archived=false&order_by=last_activity_at&per_page={}&page={}",
+            self.base_url, encode_path_segment(group), GROUP_PROJECTS_PER_PAGE, page
+        );
+
+        let response = self.send(self.client.get(&url)).await?;
+        self.handle_page(response).await
+    }
+
+    /// Newest pipelines of a project, whatever their ref or source
+    pub async fn get_project_pipelines(&self, project_id: u64, per_page: u32) -> Result<Vec<Pipeline>> {
+        let url = format!(
+            "{}/projects/{}/pipelines?per_page={}",
+            self.base_url, project_id, per_page
+        );
+
+        let response = self.send(self.client.get(&url)).await?;
+        self.handle_response(response).await
+    }
+
+    /// Newest pipelines run for a commit, whatever their ref or source
+    pub async fn get_pipelines_for_sha(&self, project_id: u64, sha: &str) -> Result<Vec<Pipeline>> {
+        let url = format!(
+            "{}/projects/{}/pipelines?sha={}&per_page={}",
+            self.base_url, project_id, sha, PIPELINES_PER_PAGE
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         self.handle_response(response).await
     }
 
+    /// All jobs of a pipeline, following pagination: the job list is sorted by status so it needs every page
     pub async fn get_pipeline_jobs(&self, project_id: u64, pipeline_id: u64) -> Result<Vec<Job>> {
+        let mut jobs = Vec::new();
+        let mut page = 1;
+        loop {
+            let url = format!(
+                "{}/projects/{}/pipelines/{}/jobs?per_page=100&page={}",
+                self.base_url, project_id, pipeline_id, page
+            );
+
+            let response = self.send(self.client.get(&url)).await?;
+            let result: Page<Job> = self.handle_page(response).await?;
+            jobs.extend(result.items);
+            match result.next_page {
+                Some(next) if next > page => page = next,
+                _ => return Ok(jobs),
+            }
+        }
+    }
+
+    pub async fn get_job(&self, project_id: u64, job_id: u64) -> Result<Job> {
         let url = format!(
-            "{}/projects/{}/pipelines/{}/jobs?per_page=100",
-            self.base_url, project_id, pipeline_id
+            "{}/projects/{}/jobs/{}",
+            self.base_url, project_id, job_id
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
         self.handle_response(response).await
     }
 
@@ -113,7 +356,7 @@ impl GitLabClient {
             self.base_url, project_id, job_id
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
 
         match response.status() {
             StatusCode::UNAUTHORIZED => {
@@ -121,14 +364,74 @@ impl GitLabClient {
                     "Invalid GitLab token or insufficient permissions".to_string()
                 ))
             }
-            StatusCode::NOT_FOUND => {
-                Err(PeeplabError::NotFound(
-                    "Job trace not found".to_string()
+            // Retention policies erase old traces, the endpoint then answers 404
+            StatusCode::NOT_FOUND | StatusCode::GONE => Err(PeeplabError::LogExpired),
+            _ => {
+                // Traces are UTF-8 whatever the declared charset, raw binary output shows as U+FFFD
+                let bytes = response.error_for_status()?.bytes().await?;
+                Ok(String::from_utf8_lossy(&bytes).into_owned())
+            }
+        }
+    }
+
+    /// Fetch part of a job trace with an HTTP Range request, starting at byte `offset`. GitLab honours
+    /// the range for traces it serves from a file; the live trace of a running job comes whole, and
+    /// the bytes before `offset` are dropped
+    pub async fn get_job_trace_range(
+        &self,
+        project_id: u64,
+        job_id: u64,
+        offset: u64,
+        max_len: u64,
+    ) -> Result<TraceChunk> {
+        let url = format!(
+            "{}/projects/{}/jobs/{}/trace",
+            self.base_url, project_id, job_id
+        );
+
+        let request = self
+            .client
+            .get(&url)
+            .header(header::RANGE, format!("bytes={}-{}", offset, offset + max_len - 1));
+        let response = self.send(request).await?;
+
+        match response.status() {
+            StatusCode::UNAUTHORIZED => {
+                Err(PeeplabError::Authentication(
+                    "Invalid GitLab token (HTTP 401)".to_string()
+                ))
+            }
+            StatusCode::FORBIDDEN => {
+                Err(PeeplabError::Authentication(
+                    "No permission to read this job's log (HTTP 403)".to_string()
                 ))
             }
+            StatusCode::NOT_FOUND | StatusCode::GONE => Err(PeeplabError::LogExpired),
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                // Offset is at (or past) the end of the trace
+                Ok(TraceChunk { data: Vec::new(), total_size: Some(offset), complete: true })
+            }
+            StatusCode::PARTIAL_CONTENT => {
+                let total_size = response
+                    .headers()
+                    .get(header::CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_content_range_total);
+                let data = response.bytes().await?.to_vec();
+                let end = offset + data.len() as u64;
+                let complete = match total_size {
+                    Some(total) => end >= total,
+                    None => (data.len() as u64) < max_len,
+                };
+                Ok(TraceChunk { data, total_size, complete })
+            }
             _ => {
+                // Server ignored the Range header and sent the whole trace
                 let response = response.error_for_status()?;
-                Ok(response.text().await?)
+                let mut data = response.bytes().await?.to_vec();
+                let total_size = Some(data.len() as u64);
+                data.drain(..(offset as usize).min(data.len()));
+                Ok(TraceChunk { data, total_size, complete: true })
             }
         }
     }
@@ -139,11 +442,377 @@ impl GitLabClient {
             self.base_url, project_id, mr_iid
         );
 
-        let response = self.client.get(&url).send().await?;
+        let response = self.send(self.client.get(&url)).await?;
+        self.handle_response(response).await
+    }
+
+    /// Retry a finished job, returning the newly created job
+    pub async fn retry_job(&self, project_id: u64, job_id: u64) -> Result<Job> {
+        let url = format!(
+            "{}/projects/{}/jobs/{}/retry",
+            self.base_url, project_id, job_id
+        );
+
+        let response = self.send(self.client.post(&url)).await?;
+        self.handle_response(response).await
+    }
+
+    /// Cancel a job; a job that already finished comes back with its final status
+    pub async fn cancel_job(&self, project_id: u64, job_id: u64) -> Result<Job> {
+        let url = format!(
+            "{}/projects/{}/jobs/{}/cancel",
+            self.base_url, project_id, job_id
+        );
+
+        let response = self.send(self.client.post(&url)).await?;
+        self.handle_response(response).await
+    }
+
+    /// Start a manual job, with optional `(key, value)` job variables
+    pub async fn play_job(&self, project_id: u64, job_id: u64, variables: &[(String, String)]) -> Result<Job> {
+        let url = format!(
+            "{}/projects/{}/jobs/{}/play",
+            self.base_url, project_id, job_id
+        );
+        let body = serde_json::json!({
+            "job_variables_attributes": variables
+                .iter()
+                .map(|(key, value)| serde_json::json!({ "key": key, "value": value }))
+                .collect::<Vec<_>>(),
+        });
+
+        let response = self.send(self.client.post(&url).json(&body)).await?;
+        if response.status() == StatusCode::BAD_REQUEST {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PeeplabError::Rejected(
+                gitlab_error_message(&body).unwrap_or_else(|| "the job can't be played".to_string()),
+            ));
+        }
+        self.handle_response(response).await
+    }
+
+    /// Whether a branch, tag or commit SHA exists in the project
+    pub async fn ref_exists(&self, project_id: u64, ref_name: &str) -> Result<bool> {
+        let url = format!(
+            "{}/projects/{}/repository/commits/{}",
+            self.base_url, project_id, encode_path_segment(ref_name)
+        );
+
+        let response = self.send(self.client.get(&url)).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        self.handle_response::<serde_json::Value>(response).await.map(|_| true)
+    }
+
+    /// Start a pipeline for a ref, with optional `(key, value)` pipeline variables
+    pub async fn create_pipeline(
+        &self,
+        project_id: u64,
+        ref_name: &str,
+        variables: &[(String, String)],
+    ) -> Result<Pipeline> {
+        let url = format!("{}/projects/{}/pipeline", self.base_url, project_id);
+        let body = serde_json::json!({
+            "ref": ref_name,
+            "variables": variables
+                .iter()
+                .map(|(key, value)| serde_json::json!({ "key": key, "value": value }))
+                .collect::<Vec<_>>(),
+        });
+
+        let response = self.send(self.client.post(&url).json(&body)).await?;
+        if response.status() == StatusCode::BAD_REQUEST {
+            let body = response.text().await.unwrap_or_default();
+            return Err(PeeplabError::Rejected(
+                gitlab_error_message(&body).unwrap_or_else(|| "invalid pipeline request".to_string()),
+            ));
+        }
+        self.handle_response(response).await
+    }
+
+    /// Stream a job's artifacts archive to `path`, reporting (received, total) bytes as it goes.
+    /// Returns false if `cancel` was set before the download finished; partial files are removed.
+    pub async fn download_job_artifacts(
+        &self,
+        project_id: u64,
+        job_id: u64,
+        path: &Path,
+        cancel: &AtomicBool,
+        mut on_progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<bool> {
+        let url = format!(
+            "{}/projects/{}/jobs/{}/artifacts",
+            self.base_url, project_id, job_id
+        );
+
+        // Not sent through `send`: a large archive may take longer than the request timeout
+        if let Some(retry_after) = self.rate_limit_remaining() {
+            return Err(PeeplabError::RateLimited { retry_after });
+        }
+        let mut response = self.client.get(&url).send().await?;
+        self.note_rate_limit(&response)?;
+        match response.status() {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                return Err(PeeplabError::Authentication(
+                    "Insufficient permissions to download artifacts".to_string()
+                ));
+            }
+            StatusCode::NOT_FOUND => {
+                return Err(PeeplabError::NotFound(
+                    "Job has no artifacts (or they expired)".to_string()
+                ));
+            }
+            _ => response = response.error_for_status()?,
+        }
+
+        let total = response.content_length();
+        // Never replace a file, even one created since the path was picked
+        let mut file = tokio::fs::OpenOptions::new().write(true).create_new(true).open(path).await?;
+        let mut received = 0u64;
+        let mut last_report = Instant::now();
+        on_progress(0, total);
+
+        let result: Result<bool> = async {
+            while let Some(chunk) = response.chunk().await? {
+                if cancel.load(Ordering::Relaxed) {
+                    return Ok(false);
+                }
+                file.write_all(&chunk).await?;
+                received += chunk.len() as u64;
+                if last_report.elapsed() >= PROGRESS_REPORT_INTERVAL {
+                    on_progress(received, total);
+                    last_report = Instant::now();
+                }
+            }
+            file.flush().await?;
+            on_progress(received, total);
+            Ok(true)
+        }
+        .await;
+
+        if !matches!(result, Ok(true)) {
+            drop(file);
+            let _ = tokio::fs::remove_file(path).await;
+        }
+        result
+    }
+
+    /// Files in a job's artifacts archive, read from its zip index without downloading the whole archive
+    pub async fn list_job_artifacts(&self, project_id: u64, job_id: u64) -> Result<Vec<ArtifactEntry>> {
+        let (tail, tail_start) =
+            self.get_artifacts_range(project_id, job_id, format!("bytes=-{}", artifacts::ARCHIVE_TAIL_LEN)).await?;
+        let (offset, size) = artifacts::central_directory_location(&tail).map_err(PeeplabError::Artifacts)?;
+
+        let directory = match offset.checked_sub(tail_start) {
+            Some(start) if start + size <= tail.len() as u64 => tail[start as usize..(start + size) as usize].to_vec(),
+            _ => {
+                let range = format!("bytes={}-{}", offset, (offset + size).saturating_sub(1));
+                let (data, start) = self.get_artifacts_range(project_id, job_id, range).await?;
+                let skip = (offset.saturating_sub(start) as usize).min(data.len());
+                data[skip..].iter().take(size as usize).copied().collect()
+            }
+        };
+        artifacts::parse_central_directory(&directory).map_err(PeeplabError::Artifacts)
+    }
+
+    /// Part of the artifacts archive and the offset it starts at. A server ignoring the range is an
+    /// error rather than a download of the whole archive into memory
+    async fn get_artifacts_range(&self, project_id: u64, job_id: u64, range: String) -> Result<(Vec<u8>, u64)> {
+        let url = format!("{}/projects/{}/jobs/{}/artifacts", self.base_url, project_id, job_id);
+        let response = self.send(self.client.get(&url).header(header::RANGE, range)).await?;
+        let start = match response.status() {
+            StatusCode::NOT_FOUND => {
+                return Err(PeeplabError::NotFound("Job has no artifacts (or they expired)".to_string()));
+            }
+            StatusCode::PARTIAL_CONTENT => response
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range_start)
+                .unwrap_or_default(),
+            status if status.is_success() => {
+                return Err(PeeplabError::Artifacts(
+                    "the server doesn't support partial downloads, download the archive instead".to_string(),
+                ));
+            }
+            _ => 0,
+        };
+        Ok((self.handle_bytes(response).await?, start))
+    }
+
+    /// One file of a job's artifacts, uncompressed
+    pub async fn get_artifact_file(&self, project_id: u64, job_id: u64, path: &str) -> Result<Vec<u8>> {
+        let url = format!(
+            "{}/projects/{}/jobs/{}/artifacts/{}",
+            self.base_url,
+            project_id,
+            job_id,
+            artifacts::encode_path(path)
+        );
+        let response = self.send(self.client.get(&url)).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(PeeplabError::NotFound(format!("'{}' is not in the job's artifacts", path)));
+        }
+        self.handle_bytes(response).await
+    }
+
+    /// Test results of a job, parsed from the JUnit XML files in its artifacts archive. XML files
+    /// that aren't JUnit reports are skipped
+    pub async fn get_job_test_report(&self, project_id: u64, job_id: u64, job_name: &str) -> Result<TestSuite> {
+        let entries = self.list_job_artifacts(project_id, job_id).await?;
+        let mut cases = Vec::new();
+        let mut found = false;
+        for entry in entries.iter().filter(|entry| junit::is_report_candidate(entry)).take(junit::MAX_REPORT_FILES) {
+            let data = self.get_artifact_file(project_id, job_id, &entry.path).await?;
+            match junit::parse_report(&String::from_utf8_lossy(&data)) {
+                Ok(report) => {
+                    found = true;
+                    cases.extend(report);
+                }
+                Err(e) => tracing::debug!(path = %entry.path, error = %e, "Skipped artifact XML"),
+            }
+        }
+        if !found {
+            return Err(PeeplabError::NotFound(
+                "No JUnit XML report in the job's artifacts archive (is it in `artifacts:paths`?)".to_string(),
+            ));
+        }
+        Ok(junit::suite(job_name, cases))
+    }
+
+    /// What each job of a pipeline `needs:`, by job name, from the GraphQL API next to the REST one.
+    /// Jobs without `needs:` wait for the previous stage and are listed with none.
+    pub async fn get_pipeline_needs(
+        &self,
+        project_path: &str,
+        pipeline_iid: u64,
+    ) -> Result<HashMap<String, Vec<String>>> {
+        let url = format!("{}/graphql", self.base_url.strip_suffix("/v4").unwrap_or(&self.base_url));
+        let mut needs = HashMap::new();
+        let mut after: Option<String> = None;
+        loop {
+            let body = serde_json::json!({
+                "query": PIPELINE_NEEDS_QUERY,
+                "variables": { "project": project_path, "iid": pipeline_iid.to_string(), "after": after },
+            });
+            let response = self.send(self.client.post(&url).json(&body)).await?;
+            let result: GraphQlResponse<PipelineNeedsData> = self.handle_response(response).await?;
+            if let Some(error) = result.errors.first() {
+                return Err(PeeplabError::Rejected(error.message.clone()));
+            }
+            let project = result
+                .data
+                .and_then(|data| data.project)
+                .ok_or_else(|| PeeplabError::NotFound(format!("Project {}", project_path)))?;
+            let jobs = project
+                .pipeline
+                .ok_or_else(|| PeeplabError::NotFound(format!("Pipeline #{}", pipeline_iid)))?
+                .jobs;
+            for job in &jobs.nodes {
+                needs.insert(job.name.clone(), job.need_names());
+            }
+            match jobs.page_info {
+                Some(page) if page.has_next_page && page.end_cursor.is_some() => after = page.end_cursor,
+                _ => return Ok(needs),
+            }
+        }
+    }
+
+    /// A single pipeline, with the user who started it that pipeline lists leave out
+    pub async fn get_pipeline(&self, project_id: u64, pipeline_id: u64) -> Result<Pipeline> {
+        let url = format!("{}/projects/{}/pipelines/{}", self.base_url, project_id, pipeline_id);
+        let response = self.send(self.client.get(&url)).await?;
+        self.handle_response(response).await
+    }
+
+    /// Retry every failed or canceled job of a pipeline
+    pub async fn retry_pipeline(&self, project_id: u64, pipeline_id: u64) -> Result<Pipeline> {
+        let url = format!("{}/projects/{}/pipelines/{}/retry", self.base_url, project_id, pipeline_id);
+        let response = self.send(self.client.post(&url)).await?;
+        self.handle_response(response).await
+    }
+
+    /// Cancel all running jobs of a pipeline
+    pub async fn cancel_pipeline(&self, project_id: u64, pipeline_id: u64) -> Result<Pipeline> {
+        let url = format!(
+            "{}/projects/{}/pipelines/{}/cancel",
+            self.base_url, project_id, pipeline_id
+        );
+
+        let response = self.send(self.client.post(&url)).await?;
         self.handle_response(response).await
     }
 }
 
+/// Percent-encode a value used as a single URL path segment (branch names contain `/`)
+fn encode_path_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Readable text from a GitLab error body such as `{"message": {"base": ["Reference not found"]}}`
+fn gitlab_error_message(body: &str) -> Option<String> {
+    fn collect(value: &serde_json::Value, out: &mut Vec<String>) {
+        match value {
+            serde_json::Value::String(text) => out.push(text.clone()),
+            serde_json::Value::Array(items) => items.iter().for_each(|item| collect(item, out)),
+            serde_json::Value::Object(fields) => fields.values().for_each(|field| collect(field, out)),
+            _ => {}
+        }
+    }
+
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let mut messages = Vec::new();
+    collect(json.get("message").or_else(|| json.get("error"))?, &mut messages);
+    (!messages.is_empty()).then(|| messages.join("; "))
+}
+
+/// Next page number from `X-Next-Page` (empty on the last page), else the `Link` header's `rel="next"`
+fn next_page(headers: &header::HeaderMap) -> Option<u32> {
+    if let Some(value) = headers.get("x-next-page").and_then(|v| v.to_str().ok()) {
+        return value.trim().parse().ok();
+    }
+    let link = headers.get(header::LINK)?.to_str().ok()?;
+    link.split(',')
+        .find(|part| part.contains("rel=\"next\""))
+        .and_then(|part| {
+            let url = part.trim().strip_prefix('<')?.split('>').next()?;
+            let query = url.split_once('?')?.1;
+            query.split('&').find_map(|pair| pair.strip_prefix("page=")?.parse().ok())
+        })
+}
+
+fn header_u64(headers: &header::HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// How long to wait from `Retry-After` (seconds), else `RateLimit-Reset` (Unix time)
+fn rate_limit_delay(headers: &header::HeaderMap, now: SystemTime) -> Duration {
+    let delay = header_u64(headers, header::RETRY_AFTER.as_str())
+        .map(Duration::from_secs)
+        .or_else(|| {
+            let reset = UNIX_EPOCH.checked_add(Duration::from_secs(header_u64(headers, "ratelimit-reset")?));
+            Some(reset.map_or(MAX_RATE_LIMIT_DELAY, |reset| reset.duration_since(now).unwrap_or_default()))
+        })
+        .unwrap_or(DEFAULT_RATE_LIMIT_DELAY);
+    delay.clamp(Duration::from_secs(1), MAX_RATE_LIMIT_DELAY)
+}
+
+/// Total size from a `Content-Range: bytes <start>-<end>/<total>` header
+fn parse_content_range_start(value: &str) -> Option<u64> {
+    value.trim().strip_prefix("bytes")?.trim().split_once('-')?.0.parse().ok()
+}
+
+fn parse_content_range_total(value: &str) -> Option<u64> {
+    value.rsplit_once('/')?.1.trim().parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,9 +888,10 @@ mod tests {
         let mut server = setup_mock_server().await;
 
         let mock = server
-            .mock("GET", "/api/v4/projects/123/merge_requests/10/pipelines?per_page=10")
+            .mock("GET", "/api/v4/projects/123/merge_requests/10/pipelines?per_page=20&page=1")
             .with_status(200)
             .with_header("content-type", "application/json")
+            .with_header("x-next-page", "2")
             .with_body(r#"[
                 {
                     "id": 456,
@@ -237,13 +907,14 @@ mod tests {
             .await;
 
         let client = GitLabClient::new(&server.url(), "test-token").unwrap();
-        let result = client.get_mr_pipelines(123, 10).await;
+        let result = client.get_mr_pipelines(123, 10, 1, None).await;
 
         mock.assert_async().await;
         assert!(result.is_ok());
-        let pipelines = result.unwrap();
-        assert_eq!(pipelines.len(), 1);
-        assert_eq!(pipelines[0].id, 456);
+        let page = result.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, 456);
+        assert_eq!(page.next_page, Some(2));
     }
 
     #[tokio::test]
@@ -251,7 +922,7 @@ mod tests {
         let mut server = setup_mock_server().await;
 
         let mock = server
-            .mock("GET", "/api/v4/projects/123/pipelines/456/jobs?per_page=100")
+            .mock("GET", "/api/v4/projects/123/pipelines/456/jobs?per_page=100&page=1")
             .with_status(200)
             .with_header("content-type", "application/json")
             .with_body(r#"[
@@ -281,67 +952,577 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_job_trace_success() {
+    async fn test_get_pipeline_jobs_follows_pages() {
         let mut server = setup_mock_server().await;
-
-        let trace_content = "Running tests...\nAll tests passed!";
-        let mock = server
-            .mock("GET", "/api/v4/projects/123/jobs/789/trace")
+        let job = |id: u64| {
+            format!(
+                r#"{{"id": {id}, "name": "job-{id}", "status": "success", "stage": "test",
+                    "created_at": "2024-01-01T10:00:00Z", "web_url": "https://gitlab.com/test/-/jobs/{id}"}}"#
+            )
+        };
+        let first = server
+            .mock("GET", "/api/v4/projects/123/pipelines/456/jobs?per_page=100&page=1")
             .with_status(200)
-            .with_body(trace_content)
+            .with_header("content-type", "application/json")
+            .with_header("x-next-page", "2")
+            .with_body(format!("[{}, {}]", job(1), job(2)))
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/api/v4/projects/123/pipelines/456/jobs?per_page=100&page=2")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("x-next-page", "")
+            .with_body(format!("[{}]", job(3)))
             .create_async()
             .await;
 
         let client = GitLabClient::new(&server.url(), "test-token").unwrap();
-        let result = client.get_job_trace(123, 789).await;
+        let jobs = client.get_pipeline_jobs(123, 456).await.unwrap();
 
-        mock.assert_async().await;
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), trace_content);
+        first.assert_async().await;
+        second.assert_async().await;
+        assert_eq!(jobs.iter().map(|j| j.id).collect::<Vec<_>>(), vec![1, 2, 3]);
     }
 
     #[tokio::test]
-    async fn test_get_job_trace_not_found() {
+    async fn test_get_pipelines_for_sha() {
         let mut server = setup_mock_server().await;
-
         let mock = server
-            .mock("GET", "/api/v4/projects/123/jobs/999/trace")
-            .with_status(404)
+            .mock("GET", "/api/v4/projects/123/pipelines?sha=0a1b2c3d&per_page=20")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[
+                {
+                    "id": 457,
+                    "iid": 79,
+                    "status": "running",
+                    "ref": "feature",
+                    "created_at": "2024-01-01T10:00:00Z",
+                    "updated_at": "2024-01-01T10:05:00Z",
+                    "web_url": "https://gitlab.com/test/-/pipelines/457"
+                }
+            ]"#)
             .create_async()
             .await;
 
         let client = GitLabClient::new(&server.url(), "test-token").unwrap();
-        let result = client.get_job_trace(123, 999).await;
+        let pipelines = client.get_pipelines_for_sha(123, "0a1b2c3d").await.unwrap();
 
         mock.assert_async().await;
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            PeeplabError::NotFound(_) => {}
-            _ => panic!("Expected NotFound error"),
-        }
+        assert_eq!(pipelines.len(), 1);
+        assert_eq!(pipelines[0].ref_name, "feature");
     }
 
-    #[tokio::test]
+    /// Zip archive of `padding` bytes of file data, then a central directory listing `name`
+    fn zip_archive(padding: usize, name: &str) -> (Vec<u8>, Vec<u8>) {
+        let mut directory = 0x0201_4b50u32.to_le_bytes().to_vec();
+        directory.extend([0u8; 20]);
+        directory.extend(42u32.to_le_bytes());
+        directory.extend((name.len() as u16).to_le_bytes());
+        directory.extend([0u8; 16]);
+        directory.extend(name.as_bytes());
+
+        let mut archive = vec![0u8; padding];
+        archive.extend(&directory);
+        archive.extend(0x0605_4b50u32.to_le_bytes());
+        archive.extend([0, 0, 0, 0, 1, 0, 1, 0]);
+        archive.extend((directory.len() as u32).to_le_bytes());
+        archive.extend((padding as u32).to_le_bytes());
+        archive.extend([0, 0]);
+        (archive, directory)
+    }
+
+    #[tokio::test]
+    async fn test_list_job_artifacts_reads_only_the_index() {
+        let mut server = setup_mock_server().await;
+        let (archive, directory) = zip_archive(150, "reports/junit.xml");
+        let total = archive.len();
+
+        // The last bytes requested miss the start of the file list, which is fetched next
+        let tail = server
+            .mock("GET", "/api/v4/projects/1/jobs/5/artifacts")
+            .match_header("range", "bytes=-65557")
+            .with_status(206)
+            .with_header("content-range", &format!("bytes 160-{}/{}", total - 1, total))
+            .with_body(&archive[160..])
+            .create_async()
+            .await;
+        let index = server
+            .mock("GET", "/api/v4/projects/1/jobs/5/artifacts")
+            .match_header("range", format!("bytes=150-{}", 150 + directory.len() - 1).as_str())
+            .with_status(206)
+            .with_header("content-range", &format!("bytes 150-{}/{}", 150 + directory.len() - 1, total))
+            .with_body(&directory)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let entries = client.list_job_artifacts(1, 5).await.unwrap();
+
+        tail.assert_async().await;
+        index.assert_async().await;
+        assert_eq!(entries, vec![ArtifactEntry { path: "reports/junit.xml".to_string(), size: 42 }]);
+    }
+
+    #[tokio::test]
+    async fn test_list_job_artifacts_refuses_whole_archive() {
+        let mut server = setup_mock_server().await;
+        let (archive, _) = zip_archive(150, "reports/junit.xml");
+        let mock = server
+            .mock("GET", "/api/v4/projects/1/jobs/5/artifacts")
+            .with_status(200)
+            .with_body(&archive)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let result = client.list_job_artifacts(1, 5).await;
+
+        mock.assert_async().await;
+        assert!(matches!(result, Err(PeeplabError::Artifacts(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_job_test_report_parses_archive_xml() {
+        let mut server = setup_mock_server().await;
+        let (archive, _) = zip_archive(0, "reports/junit.xml");
+        let total = archive.len();
+        server
+            .mock("GET", "/api/v4/projects/1/jobs/5/artifacts")
+            .with_status(206)
+            .with_header("content-range", &format!("bytes 0-{}/{}", total - 1, total))
+            .with_body(&archive)
+            .create_async()
+            .await;
+        let report = server
+            .mock("GET", "/api/v4/projects/1/jobs/5/artifacts/reports/junit.xml")
+            .with_status(200)
+            .with_body(r#"<testsuite><testcase name="ok"/><testcase name="ko"><failure message="boom"/></testcase></testsuite>"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let suite = client.get_job_test_report(1, 5, "rspec").await.unwrap();
+
+        report.assert_async().await;
+        assert_eq!((suite.name.as_str(), suite.total_count, suite.failed_count), ("rspec", 2, 1));
+        assert_eq!(suite.test_cases[0].name, "ko");
+    }
+
+    #[tokio::test]
+    async fn test_get_artifact_file() {
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("GET", "/api/v4/projects/1/jobs/5/artifacts/reports/unit%20tests.xml")
+            .with_status(200)
+            .with_body("<testsuite/>")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/v4/projects/1/jobs/5/artifacts/missing.txt")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let file = client.get_artifact_file(1, 5, "reports/unit tests.xml").await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(file, b"<testsuite/>");
+        assert!(matches!(
+            client.get_artifact_file(1, 5, "missing.txt").await,
+            Err(PeeplabError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_mr_pipelines_with_status() {
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/merge_requests/10/pipelines?per_page=20&page=1&status=failed")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("[]")
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let page = client.get_mr_pipelines(123, 10, 1, Some(&PipelineStatus::Failed)).await.unwrap();
+
+        mock.assert_async().await;
+        assert!(page.items.is_empty());
+        assert_eq!(page.next_page, None);
+    }
+
+    #[test]
+    fn test_next_page_from_link_header() {
+        let mut headers = header::HeaderMap::new();
+        assert_eq!(next_page(&headers), None);
+
+        headers.insert(
+            header::LINK,
+            header::HeaderValue::from_static(
+                "<https://gitlab.com/api/v4/projects/1/pipelines?page=1&per_page=20>; rel=\"prev\", \
+                 <https://gitlab.com/api/v4/projects/1/pipelines?page=3&per_page=20>; rel=\"next\"",
+            ),
+        );
+        assert_eq!(next_page(&headers), Some(3));
+
+        // X-Next-Page wins, and is empty on the last page
+        headers.insert("x-next-page", header::HeaderValue::from_static(""));
+        assert_eq!(next_page(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_job_success() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "id": 789,
+                "name": "test-job",
+                "status": "running",
+                "stage": "test",
+                "created_at": "2024-01-01T10:00:00Z",
+                "started_at": "2024-01-01T10:05:00Z",
+                "finished_at": null,
+                "duration": null,
+                "web_url": "https://gitlab.com/test/-/jobs/789"
+            }"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let result = client.get_job(123, 789).await;
+
+        mock.assert_async().await;
+        let job = result.unwrap();
+        assert_eq!(job.id, 789);
+        assert_eq!(job.status, crate::gitlab::JobStatus::Running);
+    }
+
+    #[tokio::test]
+    async fn test_get_current_user() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/user")
+            .match_header("PRIVATE-TOKEN", "test-token")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7, "username": "jdoe", "name": "Jane Doe", "state": "active"}"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let user = client.get_current_user().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(user.username, "jdoe");
+    }
+
+    #[tokio::test]
+    async fn test_get_group_projects() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/groups/acme%2Fplatform/projects")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("include_subgroups".into(), "true".into()),
+                mockito::Matcher::UrlEncoded("page".into(), "1".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("x-next-page", "2")
+            .with_body(r#"[{
+                "id": 5,
+                "name": "api",
+                "path": "api",
+                "path_with_namespace": "acme/platform/api",
+                "web_url": "https://gitlab.com/acme/platform/api"
+            }]"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let page = client.get_group_projects("acme/platform", 1).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(page.items[0].path_with_namespace, "acme/platform/api");
+        assert_eq!(page.next_page, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_api_under_a_proxy_prefix() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/gitlab/api/v4/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": 7, "username": "jdoe", "name": "Jane Doe", "state": "active"}"#)
+            .create_async()
+            .await;
+
+        let api_url = format!("{}/gitlab/api/v4/", server.url());
+        let client = GitLabClient::for_api(&api_url, "test-token", ClientTimeouts::default()).unwrap();
+        client.get_current_user().await.unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_job_trace_success() {
+        let mut server = setup_mock_server().await;
+
+        let trace_content = "Running tests...\nAll tests passed!";
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/trace")
+            .with_status(200)
+            .with_body(trace_content)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let result = client.get_job_trace(123, 789).await;
+
+        mock.assert_async().await;
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), trace_content);
+    }
+
+    #[tokio::test]
+    async fn test_get_job_trace_with_invalid_utf8() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/trace")
+            .with_status(200)
+            .with_header("content-type", "text/plain; charset=iso-8859-1")
+            .with_body(b"binary \xFF\xFE\x00 dump\nstill \xE2\x9C\x93 readable\n".as_slice())
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let trace = client.get_job_trace(123, 789).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(trace, "binary \u{FFFD}\u{FFFD}\x00 dump\nstill ✓ readable\n");
+    }
+
+    #[tokio::test]
+    async fn test_get_job_trace_not_found_is_expired() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/999/trace")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let result = client.get_job_trace(123, 999).await;
+
+        mock.assert_async().await;
+        assert!(matches!(result, Err(PeeplabError::LogExpired)));
+    }
+
+    #[tokio::test]
+    async fn test_get_job_trace_range_erased() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/trace")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let error = client.get_job_trace_range(123, 789, 0, 10).await.unwrap_err();
+
+        mock.assert_async().await;
+        assert!(matches!(error, PeeplabError::LogExpired));
+        assert_eq!(error.to_string(), "Log expired (job trace was erased)");
+    }
+
+    #[tokio::test]
+    async fn test_get_job_trace_range_forbidden_reports_status() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/trace")
+            .with_status(403)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let error = client.get_job_trace_range(123, 789, 0, 10).await.unwrap_err();
+
+        mock.assert_async().await;
+        assert!(matches!(error, PeeplabError::Authentication(_)));
+        assert!(error.to_string().contains("HTTP 403"));
+    }
+
+    #[tokio::test]
+    async fn test_get_job_trace_range_partial() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/trace")
+            .match_header("range", "bytes=0-9")
+            .with_status(206)
+            .with_header("content-range", "bytes 0-9/25")
+            .with_body("Running te")
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let chunk = client.get_job_trace_range(123, 789, 0, 10).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(chunk.data, b"Running te");
+        assert_eq!(chunk.total_size, Some(25));
+        assert!(!chunk.complete);
+    }
+
+    #[tokio::test]
+    async fn test_get_job_trace_range_last_chunk() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/trace")
+            .match_header("range", "bytes=20-29")
+            .with_status(206)
+            .with_header("content-range", "bytes 20-24/25")
+            .with_body("ssed!")
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let chunk = client.get_job_trace_range(123, 789, 20, 10).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(chunk.data, b"ssed!");
+        assert!(chunk.complete);
+    }
+
+    #[tokio::test]
+    async fn test_get_job_trace_range_ignored_by_server() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/trace")
+            .with_status(200)
+            .with_body("Running tests...")
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let chunk = client.get_job_trace_range(123, 789, 8, 4).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(chunk.data, b"tests...");
+        assert_eq!(chunk.total_size, Some(16));
+        assert!(chunk.complete);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_is_network_error() {
+        let mut server = setup_mock_server().await;
+        let _mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/456")
+            .with_status(200)
+            .with_chunked_body(|w| {
+                std::thread::sleep(Duration::from_millis(500));
+                w.write_all(b"{}")
+            })
+            .create_async()
+            .await;
+
+        let timeouts = ClientTimeouts {
+            connect: Duration::from_secs(1),
+            request: Duration::from_millis(100),
+        };
+        let client = GitLabClient::with_timeouts(&server.url(), "test-token", timeouts).unwrap();
+        let result = client.get_job(123, 456).await;
+
+        match result {
+            Err(PeeplabError::Network(msg)) => assert!(msg.contains("did not respond")),
+            other => panic!("expected a timeout, got {:?}", other.map(|job| job.id)),
+        }
+    }
+
+    #[tokio::test]
     async fn test_rate_limit_error() {
         let mut server = setup_mock_server().await;
 
         let mock = server
             .mock("GET", "/api/v4/projects/123/merge_requests?state=opened&per_page=20")
             .with_status(429)
+            .with_header("retry-after", "30")
+            .expect(1)
             .create_async()
             .await;
 
         let client = GitLabClient::new(&server.url(), "test-token").unwrap();
         let result = client.get_merge_requests(123).await;
+        assert!(matches!(
+            result,
+            Err(PeeplabError::RateLimited { retry_after }) if retry_after == Duration::from_secs(30)
+        ));
 
+        // Clones share the back-off and don't hit GitLab again until it ends
+        let result = client.clone().get_merge_requests(123).await;
+        assert!(matches!(result, Err(PeeplabError::RateLimited { .. })));
         mock.assert_async().await;
-        assert!(result.is_err());
-        match result.unwrap_err() {
-            PeeplabError::Network(msg) => {
-                assert!(msg.contains("rate limit"));
-            }
-            _ => panic!("Expected Network error for rate limit"),
-        }
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_rate_limit_backs_off_after_response() {
+        let mut server = setup_mock_server().await;
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/merge_requests?state=opened&per_page=20")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("ratelimit-remaining", "0")
+            .with_body("[]")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        assert!(client.get_merge_requests(123).await.unwrap().is_empty());
+        assert!(matches!(
+            client.get_merge_requests(123).await,
+            Err(PeeplabError::RateLimited { .. })
+        ));
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_rate_limit_delay() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut headers = header::HeaderMap::new();
+        assert_eq!(rate_limit_delay(&headers, now), DEFAULT_RATE_LIMIT_DELAY);
+
+        headers.insert("ratelimit-reset", header::HeaderValue::from_static("1045"));
+        assert_eq!(rate_limit_delay(&headers, now), Duration::from_secs(45));
+
+        // Retry-After wins, and a reset in the past still waits a moment
+        headers.insert(header::RETRY_AFTER, header::HeaderValue::from_static("7"));
+        assert_eq!(rate_limit_delay(&headers, now), Duration::from_secs(7));
+        headers.remove(header::RETRY_AFTER);
+        assert_eq!(rate_limit_delay(&headers, now + Duration::from_secs(100)), Duration::from_secs(1));
+
+        // Absurd values are capped rather than overflowing
+        headers.insert("ratelimit-reset", header::HeaderValue::from_static("18446744073709551615"));
+        assert_eq!(rate_limit_delay(&headers, now), MAX_RATE_LIMIT_DELAY);
+        headers.insert(header::RETRY_AFTER, header::HeaderValue::from_static("18446744073709551615"));
+        assert_eq!(rate_limit_delay(&headers, now), MAX_RATE_LIMIT_DELAY);
     }
 
     #[tokio::test]
@@ -425,4 +1606,432 @@ mod tests {
             _ => panic!("Expected NotFound error"),
         }
     }
+
+    #[tokio::test]
+    async fn test_retry_job_success() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("POST", "/api/v4/projects/123/jobs/789/retry")
+            .match_header("PRIVATE-TOKEN", "test-token")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "id": 790,
+                "name": "test-job",
+                "status": "pending",
+                "stage": "test",
+                "created_at": "2024-01-01T10:00:00Z",
+                "started_at": null,
+                "finished_at": null,
+                "duration": null,
+                "web_url": "https://gitlab.com/test/-/jobs/790"
+            }"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let job = client.retry_job(123, 789).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(job.id, 790);
+        assert_eq!(job.status, crate::gitlab::JobStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_retry_job_forbidden() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("POST", "/api/v4/projects/123/jobs/789/retry")
+            .with_status(403)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let result = client.retry_job(123, 789).await;
+
+        mock.assert_async().await;
+        match result.unwrap_err() {
+            PeeplabError::Authentication(message) => assert!(message.contains("permissions")),
+            other => panic!("Expected Authentication error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_pipeline() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("POST", "/api/v4/projects/123/pipelines/456/retry")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "id": 456,
+                "iid": 78,
+                "status": "pending",
+                "ref": "main",
+                "created_at": "2024-01-01T10:00:00Z",
+                "updated_at": "2024-01-01T11:00:00Z",
+                "web_url": "https://gitlab.com/test/repo/-/pipelines/456"
+            }"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let pipeline = client.retry_pipeline(123, 456).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(pipeline.status, crate::gitlab::PipelineStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_play_job_with_variables() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("POST", "/api/v4/projects/123/jobs/789/play")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "job_variables_attributes": [{"key": "TARGET", "value": "prod"}]
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "id": 789,
+                "name": "deploy",
+                "status": "pending",
+                "stage": "deploy",
+                "created_at": "2024-01-01T10:00:00Z",
+                "started_at": null,
+                "finished_at": null,
+                "duration": null,
+                "web_url": "https://gitlab.com/test/-/jobs/789"
+            }"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let variables = vec![("TARGET".to_string(), "prod".to_string())];
+        let job = client.play_job(123, 789, &variables).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(job.status, crate::gitlab::JobStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_play_job_errors() {
+        let mut server = setup_mock_server().await;
+
+        let _forbidden = server
+            .mock("POST", "/api/v4/projects/123/jobs/789/play")
+            .with_status(403)
+            .create_async()
+            .await;
+        let _unplayable = server
+            .mock("POST", "/api/v4/projects/123/jobs/790/play")
+            .with_status(400)
+            .with_body(r#"{"message": "400 Bad request - Unplayable Job"}"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        match client.play_job(123, 789, &[]).await.unwrap_err() {
+            PeeplabError::Authentication(message) => assert!(message.contains("permissions")),
+            other => panic!("Expected Authentication error, got {:?}", other),
+        }
+        match client.play_job(123, 790, &[]).await.unwrap_err() {
+            PeeplabError::Rejected(message) => assert!(message.contains("Unplayable")),
+            other => panic!("Expected Rejected error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_job_returns_final_status() {
+        let mut server = setup_mock_server().await;
+
+        // GitLab answers with the job as is when it already finished
+        let mock = server
+            .mock("POST", "/api/v4/projects/123/jobs/789/cancel")
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "id": 789,
+                "name": "test-job",
+                "status": "success",
+                "stage": "test",
+                "created_at": "2024-01-01T10:00:00Z",
+                "started_at": "2024-01-01T10:05:00Z",
+                "finished_at": "2024-01-01T10:06:00Z",
+                "duration": 60.0,
+                "web_url": "https://gitlab.com/test/-/jobs/789"
+            }"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let job = client.cancel_job(123, 789).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(job.status, crate::gitlab::JobStatus::Success);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_pipeline_success() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("POST", "/api/v4/projects/123/pipelines/456/cancel")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "id": 456,
+                "iid": 78,
+                "status": "canceled",
+                "ref": "main",
+                "created_at": "2024-01-01T10:00:00Z",
+                "updated_at": "2024-01-01T11:00:00Z",
+                "web_url": "https://gitlab.com/test/repo/-/pipelines/456"
+            }"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let pipeline = client.cancel_pipeline(123, 456).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(pipeline.status, crate::gitlab::PipelineStatus::Canceled);
+    }
+
+    #[tokio::test]
+    async fn test_get_pipeline_needs_follows_pages() {
+        let mut server = setup_mock_server().await;
+
+        let first = server
+            .mock("POST", "/api/graphql")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "variables": { "project": "group/repo", "iid": "78", "after": null }
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"project": {"pipeline": {"jobs": {
+                "pageInfo": {"hasNextPage": true, "endCursor": "abc"},
+                "nodes": [
+                    {"name": "build", "needs": {"nodes": []}},
+                    {"name": "unit", "needs": {"nodes": [{"name": "build"}]}}
+                ]
+            }}}}}"#)
+            .create_async()
+            .await;
+        let second = server
+            .mock("POST", "/api/graphql")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({ "variables": { "after": "abc" } })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"project": {"pipeline": {"jobs": {
+                "pageInfo": {"hasNextPage": false, "endCursor": null},
+                "nodes": [{"name": "deploy", "needs": {"nodes": [{"name": "build"}, {"name": "unit"}]}}]
+            }}}}}"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let needs = client.get_pipeline_needs("group/repo", 78).await.unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+        assert_eq!(needs["build"], Vec::<String>::new());
+        assert_eq!(needs["deploy"], vec!["build", "unit"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_pipeline_needs_errors() {
+        let mut server = setup_mock_server().await;
+        let _mock = server
+            .mock("POST", "/api/graphql")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": {"project": {"pipeline": null}}}"#)
+            .create_async()
+            .await;
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let error = client.get_pipeline_needs("group/repo", 78).await.unwrap_err();
+        assert!(matches!(error, PeeplabError::NotFound(_)));
+
+        let _mock = server
+            .mock("POST", "/api/graphql")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data": null, "errors": [{"message": "Field 'needs' doesn't exist"}]}"#)
+            .create_async()
+            .await;
+        let error = client.get_pipeline_needs("group/repo", 78).await.unwrap_err();
+        assert!(matches!(error, PeeplabError::Rejected(message) if message.contains("needs")));
+    }
+
+    #[tokio::test]
+    async fn test_download_job_artifacts() {
+        let mut server = setup_mock_server().await;
+        let archive = vec![7u8; 4096];
+
+        let mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/artifacts")
+            .with_status(200)
+            .with_body(archive.clone())
+            .create_async()
+            .await;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("artifacts.zip");
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let mut reports = Vec::new();
+        let finished = client
+            .download_job_artifacts(123, 789, &path, &AtomicBool::new(false), |received, total| {
+                reports.push((received, total))
+            })
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert!(finished);
+        assert_eq!(std::fs::read(&path).unwrap(), archive);
+        assert_eq!(reports.last(), Some(&(4096, Some(4096))));
+    }
+
+    #[tokio::test]
+    async fn test_download_job_artifacts_canceled() {
+        let mut server = setup_mock_server().await;
+
+        let _mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/artifacts")
+            .with_status(200)
+            .with_body(vec![7u8; 4096])
+            .create_async()
+            .await;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("artifacts.zip");
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let finished = client
+            .download_job_artifacts(123, 789, &path, &AtomicBool::new(true), |_, _| {})
+            .await
+            .unwrap();
+
+        assert!(!finished);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_download_job_artifacts_missing() {
+        let mut server = setup_mock_server().await;
+
+        let _mock = server
+            .mock("GET", "/api/v4/projects/123/jobs/789/artifacts")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("artifacts.zip");
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let result = client
+            .download_job_artifacts(123, 789, &path, &AtomicBool::new(false), |_, _| {})
+            .await;
+
+        assert!(matches!(result, Err(PeeplabError::NotFound(_))));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(encode_path_segment("feature/login-v2"), "feature%2Flogin-v2");
+        assert_eq!(encode_path_segment("v1.0"), "v1.0");
+    }
+
+    #[test]
+    fn test_gitlab_error_message() {
+        assert_eq!(
+            gitlab_error_message(r#"{"message": {"base": ["Reference not found"]}}"#),
+            Some("Reference not found".to_string())
+        );
+        assert_eq!(
+            gitlab_error_message(r#"{"message": "400 Bad request - ref is missing"}"#),
+            Some("400 Bad request - ref is missing".to_string())
+        );
+        assert_eq!(gitlab_error_message("not json"), None);
+    }
+
+    #[tokio::test]
+    async fn test_ref_exists() {
+        let mut server = setup_mock_server().await;
+
+        let found = server
+            .mock("GET", "/api/v4/projects/123/repository/commits/feature%2Flogin")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": "abc123"}"#)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("GET", "/api/v4/projects/123/repository/commits/nope")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        assert!(client.ref_exists(123, "feature/login").await.unwrap());
+        assert!(!client.ref_exists(123, "nope").await.unwrap());
+        found.assert_async().await;
+        missing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_create_pipeline_with_variables() {
+        let mut server = setup_mock_server().await;
+
+        let mock = server
+            .mock("POST", "/api/v4/projects/123/pipeline")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "ref": "main",
+                "variables": [{"key": "DEPLOY", "value": "staging"}]
+            })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "id": 456,
+                "iid": 78,
+                "status": "created",
+                "ref": "main",
+                "created_at": "2024-01-01T10:00:00Z",
+                "updated_at": "2024-01-01T10:00:00Z",
+                "web_url": "https://gitlab.com/test/repo/-/pipelines/456"
+            }"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        let variables = vec![("DEPLOY".to_string(), "staging".to_string())];
+        let pipeline = client.create_pipeline(123, "main", &variables).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(pipeline.id, 456);
+        assert_eq!(pipeline.status, crate::gitlab::PipelineStatus::Created);
+    }
+
+    #[tokio::test]
+    async fn test_create_pipeline_rejected() {
+        let mut server = setup_mock_server().await;
+
+        let _mock = server
+            .mock("POST", "/api/v4/projects/123/pipeline")
+            .with_status(400)
+            .with_body(r#"{"message": {"base": ["No stages / jobs for this pipeline."]}}"#)
+            .create_async()
+            .await;
+
+        let client = GitLabClient::new(&server.url(), "test-token").unwrap();
+        match client.create_pipeline(123, "main", &[]).await.unwrap_err() {
+            PeeplabError::Rejected(message) => assert_eq!(message, "No stages / jobs for this pipeline."),
+            other => panic!("Expected Rejected error, got {:?}", other),
+        }
+    }
 }
@@ -1,5 +1,5 @@
 pub mod client;
 pub mod models;
 
-pub use client::GitLabClient;
-pub use models::{Job, JobStatus, MergeRequest, Note, Pipeline, PipelineStatus};
+pub use client::{ClientTimeouts, GitLabClient};
+pub use models::{ArtifactsFile, Job, JobArtifact, JobStatus, MergeRequest, Note, Page, Pipeline, PipelineStatus, Project, TestCase, TestSuite, TraceChunk, User};
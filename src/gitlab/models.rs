@@ -10,7 +10,7 @@ pub struct Project {
     pub web_url: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct MergeRequest {
     pub id: u64,
     pub iid: u64,
@@ -18,11 +18,13 @@ pub struct MergeRequest {
     pub author: User,
     pub state: String,
     pub web_url: String,
+    #[serde(default)]
+    pub source_branch: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Pipeline {
     pub id: u64,
     pub iid: u64,
@@ -32,11 +34,61 @@ pub struct Pipeline {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub web_url: String,
+    #[serde(default)]
+    pub source: Option<String>, // What started it: "push", "schedule", "merge_request_event", "api", "web", ...
+    #[serde(default)]
+    pub user: Option<User>, // Who started it, not listed by every endpoint
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+impl Pipeline {
+    /// What and who started the pipeline, e.g. `schedule · alice` or `MR !12 · bob`
+    pub fn trigger(&self) -> Option<String> {
+        let source = self.source.as_deref().map(|source| match source {
+            "merge_request_event" => match merge_request_ref(&self.ref_name) {
+                Some(iid) => format!("MR !{}", iid),
+                None => "merge request".to_string(),
+            },
+            "web" => "manual".to_string(),
+            "parent_pipeline" => "child pipeline".to_string(),
+            "pipeline" => "multi-project".to_string(),
+            other => other.replace('_', " "),
+        });
+        let user = self.user.as_ref().map(|user| user.username.clone());
+        match (source, user) {
+            (Some(source), Some(user)) => Some(format!("{} · {}", source, user)),
+            (source, user) => source.or(user),
+        }
+    }
+
+    /// IID of the merge request a merge request pipeline ran for
+    pub fn merge_request_iid(&self) -> Option<u64> {
+        merge_request_ref(&self.ref_name)
+    }
+}
+
+/// IID of the merge request a `refs/merge-requests/<iid>/head` (or `/merge`, `/train`) ref belongs to
+fn merge_request_ref(ref_name: &str) -> Option<u64> {
+    ref_name.strip_prefix("refs/merge-requests/")?.split('/').next()?.parse().ok()
+}
+
+/// Glyph for a job or pipeline status given by its API name, shared so every view agrees
+pub fn status_symbol(status: &str) -> &'static str {
+    match status {
+        "success" => "✓",
+        "failed" => "✗",
+        "running" => "⟳",
+        "pending" | "created" | "preparing" | "waiting_for_resource" | "scheduled" => "○",
+        "canceled" | "canceling" => "⊘",
+        "skipped" => "⊝",
+        "manual" => "▶",
+        _ => "•",
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum PipelineStatus {
+    #[default]
     Created,
     WaitingForResource,
     Preparing,
@@ -44,6 +96,7 @@ pub enum PipelineStatus {
     Running,
     Success,
     Failed,
+    Canceling, // Cancel requested, jobs still stopping
     Canceled,
     Skipped,
     Manual,
@@ -51,19 +104,41 @@ pub enum PipelineStatus {
 
 impl PipelineStatus {
     pub fn symbol(&self) -> &'static str {
+        status_symbol(self.api_name())
+    }
+
+    /// Name used by the API, e.g. in the `status` query parameter
+    pub fn api_name(&self) -> &'static str {
         match self {
-            PipelineStatus::Success => "✓",
-            PipelineStatus::Failed => "✗",
-            PipelineStatus::Running => "⟳",
-            PipelineStatus::Pending | PipelineStatus::Created | PipelineStatus::Preparing => "○",
-            PipelineStatus::Canceled => "⊘",
-            PipelineStatus::Skipped => "⊝",
-            _ => "•",
+            PipelineStatus::Created => "created",
+            PipelineStatus::WaitingForResource => "waiting_for_resource",
+            PipelineStatus::Preparing => "preparing",
+            PipelineStatus::Pending => "pending",
+            PipelineStatus::Running => "running",
+            PipelineStatus::Success => "success",
+            PipelineStatus::Failed => "failed",
+            PipelineStatus::Canceling => "canceling",
+            PipelineStatus::Canceled => "canceled",
+            PipelineStatus::Skipped => "skipped",
+            PipelineStatus::Manual => "manual",
         }
     }
+
+    /// Whether the pipeline can still change status
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            PipelineStatus::Created
+                | PipelineStatus::WaitingForResource
+                | PipelineStatus::Preparing
+                | PipelineStatus::Pending
+                | PipelineStatus::Running
+                | PipelineStatus::Canceling
+        )
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Job {
     pub id: u64,
     pub name: String,
@@ -73,17 +148,122 @@ pub struct Job {
     pub started_at: Option<DateTime<Utc>>,
     pub finished_at: Option<DateTime<Utc>>,
     pub duration: Option<f64>,
+    #[serde(default)]
+    pub queued_duration: Option<f64>, // Seconds spent waiting for a runner
+    #[serde(default)]
+    pub user: Option<User>, // Who started or last retried it
     pub web_url: String,
+    #[serde(default)]
+    pub artifacts_file: Option<ArtifactsFile>, // Absent when the job kept no artifacts archive
+    #[serde(default)]
+    pub artifacts: Vec<JobArtifact>, // Archive, trace and reports the job uploaded
+    #[serde(default)]
+    pub erased_at: Option<DateTime<Utc>>, // When its trace and artifacts were erased
+}
+
+impl Job {
+    /// Whether the job uploaded a JUnit report (`artifacts:reports:junit`)
+    pub fn has_test_report(&self) -> bool {
+        self.artifacts.iter().any(|artifact| artifact.file_type == "junit")
+    }
+
+    /// Seconds the job ran, counting up from its start while it is still running
+    pub fn run_seconds(&self, now: DateTime<Utc>) -> Option<f64> {
+        match (&self.status, self.started_at) {
+            (JobStatus::Running, Some(started_at)) => Some(seconds_between(started_at, now)),
+            _ => self.duration,
+        }
+    }
+
+    /// Seconds the job waited for a runner, counting up while it is still pending
+    pub fn queued_seconds(&self, now: DateTime<Utc>) -> Option<f64> {
+        match self.status {
+            JobStatus::Pending if self.queued_duration.is_none() => Some(seconds_between(self.created_at, now)),
+            _ => self.queued_duration,
+        }
+    }
+}
+
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds().max(0) as f64 / 1000.0
+}
+
+/// A duration as short as it reads well: `45s`, `3m12s`, `1h05m`
+pub fn compact_duration(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArtifactsFile {
+    pub filename: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobArtifact {
+    pub file_type: String, // "archive", "trace", "junit", ...
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct TestSuite {
+    pub name: String,
+    pub total_count: u64,
+    pub success_count: u64,
+    pub failed_count: u64,
+    pub skipped_count: u64,
+    pub error_count: u64,
+    #[serde(default)]
+    pub test_cases: Vec<TestCase>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct TestCase {
+    pub status: String, // "success", "failed", "skipped" or "error"
+    pub name: String,
+    #[serde(default)]
+    pub classname: Option<String>,
+    #[serde(default)]
+    pub execution_time: f64,
+    #[serde(default)]
+    pub system_output: Option<String>, // Failure message
+    #[serde(default)]
+    pub stack_trace: Option<String>,
+}
+
+impl TestCase {
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status.as_str(), "failed" | "error")
+    }
+
+    /// Failure message and stack trace, whichever were reported
+    pub fn message(&self) -> Option<String> {
+        let parts: Vec<&str> = [&self.system_output, &self.stack_trace]
+            .into_iter()
+            .flatten()
+            .map(|text| text.trim())
+            .filter(|text| !text.is_empty())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n"))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum JobStatus {
+    #[default]
     Created,
     Pending,
     Running,
     Success,
     Failed,
+    Canceling, // Cancel requested, runner still stopping the job
     Canceled,
     Skipped,
     Manual,
@@ -91,19 +271,34 @@ pub enum JobStatus {
 
 impl JobStatus {
     pub fn symbol(&self) -> &'static str {
+        status_symbol(self.api_name())
+    }
+
+    /// Name used by the API
+    pub fn api_name(&self) -> &'static str {
         match self {
-            JobStatus::Success => "✓",
-            JobStatus::Failed => "✗",
-            JobStatus::Running => "⟳",
-            JobStatus::Pending | JobStatus::Created => "○",
-            JobStatus::Canceled => "⊘",
-            JobStatus::Skipped => "⊝",
-            JobStatus::Manual => "⊙",
+            JobStatus::Created => "created",
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Success => "success",
+            JobStatus::Failed => "failed",
+            JobStatus::Canceling => "canceling",
+            JobStatus::Canceled => "canceled",
+            JobStatus::Skipped => "skipped",
+            JobStatus::Manual => "manual",
         }
     }
+
+    /// Whether the job can still change status
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Created | JobStatus::Pending | JobStatus::Running | JobStatus::Canceling
+        )
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct User {
     pub id: u64,
     pub username: String,
@@ -149,6 +344,84 @@ pub struct Note {
     pub position: Option<Position>,
 }
 
+/// One page of a paginated list endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_page: Option<u32>, // None on the last page
+}
+
+/// Answer of the GraphQL API, which reports failures as `errors` next to whatever data it could get
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphQlResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphQlError {
+    pub message: String,
+}
+
+/// A GraphQL list, one page of it with the cursor to the next
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection<T> {
+    pub nodes: Vec<T>,
+    #[serde(default)]
+    pub page_info: Option<PageInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    pub has_next_page: bool,
+    pub end_cursor: Option<String>,
+}
+
+/// `project { pipeline { jobs { name needs } } }`: the REST job list doesn't say what jobs need
+#[derive(Debug, Clone, Deserialize)]
+pub struct PipelineNeedsData {
+    pub project: Option<PipelineNeedsProject>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PipelineNeedsProject {
+    pub pipeline: Option<PipelineNeedsPipeline>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PipelineNeedsPipeline {
+    pub jobs: Connection<JobNeeds>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobNeeds {
+    pub name: String,
+    #[serde(default)]
+    pub needs: Option<Connection<NeededJob>>, // Empty for jobs that run after the previous stage
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NeededJob {
+    pub name: String,
+}
+
+impl JobNeeds {
+    pub fn need_names(&self) -> Vec<String> {
+        self.needs.iter().flat_map(|needs| needs.nodes.iter().map(|need| need.name.clone())).collect()
+    }
+}
+
+/// A byte range of a job trace returned by a ranged request
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceChunk {
+    pub data: Vec<u8>,
+    pub total_size: Option<u64>, // Full trace size when the server reports it
+    pub complete: bool,          // No more data after this chunk
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,6 +451,27 @@ mod tests {
         assert_eq!(PipelineStatus::Skipped.symbol(), "⊝");
     }
 
+    #[test]
+    fn test_job_test_report_detection() {
+        let json = r#"{"id": 1, "name": "rspec", "status": "failed", "stage": "test",
+            "created_at": "2024-01-01T10:00:00Z", "started_at": null, "finished_at": null, "duration": null,
+            "web_url": "https://gitlab.com/test/-/jobs/1",
+            "artifacts": [{"file_type": "trace", "size": 10}, {"file_type": "junit", "size": 2048}]}"#;
+        let job: Job = serde_json::from_str(json).unwrap();
+        assert!(job.has_test_report());
+    }
+
+    #[test]
+    fn test_active_statuses() {
+        assert!(PipelineStatus::Running.is_active());
+        assert!(PipelineStatus::WaitingForResource.is_active());
+        assert!(!PipelineStatus::Success.is_active());
+        assert!(!PipelineStatus::Manual.is_active());
+        assert!(JobStatus::Pending.is_active());
+        assert!(!JobStatus::Failed.is_active());
+        assert!(!JobStatus::Manual.is_active());
+    }
+
     #[test]
     fn test_job_status_deserialization() {
         let json = r#""success""#;
@@ -191,6 +485,10 @@ mod tests {
         let json = r#""running""#;
         let status: JobStatus = serde_json::from_str(json).unwrap();
         assert_eq!(status, JobStatus::Running);
+
+        let json = r#""canceling""#;
+        let status: JobStatus = serde_json::from_str(json).unwrap();
+        assert_eq!(status, JobStatus::Canceling);
     }
 
     #[test]
@@ -201,7 +499,8 @@ mod tests {
         assert_eq!(JobStatus::Pending.symbol(), "○");
         assert_eq!(JobStatus::Canceled.symbol(), "⊘");
         assert_eq!(JobStatus::Skipped.symbol(), "⊝");
-        assert_eq!(JobStatus::Manual.symbol(), "⊙");
+        assert_eq!(JobStatus::Manual.symbol(), "▶");
+        assert_eq!(PipelineStatus::Manual.symbol(), JobStatus::Manual.symbol());
     }
 
     #[test]
@@ -246,6 +545,34 @@ mod tests {
         assert_eq!(pipeline.iid, 78);
         assert_eq!(pipeline.status, PipelineStatus::Success);
         assert_eq!(pipeline.ref_name, "main");
+        assert!(pipeline.trigger().is_none());
+    }
+
+    #[test]
+    fn test_pipeline_trigger() {
+        let json = r#"{
+            "id": 456,
+            "iid": 78,
+            "status": "running",
+            "ref": "refs/merge-requests/12/head",
+            "created_at": "2024-01-01T10:00:00Z",
+            "updated_at": "2024-01-01T11:00:00Z",
+            "web_url": "https://gitlab.com/test/repo/-/pipelines/456",
+            "source": "merge_request_event",
+            "user": {"id": 1, "username": "alice", "name": "Alice"}
+        }"#;
+        let mut pipeline: Pipeline = serde_json::from_str(json).unwrap();
+        assert_eq!(pipeline.trigger().as_deref(), Some("MR !12 · alice"));
+
+        pipeline.ref_name = "main".to_string();
+        assert_eq!(pipeline.trigger().as_deref(), Some("merge request · alice"));
+        pipeline.source = Some("web".to_string());
+        assert_eq!(pipeline.trigger().as_deref(), Some("manual · alice"));
+        pipeline.user = None;
+        pipeline.source = Some("external_pull_request_event".to_string());
+        assert_eq!(pipeline.trigger().as_deref(), Some("external pull request event"));
+        pipeline.source = None;
+        assert!(pipeline.trigger().is_none());
     }
 
     #[test]
@@ -259,7 +586,8 @@ mod tests {
             "started_at": "2024-01-01T10:05:00Z",
             "finished_at": "2024-01-01T10:10:00Z",
             "duration": 300.5,
-            "web_url": "https://gitlab.com/test/repo/-/jobs/789"
+            "web_url": "https://gitlab.com/test/repo/-/jobs/789",
+            "erased_at": "2024-03-01T00:00:00Z"
         }"#;
 
         let job: Job = serde_json::from_str(json).unwrap();
@@ -268,6 +596,65 @@ mod tests {
         assert_eq!(job.status, JobStatus::Failed);
         assert_eq!(job.stage, "test");
         assert_eq!(job.duration, Some(300.5));
+        assert!(job.queued_duration.is_none());
+        assert!(job.artifacts_file.is_none());
+        assert!(job.erased_at.is_some());
+    }
+
+    #[test]
+    fn test_job_run_and_queue_times() {
+        let json = r#"{
+            "id": 789,
+            "name": "test-job",
+            "status": "running",
+            "stage": "test",
+            "created_at": "2024-01-01T10:00:00Z",
+            "started_at": "2024-01-01T10:00:45Z",
+            "finished_at": null,
+            "duration": null,
+            "queued_duration": 45.2,
+            "web_url": "https://gitlab.com/test/repo/-/jobs/789"
+        }"#;
+        let mut job: Job = serde_json::from_str(json).unwrap();
+        let now: DateTime<Utc> = "2024-01-01T10:03:57Z".parse().unwrap();
+        // A running job's time counts up from its start
+        assert_eq!(job.run_seconds(now), Some(192.0));
+        assert_eq!(job.queued_seconds(now), Some(45.2));
+
+        job.status = JobStatus::Pending;
+        job.started_at = None;
+        job.queued_duration = None;
+        assert_eq!(job.run_seconds(now), None);
+        assert_eq!(job.queued_seconds(now), Some(237.0));
+    }
+
+    #[test]
+    fn test_compact_duration() {
+        assert_eq!(compact_duration(0.4), "0s");
+        assert_eq!(compact_duration(45.9), "45s");
+        assert_eq!(compact_duration(192.0), "3m12s");
+        assert_eq!(compact_duration(3900.0), "1h05m");
+    }
+
+    #[test]
+    fn test_job_with_artifacts() {
+        let json = r#"{
+            "id": 789,
+            "name": "build",
+            "status": "success",
+            "stage": "build",
+            "created_at": "2024-01-01T10:00:00Z",
+            "started_at": "2024-01-01T10:05:00Z",
+            "finished_at": "2024-01-01T10:10:00Z",
+            "duration": 300.5,
+            "web_url": "https://gitlab.com/test/repo/-/jobs/789",
+            "artifacts_file": {"filename": "artifacts.zip", "size": 1024}
+        }"#;
+
+        let job: Job = serde_json::from_str(json).unwrap();
+        let artifacts = job.artifacts_file.unwrap();
+        assert_eq!(artifacts.filename, "artifacts.zip");
+        assert_eq!(artifacts.size, 1024);
     }
 
     #[test]
@@ -289,6 +676,7 @@ mod tests {
         assert!(job.started_at.is_none());
         assert!(job.finished_at.is_none());
         assert!(job.duration.is_none());
+        assert!(job.erased_at.is_none());
     }
 
     #[test]
@@ -0,0 +1,164 @@
+use crate::artifacts::{ArtifactEntry, MAX_VIEWED_FILE_SIZE};
+use crate::gitlab::{TestCase, TestSuite};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Most XML files of an artifacts archive downloaded looking for JUnit reports
+pub const MAX_REPORT_FILES: usize = 20;
+
+/// Whether an archive file may be a JUnit report worth downloading
+pub fn is_report_candidate(entry: &ArtifactEntry) -> bool {
+    entry.path.to_ascii_lowercase().ends_with(".xml") && entry.size <= MAX_VIEWED_FILE_SIZE
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
+
+fn new_case(element: &BytesStart) -> TestCase {
+    TestCase {
+        status: "success".to_string(),
+        name: attribute(element, "name").unwrap_or_default(),
+        classname: attribute(element, "classname"),
+        execution_time: attribute(element, "time").and_then(|time| time.parse().ok()).unwrap_or_default(),
+        system_output: None,
+        stack_trace: None,
+    }
+}
+
+/// Test cases of a JUnit XML report, in report order. A `<failure>` or `<error>` child fails a
+/// case, its message becomes the case's output and its text the stack trace; `<skipped>` skips it
+pub fn parse_report(xml: &str) -> Result<Vec<TestCase>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut cases = Vec::new();
+    let mut is_report = false;
+    let mut case: Option<TestCase> = None;
+    let mut in_failure = false;
+
+    loop {
+        let event = reader.read_event().map_err(|e| e.to_string())?;
+        match &event {
+            Event::Start(element) | Event::Empty(element) => {
+                let empty = matches!(event, Event::Empty(_));
+                match element.local_name().as_ref() {
+                    b"testsuites" | b"testsuite" => is_report = true,
+                    b"testcase" if empty => cases.push(new_case(element)),
+                    b"testcase" => case = Some(new_case(element)),
+                    name @ (b"failure" | b"error" | b"skipped") => {
+                        if let Some(case) = case.as_mut().filter(|case| !case.is_failure()) {
+                            case.status = match name {
+                                b"failure" => "failed",
+                                b"error" => "error",
+                                _ => "skipped",
+                            }
+                            .to_string();
+                            case.system_output = attribute(element, "message");
+                            in_failure = !empty && case.is_failure();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(text) if in_failure => {
+                let text = text.unescape().map_err(|e| e.to_string())?;
+                if let Some(case) = case.as_mut() {
+                    case.stack_trace.get_or_insert_with(String::new).push_str(&text);
+                }
+            }
+            Event::CData(text) if in_failure => {
+                if let Some(case) = case.as_mut() {
+                    case.stack_trace.get_or_insert_with(String::new).push_str(&String::from_utf8_lossy(text));
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"failure" | b"error" => in_failure = false,
+                b"testcase" => cases.extend(case.take()),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !is_report {
+        return Err("not a JUnit report".to_string());
+    }
+    Ok(cases)
+}
+
+/// A job's results from the cases of all its reports: failures first, then skipped tests, each in
+/// report order
+pub fn suite(name: &str, mut cases: Vec<TestCase>) -> TestSuite {
+    let count = |status: &str| cases.iter().filter(|case| case.status == status).count() as u64;
+    let mut suite = TestSuite {
+        name: name.to_string(),
+        total_count: cases.len() as u64,
+        success_count: count("success"),
+        failed_count: count("failed"),
+        skipped_count: count("skipped"),
+        error_count: count("error"),
+        test_cases: Vec::new(),
+    };
+    cases.sort_by_key(|case| match case.status.as_str() {
+        "failed" | "error" => 0,
+        "skipped" => 1,
+        _ => 2,
+    });
+    suite.test_cases = cases;
+    suite
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="calc" tests="4">
+    <testcase name="adds" classname="Calc" time="0.1"/>
+    <testcase name="divides" classname="Calc" time="0.25">
+      <failure message="expected 2, got 3" type="AssertionError">at calc.rs:12 &amp; more</failure>
+    </testcase>
+    <testcase name="pending"><skipped/></testcase>
+    <testcase name="crashes"><error message="panicked"><![CDATA[<stack>]]></error><system-out>noise</system-out></testcase>
+  </testsuite>
+</testsuites>"#;
+
+    #[test]
+    fn test_parse_report() {
+        let cases = parse_report(REPORT).unwrap();
+        let summary: Vec<(&str, &str)> = cases.iter().map(|case| (case.name.as_str(), case.status.as_str())).collect();
+        assert_eq!(summary, vec![("adds", "success"), ("divides", "failed"), ("pending", "skipped"), ("crashes", "error")]);
+        assert_eq!(cases[1].classname.as_deref(), Some("Calc"));
+        assert_eq!(cases[1].execution_time, 0.25);
+        assert_eq!(cases[1].message().as_deref(), Some("expected 2, got 3\nat calc.rs:12 & more"));
+        assert_eq!(cases[3].message().as_deref(), Some("panicked\n<stack>"));
+
+        // A lone suite is a report too, other XML isn't
+        assert_eq!(parse_report(r#"<testsuite name="empty"/>"#).unwrap(), vec![]);
+        assert!(parse_report("<project><modelVersion/></project>").is_err());
+        assert!(parse_report("<testsuite><testcase></testsuite>").is_err());
+    }
+
+    #[test]
+    fn test_suite_lists_failures_first() {
+        let suite = suite("rspec", parse_report(REPORT).unwrap());
+        assert_eq!((suite.total_count, suite.success_count, suite.failed_count), (4, 1, 1));
+        assert_eq!((suite.skipped_count, suite.error_count), (1, 1));
+        let names: Vec<&str> = suite.test_cases.iter().map(|case| case.name.as_str()).collect();
+        assert_eq!(names, vec!["divides", "crashes", "pending", "adds"]);
+    }
+
+    #[test]
+    fn test_report_candidates() {
+        let entry = |path: &str, size| ArtifactEntry { path: path.to_string(), size };
+        assert!(is_report_candidate(&entry("reports/JUNIT.XML", 10)));
+        assert!(!is_report_candidate(&entry("coverage/index.html", 10)));
+        assert!(!is_report_candidate(&entry("huge.xml", MAX_VIEWED_FILE_SIZE + 1)));
+    }
+}
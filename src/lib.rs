@@ -1,10 +1,21 @@
 // Library interface for peeplab - enables testing of internal modules
 pub mod app;
+pub mod artifacts;
+pub mod browser;
+pub mod cache;
+pub mod clipboard;
+pub mod color;
 pub mod config;
+pub mod diagnostics;
 pub mod editor;
 pub mod error;
 pub mod events;
+pub mod export;
 pub mod git;
 pub mod gitlab;
+pub mod junit;
+pub mod log_buffer;
+pub mod log_diff;
 pub mod log_processor;
+pub mod notify;
 pub mod ui;
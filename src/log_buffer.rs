@@ -0,0 +1,522 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File, OpenOptions};
+use std::hash::Hasher;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Text of a log kept in memory. Past it the log moves to a temporary file and only the part
+/// around the viewport stays in memory
+pub const RESIDENT_LIMIT: usize = 8 * 1024 * 1024;
+
+/// Bytes read at once from the file for text outside the resident part, so scans going through
+/// the log line by line don't read every line on its own
+const READ_AHEAD: usize = 256 * 1024;
+
+/// Job log text that can grow chunk by chunk, with a lazily built line index
+///
+/// Once the text outgrows `resident_limit` it is written to a temporary file, and only a window
+/// of whole lines, moved with [`LogBuffer::keep_resident`], stays in memory
+#[derive(Debug)]
+pub struct LogBuffer {
+    resident: Arc<String>, // The whole text, or lines from `resident_from` on once spilled. Copied if shared when appended to
+    resident_from: usize,  // Byte offset of `resident` in the text, always a line start
+    len: usize,            // Bytes of decoded text
+    spill: Option<SpillFile>, // The whole text, once it outgrew `resident_limit`
+    resident_limit: usize,
+    read_ahead: RefCell<(usize, String)>, // Text last read from the spill file and its offset
+    pending_bytes: Vec<u8>, // Incomplete UTF-8 sequence split across chunk boundaries
+    received_bytes: usize,  // Trace bytes appended, more or fewer than `len` once invalid ones are replaced
+    line_starts: RefCell<Vec<usize>>, // Byte offset of each line start, built on first use
+    indexed_up_to: RefCell<usize>,    // Bytes of text already scanned for line starts
+    hasher: DefaultHasher, // Fed the text as it arrives, tells logs apart without reading them again
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::with_resident_limit(RESIDENT_LIMIT)
+    }
+}
+
+impl LogBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty buffer moving its text to a file past `limit` bytes
+    pub fn with_resident_limit(limit: usize) -> Self {
+        Self {
+            resident: Arc::default(),
+            resident_from: 0,
+            len: 0,
+            spill: None,
+            resident_limit: limit,
+            read_ahead: RefCell::default(),
+            pending_bytes: Vec::new(),
+            received_bytes: 0,
+            line_starts: RefCell::default(),
+            indexed_up_to: RefCell::default(),
+            hasher: DefaultHasher::new(),
+        }
+    }
+
+    /// Append raw bytes from the trace, holding back a trailing partial UTF-8 sequence
+    pub fn append_bytes(&mut self, bytes: &[u8]) {
+        self.received_bytes += bytes.len();
+        let mut data = std::mem::take(&mut self.pending_bytes);
+        data.extend_from_slice(bytes);
+
+        let mut rest = data.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    self.push_text(valid);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    // `valid_up_to` guarantees this prefix decodes
+                    self.push_text(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(len) => {
+                            // Invalid bytes in the middle of the data
+                            self.push_text(char::REPLACEMENT_CHARACTER.encode_utf8(&mut [0; 4]));
+                            rest = &after[len..];
+                        }
+                        None => {
+                            // Sequence cut off at the end, wait for the next chunk
+                            self.pending_bytes = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Append already-decoded text
+    pub fn append_str(&mut self, text: &str) {
+        self.flush_pending();
+        self.received_bytes += text.len();
+        self.push_text(text);
+    }
+
+    /// Decode any held-back bytes once no more data will arrive
+    pub fn flush_pending(&mut self) {
+        if !self.pending_bytes.is_empty() {
+            let pending = std::mem::take(&mut self.pending_bytes);
+            self.push_text(&String::from_utf8_lossy(&pending));
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.hasher.write(text.as_bytes());
+        if self.spill.is_none() {
+            Arc::make_mut(&mut self.resident).push_str(text);
+            self.len += text.len();
+            if self.len > self.resident_limit {
+                self.spill();
+            }
+            return;
+        }
+
+        if let Some(Err(e)) = self.spill.as_ref().map(|spill| spill.append(text)) {
+            tracing::warn!(error = %e, "Couldn't write to the file holding a large log");
+        }
+        // Spilled text is indexed as it arrives, it may not be in memory later
+        self.ensure_indexed();
+        let offset = self.len;
+        self.line_starts.get_mut().extend(text.match_indices('\n').map(|(i, _)| offset + i + 1));
+        let at_tail = self.resident_from + self.resident.len() == self.len;
+        self.len += text.len();
+        *self.indexed_up_to.get_mut() = self.len;
+
+        // A window at the end of the log, e.g. while following, keeps up with it
+        if at_tail {
+            Arc::make_mut(&mut self.resident).push_str(text);
+            if self.resident.len() > self.resident_limit {
+                let from = self.line_start_at_or_after(self.len - self.resident_limit / 2);
+                self.resident = Arc::new(self.resident[from - self.resident_from..].to_string());
+                self.resident_from = from;
+            }
+        }
+    }
+
+    /// Move the text to a temporary file, keeping its end in memory
+    fn spill(&mut self) {
+        self.ensure_indexed();
+        let spill = SpillFile::create().and_then(|spill| spill.append(&self.resident).map(|_| spill));
+        match spill {
+            Ok(spill) => {
+                self.spill = Some(spill);
+                let from = self.line_start_at_or_after(self.len - self.resident_limit / 2);
+                self.resident = Arc::new(self.resident[from..].to_string());
+                self.resident_from = from;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Couldn't move a large log to a temporary file, keeping it in memory");
+                self.resident_limit = usize::MAX;
+            }
+        }
+    }
+
+    /// Keep the text of these lines and some around them in memory, reading it back from the file
+    /// if it was let go. Nothing changes while the whole text is in memory
+    pub fn keep_resident(&mut self, lines: Range<usize>) {
+        let Some(spill) = &self.spill else {
+            return;
+        };
+        let wanted = self.line_start(lines.start)..self.line_start(lines.end.max(lines.start));
+        if wanted.start >= self.resident_from && wanted.end <= self.resident_from + self.resident.len() {
+            return;
+        }
+
+        // Room is left on both sides, so scrolling a little further doesn't read the file again
+        let margin = (self.resident_limit / 2).saturating_sub(wanted.len()) / 2;
+        let start = self.line_start_at_or_after(wanted.start.saturating_sub(margin));
+        let end = match wanted.end + margin {
+            end if end >= self.len => self.len,
+            end => self.line_start_at_or_before(end),
+        };
+        match spill.read(start..end) {
+            Ok(text) => {
+                self.resident = Arc::new(text);
+                self.resident_from = start;
+            }
+            Err(e) => tracing::warn!(error = %e, "Couldn't read back a large log from its file"),
+        }
+    }
+
+    /// The whole decoded text, read back from the file if it doesn't fit in memory
+    pub fn to_text(&self) -> Cow<'_, str> {
+        self.bytes_text(0..self.len)
+    }
+
+    /// The decoded text, shared rather than copied, unless it doesn't fit in memory
+    pub fn shared(&self) -> Option<Arc<String>> {
+        self.spill.is_none().then(|| Arc::clone(&self.resident))
+    }
+
+    /// Bytes of text kept in memory
+    pub fn resident_len(&self) -> usize {
+        self.resident.len()
+    }
+
+    /// Hash of the decoded text
+    pub fn content_hash(&self) -> u64 {
+        self.hasher.clone().finish()
+    }
+
+    /// Whether the decoded text is `text`, told by its hash so a spilled log isn't read back
+    pub fn has_text(&self, text: &str) -> bool {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(text.as_bytes());
+        self.len == text.len() && self.content_hash() == hasher.finish()
+    }
+
+    /// Bytes of the trace received so far, the offset to resume downloading from
+    pub fn len_bytes(&self) -> usize {
+        self.received_bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len_bytes() == 0
+    }
+
+    /// Whether the text ends with a complete line, or is empty
+    pub fn ends_with_newline(&self) -> bool {
+        self.ensure_indexed();
+        self.len == 0 || self.line_starts.borrow().last() == Some(&self.len)
+    }
+
+    /// Number of lines, counted the same way as `str::lines`
+    pub fn line_count(&self) -> usize {
+        self.ensure_indexed();
+        let starts = self.line_starts.borrow();
+        match starts.last() {
+            // A final newline doesn't start another (empty) line
+            Some(&last) if last == self.len => starts.len() - 1,
+            _ => starts.len(),
+        }
+    }
+
+    /// Byte offset where a line starts, or the text length past the last line
+    pub fn line_start(&self, line: usize) -> usize {
+        self.ensure_indexed();
+        self.line_starts.borrow().get(line).copied().unwrap_or(self.len)
+    }
+
+    /// Start of the first line starting at or after `offset`, the text length if none does
+    fn line_start_at_or_after(&self, offset: usize) -> usize {
+        let starts = self.line_starts.borrow();
+        starts.get(starts.partition_point(|&start| start < offset)).copied().unwrap_or(self.len)
+    }
+
+    /// Start of the last line starting at or before `offset`
+    fn line_start_at_or_before(&self, offset: usize) -> usize {
+        let starts = self.line_starts.borrow();
+        starts.partition_point(|&start| start <= offset).checked_sub(1).map_or(0, |i| starts[i])
+    }
+
+    /// Text of a single line without its line ending
+    pub fn line(&self, line: usize) -> Option<Cow<'_, str>> {
+        if line >= self.line_count() {
+            return None;
+        }
+        let trim = |text: &str| -> usize {
+            let text = text.strip_suffix('\n').unwrap_or(text);
+            text.strip_suffix('\r').unwrap_or(text).len()
+        };
+        Some(match self.text(line..line + 1) {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[..trim(text)]),
+            Cow::Owned(mut text) => {
+                text.truncate(trim(&text));
+                Cow::Owned(text)
+            }
+        })
+    }
+
+    /// Text of a range of lines, line endings included
+    pub fn text(&self, lines: Range<usize>) -> Cow<'_, str> {
+        self.bytes_text(self.line_start(lines.start)..self.line_start(lines.end.max(lines.start)))
+    }
+
+    /// Text between two line starts, from memory when it is there
+    fn bytes_text(&self, bytes: Range<usize>) -> Cow<'_, str> {
+        let resident_end = self.resident_from + self.resident.len();
+        if bytes.start >= self.resident_from && bytes.end <= resident_end {
+            return Cow::Borrowed(&self.resident[bytes.start - self.resident_from..bytes.end - self.resident_from]);
+        }
+        let Some(spill) = &self.spill else {
+            return Cow::Borrowed("");
+        };
+
+        {
+            let (cached_from, cached) = &*self.read_ahead.borrow();
+            if bytes.start >= *cached_from && bytes.end <= cached_from + cached.len() {
+                return Cow::Owned(cached[bytes.start - cached_from..bytes.end - cached_from].to_string());
+            }
+        }
+        let read = |range: Range<usize>| {
+            spill.read(range).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Couldn't read back a large log from its file");
+                String::new()
+            })
+        };
+        if bytes.len() >= READ_AHEAD {
+            return Cow::Owned(read(bytes));
+        }
+
+        // Lines after these are likely asked for next
+        let end = match bytes.start + READ_AHEAD {
+            end if end >= self.len => self.len,
+            end => self.line_start_at_or_before(end).max(bytes.end),
+        };
+        let text = read(bytes.start..end);
+        let wanted = text.get(..bytes.len()).unwrap_or_default().to_string();
+        *self.read_ahead.borrow_mut() = (bytes.start, text);
+        Cow::Owned(wanted)
+    }
+
+    /// Extend the line index over text appended since the last lookup
+    fn ensure_indexed(&self) {
+        let mut indexed_up_to = self.indexed_up_to.borrow_mut();
+        if *indexed_up_to == self.len && *indexed_up_to > 0 {
+            return;
+        }
+
+        let mut starts = self.line_starts.borrow_mut();
+        if starts.is_empty() && self.len > 0 {
+            starts.push(0);
+        }
+        // Text not indexed yet is still in memory, spilled text is indexed as it arrives
+        let tail = &self.resident.as_bytes()[*indexed_up_to - self.resident_from..];
+        starts.extend(
+            tail.iter()
+                .enumerate()
+                .filter(|(_, &b)| b == b'\n')
+                .map(|(i, _)| *indexed_up_to + i + 1),
+        );
+        *indexed_up_to = self.len;
+    }
+}
+
+impl From<String> for LogBuffer {
+    fn from(content: String) -> Self {
+        Self::from(Arc::new(content))
+    }
+}
+
+impl From<Arc<String>> for LogBuffer {
+    fn from(content: Arc<String>) -> Self {
+        let mut buffer = Self::new();
+        buffer.hasher.write(content.as_bytes());
+        buffer.received_bytes = content.len();
+        buffer.len = content.len();
+        buffer.resident = content;
+        if buffer.len > buffer.resident_limit {
+            buffer.spill();
+        }
+        buffer
+    }
+}
+
+/// Temporary file holding the text of a log too big to keep in memory, only readable by this
+/// user and removed once dropped
+#[derive(Debug)]
+struct SpillFile {
+    file: RefCell<File>,
+    path: PathBuf,
+}
+
+impl SpillFile {
+    fn create() -> std::io::Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!("peeplab-{}-{}.log", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(name);
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(&path)?;
+        // Logs may hold secrets: on unix the file goes away right now, staying open for this buffer
+        #[cfg(unix)]
+        let _ = fs::remove_file(&path);
+        Ok(Self { file: RefCell::new(file), path })
+    }
+
+    fn append(&self, text: &str) -> std::io::Result<()> {
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(text.as_bytes())
+    }
+
+    /// Text between two char boundaries of what was appended
+    fn read(&self, bytes: Range<usize>) -> std::io::Result<String> {
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(bytes.start as u64))?;
+        let mut data = vec![0; bytes.len()];
+        file.read_exact(&mut data)?;
+        Ok(String::from_utf8(data).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index_matches_str_lines() {
+        for content in ["", "one", "one\n", "one\ntwo", "one\ntwo\n", "a\r\nb\r\n\nc"] {
+            let buffer = LogBuffer::from(content.to_string());
+            let expected: Vec<&str> = content.lines().collect();
+            assert_eq!(buffer.line_count(), expected.len(), "content {:?}", content);
+            for (i, line) in expected.iter().enumerate() {
+                assert_eq!(buffer.line(i).as_deref(), Some(*line));
+            }
+            assert_eq!(buffer.line(expected.len()), None);
+        }
+    }
+
+    #[test]
+    fn test_index_extends_after_append() {
+        let mut buffer = LogBuffer::from("first\nsec".to_string());
+        assert_eq!(buffer.line_count(), 2);
+        buffer.append_str("ond\nthird\n");
+        assert_eq!(buffer.line_count(), 3);
+        assert_eq!(buffer.line(1).as_deref(), Some("second"));
+        assert_eq!(buffer.line_start(2), "first\nsecond\n".len());
+    }
+
+    #[test]
+    fn test_shared_content_is_copied_on_append() {
+        let mut buffer = LogBuffer::from("first\n".to_string());
+        let shared = buffer.shared().unwrap();
+        assert!(Arc::ptr_eq(&shared, &LogBuffer::from(Arc::clone(&shared)).shared().unwrap()));
+        buffer.append_str("second\n");
+        assert_eq!(shared.as_str(), "first\n");
+        assert_eq!(buffer.to_text(), "first\nsecond\n");
+    }
+
+    #[test]
+    fn test_append_bytes_handles_split_utf8() {
+        let text = "build ✓ done";
+        let bytes = text.as_bytes();
+        let split = text.find('✓').unwrap() + 1; // In the middle of the 3-byte check mark
+
+        let mut buffer = LogBuffer::new();
+        buffer.append_bytes(&bytes[..split]);
+        assert_eq!(buffer.to_text(), "build ");
+        buffer.append_bytes(&bytes[split..]);
+        assert_eq!(buffer.to_text(), text);
+    }
+
+    #[test]
+    fn test_append_bytes_replaces_invalid_bytes() {
+        let mut buffer = LogBuffer::new();
+        buffer.append_bytes(b"ok \xFF\xFE still ok");
+        assert_eq!(buffer.to_text(), "ok \u{FFFD}\u{FFFD} still ok");
+        // Offsets stay those of the trace, not of the decoded text
+        assert_eq!(buffer.len_bytes(), 14);
+    }
+
+    #[test]
+    fn test_flush_pending_decodes_truncated_tail() {
+        let mut buffer = LogBuffer::new();
+        buffer.append_bytes(b"end \xE2\x9C");
+        assert_eq!(buffer.to_text(), "end ");
+        buffer.flush_pending();
+        assert_eq!(buffer.to_text(), "end \u{FFFD}");
+    }
+
+    #[test]
+    fn test_spilled_log_keeps_a_window_in_memory() {
+        let text: String = (0..1000).map(|i| format!("line {}\n", i)).collect();
+        let mut buffer = LogBuffer::with_resident_limit(1024);
+        for chunk in text.as_bytes().chunks(100) {
+            buffer.append_bytes(chunk);
+        }
+        assert!(buffer.shared().is_none());
+        assert!(buffer.resident_len() <= 1024);
+        assert_eq!(buffer.line_count(), 1000);
+        assert!(buffer.has_text(&text));
+        assert_eq!(buffer.to_text(), text);
+
+        // Lines outside the window are read back, the window moves where it's asked to
+        assert_eq!(buffer.line(3).as_deref(), Some("line 3"));
+        assert_eq!(buffer.text(10..12), "line 10\nline 11\n");
+        buffer.keep_resident(500..510);
+        assert!(buffer.resident_len() <= 1024);
+        assert!(matches!(buffer.text(500..510), Cow::Borrowed(_)));
+        assert_eq!(buffer.line(505).as_deref(), Some("line 505"));
+
+        // Appending doesn't move a window away from the end
+        buffer.append_str("last\n");
+        assert_eq!(buffer.line(1000).as_deref(), Some("last"));
+        assert!(matches!(buffer.text(500..510), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_large_log_spills_on_load() {
+        let text = "0123456789\n".repeat(RESIDENT_LIMIT / 10);
+        let buffer = LogBuffer::from(text.clone());
+        assert!(buffer.shared().is_none());
+        assert!(buffer.resident_len() <= RESIDENT_LIMIT / 2);
+        assert_eq!(buffer.line_count(), RESIDENT_LIMIT / 10);
+        assert_eq!(buffer.line(0).as_deref(), Some("0123456789"));
+        assert!(buffer.has_text(&text) && !buffer.has_text("0123456789\n"));
+    }
+}
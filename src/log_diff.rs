@@ -0,0 +1,199 @@
+use crate::app::TimestampDisplayMode;
+use crate::log_processor::process_log_content;
+use ratatui::text::Line;
+use similar::{capture_diff_slices_deadline, Algorithm, DiffOp};
+use std::time::{Duration, Instant};
+
+/// Longest a diff may take before the rest of the logs are shown as one change
+const DIFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Equal,
+    Removed, // Only in the left log
+    Added,   // Only in the right log
+    Changed, // Different text on both sides
+}
+
+/// One row of the side-by-side view, pointing at a line of either log or both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffRow {
+    pub kind: DiffKind,
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+}
+
+/// Both logs processed for display, with the rows pairing up their lines
+#[derive(Debug, Clone, Default)]
+pub struct DiffContent {
+    pub left_lines: Vec<Line<'static>>,
+    pub right_lines: Vec<Line<'static>>,
+    pub rows: Vec<DiffRow>,
+}
+
+impl DiffContent {
+    /// Diff two raw traces line by line, ignoring timestamps and ANSI colors
+    pub fn compute(left: &str, right: &str) -> Self {
+        let left_lines = process_log_content(left, &TimestampDisplayMode::Hidden).into_rows();
+        let right_lines = process_log_content(right, &TimestampDisplayMode::Hidden).into_rows();
+        let rows = diff_rows(&plain_text(&left_lines), &plain_text(&right_lines));
+        DiffContent { left_lines, right_lines, rows }
+    }
+
+    /// Rows where a run of differences starts
+    pub fn change_starts(&self) -> Vec<usize> {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|&(i, row)| {
+                row.kind != DiffKind::Equal && (i == 0 || self.rows[i - 1].kind == DiffKind::Equal)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Number of rows that differ
+    pub fn changed_rows(&self) -> usize {
+        self.rows.iter().filter(|row| row.kind != DiffKind::Equal).count()
+    }
+}
+
+/// A comparison of two job logs, open in the side-by-side view
+#[derive(Debug, Clone)]
+pub struct LogDiff {
+    pub left_job: (u64, String), // Job id and name
+    pub right_job: (u64, String),
+    pub content: Option<DiffContent>, // None while the logs load
+    pub error: Option<String>,
+    pub scroll: usize, // First row shown
+}
+
+impl LogDiff {
+    pub fn new(left_job: (u64, String), right_job: (u64, String)) -> Self {
+        LogDiff { left_job, right_job, content: None, error: None, scroll: 0 }
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.content.as_ref().map_or(0, |content| content.rows.len())
+    }
+
+    /// Scroll so the next run of differences after the top row is at the top, if any
+    pub fn next_change(&mut self) -> bool {
+        let Some(content) = &self.content else {
+            return false;
+        };
+        match content.change_starts().into_iter().find(|&row| row > self.scroll) {
+            Some(row) => {
+                self.scroll = row;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Scroll so the previous run of differences before the top row is at the top, if any
+    pub fn prev_change(&mut self) -> bool {
+        let Some(content) = &self.content else {
+            return false;
+        };
+        match content.change_starts().into_iter().rev().find(|&row| row < self.scroll) {
+            Some(row) => {
+                self.scroll = row;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn plain_text(lines: &[Line]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect())
+        .collect()
+}
+
+/// Pair up the lines of two logs, lines replaced by others are shown side by side as changed
+fn diff_rows(left: &[String], right: &[String]) -> Vec<DiffRow> {
+    let deadline = Some(Instant::now() + DIFF_TIMEOUT);
+    let ops = capture_diff_slices_deadline(Algorithm::Patience, left, right, deadline);
+
+    let mut rows = Vec::with_capacity(left.len().max(right.len()));
+    for op in ops {
+        let (left_range, right_range) = (op.old_range(), op.new_range());
+        if let DiffOp::Equal { .. } = op {
+            rows.extend(left_range.zip(right_range).map(|(l, r)| DiffRow {
+                kind: DiffKind::Equal,
+                left: Some(l),
+                right: Some(r),
+            }));
+            continue;
+        }
+
+        let paired = left_range.len().min(right_range.len());
+        rows.extend(left_range.clone().zip(right_range.clone()).map(|(l, r)| DiffRow {
+            kind: DiffKind::Changed,
+            left: Some(l),
+            right: Some(r),
+        }));
+        rows.extend(left_range.skip(paired).map(|l| DiffRow { kind: DiffKind::Removed, left: Some(l), right: None }));
+        rows.extend(right_range.skip(paired).map(|r| DiffRow { kind: DiffKind::Added, left: None, right: Some(r) }));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: &DiffContent) -> Vec<DiffKind> {
+        content.rows.iter().map(|row| row.kind).collect()
+    }
+
+    #[test]
+    fn test_identical_logs_have_no_changes() {
+        let content = DiffContent::compute("a\nb\n", "a\nb\n");
+        assert_eq!(kinds(&content), vec![DiffKind::Equal, DiffKind::Equal]);
+        assert!(content.change_starts().is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_and_changed_lines() {
+        let content = DiffContent::compute("setup\nold step\nremoved\ndone\n", "setup\nnew step\ndone\nextra\n");
+        assert_eq!(
+            content.rows,
+            vec![
+                DiffRow { kind: DiffKind::Equal, left: Some(0), right: Some(0) },
+                DiffRow { kind: DiffKind::Changed, left: Some(1), right: Some(1) },
+                DiffRow { kind: DiffKind::Removed, left: Some(2), right: None },
+                DiffRow { kind: DiffKind::Equal, left: Some(3), right: Some(2) },
+                DiffRow { kind: DiffKind::Added, left: None, right: Some(3) },
+            ]
+        );
+        assert_eq!(content.change_starts(), vec![1, 4]);
+        assert_eq!(content.changed_rows(), 3);
+    }
+
+    #[test]
+    fn test_timestamps_and_colors_are_ignored() {
+        let left = "2024-01-15T10:30:45.123456Z 00O \x1b[32mok\x1b[0m\n";
+        let right = "2024-02-01T08:00:00.000000Z 00O ok\n";
+        let content = DiffContent::compute(left, right);
+        assert_eq!(kinds(&content), vec![DiffKind::Equal]);
+    }
+
+    #[test]
+    fn test_jump_between_changes() {
+        let mut diff = LogDiff::new((1, "a".into()), (2, "b".into()));
+        assert!(!diff.next_change());
+        diff.content = Some(DiffContent::compute("a\nx\nb\nc\ny\n", "a\nX\nb\nc\nY\n"));
+
+        assert!(diff.next_change());
+        assert_eq!(diff.scroll, 1);
+        assert!(diff.next_change());
+        assert_eq!(diff.scroll, 4);
+        assert!(!diff.next_change());
+        assert!(diff.prev_change());
+        assert_eq!(diff.scroll, 1);
+    }
+}
@@ -1,31 +1,53 @@
 use anyhow::Result;
 use crossterm::{
     cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
 use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use tokio::sync::mpsc;
 
-mod app;
-mod config;
-mod editor;
-mod error;
-mod events;
-mod git;
-mod gitlab;
-mod log_processor;
-mod ui;
+use peeplab::app::{self, App};
+use peeplab::events::{map_event_to_action, Action, Effect, EventHandler, Keymap};
+use peeplab::events::handler::{INPUT_THREAD, TICK_THREAD};
+use peeplab::gitlab::{ClientTimeouts, GitLabClient};
+use peeplab::error::PeeplabError;
+use peeplab::color::ColorSupport;
+use peeplab::log_diff::DiffContent;
+use peeplab::cache::{self, CacheKey};
+use peeplab::gitlab::TraceChunk;
+use peeplab::{artifacts, browser, clipboard, config, diagnostics, editor, export, git, ui};
 
-use app::App;
-use events::{map_event_to_action, Action, Effect, EventHandler};
-use gitlab::GitLabClient;
+/// Shared by every prefetch, so pipelines opened one after another don't add up to more requests
+static LOG_PREFETCH_PERMITS: tokio::sync::Semaphore = tokio::sync::Semaphore::const_new(app::LOG_PREFETCH_CONCURRENCY);
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Diagnostics go to a file since the TUI owns the terminal; without one peeplab still runs
+    let verbose = verbose_flag();
+    let log_path = match diagnostics::init(verbose) {
+        Ok(path) => {
+            if verbose {
+                eprintln!("Writing diagnostics to {}", path.display());
+            }
+            Some(path)
+        }
+        Err(e) => {
+            if verbose {
+                eprintln!("Warning: Could not open the diagnostics log: {}", e);
+            }
+            None
+        }
+    };
+    install_panic_hook(log_path);
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "Starting");
+
     // Load configuration
     let settings = match config::load_config() {
         Ok(s) => s,
@@ -41,56 +63,87 @@ async fn main() -> Result<()> {
         }
     };
 
-    // Initialize GitLab client
-    let gitlab_client = GitLabClient::new(&settings.gitlab.instance_url, &settings.gitlab.token)?;
+    // Build key bindings, rejecting unknown actions or keys before touching the terminal
+    let keymap = match Keymap::from_config(&settings.keys) {
+        Ok(keymap) => keymap,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("\nCheck the [keys.*] sections of {:?}", config::get_config_path()?);
+            std::process::exit(1);
+        }
+    };
+
+    let theme = match ui::theme::Theme::by_name(&settings.ui.theme) {
+        Some(theme) => theme,
+        None => {
+            let names: Vec<&str> = ui::theme::THEMES.iter().map(|theme| theme.name).collect();
+            eprintln!("Unknown theme '{}', expected one of: {}", settings.ui.theme, names.join(", "));
+            eprintln!("\nCheck the [ui] section of {:?}", config::get_config_path()?);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = peeplab::log_processor::set_redact_patterns(&settings.ui.redact_patterns) {
+        eprintln!("{}", e);
+        eprintln!("\nCheck redact_patterns in the [ui] section of {:?}", config::get_config_path()?);
+        std::process::exit(1);
+    }
 
-    // Determine project ID: use config value or detect from git
-    let project_id = match settings.gitlab.default_project_id {
-        Some(id) => {
-            eprintln!("Using project ID from config: {}", id);
-            id
+    if let Err(e) = peeplab::log_processor::set_prefix_patterns(&settings.ui.prefix_patterns) {
+        eprintln!("{}", e);
+        eprintln!("\nCheck prefix_patterns in the [ui] section of {:?}", config::get_config_path()?);
+        std::process::exit(1);
+    }
+
+    match peeplab::log_processor::DisplayZone::parse(&settings.ui.timezone) {
+        Ok(zone) => peeplab::log_processor::set_display_zone(zone),
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("\nCheck timezone in the [ui] section of {:?}", config::get_config_path()?);
+            std::process::exit(1);
         }
+    }
+
+    // `--color` wins over `[ui] color`, "auto" guesses from the environment
+    let color = color_flag().unwrap_or_else(|| settings.ui.color.clone());
+    let color_support = match ColorSupport::parse(&color) {
+        Some(support) => support,
+        None if color.eq_ignore_ascii_case("auto") => ColorSupport::detect(),
         None => {
-            eprintln!("No project ID in config, detecting from git repository...");
-            match git::detect_project_from_git() {
-                Ok(git_project) => {
-                    eprintln!("Detected GitLab project: {}", git_project.path());
-
-                    // Check if the git remote host matches the configured instance
-                    let instance_host = settings.gitlab.instance_url
-                        .trim_start_matches("https://")
-                        .trim_start_matches("http://")
-                        .trim_end_matches('/');
-
-                    if !git_project.host.contains(instance_host) && !instance_host.contains(&git_project.host) {
-                        eprintln!("Warning: Git remote host '{}' doesn't match configured instance '{}'",
-                            git_project.host, instance_host);
-                    }
+            eprintln!("Unknown color mode '{}', expected one of: auto, truecolor, 256, 16, none", color);
+            std::process::exit(1);
+        }
+    };
 
-                    // Resolve project path to ID via API
-                    eprintln!("Resolving project path to ID...");
-                    match gitlab_client.get_project_by_path(&git_project.path()).await {
-                        Ok(project) => {
-                            eprintln!("Found project: {} (ID: {})", project.path_with_namespace, project.id);
-                            project.id
-                        }
-                        Err(e) => {
-                            eprintln!("Error: Failed to resolve project '{}': {}", git_project.path(), e);
-                            eprintln!("\nPlease either:");
-                            eprintln!("1. Add 'default_project_id' to your config file, or");
-                            eprintln!("2. Ensure you're in a git repository with a GitLab remote");
-                            std::process::exit(1);
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                    eprintln!("\nPlease either:");
-                    eprintln!("1. Add 'default_project_id' to your config file, or");
-                    eprintln!("2. Run this command from a git repository with a GitLab remote");
-                    std::process::exit(1);
-                }
-            }
+    // Initialize the GitLab client shared by every request
+    let timeouts = ClientTimeouts {
+        connect: Duration::from_secs(settings.gitlab.connect_timeout_secs.max(1)),
+        request: Duration::from_secs(settings.gitlab.request_timeout_secs.max(1)),
+    };
+    let profiles = settings.gitlab_profiles();
+    let gitlab_client = GitLabClient::for_api(&profiles[0].api_url, &settings.gitlab.token, timeouts)?;
+
+    // Determine project ID: detect from git, falling back to the config value
+    let instance_host = profiles[0].host();
+    tracing::info!(instance = %instance_host, "Using GitLab instance");
+    let detected =
+        detect_project(&gitlab_client, &instance_host, settings.gitlab.default_project_id.is_some()).await;
+    let (project_id, project_path) = match (detected, settings.gitlab.default_project_id) {
+        (Ok((id, path)), _) => {
+            eprintln!("Found project: {} (ID: {})", path, id);
+            (id, Some(path))
+        }
+        (Err(e), Some(id)) => {
+            eprintln!("{}", e);
+            eprintln!("Using project ID from config: {}", id);
+            (id, None)
+        }
+        (Err(e), None) => {
+            eprintln!("Error: {}", e);
+            eprintln!("\nPlease either:");
+            eprintln!("1. Add 'default_project_id' to your config file, or");
+            eprintln!("2. Run this command from a git repository with a GitLab remote");
+            std::process::exit(1);
         }
     };
 
@@ -111,25 +164,68 @@ async fn main() -> Result<()> {
         None
     };
 
+    // Keep fetched data for when GitLab can't be reached
+    if settings.app.cache_max_size_mb > 0 {
+        if let Some(dir) = cache::default_dir() {
+            let max_age = Duration::from_secs(settings.app.cache_max_age_days * 24 * 60 * 60);
+            cache::init(dir, max_age, settings.app.cache_max_size_mb * 1024 * 1024);
+        }
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.hide_cursor()?;
 
     // Create app state
     let mut app = App::new(project_id, current_branch, settings.app.focus_current_branch, settings.app.auto_refresh_interval_minutes);
+    app.mouse_scroll_lines = settings.ui.mouse_scroll_lines.max(1);
+    app.scroll_step = settings.ui.scroll_step.max(1);
+    app.fast_scroll_step = settings.ui.fast_scroll_step.max(1);
+    app.match_position = settings.ui.match_position.clamp(0.0, 1.0);
+    app.search_wrap = settings.ui.search_wrap;
+    app.keymap = keymap;
+    app.instance_host = instance_host;
+    app.profiles = profiles;
+    app.project_path = project_path;
+    app.theme = theme;
+    app.color_support = color_support;
+    app.failed_pipeline_jump = settings.app.failed_pipeline;
+    app.notify_mode = settings.app.notify;
+    app.log_prefetch_mode = settings.app.prefetch_logs;
+    app.group = settings.gitlab.group.clone();
+    app.filter_context = settings.ui.filter_context.min(app::MAX_FILTER_CONTEXT);
+    app.search_debounce = match settings.ui.search_debounce_ms {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
+    };
+    app.live_refresh_interval = match settings.app.live_refresh_interval_secs {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    };
+    let preferences = config::load_preferences();
+    app.apply_preferences(&preferences);
+    if settings.app.restore_last_location && !no_restore_flag() {
+        // A location in another project can't be reached from here
+        app.restore_location = preferences.last_location.filter(|location| location.project_id == project_id);
+    }
 
     // Create event handler
     let mut event_handler = EventHandler::new(Duration::from_secs(settings.app.refresh_interval));
 
     // Create action channel
-    let (action_tx, mut action_rx) = mpsc::unbounded_channel::<Action>();
+    let (action_tx, mut action_rx) = mpsc::unbounded_channel::<(Option<u64>, Action)>();
+
+    // Who and where the status bar shows, fetched once
+    let with_project = app.project_path.is_none();
+    let session_tx = ActionSender { tx: action_tx.clone(), generation: app.profile_generation };
+    handle_effect(Effect::FetchSessionInfo { project_id, with_project }, &gitlab_client, session_tx).await?;
 
     // Initial fetch of merge requests
-    let initial_action_tx = action_tx.clone();
+    let initial_action_tx = ActionSender { tx: action_tx.clone(), generation: app.profile_generation };
     tokio::spawn(async move {
         let _ = initial_action_tx.send(Action::Refresh);
     });
@@ -138,13 +234,17 @@ async fn main() -> Result<()> {
     let result = run_app(
         &mut terminal,
         &mut app,
-        &gitlab_client,
+        gitlab_client,
+        timeouts,
         &mut event_handler,
         &mut action_rx,
         action_tx,
     )
     .await;
 
+    // Remember viewer choices for the next session; failing to save is not worth an error
+    let _ = config::save_preferences(&app.preferences());
+
     // Drop event handler to stop background tasks before terminal cleanup
     drop(event_handler);
     drop(action_rx);
@@ -157,6 +257,7 @@ async fn main() -> Result<()> {
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
+        DisableMouseCapture,
         cursor::Show,
         cursor::MoveToColumn(0)
     )?;
@@ -167,24 +268,69 @@ async fn main() -> Result<()> {
     result
 }
 
+/// Look up the project of the git `origin` remote. A remote on another host than the instance is
+/// only tried when there is no configured project to fall back to.
+async fn detect_project(
+    client: &GitLabClient,
+    instance_host: &str,
+    has_fallback: bool,
+) -> std::result::Result<(u64, String), String> {
+    let git_project = git::detect_project_from_git().map_err(|e| e.to_string())?;
+    eprintln!("Detected git remote project: {}", git_project.path());
+
+    if !git_project.is_on_host(instance_host) {
+        let mismatch =
+            format!("Git remote host '{}' doesn't match configured instance '{}'", git_project.host, instance_host);
+        if has_fallback {
+            return Err(mismatch);
+        }
+        eprintln!("Warning: {}", mismatch);
+    }
+
+    eprintln!("Resolving project path to ID...");
+    client
+        .get_project_by_path(&git_project.path())
+        .await
+        .map(|project| (project.id, project.path_with_namespace))
+        .map_err(|e| format!("Failed to resolve project '{}': {}", git_project.path(), e))
+}
+
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
-    gitlab_client: &GitLabClient,
+    mut gitlab_client: GitLabClient,
+    timeouts: ClientTimeouts,
     event_handler: &mut EventHandler,
-    action_rx: &mut mpsc::UnboundedReceiver<Action>,
-    action_tx: mpsc::UnboundedSender<Action>,
+    action_rx: &mut mpsc::UnboundedReceiver<(Option<u64>, Action)>,
+    action_tx: mpsc::UnboundedSender<(Option<u64>, Action)>,
 ) -> Result<()> {
+    let mut client_profile = app.active_profile;
     loop {
+        // Lay out the log viewer before drawing, wrapped rows depend on its width
+        if app.mode == app::AppMode::ViewingLog {
+            let size = terminal.size()?;
+            let area = ratatui::layout::Rect::new(0, 0, size.width, size.height);
+            app.set_log_layout(ui::components::log_viewer::content_area(app, area));
+        }
+        if app.mode == app::AppMode::ComparingLogs {
+            let size = terminal.size()?;
+            let area = ratatui::layout::Rect::new(0, 0, size.width, size.height);
+            app.log_viewport_height = ui::components::log_diff::content_height(area).max(1);
+        }
+
+        if let Some(file) = app.artifact_browser.as_mut().and_then(|b| b.file.as_mut()) {
+            let size = terminal.size()?;
+            let area = ratatui::layout::Rect::new(0, 0, size.width, size.height);
+            file.height = ui::components::artifact_browser::content_height(area).max(1);
+        }
+
         // Render
         terminal.draw(|f| ui::render(f, app))?;
 
-        // Update viewport height for log viewer centering
-        if app.mode == app::AppMode::ViewingLog {
+        if app.mode == app::AppMode::ShowingHelp {
             let size = terminal.size()?;
-            // Approximate content height: total height - tabs(3) - pipeline(10) - borders(2) - search bar(0-3)
-            let estimated_log_height = size.height.saturating_sub(17) as usize;
-            app.log_viewport_height = estimated_log_height.max(10); // At least 10 lines
+            let area = ratatui::layout::Rect::new(0, 0, size.width, size.height);
+            app.help_max_scroll = ui::components::help::max_scroll(app, area);
         }
 
         // Handle events
@@ -192,14 +338,34 @@ async fn run_app(
             // User input events
             Some(event) = event_handler.next() => {
                 let action = map_event_to_action(event, app);
-                action_tx.send(action)?;
+                action_tx.send((None, action))?; // Input is never stale, even right after a profile switch
             }
 
+            // Redraw to animate the spinner while a log loads
+            _ = tokio::time::sleep(ui::components::log_viewer::SPINNER_FRAME), if app.log_loading_since.is_some() => {}
+
+            // Redraw every second so running and queued jobs' times count up
+            _ = tokio::time::sleep(Duration::from_secs(1)), if app.shows_live_job_times() => {}
+
             // Actions from various sources
-            Some(action) = action_rx.recv() => {
+            Some((generation, action)) = action_rx.recv() => {
+                // Answers from the instance of a previous profile would mix into the new one
+                if generation.is_some_and(|generation| generation != app.profile_generation) {
+                    tracing::debug!(generation, "Dropped an action from before a profile switch");
+                    continue;
+                }
+                log_failure(&action);
                 // Update state and get effects
-                if let Some(effect) = app.update(action) {
-                    handle_effect(effect, gitlab_client, action_tx.clone()).await?;
+                let effect = app.update(action);
+                // Requests after a profile switch go to that profile's instance
+                if app.active_profile != client_profile {
+                    let profile = &app.profiles[app.active_profile];
+                    gitlab_client = GitLabClient::for_api(&profile.api_url, &profile.token, timeouts)?;
+                    client_profile = app.active_profile;
+                }
+                if let Some(effect) = effect {
+                    let sender = ActionSender { tx: action_tx.clone(), generation: app.profile_generation };
+                    handle_effect(effect, &gitlab_client, sender).await?;
                 }
 
                 if app.should_quit {
@@ -212,22 +378,162 @@ async fn run_app(
     Ok(())
 }
 
+/// Value of `--color <mode>` or `--color=<mode>` on the command line
+fn color_flag() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--color" {
+            return args.next();
+        }
+        if let Some(mode) = arg.strip_prefix("--color=") {
+            return Some(mode.to_string());
+        }
+    }
+    None
+}
+
+/// Restore the terminal and record the panic before the default hook prints it. A panic in a
+/// background task only ends that task: it is recorded, and the TUI keeps running untouched.
+/// Without the input or tick thread the TUI can't go on, so their panics end it too
+fn install_panic_hook(log_path: Option<PathBuf>) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let critical = matches!(std::thread::current().name(), Some("main" | INPUT_THREAD | TICK_THREAD));
+        if !critical {
+            let backtrace = std::backtrace::Backtrace::force_capture();
+            tracing::error!(panic = %info, %backtrace, "Background task panicked");
+            return;
+        }
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, cursor::Show);
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        tracing::error!(panic = %info, %backtrace, "Panicked");
+        default_hook(info);
+        if let Some(path) = &log_path {
+            eprintln!("The panic was recorded in {}", path.display());
+        }
+        std::process::exit(101);
+    }));
+}
+
+/// Whether `--verbose` asks for debug diagnostics in the log file
+fn verbose_flag() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--verbose")
+}
+
+/// Whether `--no-restore` asks to start fresh instead of at the last session's location
+fn no_restore_flag() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--no-restore")
+}
+
+/// Record failures reported by background tasks in the diagnostics log
+fn log_failure(action: &Action) {
+    match action {
+        Action::ApiError(error)
+        | Action::JobActionFailed(error)
+        | Action::PipelineTriggerFailed(error)
+        | Action::HeadPipelinesFailed(error)
+        | Action::LogExportFailed(error)
+        | Action::UrlOpenFailed(error) => tracing::warn!(%error, "Action failed"),
+        Action::JobTraceFailed { job_id, error, .. }
+        | Action::JobTracePollFailed { job_id, error, .. }
+        | Action::JobLogPrefetchFailed { job_id, error, .. } => {
+            tracing::warn!(job_id, %error, "Log fetch failed")
+        }
+        Action::ArtifactDownloadFailed { job_id, error }
+        | Action::TestReportFailed { job_id, error }
+        | Action::ArtifactListFailed { job_id, error }
+        | Action::ArtifactFileFailed { job_id, error, .. } => tracing::warn!(job_id, %error, "Artifact request failed"),
+        Action::JobNeedsFailed { pipeline_id, error } => tracing::warn!(pipeline_id, %error, "Job needs fetch failed"),
+        Action::PipelineRetryFailed { pipeline_id, error } => {
+            tracing::warn!(pipeline_id, %error, "Pipeline retry failed")
+        }
+        Action::LogDiffFailed { left_id, right_id, error } => {
+            tracing::warn!(left_id, right_id, %error, "Log comparison failed")
+        }
+        _ => {}
+    }
+}
+
+/// Sends the answers to effects, tagged with the profile generation they were requested under.
+/// Input goes through the same channel untagged
+#[derive(Clone)]
+struct ActionSender {
+    tx: mpsc::UnboundedSender<(Option<u64>, Action)>,
+    generation: u64,
+}
+
+impl ActionSender {
+    /// Whether the action was delivered, it isn't once the app has quit
+    fn send(&self, action: Action) -> bool {
+        self.tx.send((Some(self.generation), action)).is_ok()
+    }
+}
+
+/// Result of a fetch, saved to the offline cache; when GitLab can't be reached the cached copy
+/// stands in for it, announced to the app before it arrives
+async fn fetch_cached<T>(
+    key: CacheKey,
+    fetch: impl std::future::Future<Output = peeplab::error::Result<T>>,
+    action_tx: &ActionSender,
+) -> peeplab::error::Result<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    match fetch.await {
+        Ok(value) => {
+            cache::store(&key, &value);
+            note_online(action_tx);
+            Ok(value)
+        }
+        Err(e) if e.is_unreachable() => match cache::load(&key) {
+            Some(cached) => {
+                tracing::info!(?key, error = %e, "Serving cached data");
+                note_offline(action_tx, cached.saved_at);
+                Ok(cached.value)
+            }
+            None => Err(e),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+fn note_online(action_tx: &ActionSender) {
+    if cache::mark_online() {
+        let _ = action_tx.send(Action::BackOnline);
+    }
+}
+
+fn note_offline(action_tx: &ActionSender, saved_at: chrono::DateTime<chrono::Utc>) {
+    cache::mark_offline();
+    let _ = action_tx.send(Action::ServedFromCache { saved_at });
+}
+
+/// Rate limiting pauses polling instead of showing up as an error
+fn api_error_action(error: PeeplabError) -> Action {
+    match error {
+        PeeplabError::RateLimited { retry_after } => Action::RateLimited { retry_after },
+        error => Action::ApiError(error.to_string()),
+    }
+}
+
 async fn handle_effect(
     effect: Effect,
     gitlab_client: &GitLabClient,
-    action_tx: mpsc::UnboundedSender<Action>,
+    action_tx: ActionSender,
 ) -> Result<()> {
     match effect {
         Effect::FetchMergeRequests { project_id } => {
             let action_tx = action_tx.clone();
             let client = gitlab_client.clone();
             tokio::spawn(async move {
-                match client.get_merge_requests(project_id).await {
+                let key = CacheKey::MergeRequests { host: client.host(), project_id, branch: None };
+                match fetch_cached(key, client.get_merge_requests(project_id), &action_tx).await {
                     Ok(mrs) => {
                         let _ = action_tx.send(Action::MergeRequestsLoaded(mrs));
                     }
                     Err(e) => {
-                        let _ = action_tx.send(Action::ApiError(e.to_string()));
+                        let _ = action_tx.send(api_error_action(e));
                     }
                 }
             });
@@ -237,19 +543,35 @@ async fn handle_effect(
             mr_index,
             project_id,
             mr_iid,
+            page,
+            status,
         } => {
             let action_tx = action_tx.clone();
             let client = gitlab_client.clone();
             tokio::spawn(async move {
-                match client.get_mr_pipelines(project_id, mr_iid).await {
-                    Ok(pipelines) => {
+                let fetch = async {
+                    let result = client.get_mr_pipelines(project_id, mr_iid, page, status.as_ref()).await?;
+                    Ok((result.items, result.next_page))
+                };
+                // Only the first page of the unfiltered list is cached
+                let result = if page == 1 && status.is_none() {
+                    let key = CacheKey::Pipelines { host: client.host(), project_id, mr_iid };
+                    fetch_cached(key, fetch, &action_tx).await
+                } else {
+                    fetch.await
+                };
+                match result {
+                    Ok((pipelines, next_page)) => {
                         let _ = action_tx.send(Action::PipelinesLoaded {
                             mr_index,
+                            page,
+                            status,
                             pipelines,
+                            next_page,
                         });
                     }
                     Err(e) => {
-                        let _ = action_tx.send(Action::ApiError(e.to_string()));
+                        let _ = action_tx.send(api_error_action(e));
                     }
                 }
             });
@@ -263,7 +585,8 @@ async fn handle_effect(
             let action_tx = action_tx.clone();
             let client = gitlab_client.clone();
             tokio::spawn(async move {
-                match client.get_pipeline_jobs(project_id, pipeline_id).await {
+                let key = CacheKey::Jobs { host: client.host(), project_id, pipeline_id };
+                match fetch_cached(key, client.get_pipeline_jobs(project_id, pipeline_id), &action_tx).await {
                     Ok(jobs) => {
                         let _ = action_tx.send(Action::JobsLoaded {
                             mr_index,
@@ -272,27 +595,422 @@ async fn handle_effect(
                         });
                     }
                     Err(e) => {
-                        let _ = action_tx.send(Action::ApiError(e.to_string()));
+                        let _ = action_tx.send(api_error_action(e));
+                    }
+                }
+            });
+        }
+
+        Effect::FetchPipeline { mr_index, project_id, pipeline_id } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let action = match client.get_pipeline(project_id, pipeline_id).await {
+                    Ok(pipeline) => Action::PipelineLoaded { mr_index, pipeline },
+                    Err(e) => Action::PipelineFetchFailed { mr_index, pipeline_id, error: e.to_string() },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::FetchJobTrace { project_id, job_id, job_name, offset } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                match client.get_job_trace_range(project_id, job_id, offset, app::LOG_FETCH_CHUNK_SIZE).await {
+                    Ok(chunk) => {
+                        note_online(&action_tx);
+                        let _ = action_tx.send(Action::JobTraceChunkLoaded { job_id, job_name, offset, chunk });
+                    }
+                    // The cached log is redacted, so its offsets aren't the trace's: only a download
+                    // from the start can fall back on it
+                    Err(e) => match (e.is_unreachable() && offset == 0)
+                        .then(|| cache::load_log(&CacheKey::Log { host: client.host(), project_id, job_id }))
+                        .flatten()
+                    {
+                        Some(cached) => {
+                            note_offline(&action_tx, cached.saved_at);
+                            let total_size = Some(cached.value.len() as u64);
+                            let chunk = TraceChunk { data: cached.value.into_bytes(), total_size, complete: true };
+                            let _ = action_tx.send(Action::JobTraceChunkLoaded { job_id, job_name, offset, chunk });
+                        }
+                        None if matches!(e, PeeplabError::LogExpired) => {
+                            let _ = action_tx.send(Action::JobTraceExpired { job_id });
+                        }
+                        None => {
+                            let _ = action_tx.send(Action::JobTraceFailed { job_id, error: e.to_string() });
+                        }
+                    },
+                }
+            });
+        }
+
+        Effect::PrefetchJobLogs { project_id, pipeline_id, job_ids, cancel } => {
+            for job_id in job_ids {
+                let (action_tx, client, cancel) = (action_tx.clone(), gitlab_client.clone(), cancel.clone());
+                tokio::spawn(async move {
+                    let Ok(_permit) = LOG_PREFETCH_PERMITS.acquire().await else {
+                        return;
+                    };
+                    // Skipped once the pipeline is left, including while waiting for a permit
+                    if cancel.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let action = match client.get_job_trace(project_id, job_id).await {
+                        Ok(trace) => Action::JobLogPrefetched { pipeline_id, job_id, trace },
+                        Err(e) => Action::JobLogPrefetchFailed { pipeline_id, job_id, error: e.to_string() },
+                    };
+                    if !cancel.load(Ordering::Relaxed) {
+                        let _ = action_tx.send(action);
+                    }
+                });
+            }
+        }
+
+        Effect::FetchLogDiff { project_id, left_id, right_id } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let traces = tokio::try_join!(
+                    client.get_job_trace(project_id, left_id),
+                    client.get_job_trace(project_id, right_id),
+                );
+                let action = match traces {
+                    Ok((left, right)) => {
+                        // Diffing long logs takes a while, keep it off the event loop
+                        match tokio::task::spawn_blocking(move || DiffContent::compute(&left, &right)).await {
+                            Ok(content) => Action::LogDiffLoaded { left_id, right_id, content },
+                            Err(e) => Action::LogDiffFailed { left_id, right_id, error: e.to_string() },
+                        }
+                    }
+                    Err(e) => Action::LogDiffFailed { left_id, right_id, error: e.to_string() },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::RetryJob { mr_index, project_id, pipeline_id, job_id } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                match client.retry_job(project_id, job_id).await {
+                    Ok(job) => {
+                        let _ = action_tx.send(Action::JobRetried { mr_index, pipeline_id, job });
+                    }
+                    Err(e) => {
+                        let _ = action_tx.send(Action::JobActionFailed(format!("Retry failed: {}", e)));
                     }
                 }
             });
         }
 
-        Effect::FetchJobTrace { project_id, job_id, job_name } => {
+        Effect::CancelJob { mr_index, project_id, pipeline_id, job_id } => {
             let action_tx = action_tx.clone();
             let client = gitlab_client.clone();
             tokio::spawn(async move {
-                match client.get_job_trace(project_id, job_id).await {
-                    Ok(trace) => {
-                        let _ = action_tx.send(Action::JobTraceLoaded { job_id, job_name, trace });
+                match client.cancel_job(project_id, job_id).await {
+                    Ok(job) => {
+                        let _ = action_tx.send(Action::JobCanceled { mr_index, pipeline_id, job });
                     }
                     Err(e) => {
-                        let _ = action_tx.send(Action::ApiError(e.to_string()));
+                        let _ = action_tx.send(Action::JobActionFailed(format!("Cancel failed: {}", e)));
                     }
                 }
             });
         }
 
+        Effect::PlayJob { mr_index, project_id, pipeline_id, job_id, variables } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                match client.play_job(project_id, job_id, &variables).await {
+                    Ok(job) => {
+                        let _ = action_tx.send(Action::JobPlayed { mr_index, pipeline_id, job });
+                    }
+                    Err(e) => {
+                        let _ = action_tx.send(Action::JobActionFailed(format!("Play failed: {}", e)));
+                    }
+                }
+            });
+        }
+
+        Effect::CancelPipeline { mr_index, project_id, pipeline_id } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                match client.cancel_pipeline(project_id, pipeline_id).await {
+                    Ok(pipeline) => {
+                        let _ = action_tx.send(Action::PipelineCanceled { mr_index, pipeline });
+                    }
+                    Err(e) => {
+                        let _ = action_tx.send(Action::JobActionFailed(format!("Cancel failed: {}", e)));
+                    }
+                }
+            });
+        }
+
+        Effect::RetryPipeline { mr_index, project_id, pipeline_id } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let action = match client.retry_pipeline(project_id, pipeline_id).await {
+                    Ok(pipeline) => Action::PipelineRetried { mr_index, pipeline },
+                    Err(e) => Action::PipelineRetryFailed { pipeline_id, error: e.to_string() },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::TriggerPipeline { project_id, ref_name, variables } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                // Check the ref first, GitLab's own error for a missing ref is less clear
+                let action = match client.ref_exists(project_id, &ref_name).await {
+                    Ok(false) => Action::PipelineTriggerFailed(format!("ref '{}' does not exist", ref_name)),
+                    Err(e) => Action::PipelineTriggerFailed(e.to_string()),
+                    Ok(true) => match client.create_pipeline(project_id, &ref_name, &variables).await {
+                        Ok(pipeline) => Action::PipelineTriggered(pipeline),
+                        Err(e) => Action::PipelineTriggerFailed(e.to_string()),
+                    },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::FetchTestReport { project_id, job_id, job_name } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let action = match client.get_job_test_report(project_id, job_id, &job_name).await {
+                    Ok(suite) => Action::TestReportLoaded { job_id, suite },
+                    Err(e) => Action::TestReportFailed { job_id, error: e.to_string() },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::FetchJobNeeds { project_path, pipeline_id, pipeline_iid } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let action = match client.get_pipeline_needs(&project_path, pipeline_iid).await {
+                    Ok(needs) => Action::JobNeedsLoaded { pipeline_id, needs },
+                    Err(e) => Action::JobNeedsFailed { pipeline_id, error: e.to_string() },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::FetchArtifactList { project_id, job_id } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let action = match client.list_job_artifacts(project_id, job_id).await {
+                    Ok(entries) => Action::ArtifactListLoaded { job_id, entries },
+                    Err(e) => Action::ArtifactListFailed { job_id, error: e.to_string() },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::FetchArtifactFile { project_id, job_id, path } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let text = client
+                    .get_artifact_file(project_id, job_id, &path)
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(artifacts::decode_text);
+                let action = match text {
+                    Ok(text) => Action::ArtifactFileLoaded { job_id, path, text },
+                    Err(error) => Action::ArtifactFileFailed { job_id, path, error },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::FetchHeadPipelines { project_id, delay } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let action = match git::get_head_sha() {
+                    Ok(sha) => match client.get_pipelines_for_sha(project_id, &sha).await {
+                        Ok(pipelines) => Action::HeadPipelinesLoaded { sha, pipelines },
+                        Err(e) => Action::HeadPipelinesFailed(e.to_string()),
+                    },
+                    Err(e) => Action::HeadPipelinesFailed(e.to_string()),
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::FetchGroupPipelines { group, page } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let projects = match client.get_group_projects(&group, page).await {
+                    Ok(projects) => projects,
+                    Err(e) => {
+                        let _ = action_tx.send(Action::GroupPipelinesFailed { group, error: e.to_string() });
+                        return;
+                    }
+                };
+                let _ = action_tx.send(Action::GroupProjectsLoaded {
+                    group: group.clone(),
+                    project_count: projects.items.len(),
+                    next_page: projects.next_page,
+                });
+
+                // Only a few projects are fetched at once, large groups would otherwise hit the rate limit
+                let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(app::GROUP_FETCH_CONCURRENCY));
+                for project in projects.items {
+                    let Ok(permit) = permits.clone().acquire_owned().await else {
+                        return;
+                    };
+                    let (action_tx, client, group) = (action_tx.clone(), client.clone(), group.clone());
+                    tokio::spawn(async move {
+                        let action =
+                            match client.get_project_pipelines(project.id, app::GROUP_PIPELINES_PER_PROJECT).await {
+                                Ok(pipelines) => Action::GroupPipelinesLoaded {
+                                    group,
+                                    project_id: project.id,
+                                    project_path: project.path_with_namespace,
+                                    pipelines,
+                                },
+                                Err(e) => Action::GroupProjectFailed {
+                                    group,
+                                    project_path: project.path_with_namespace,
+                                    error: e.to_string(),
+                                },
+                            };
+                        let _ = action_tx.send(action);
+                        drop(permit);
+                    });
+                }
+            });
+        }
+
+        Effect::DownloadArtifacts { project_id, job_id, path, cancel } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let progress_tx = action_tx.clone();
+                let result = client
+                    .download_job_artifacts(project_id, job_id, &path, &cancel, |received, total| {
+                        let _ = progress_tx.send(Action::ArtifactDownloadProgress { job_id, received, total });
+                    })
+                    .await;
+
+                let action = match result {
+                    Ok(true) => Action::ArtifactsDownloaded { job_id, path },
+                    Ok(false) => Action::ArtifactDownloadCanceled { job_id },
+                    Err(e) => Action::ArtifactDownloadFailed { job_id, error: e.to_string() },
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
+        Effect::ScheduleLivePoll { delay } => {
+            let action_tx = action_tx.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let _ = action_tx.send(Action::LivePollDue);
+            });
+        }
+
+        Effect::ContinueSearch { generation } => {
+            // Goes through the channel so pending input is handled before the next step
+            let _ = action_tx.send(Action::ContinueSearch { generation });
+        }
+
+        Effect::DebounceSearch { generation, delay } => {
+            let action_tx = action_tx.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let _ = action_tx.send(Action::SearchInputSettled { generation });
+            });
+        }
+
+        Effect::Batch(effects) => {
+            for effect in effects {
+                Box::pin(handle_effect(effect, gitlab_client, action_tx.clone())).await?;
+            }
+        }
+
+        Effect::PollJobTrace { project_id, job_id, offset, delay } => {
+            let action_tx = action_tx.clone();
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+
+                // Fetch the job first so a job finishing mid-poll still gets its final trace.
+                // While it runs the bytes past `offset` are asked for (GitLab sends a live trace whole,
+                // only its new part is kept), once it ends the whole trace
+                let result = match client.get_job(project_id, job_id).await {
+                    Ok(job) if job.status.is_active() => client
+                        .get_job_trace_range(project_id, job_id, offset, app::LOG_FETCH_CHUNK_SIZE)
+                        .await
+                        .map(|chunk| Action::JobTraceStreamed { job_id, offset, chunk, status: job.status }),
+                    Ok(job) => client
+                        .get_job_trace(project_id, job_id)
+                        .await
+                        .map(|trace| Action::JobTraceRefreshed { job_id, trace, status: job.status }),
+                    Err(e) => Err(e),
+                };
+
+                match result {
+                    Ok(action) => {
+                        let _ = action_tx.send(action);
+                    }
+                    Err(e) => {
+                        let _ = action_tx.send(Action::JobTracePollFailed {
+                            job_id,
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            });
+        }
+
+        Effect::FetchSessionInfo { project_id, with_project } => {
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                // The status bar just leaves out what could not be fetched
+                if let Ok(user) = client.get_current_user().await {
+                    let _ = action_tx.send(Action::CurrentUserLoaded(user));
+                }
+                if with_project {
+                    if let Ok(project) = client.get_project(project_id).await {
+                        let _ = action_tx.send(Action::ProjectPathLoaded(project.path_with_namespace));
+                    }
+                }
+            });
+        }
+
+        Effect::ResolveProject { profile } => {
+            let client = gitlab_client.clone();
+            tokio::spawn(async move {
+                let action = match git::detect_project_from_git() {
+                    Ok(git_project) => match client.get_project_by_path(&git_project.path()).await {
+                        Ok(project) => Action::ProjectResolved {
+                            profile,
+                            project_id: project.id,
+                            project_path: Some(project.path_with_namespace),
+                        },
+                        Err(e) => Action::ApiError(format!("Project '{}' not found: {}", git_project.path(), e)),
+                    },
+                    Err(e) => Action::ApiError(format!(
+                        "Set default_project_id for this profile, the project could not be detected: {}",
+                        e
+                    )),
+                };
+                let _ = action_tx.send(action);
+            });
+        }
+
         Effect::FetchNotes {
             mr_index,
             project_id,
@@ -306,7 +1024,7 @@ async fn handle_effect(
                         let _ = action_tx.send(Action::NotesLoaded { mr_index, notes });
                     }
                     Err(e) => {
-                        let _ = action_tx.send(Action::ApiError(e.to_string()));
+                        let _ = action_tx.send(api_error_action(e));
                     }
                 }
             });
@@ -325,15 +1043,15 @@ async fn handle_effect(
             let action_tx = action_tx.clone();
             let client = gitlab_client.clone();
             tokio::spawn(async move {
-                match client
-                    .get_merge_requests_by_branch(project_id, &source_branch)
-                    .await
-                {
+                let key =
+                    CacheKey::MergeRequests { host: client.host(), project_id, branch: Some(source_branch.clone()) };
+                let fetch = client.get_merge_requests_by_branch(project_id, &source_branch);
+                match fetch_cached(key, fetch, &action_tx).await {
                     Ok(mrs) => {
                         let _ = action_tx.send(Action::MergeRequestsLoaded(mrs));
                     }
                     Err(e) => {
-                        let _ = action_tx.send(Action::ApiError(e.to_string()));
+                        let _ = action_tx.send(api_error_action(e));
                     }
                 }
             });
@@ -347,10 +1065,11 @@ async fn handle_effect(
             let action_tx = action_tx.clone();
             let client = gitlab_client.clone();
             tokio::spawn(async move {
+                let key = CacheKey::MergeRequests { host: client.host(), project_id, branch: source_branch.clone() };
                 let result = if let Some(branch) = source_branch {
-                    client.get_merge_requests_by_branch(project_id, &branch).await
+                    fetch_cached(key, client.get_merge_requests_by_branch(project_id, &branch), &action_tx).await
                 } else {
-                    client.get_merge_requests(project_id).await
+                    fetch_cached(key, client.get_merge_requests(project_id), &action_tx).await
                 };
 
                 match result {
@@ -358,25 +1077,60 @@ async fn handle_effect(
                         let _ = action_tx.send(Action::MergeRequestsLoaded(mrs));
                     }
                     Err(e) => {
-                        let _ = action_tx.send(Action::ApiError(e.to_string()));
+                        let _ = action_tx.send(api_error_action(e));
                     }
                 }
             });
         }
 
-        Effect::OpenUrl(url) => {
-            // Open URL in default browser
-            tokio::task::spawn_blocking(move || {
-                #[cfg(target_os = "linux")]
-                let _ = std::process::Command::new("xdg-open").arg(&url).spawn();
+        Effect::CopyToClipboard { text, line_count } => {
+            let action_tx = action_tx.clone();
+            tokio::task::spawn_blocking(move || match clipboard::copy_to_clipboard(&text) {
+                Ok(()) => {
+                    let _ = action_tx.send(Action::CopiedToClipboard(line_count));
+                }
+                Err(e) => {
+                    let _ = action_tx.send(Action::ClipboardUnavailable(e.to_string()));
+                }
+            });
+        }
+
+        Effect::ExportLog { path, content } => {
+            let action_tx = action_tx.clone();
+            tokio::task::spawn_blocking(move || match export::write_log_file(&path, &content) {
+                Ok(()) => {
+                    let _ = action_tx.send(Action::LogExported(path));
+                }
+                Err(e) => {
+                    let _ = action_tx.send(Action::LogExportFailed(e.to_string()));
+                }
+            });
+        }
 
-                #[cfg(target_os = "macos")]
-                let _ = std::process::Command::new("open").arg(&url).spawn();
+        Effect::SaveJobLog { project_id, job_id, log } => {
+            let key = CacheKey::Log { host: gitlab_client.host(), project_id, job_id };
+            tokio::task::spawn_blocking(move || cache::store_log(&key, &log));
+        }
 
-                #[cfg(target_os = "windows")]
-                let _ = std::process::Command::new("cmd").args(&["/C", "start", &url]).spawn();
-            })
-            .await?;
+        Effect::Notify { mode, summary, body, urgent } => {
+            // Best effort: without a notifier (e.g. over SSH) the bell alone has to do
+            if matches!(mode, app::NotifyMode::Bell | app::NotifyMode::Both) {
+                let _ = peeplab::notify::ring_bell();
+            }
+            if matches!(mode, app::NotifyMode::Desktop | app::NotifyMode::Both) {
+                tokio::task::spawn_blocking(move || {
+                    let _ = peeplab::notify::desktop_notification(&summary, &body, urgent);
+                });
+            }
+        }
+
+        Effect::OpenUrl(url) => {
+            let action_tx = action_tx.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = browser::open_url(&url) {
+                    let _ = action_tx.send(Action::UrlOpenFailed(e.to_string()));
+                }
+            });
         }
     }
 
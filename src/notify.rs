@@ -0,0 +1,24 @@
+use crate::error::{PeeplabError, Result};
+use notify_rust::Notification;
+#[cfg(not(target_os = "macos"))]
+use notify_rust::Urgency;
+use std::io::Write;
+
+/// Ring the terminal bell, which most terminals turn into a sound or an urgency hint
+pub fn ring_bell() -> Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(b"\x07")?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Show a desktop notification, urgent ones stay up until dismissed where the platform has urgency levels
+pub fn desktop_notification(summary: &str, body: &str, urgent: bool) -> Result<()> {
+    let mut notification = Notification::new();
+    notification.appname("peeplab").summary(summary).body(body);
+    #[cfg(not(target_os = "macos"))]
+    notification.urgency(if urgent { Urgency::Critical } else { Urgency::Normal });
+    #[cfg(target_os = "macos")]
+    let _ = urgent;
+    notification.show().map(|_| ()).map_err(|e| PeeplabError::Notification(e.to_string()))
+}
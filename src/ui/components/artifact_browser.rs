@@ -0,0 +1,119 @@
+use crate::app::{App, ArtifactBrowser, ArtifactFileView};
+use crate::ui::theme::Theme;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+fn popup_area(area: Rect) -> Rect {
+    centered_rect(90, 85, area)
+}
+
+/// Lines of an artifact file that fit in the popup
+pub fn content_height(area: Rect) -> usize {
+    popup_area(area).height.saturating_sub(2) as usize
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KiB", b as f64 / 1024.0),
+        b => format!("{:.1} MiB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+fn message(theme: &Theme, text: &str, error: bool) -> Paragraph<'static> {
+    let color = if error { theme.error } else { theme.muted };
+    Paragraph::new(Line::from(Span::styled(text.to_string(), Style::default().fg(color)))).alignment(Alignment::Center)
+}
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let Some(browser) = &app.artifact_browser else {
+        return;
+    };
+    let popup = popup_area(area);
+    f.render_widget(Clear, popup);
+    match &browser.file {
+        Some(file) => render_file(f, app, browser, file, popup),
+        None => render_list(f, app, browser, popup),
+    }
+}
+
+fn render_list(f: &mut Frame, app: &App, browser: &ArtifactBrowser, popup: Rect) {
+    let theme = app.theme;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title))
+        .title(format!(" Artifacts of {} ", browser.job_name))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(Span::styled(
+            " ↑/↓ select, Enter view, Esc close ",
+            Style::default().fg(theme.muted),
+        )));
+
+    let entries = match (&browser.error, &browser.entries) {
+        (Some(error), _) => return f.render_widget(message(theme, error, true).block(block), popup),
+        (None, None) => return f.render_widget(message(theme, "Reading the archive…", false).block(block), popup),
+        (None, Some(entries)) if entries.is_empty() => {
+            return f.render_widget(message(theme, "The archive has no files", false).block(block), popup)
+        }
+        (None, Some(entries)) => entries,
+    };
+
+    let width = entries.iter().map(|entry| entry.path.chars().count()).max().unwrap_or(0);
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|entry| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<width$}  ", entry.path, width = width)),
+                Span::styled(format_size(entry.size), Style::default().fg(theme.muted)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(theme.selection_bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    let mut state = ListState::default();
+    state.select(Some(browser.selected));
+    f.render_stateful_widget(list, popup, &mut state);
+}
+
+fn render_file(f: &mut Frame, app: &App, browser: &ArtifactBrowser, file: &ArtifactFileView, popup: Rect) {
+    let theme = app.theme;
+    let position = match &file.lines {
+        Some(lines) if !lines.is_empty() => {
+            let last = (file.scroll + file.height).min(lines.len());
+            format!(" {}-{}/{} ", file.scroll + 1, last, lines.len())
+        }
+        _ => String::new(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(format!(" {} ({}) ", file.path, browser.job_name))
+        .title_bottom(Line::from(Span::styled(
+            " j/k scroll, Space/PgUp page, g/G top/bottom, Esc back ",
+            Style::default().fg(theme.muted),
+        )))
+        .title_bottom(Line::from(position).alignment(Alignment::Right));
+
+    let lines = match (&file.error, &file.lines) {
+        (Some(error), _) => return f.render_widget(message(theme, error, true).block(block), popup),
+        (None, None) => return f.render_widget(message(theme, "Loading…", false).block(block), popup),
+        (None, Some(lines)) => lines,
+    };
+    let visible: Vec<Line> = lines
+        .iter()
+        .skip(file.scroll)
+        .take(file.height.max(1))
+        .map(|line| Line::from(line.as_str()))
+        .collect();
+    f.render_widget(Paragraph::new(visible).block(block), popup);
+}
@@ -0,0 +1,70 @@
+use crate::app::App;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let Some(palette) = &app.command_palette else {
+        return;
+    };
+    let popup_area = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.title))
+        .title(" Command Palette ")
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(Span::styled(
+            " ↑/↓ select, Enter run, Esc close ",
+            Style::default().fg(app.theme.muted),
+        )));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(inner);
+
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled(": ", Style::default().fg(app.theme.accent)),
+        Span::styled(palette.query.as_str(), Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD)),
+        Span::styled("_", Style::default().fg(app.theme.title).add_modifier(Modifier::SLOW_BLINK)),
+    ]));
+    f.render_widget(input, chunks[0]);
+
+    let entries = app.palette_entries();
+    if entries.is_empty() {
+        let empty = Paragraph::new(Span::styled(
+            "No matching action in this view",
+            Style::default().fg(app.theme.muted),
+        ));
+        f.render_widget(empty, chunks[1]);
+        return;
+    }
+
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|entry| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<40}", entry.description)),
+                Span::styled(entry.keys.clone(), Style::default().fg(app.theme.accent)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(app.theme.selection_bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    let mut state = ListState::default();
+    state.select(Some(palette.selected.min(entries.len() - 1)));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
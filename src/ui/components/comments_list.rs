@@ -2,7 +2,7 @@ use crate::app::App;
 use chrono::Utc;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
@@ -39,7 +39,7 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
         let block = Block::default()
             .borders(Borders::ALL)
             .title("Comments")
-            .style(Style::default().fg(Color::Yellow));
+            .style(Style::default().fg(app.theme.title));
         f.render_widget(block, area);
         return;
     }
@@ -53,7 +53,7 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
         let block = Block::default()
             .borders(Borders::ALL)
             .title("Comments")
-            .style(Style::default().fg(Color::Gray));
+            .style(Style::default().fg(app.theme.inactive));
         f.render_widget(block, area);
         return;
     }
@@ -65,7 +65,7 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
         .iter()
         .map(|note| {
             let author_style = Style::default()
-                .fg(Color::Cyan)
+                .fg(app.theme.accent)
                 .add_modifier(Modifier::BOLD);
 
             let time_ago = format_relative_time(note.created_at);
@@ -74,7 +74,7 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
             let mut header_spans = vec![
                 Span::styled(&note.author.name, author_style),
                 Span::raw(" • "),
-                Span::styled(time_ago, Style::default().fg(Color::DarkGray)),
+                Span::styled(time_ago, Style::default().fg(app.theme.muted)),
             ];
 
             // Add file and line information if available
@@ -83,13 +83,13 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
                     header_spans.push(Span::raw(" • "));
                     header_spans.push(Span::styled(
                         new_path.clone(),
-                        Style::default().fg(Color::Yellow),
+                        Style::default().fg(app.theme.title),
                     ));
 
                     if let Some(new_line) = position.new_line {
                         header_spans.push(Span::styled(
                             format!(":{}", new_line),
-                            Style::default().fg(Color::Yellow),
+                            Style::default().fg(app.theme.title),
                         ));
                     }
                 }
@@ -139,7 +139,7 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
         )
         .highlight_style(
             Style::default()
-                .bg(Color::DarkGray)
+                .bg(app.theme.selection_bg)
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("> ");
@@ -0,0 +1,44 @@
+use crate::app::Confirmation;
+use crate::ui::theme::Theme;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+pub fn render(f: &mut Frame, confirmation: &Confirmation, theme: &Theme, area: Rect) {
+    let popup_area = centered_rect(50, 20, area);
+    f.render_widget(Clear, popup_area);
+
+    let text = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            confirmation.prompt.as_str(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("y/Enter", Style::default().fg(theme.accent)),
+            Span::raw(" confirm   "),
+            Span::styled("n/Esc", Style::default().fg(theme.accent)),
+            Span::raw(" cancel"),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.title))
+                .title(" Confirm ")
+                .title_alignment(Alignment::Center),
+        )
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center);
+
+    f.render_widget(paragraph, popup_area);
+}
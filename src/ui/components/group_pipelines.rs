@@ -0,0 +1,94 @@
+use crate::app::App;
+use crate::ui::components::pipeline_list::format_relative_time;
+use crate::ui::status::status_style;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let Some(view) = &app.group_pipelines else {
+        return;
+    };
+    let popup_area = centered_rect(80, 70, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut title = format!(" Pipelines in {} ({}", view.group, view.sort.label());
+    if let Some(status) = &view.status_filter {
+        title.push_str(&format!(", {} only", status.api_name().replace('_', " ")));
+    }
+    if view.projects_loading > 0 {
+        title.push_str(&format!(", loading {} projects", view.projects_loading));
+    } else if view.loading_page {
+        title.push_str(", loading");
+    }
+    if !view.failed_projects.is_empty() {
+        title.push_str(&format!(", {} failed", view.failed_projects.len()));
+    }
+    title.push_str(") ");
+    let hint = " ↑/↓ select, Enter open, 1-4 filter, 0 all, s sort, r refresh, Esc close ";
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.title))
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(Span::styled(hint, Style::default().fg(app.theme.muted))));
+
+    let message = |text: String, style: Style| {
+        Paragraph::new(Line::from(Span::styled(text, style))).alignment(Alignment::Center).block(block.clone())
+    };
+    if let Some(error) = &view.error {
+        f.render_widget(message(error.clone(), Style::default().fg(app.theme.error)), popup_area);
+        return;
+    }
+    let rows = view.visible();
+    if rows.is_empty() {
+        let text = if view.loading_page || view.projects_loading > 0 {
+            "Loading…"
+        } else if view.status_filter.is_some() {
+            "No pipeline with this status"
+        } else {
+            "No pipeline in this group"
+        };
+        f.render_widget(message(text.to_string(), Style::default().fg(app.theme.muted)), popup_area);
+        return;
+    }
+
+    let mut items: Vec<ListItem> = rows
+        .iter()
+        .map(|row| {
+            let pipeline = &row.pipeline;
+            let (status_color, symbol) = status_style(app.theme, pipeline.status.api_name());
+            let mut spans = vec![
+                Span::styled(format!("{} ", symbol), Style::default().fg(status_color)),
+                Span::styled(row.project_path.clone(), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format!(" #{} on ", pipeline.iid)),
+                Span::styled(pipeline.ref_name.clone(), Style::default().fg(app.theme.accent)),
+                Span::raw(" - "),
+                Span::styled(format_relative_time(&pipeline.created_at), Style::default().fg(app.theme.muted)),
+            ];
+            if let Some(trigger) = pipeline.trigger() {
+                spans.push(Span::styled(format!(" · {}", trigger), Style::default().fg(app.theme.muted)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    if view.next_page.is_some() {
+        let text = if view.loading_page { "  Loading more projects…" } else { "  ↓ More projects" };
+        items.push(ListItem::new(Line::from(Span::styled(text, Style::default().fg(app.theme.muted)))));
+    }
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(app.theme.selection_bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    let mut state = ListState::default();
+    state.select(Some(view.selected));
+    f.render_stateful_widget(list, popup_area, &mut state);
+}
@@ -0,0 +1,90 @@
+use crate::app::App;
+use crate::ui::components::pipeline_list::format_relative_time;
+use crate::ui::status::status_style;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let Some(head) = &app.head_pipelines else {
+        return;
+    };
+    let popup_area = centered_rect(60, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let title = match &head.sha {
+        Some(sha) => format!(" Pipelines for HEAD ({}) ", &sha[..sha.len().min(8)]),
+        None => " Pipelines for HEAD ".to_string(),
+    };
+    let hint = match &head.pipelines {
+        Some(pipelines) if pipelines.is_empty() && head.polling => " p stop polling, r refresh, Esc close ",
+        Some(pipelines) if pipelines.is_empty() => " p poll until one starts, r refresh, Esc close ",
+        _ => " ↑/↓ select, Enter open, r refresh, Esc close ",
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.title))
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(Span::styled(hint, Style::default().fg(app.theme.muted))));
+
+    let message = |text: String, style: Style| {
+        Paragraph::new(Line::from(Span::styled(text, style))).alignment(Alignment::Center).block(block.clone())
+    };
+    if let Some(error) = &head.error {
+        f.render_widget(message(error.clone(), Style::default().fg(app.theme.error)), popup_area);
+        return;
+    }
+    let pipelines = match &head.pipelines {
+        None => {
+            f.render_widget(message("Loading…".to_string(), Style::default().fg(app.theme.muted)), popup_area);
+            return;
+        }
+        Some(pipelines) if pipelines.is_empty() => {
+            let text = if head.polling {
+                "No pipeline for HEAD yet, polling…"
+            } else {
+                "No pipeline for HEAD yet"
+            };
+            f.render_widget(message(text.to_string(), Style::default().fg(app.theme.muted)), popup_area);
+            return;
+        }
+        Some(pipelines) => pipelines,
+    };
+
+    let items: Vec<ListItem> = pipelines
+        .iter()
+        .map(|pipeline| {
+            let status = pipeline.status.api_name();
+            let (status_color, symbol) = status_style(app.theme, status);
+            let mut spans = vec![
+                Span::styled(format!("{} ", symbol), Style::default().fg(status_color)),
+                Span::raw(format!("Pipeline #{} ", pipeline.iid)),
+                Span::styled(format!("({})", status.replace('_', " ")), Style::default().fg(status_color)),
+                Span::raw(" on "),
+                Span::styled(pipeline.ref_name.clone(), Style::default().fg(app.theme.accent)),
+                Span::raw(" - "),
+                Span::styled(format_relative_time(&pipeline.created_at), Style::default().fg(app.theme.muted)),
+            ];
+            if let Some(trigger) = pipeline.trigger() {
+                spans.push(Span::styled(format!(" · {}", trigger), Style::default().fg(app.theme.muted)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(app.theme.selection_bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    let mut state = ListState::default();
+    state.select(Some(head.selected));
+    f.render_stateful_widget(list, popup_area, &mut state);
+}
@@ -1,172 +1,123 @@
+use crate::app::App;
+use crate::events::KeyContext;
+use crate::ui::status::{status_style, LEGEND};
+use crate::ui::theme::Theme;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 
-pub fn render(f: &mut Frame, area: Rect) {
-    // Calculate the popup area (centered)
-    let popup_area = centered_rect(60, 70, area);
+/// Width of the key column, longer key lists push the description right
+const KEYS_WIDTH: usize = 18;
+
+/// Keys that are not rebindable: they only apply while typing in a prompt
+const TYPING_KEYS: &[(&str, &str)] = &[
+    ("Enter / Esc", "Submit / cancel the search, filter, job filter or pipeline prompt"),
+    ("Ctrl+R", "Cycle search mode: literal, case-insensitive, regex"),
+    ("Ctrl+W", "Match whole words only, kept for later searches"),
+    ("↑ / ↓", "Recall older/newer searches"),
+    ("Ctrl+V", "Invert the log filter"),
+];
+
+fn heading(theme: &Theme, text: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        text.to_string(),
+        Style::default().fg(theme.heading).add_modifier(Modifier::BOLD),
+    ))
+}
+
+fn key_line(theme: &Theme, keys: &str, description: &str) -> Line<'static> {
+    Line::from(vec![
+        Span::raw("  "),
+        Span::styled(format!("{:<width$}", keys, width = KEYS_WIDTH), Style::default().fg(theme.accent)),
+        Span::raw(format!(" {}", description)),
+    ])
+}
+
+/// Help content, generated from the current key bindings so it matches any `[keys.*]` overrides
+fn help_lines(app: &App) -> Vec<Line<'static>> {
+    let theme = app.theme;
+    let mut lines = Vec::new();
+    for (title, entries) in app.keymap.help_sections() {
+        if entries.is_empty() {
+            continue;
+        }
+        lines.push(heading(theme, &format!("{}:", title)));
+        for entry in entries {
+            lines.push(key_line(theme, &entry.keys_label(), entry.description));
+        }
+        lines.push(Line::from(""));
+    }
+
+    lines.push(heading(theme, "While Typing:"));
+    for (keys, description) in TYPING_KEYS {
+        lines.push(key_line(theme, keys, description));
+    }
+    lines.push(Line::from(Span::styled(
+        "  In the log viewer, a number before a motion repeats it, e.g. 10j",
+        Style::default().fg(theme.muted),
+    )));
+    lines.push(Line::from(""));
+
+    lines.push(heading(theme, "Status Indicators:"));
+    lines.push(Line::from(
+        std::iter::once(Span::raw(" "))
+            .chain(LEGEND.iter().map(|&status| {
+                let (color, symbol) = status_style(theme, status);
+                Span::styled(format!(" {} {} ", symbol, status), Style::default().fg(color))
+            }))
+            .collect::<Vec<_>>(),
+    ));
+    lines
+}
+
+fn popup_area(area: Rect) -> Rect {
+    centered_rect(70, 80, area)
+}
+
+/// How far the help can scroll before its last line reaches the bottom of the popup
+pub fn max_scroll(app: &App, area: Rect) -> u16 {
+    let visible = popup_area(area).height.saturating_sub(2) as usize;
+    help_lines(app).len().saturating_sub(visible) as u16
+}
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let popup_area = popup_area(area);
 
     // Clear the background
     f.render_widget(Clear, popup_area);
 
-    // Create the help content
-    let help_text = vec![
-        Line::from(vec![Span::styled(
-            "Keyboard Controls",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("q", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-            Span::raw(" or "),
-            Span::styled("Ctrl+C", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-            Span::raw(" - Quit the application"),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("?", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-            Span::raw(" - Show/hide this help"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Navigation:",
-            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("←/→", Style::default().fg(Color::Cyan)),
-            Span::raw(" or "),
-            Span::styled("h/l", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Switch between MR tabs"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
-            Span::raw(" or "),
-            Span::styled("k/j", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Navigate jobs"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("[/]", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Switch between pipelines"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Actions:",
-            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("Enter", Style::default().fg(Color::Cyan)),
-            Span::raw(" - View selected job log"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("r", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Refresh all data"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("d", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Remove current MR from tracking"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("c", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Toggle between jobs and comments view"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("o", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Open current MR in browser"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Log Viewer:",
-            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("q/Esc", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Close log viewer"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
-            Span::raw(" or "),
-            Span::styled("k/j", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Scroll log"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("PgUp/PgDn", Style::default().fg(Color::Cyan)),
-            Span::raw(", "),
-            Span::styled("Home/End", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Jump in log"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("t", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Toggle timestamp display (hidden/date/full)"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("/", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Start search"),
-        ]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("n/N", Style::default().fg(Color::Cyan)),
-            Span::raw(" - Next/previous search result"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Status Indicators:",
-            Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![
-            Span::raw("  "),
-            Span::styled("✓", Style::default().fg(Color::Green)),
-            Span::raw(" - Success"),
-            Span::raw("  "),
-            Span::styled("✗", Style::default().fg(Color::Red)),
-            Span::raw(" - Failed"),
-            Span::raw("  "),
-            Span::styled("⟳", Style::default().fg(Color::Yellow)),
-            Span::raw(" - Running"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Press Esc or ? to close",
-            Style::default()
-                .fg(Color::DarkGray)
-                .add_modifier(Modifier::ITALIC),
-        )]),
-    ];
-
-    let paragraph = Paragraph::new(help_text)
+    let close_keys = app
+        .keymap
+        .help_entries(KeyContext::Help)
+        .into_iter()
+        .find(|entry| entry.description == "Close help")
+        .map(|entry| entry.keys_label())
+        .unwrap_or_default();
+
+    let paragraph = Paragraph::new(help_lines(app))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow))
-                .title(" Help ")
-                .title_alignment(Alignment::Center),
+                .border_style(Style::default().fg(app.theme.title))
+                .title(" Keyboard Controls ")
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(Span::styled(
+                    format!(" ↑/↓ scroll, {} close ", close_keys),
+                    Style::default().fg(app.theme.muted).add_modifier(Modifier::ITALIC),
+                ))),
         )
-        .wrap(Wrap { trim: true })
+        .scroll((app.help_scroll.min(max_scroll(app, area)), 0))
         .alignment(Alignment::Left);
 
     f.render_widget(paragraph, popup_area);
 }
 
 /// Helper function to create a centered rectangle
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
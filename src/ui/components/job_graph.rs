@@ -0,0 +1,92 @@
+use crate::app::{App, JobGraphRow};
+use crate::ui::status::status_style;
+use crate::ui::theme::Theme;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+/// `└─ <glyph> name  stage`, indented under the job it needs, with what kept it from running
+fn row_line(theme: &Theme, row: &JobGraphRow) -> Line<'static> {
+    let (color, symbol) = status_style(theme, row.job.status.api_name());
+    let mut spans = Vec::new();
+    if row.depth > 0 {
+        spans.push(Span::styled(
+            format!("{}└─ ", "   ".repeat(row.depth - 1)),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    spans.push(Span::styled(format!("{} ", symbol), Style::default().fg(color)));
+    spans.push(Span::raw(row.job.name.clone()));
+    spans.push(Span::styled(format!("  {}", row.job.stage), Style::default().fg(theme.muted)));
+    if row.repeated {
+        spans.push(Span::styled("  ↑ listed above", Style::default().fg(theme.muted)));
+    }
+    if !row.blocked_by.is_empty() {
+        let blockers: Vec<String> = row
+            .blocked_by
+            .iter()
+            .map(|job| format!("{} ({})", job.name, job.status.api_name()))
+            .collect();
+        spans.push(Span::styled(
+            format!("  ← blocked by {}", blockers.join(", ")),
+            Style::default().fg(theme.failed).add_modifier(Modifier::BOLD),
+        ));
+    }
+    Line::from(spans)
+}
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let Some(view) = &app.job_graph else {
+        return;
+    };
+    let theme = app.theme;
+    let popup = centered_rect(85, 80, area);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title))
+        .title(format!(" Needs of pipeline #{} ", view.pipeline_iid))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(Span::styled(
+            " ↑/↓ select, Enter open log, Esc close ",
+            Style::default().fg(theme.muted),
+        )));
+
+    let message = |text: String, style: Style| {
+        Paragraph::new(Line::from(Span::styled(text, style))).alignment(Alignment::Center).block(block.clone())
+    };
+    if let Some(error) = &view.error {
+        return f.render_widget(message(error.clone(), Style::default().fg(theme.error)), popup);
+    }
+    if view.needs.is_none() {
+        return f.render_widget(message("Loading…".to_string(), Style::default().fg(theme.muted)), popup);
+    }
+
+    let rows = app.job_graph_rows();
+    if rows.is_empty() {
+        return f.render_widget(message("No jobs loaded".to_string(), Style::default().fg(theme.muted)), popup);
+    }
+    // Without any `needs:` every job waits for the previous stage, which the job list shows better
+    let text = if rows.iter().all(|row| row.depth == 0) {
+        " No job uses needs:, jobs run stage by stage "
+    } else {
+        " Jobs are listed under the jobs they need "
+    };
+    let block = block.title(Line::from(Span::styled(text, Style::default().fg(theme.muted))).alignment(Alignment::Right));
+
+    let items: Vec<ListItem> = rows.iter().map(|row| ListItem::new(row_line(theme, row))).collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(theme.selection_bg))
+        .highlight_symbol("> ");
+    let mut state = ListState::default();
+    state.select(Some(view.selected));
+    f.render_stateful_widget(list, popup, &mut state);
+}
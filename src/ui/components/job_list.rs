@@ -1,25 +1,113 @@
-use crate::app::App;
-use crate::gitlab::JobStatus;
+use crate::app::{App, JobSort};
+use crate::gitlab::models::{compact_duration, Job};
+use crate::ui::status::{status_span, status_style};
+use chrono::{DateTime, Utc};
 use ratatui::{
-    layout::{Constraint, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Cell, Row, Table, TableState},
     Frame,
 };
 
-fn format_duration(duration: Option<f64>) -> String {
-    match duration {
-        Some(d) => {
-            let minutes = (d / 60.0) as u64;
-            let seconds = (d % 60.0) as u64;
-            if minutes > 0 {
-                format!("{}m {:02}s", minutes, seconds)
-            } else {
-                format!("{}s", seconds)
-            }
+/// Queue times shorter than this aren't worth showing
+const MIN_SHOWN_QUEUE_SECS: f64 = 10.0;
+
+/// Run time, live while running, then the time spent queued when it was long
+fn duration_cell(app: &App, job: &Job, now: DateTime<Utc>) -> Line<'static> {
+    let mut spans = vec![Span::raw(job.run_seconds(now).map_or("-".to_string(), compact_duration))];
+    if let Some(queued) = job.queued_seconds(now).filter(|&queued| queued >= MIN_SHOWN_QUEUE_SECS) {
+        spans.push(Span::styled(
+            format!(" queued {}", compact_duration(queued)),
+            Style::default().fg(app.theme.muted),
+        ));
+    }
+    Line::from(spans)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Text with the characters at `positions` highlighted
+fn highlighted(app: &App, text: &str, positions: &[usize], style: Style) -> Vec<Span<'static>> {
+    if positions.is_empty() {
+        return vec![Span::styled(text.to_string(), style)];
+    }
+    let matched = style.fg(app.theme.accent).add_modifier(Modifier::BOLD);
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let style = if positions.contains(&i) { matched } else { style };
+            Span::styled(c.to_string(), style)
+        })
+        .collect()
+}
+
+/// Job name with the characters matching the job filter highlighted
+fn highlighted_name(app: &App, name: &str) -> Vec<Span<'static>> {
+    let positions = app.job_filter_match(name).map(|(_, positions)| positions).unwrap_or_default();
+    highlighted(app, name, &positions, Style::default())
+}
+
+/// A stage name or job status, with what a column search found in it highlighted
+fn searched_cell(app: &App, text: &str, style: Style) -> Line<'static> {
+    Line::from(highlighted(app, text, &app.job_search_positions(text), style))
+}
+
+/// Status cell, its name highlighted where a column search matched it
+fn status_cell(app: &App, status: &str) -> Line<'static> {
+    let span = status_span(app.theme, status);
+    let positions = app.job_search_positions(status);
+    if positions.is_empty() {
+        return Line::from(span);
+    }
+    let (symbol, name) = span.content.split_once(' ').unwrap_or_default();
+    let mut spans = vec![Span::styled(format!("{} ", symbol), span.style)];
+    spans.extend(highlighted(app, name, &positions, span.style));
+    Line::from(spans)
+}
+
+/// Pipeline status and duration, then its jobs counted by status, e.g. `✗ failed 4m12s │ 12 ✓ · 2 ✗`
+fn summary_header(app: &App, now: DateTime<Utc>) -> Option<Line<'static>> {
+    let summary = app.pipeline_summary(now)?;
+    let mut spans = vec![Span::raw(" "), status_span(app.theme, summary.status.api_name())];
+    if let Some(seconds) = summary.seconds {
+        spans.push(Span::raw(format!(" {}", compact_duration(seconds))));
+    }
+    spans.push(Span::styled(" │", Style::default().fg(app.theme.muted)));
+    for (i, (status, count)) in summary.counts.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" ·", Style::default().fg(app.theme.muted)));
         }
-        None => "-".to_string(),
+        let (color, symbol) = status_style(app.theme, status.api_name());
+        spans.push(Span::styled(format!(" {} {}", count, symbol), Style::default().fg(color)));
     }
+    spans.push(Span::raw(" "));
+    Some(Line::from(spans).alignment(Alignment::Right))
+}
+
+/// The job filter while it is being typed
+fn filter_bar(app: &App) -> Option<Line<'static>> {
+    let filter = app.job_filter.as_ref().filter(|filter| filter.editing)?;
+    let typed = Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD);
+    let prompt = if filter.columns { " name/stage/status: " } else { " / " };
+    Some(Line::from(vec![
+        Span::styled(prompt, Style::default().fg(app.theme.accent)),
+        Span::styled(filter.query.clone(), typed),
+        Span::styled("_ ", typed.add_modifier(Modifier::SLOW_BLINK)),
+    ]))
 }
 
 pub fn render(f: &mut Frame, app: &App, area: Rect) {
@@ -32,47 +120,131 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
         }
     };
 
-    let jobs = match app.get_selected_jobs() {
-        Some(jobs) if !jobs.is_empty() => jobs,
-        _ => {
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .title("Jobs")
-                .style(Style::default().fg(Color::Gray));
-            f.render_widget(block, area);
-            return;
-        }
+    let pipeline_watched = app.get_selected_pipeline().is_some_and(|p| app.watched_pipelines.contains(&p.id));
+
+    let mut title = match &app.stage_filter {
+        Some(stage) => format!("Jobs (stage: {}, s shows all)", stage),
+        None => "Jobs".to_string(),
     };
+    if let Some(filter) = app.job_filter.as_ref().filter(|filter| !filter.editing) {
+        let kind = if filter.columns { "search" } else { "name" };
+        title.push_str(&format!(" [{}: {}, Esc clears]", kind, filter.query));
+    }
+    if pipeline_watched {
+        title.push_str(" [watching, W stops]");
+    }
+    if app.job_sort != JobSort::Stage {
+        title.push_str(&format!(" [{}, S cycles]", app.job_sort.label()));
+    }
 
-    let rows: Vec<Row> = jobs
-        .iter()
-        .map(|job| {
-            let (status_color, status_text) = match job.status {
-                JobStatus::Success => (Color::Green, format!("{} success", job.status.symbol())),
-                JobStatus::Failed => (Color::Red, format!("{} failed", job.status.symbol())),
-                JobStatus::Running => (Color::Yellow, format!("{} running", job.status.symbol())),
-                JobStatus::Pending => (Color::Blue, format!("{} pending", job.status.symbol())),
-                JobStatus::Canceled => (Color::Gray, format!("{} canceled", job.status.symbol())),
-                JobStatus::Skipped => (Color::DarkGray, format!("{} skipped", job.status.symbol())),
-                _ => (Color::Gray, format!("{} {:?}", job.status.symbol(), job.status).to_lowercase()),
-            };
+    let groups = app.job_stage_groups();
+    let now = Utc::now();
+    if groups.is_empty() {
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .style(Style::default().fg(app.theme.inactive));
+        if let Some(filter) = filter_bar(app) {
+            block = block.title_bottom(filter);
+        }
+        f.render_widget(block, area);
+        return;
+    }
 
+    // A header row per stage, followed by its jobs unless folded
+    let mut rows: Vec<Row> = Vec::new();
+    let mut selected_row = None;
+    let mut job_position = 0;
+    for group in &groups {
+        let (marker, count) = if group.collapsed {
+            ("▸", format!("{} jobs", group.jobs.len()))
+        } else {
+            ("▾", String::new())
+        };
+        let mut header = searched_cell(app, group.name, Style::default());
+        header.spans.insert(0, Span::raw(format!("{} ", marker)));
+        rows.push(
             Row::new(vec![
-                Cell::from(job.stage.clone()),
-                Cell::from(job.name.clone()),
-                Cell::from(status_text).style(Style::default().fg(status_color)),
-                Cell::from(format_duration(job.duration)),
+                Cell::from(header),
+                Cell::from(count).style(Style::default().fg(app.theme.muted)),
+                Cell::from(status_span(app.theme, group.status.api_name())),
+                Cell::from(""),
             ])
-        })
-        .collect();
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        );
+        if group.collapsed {
+            continue;
+        }
+
+        for job in &group.jobs {
+            if job_position == app.selected_job_index {
+                selected_row = Some(rows.len());
+            }
+            job_position += 1;
+
+            let mut name = Vec::new();
+            // Job marked as the left side of a log comparison
+            if app.compare_job.as_ref().is_some_and(|(id, _)| *id == job.id) {
+                name.push(Span::styled("⇄ ", Style::default().fg(app.theme.accent)));
+            }
+            // Job announced when it finishes, on its own or with its pipeline
+            if pipeline_watched || app.watched_jobs.contains(&job.id) {
+                name.push(Span::styled("◉ ", Style::default().fg(app.theme.running)));
+            }
+            name.extend(highlighted_name(app, &job.name));
+            // Mark jobs whose artifacts can be downloaded with `a`
+            if let Some(artifacts) = &job.artifacts_file {
+                name.push(Span::raw(format!(" [{}]", format_size(artifacts.size))));
+            }
+            let name = Line::from(name);
+
+            // Sorted jobs are listed together, each shows its own stage
+            let stage = match app.job_sort {
+                JobSort::Stage => String::new(),
+                _ => job.stage.clone(),
+            };
+            rows.push(Row::new(vec![
+                Cell::from(searched_cell(app, &stage, Style::default().fg(app.theme.muted))),
+                Cell::from(name),
+                Cell::from(status_cell(app, job.status.api_name())),
+                Cell::from(duration_cell(app, job, now)),
+            ]));
+        }
+    }
+
+    let mut block = Block::default().borders(Borders::ALL).title(title);
+    if let Some(summary) = summary_header(app, now) {
+        block = block.title(summary);
+    }
+    if let Some(filter) = filter_bar(app) {
+        block = block.title_bottom(filter);
+    } else if let Some(download) = &app.artifact_download {
+        let progress = match download.total {
+            Some(total) if total > 0 => format!(
+                "{}% of {}",
+                (download.received * 100 / total).min(100),
+                format_size(total)
+            ),
+            _ => format_size(download.received),
+        };
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" Downloading artifacts of '{}': {} (Esc to cancel) ", download.job_name, progress),
+            Style::default().fg(app.theme.accent),
+        )));
+    } else if let Some(status) = app.visible_status() {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {} ", status),
+            Style::default().fg(app.theme.title),
+        )));
+    }
 
     let table = Table::new(
         rows,
         [
             Constraint::Percentage(20),
-            Constraint::Percentage(40),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
+            Constraint::Percentage(35),
+            Constraint::Percentage(15),
+            Constraint::Percentage(30),
         ],
     )
     .header(
@@ -80,16 +252,16 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
             .style(Style::default().add_modifier(Modifier::BOLD))
             .bottom_margin(1),
     )
-    .block(Block::default().borders(Borders::ALL).title("Jobs"))
+    .block(block)
     .highlight_style(
         Style::default()
-            .bg(Color::DarkGray)
+            .bg(app.theme.selection_bg)
             .add_modifier(Modifier::BOLD),
     )
     .highlight_symbol("> ");
 
     let mut state = TableState::default();
-    state.select(Some(app.selected_job_index));
+    state.select(selected_row);
 
     f.render_stateful_widget(table, area, &mut state);
 }
@@ -0,0 +1,136 @@
+use crate::app::App;
+use crate::log_diff::{DiffContent, DiffKind, DiffRow, LogDiff};
+use crate::ui::components::help::centered_rect;
+use crate::ui::theme::Theme;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// Rows of each pane (inside the borders) when the comparison is drawn in `area`
+pub fn content_height(area: Rect) -> usize {
+    centered_rect(95, 90, area).height.saturating_sub(2) as usize
+}
+
+fn kind_color(theme: &Theme, kind: DiffKind) -> Color {
+    match kind {
+        DiffKind::Equal => theme.muted,
+        DiffKind::Removed => theme.failed,
+        DiffKind::Added => theme.success,
+        DiffKind::Changed => theme.running,
+    }
+}
+
+fn kind_sign(kind: DiffKind) -> &'static str {
+    match kind {
+        DiffKind::Equal => " ",
+        DiffKind::Removed => "-",
+        DiffKind::Added => "+",
+        DiffKind::Changed => "~",
+    }
+}
+
+/// One row of a pane: line number and change sign, then the line, tinted when it differs
+fn pane_line(theme: &Theme, content: &DiffContent, row: &DiffRow, side: Side) -> Line<'static> {
+    let (index, lines) = match side {
+        Side::Left => (row.left, &content.left_lines),
+        Side::Right => (row.right, &content.right_lines),
+    };
+    let width = lines.len().max(1).to_string().len();
+    let Some(index) = index else {
+        return Line::from(Span::raw(" ".repeat(width + 3)));
+    };
+
+    let color = kind_color(theme, row.kind);
+    let mut line = lines.get(index).cloned().unwrap_or_default();
+    if row.kind != DiffKind::Equal {
+        line = line.patch_style(Style::default().fg(color));
+    }
+    let mut spans = vec![Span::styled(
+        format!("{:>width$} {} ", index + 1, kind_sign(row.kind), width = width),
+        Style::default().fg(color),
+    )];
+    spans.extend(line.spans);
+    Line::from(spans)
+}
+
+fn pane_title(job: &(u64, String)) -> String {
+    format!(" {} (#{}) ", job.1, job.0)
+}
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let Some(diff) = &app.log_diff else {
+        return;
+    };
+    let theme = app.theme;
+    let popup = centered_rect(95, 90, area);
+    f.render_widget(Clear, popup);
+
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(popup);
+
+    let block = |job, side: Side| {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(pane_title(job))
+            .border_style(Style::default().fg(theme.accent));
+        match side {
+            Side::Left => block.title_bottom(summary(diff)),
+            Side::Right => block.title_bottom(Line::from(position(app, diff)).alignment(Alignment::Right)),
+        }
+    };
+
+    let Some(content) = &diff.content else {
+        let message = match &diff.error {
+            Some(error) => Line::from(Span::styled(
+                format!("Failed to load logs: {}", error),
+                Style::default().fg(theme.error),
+            )),
+            None => Line::from(Span::styled("Loading logs…", Style::default().fg(theme.muted))),
+        };
+        let paragraph = Paragraph::new(message).alignment(Alignment::Center);
+        f.render_widget(paragraph.clone().block(block(&diff.left_job, Side::Left)), panes[0]);
+        f.render_widget(paragraph.block(block(&diff.right_job, Side::Right)), panes[1]);
+        return;
+    };
+
+    let height = panes[0].height.saturating_sub(2) as usize;
+    let rows = content.rows.iter().skip(diff.scroll).take(height);
+    let left: Vec<Line> = rows.clone().map(|row| pane_line(theme, content, row, Side::Left)).collect();
+    let right: Vec<Line> = rows.map(|row| pane_line(theme, content, row, Side::Right)).collect();
+
+    f.render_widget(Paragraph::new(left).block(block(&diff.left_job, Side::Left)), panes[0]);
+    f.render_widget(Paragraph::new(right).block(block(&diff.right_job, Side::Right)), panes[1]);
+}
+
+fn summary(diff: &LogDiff) -> String {
+    match &diff.content {
+        Some(content) if content.change_starts().is_empty() => " Logs are identical ".to_string(),
+        Some(content) => format!(
+            " {} differences, {} lines ",
+            content.change_starts().len(),
+            content.changed_rows()
+        ),
+        None => String::new(),
+    }
+}
+
+fn position(app: &App, diff: &LogDiff) -> String {
+    let rows = diff.row_count();
+    if rows == 0 {
+        return String::new();
+    }
+    let last = (diff.scroll + app.log_viewport_height).min(rows);
+    format!(" {}-{}/{} ", diff.scroll + 1, last, rows)
+}
@@ -1,62 +1,277 @@
-use crate::app::{App, TimestampDisplayMode};
+use crate::app::{App, MinimapMark, ScrollbarMark, TimestampDisplayMode};
+use crate::events::KeyContext;
+use crate::log_processor::{display_zone, wrap_line, LogSection, URL_RE};
+use crate::ui::status::status_style;
+use crate::ui::theme::Theme;
+use regex::Regex;
+use std::time::Duration;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
     Frame,
 };
 
-/// Highlight search query matches in a line
-fn highlight_search_in_line(line: &Line, query: &str) -> Line<'static> {
+/// Highlight search regex matches in a line, keeping the original ANSI-derived span styles
+fn highlight_search_in_line(line: &Line<'static>, regex: &Regex, highlight: Style) -> Line<'static> {
     // Convert line to plain text for searching
     let line_text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
-    let query_lower = query.to_lowercase();
-    let line_lower = line_text.to_lowercase();
 
-    // Find all match positions
-    let mut matches: Vec<(usize, usize)> = Vec::new();
-    let mut start = 0;
-    while let Some(pos) = line_lower[start..].find(&query_lower) {
-        let match_start = start + pos;
-        let match_end = match_start + query.len();
-        matches.push((match_start, match_end));
-        start = match_end;
-    }
+    // Find all match positions (skip empty matches, they can't be highlighted)
+    let matches: Vec<(usize, usize)> = regex
+        .find_iter(&line_text)
+        .filter(|m| !m.is_empty())
+        .map(|m| (m.start(), m.end()))
+        .collect();
 
     if matches.is_empty() {
-        // Return owned version of the line with plain text
-        return Line::from(line_text);
+        return line.clone();
     }
 
-    // Build new line with highlights
+    // Split each span at match boundaries so matched segments get the highlight
+    // patched on top of their existing style
     let mut new_spans = Vec::new();
-    let mut last_end = 0;
+    let mut span_start = 0;
+
+    for span in &line.spans {
+        let content = span.content.as_ref();
+        let span_end = span_start + content.len();
+        let mut pos = span_start;
+
+        for &(match_start, match_end) in &matches {
+            if match_end <= pos || match_start >= span_end {
+                continue;
+            }
+
+            let seg_start = match_start.max(pos);
+            let seg_end = match_end.min(span_end);
+
+            // Text before the match keeps the span style
+            if seg_start > pos {
+                new_spans.push(Span::styled(
+                    content[pos - span_start..seg_start - span_start].to_string(),
+                    span.style,
+                ));
+            }
+
+            new_spans.push(Span::styled(
+                content[seg_start - span_start..seg_end - span_start].to_string(),
+                span.style.patch(highlight),
+            ));
+
+            pos = seg_end;
+        }
+
+        // Remaining text of the span
+        if pos < span_end {
+            new_spans.push(Span::styled(
+                content[pos - span_start..].to_string(),
+                span.style,
+            ));
+        }
+
+        span_start = span_end;
+    }
+
+    Line::from(new_spans).style(line.style)
+}
+
+/// Drop the first `columns` characters of a line, keeping span styles
+fn skip_columns(line: Line<'static>, columns: usize) -> Line<'static> {
+    if columns == 0 {
+        return line;
+    }
 
-    for (match_start, match_end) in matches {
-        // Add text before match
-        if match_start > last_end {
-            new_spans.push(Span::raw(line_text[last_end..match_start].to_string()));
+    let mut remaining = columns;
+    let mut spans = Vec::new();
+    for span in line.spans {
+        if remaining == 0 {
+            spans.push(span);
+            continue;
         }
+        let char_count = span.content.chars().count();
+        if char_count <= remaining {
+            remaining -= char_count;
+            continue;
+        }
+        let content: String = span.content.chars().skip(remaining).collect();
+        remaining = 0;
+        spans.push(Span::styled(content, span.style));
+    }
+
+    Line::from(spans).style(line.style)
+}
 
-        // Add highlighted match
-        new_spans.push(Span::styled(
-            line_text[match_start..match_end].to_string(),
-            Style::default()
-                .bg(Color::Yellow)
-                .fg(Color::Black)
-                .add_modifier(Modifier::BOLD),
+/// Prefix a line with the left gutter: source line numbers when enabled, then error markers
+/// (the marker column is left out when nothing is marked)
+fn with_gutter(app: &App, line_number: usize, line: Line<'static>) -> Line<'static> {
+    let mut spans = Vec::new();
+
+    if app.show_line_numbers {
+        let width = app.log_line_count().max(1).to_string().len();
+        spans.push(Span::styled(
+            format!("{:>width$} ", line_number + 1, width = width),
+            Style::default().fg(app.theme.muted),
         ));
+    }
+
+    if !app.log_error_lines.is_empty() {
+        spans.push(if app.log_error_lines.binary_search(&line_number).is_ok() {
+            Span::styled("▌", Style::default().fg(app.theme.error))
+        } else {
+            Span::raw(" ")
+        });
+    }
 
-        last_end = match_end;
+    if app.current_bookmarks().is_some() {
+        spans.push(if app.is_bookmarked(line_number) {
+            Span::styled("◆", Style::default().fg(app.theme.accent))
+        } else {
+            Span::raw(" ")
+        });
     }
 
-    // Add remaining text
-    if last_end < line_text.len() {
-        new_spans.push(Span::raw(line_text[last_end..].to_string()));
+    if spans.is_empty() {
+        return line;
     }
+    spans.extend(line.spans);
+    Line::from(spans).style(line.style)
+}
 
-    Line::from(new_spans)
+/// Indent a continuation row of a wrapped line by the gutter width
+fn blank_gutter(app: &App, line: Line<'static>) -> Line<'static> {
+    let width = app.log_gutter_width();
+    if width == 0 {
+        return line;
+    }
+    let mut spans = vec![Span::raw(" ".repeat(width))];
+    spans.extend(line.spans);
+    Line::from(spans).style(line.style)
+}
+
+/// Build the one-line header shown for a collapsible section
+fn section_header_line(app: &App, section: &LogSection) -> Line<'static> {
+    let collapsed = app.collapsed_sections.contains(&section.name);
+    let indicator = if collapsed { "▶ " } else { "▼ " };
+
+    let mut spans = vec![Span::styled(
+        indicator,
+        Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD),
+    )];
+
+    if section.header.is_empty() {
+        spans.push(Span::styled(
+            section.name.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    } else {
+        // Headers usually carry their own ANSI colors
+        spans.extend(crate::log_processor::ansi_line(&section.header).spans);
+    }
+
+    spans.push(Span::styled(
+        format!(" {}", section.duration_label(app.log_job_running)),
+        Style::default().fg(app.theme.muted),
+    ));
+
+    Line::from(spans)
+}
+
+/// Spinner shown while the first chunk of a log is on its way
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How often the spinner advances, the event loop redraws at this pace while loading
+pub const SPINNER_FRAME: Duration = Duration::from_millis(100);
+
+/// Timestamp mode indicator, noting the zone times are converted to from UTC
+fn zoned_indicator(mode: &str) -> String {
+    match display_zone().label() {
+        Some(zone) => format!("[Timestamps: {} UTC→{}]", mode, zone),
+        None => format!("[Timestamps: {}]", mode),
+    }
+}
+
+/// Keys bound to retrying a failed log fetch, for the error message
+fn retry_keys(app: &App) -> String {
+    app.keymap
+        .help_entries(KeyContext::Log)
+        .into_iter()
+        .find(|entry| entry.description == "Retry loading a log that failed")
+        .map(|entry| entry.keys_label())
+        .unwrap_or_else(|| "the command palette".to_string())
+}
+
+/// Numbered job names of the open log tabs, the active one highlighted, when there are several
+fn tab_bar(app: &App) -> Option<Line<'static>> {
+    let names = app.log_tab_names();
+    if names.len() < 2 {
+        return None;
+    }
+    let mut spans = Vec::new();
+    for (index, name) in names.into_iter().enumerate() {
+        let style = if index == app.active_log_tab {
+            Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default().fg(app.theme.muted)
+        };
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(format!(" {}:{} ", index + 1, name), style));
+    }
+    spans.push(Span::raw(" "));
+    Some(Line::from(spans))
+}
+
+/// Scrollbar on the right border, with search matches and bookmarks ticked along the track
+fn render_scrollbar(f: &mut Frame, app: &App, area: Rect, offset: usize, max_offset: usize, viewport: usize) {
+    let track = area.inner(Margin { vertical: 1, horizontal: 0 });
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .track_style(Style::default().fg(app.theme.inactive))
+        .thumb_style(Style::default().fg(app.theme.muted));
+    let mut state = ScrollbarState::new(max_offset + 1).position(offset).viewport_content_length(viewport);
+    f.render_stateful_widget(scrollbar, track, &mut state);
+
+    let column = track.right().saturating_sub(1);
+    for (row, mark) in app.scrollbar_marks(track.height as usize) {
+        let (symbol, color) = match mark {
+            ScrollbarMark::SearchMatch => ("━", app.theme.search_match_bg),
+            ScrollbarMark::Bookmark => ("◆", app.theme.accent),
+        };
+        f.buffer_mut()[(column, track.y + row as u16)].set_symbol(symbol).set_fg(color);
+    }
+}
+
+/// Minimap in the last column of the log lines, one mark per row summarizing that stretch of the log
+fn render_minimap(f: &mut Frame, app: &App, content: Rect) {
+    let column = content.right();
+    for (row, mark) in app.minimap_marks(content.height as usize) {
+        let color = match mark {
+            MinimapMark::Error => app.theme.error,
+            MinimapMark::Warning => app.theme.warning,
+            MinimapMark::Section => app.theme.heading,
+        };
+        f.buffer_mut()[(column, content.y + row as u16)].set_symbol("▌").set_fg(color);
+    }
+}
+
+fn link_style(theme: &Theme) -> Style {
+    Style::default().fg(theme.accent).add_modifier(Modifier::UNDERLINED)
+}
+
+fn match_style(theme: &Theme) -> Style {
+    Style::default()
+        .bg(theme.search_match_bg)
+        .fg(theme.match_fg)
+        .add_modifier(Modifier::BOLD)
+}
+
+fn current_match_style(theme: &Theme) -> Style {
+    Style::default()
+        .bg(theme.current_match_bg)
+        .fg(theme.match_fg)
+        .add_modifier(Modifier::BOLD)
 }
 
 /// Helper function to create a centered rectangle
@@ -80,9 +295,41 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+/// Smallest popup worth drawing, below it the viewer takes the whole screen
+const MIN_POPUP_WIDTH: u16 = 40;
+const MIN_POPUP_HEIGHT: u16 = 10;
+
+/// Area of the viewer popup, sized by `log_popup_percent`
+fn popup_area(app: &App, area: Rect) -> Rect {
+    let popup = centered_rect(app.log_popup_percent, app.log_popup_percent, area);
+    if popup.width < MIN_POPUP_WIDTH || popup.height < MIN_POPUP_HEIGHT {
+        area
+    } else {
+        popup
+    }
+}
+
+/// Screen area holding log lines (inside the borders) when the viewer is drawn in `area`
+pub fn content_area(app: &App, area: Rect) -> Rect {
+    let log_area = popup_area(app, area);
+    let input_bar = if app.is_searching || app.is_filtering { 3 } else { 0 };
+    let height = log_area.height.saturating_sub(2 + input_bar); // Account for borders
+    let minimap = if app.minimap_visible(height as usize) { 1 } else { 0 };
+    Rect {
+        x: log_area.x + 1,
+        y: log_area.y + 1,
+        width: log_area.width.saturating_sub(2 + minimap),
+        height,
+    }
+}
+
+/// Rows available for log lines when the viewer is drawn in `area`
+pub fn content_height(app: &App, area: Rect) -> usize {
+    content_area(app, area).height as usize
+}
+
 pub fn render(f: &mut Frame, app: &App, area: Rect) {
-    // Calculate the log viewer area (90% width, 90% height, centered)
-    let log_area = centered_rect(90, 90, area);
+    let log_area = popup_area(app, area);
 
     // Clear the background to prevent rendering artifacts
     f.render_widget(Clear, log_area);
@@ -90,11 +337,52 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
     let _log_content = match &app.log_content {
         Some(content) => content,
         None => {
+            let title = match &app.log_job_name {
+                Some(name) => format!("Job Log: {}", name),
+                None => "Job Log".to_string(),
+            };
             let block = Block::default()
                 .borders(Borders::ALL)
-                .title("Job Log")
-                .style(Style::default().fg(Color::Gray));
+                .title(title)
+                .style(Style::default().fg(app.theme.inactive));
+            let inner = block.inner(log_area);
             f.render_widget(block, log_area);
+            if app.log_expired {
+                let message = Line::from(Span::styled(
+                    "Log expired (job trace was erased)",
+                    Style::default().fg(app.theme.muted).add_modifier(Modifier::BOLD),
+                ));
+                let middle = Rect { y: inner.y + inner.height / 2, height: 1.min(inner.height), ..inner };
+                f.render_widget(Paragraph::new(message).alignment(Alignment::Center), middle);
+            } else if let Some(error) = &app.log_error {
+                let message = vec![
+                    Line::from(Span::styled(
+                        format!("Failed to load log: {}", error),
+                        Style::default().fg(app.theme.error).add_modifier(Modifier::BOLD),
+                    )),
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        format!("Press {} to retry", retry_keys(app)),
+                        Style::default().fg(app.theme.muted),
+                    )),
+                ];
+                let middle = Rect { y: inner.y + inner.height / 2, height: 3.min(inner.height), ..inner };
+                f.render_widget(
+                    Paragraph::new(message).alignment(Alignment::Center).wrap(Wrap { trim: true }),
+                    middle,
+                );
+            } else if let Some(since) = app.log_loading_since {
+                let frame = (since.elapsed().as_millis() / SPINNER_FRAME.as_millis()) as usize % SPINNER.len();
+                let middle = Rect { y: inner.y + inner.height / 2, height: 1.min(inner.height), ..inner };
+                f.render_widget(
+                    Paragraph::new(Span::styled(
+                        format!("{} Loading log…", SPINNER[frame]),
+                        Style::default().fg(app.theme.accent),
+                    ))
+                    .alignment(Alignment::Center),
+                    middle,
+                );
+            }
             return;
         }
     };
@@ -104,77 +392,181 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
         .as_deref()
         .unwrap_or("Unknown Job");
 
-    // Use cached processed lines for instant rendering
-    let lines = &app.log_processed_lines;
-
-    // Calculate visible range based on scroll offset
-    let content_height = log_area.height.saturating_sub(2) as usize; // Account for borders
-    let total_lines = lines.len();
-    let max_offset = total_lines.saturating_sub(content_height);
+    // Visible range from the scroll offset (in lines of the folded view), filled row by row
+    // since a wrapped line takes several rows
+    let content_height = content_height(app, area);
+    let total_lines = app.log_visible_lines.len();
+    let max_offset = app.max_log_scroll_offset();
     let scroll_offset = app.log_scroll_offset.min(max_offset);
+    let wrap_width = app.log_wrap_width();
 
     // Get visible lines with search highlighting
-    let visible_lines: Vec<Line> = if total_lines > 0 {
-        let start = scroll_offset;
-        let end = (scroll_offset + content_height).min(total_lines);
-
-        lines[start..end]
+    let visible_lines: Vec<Line> = if let Some(message) = app.log_empty_message() {
+        vec![Line::from(Span::styled(message, Style::default().fg(app.theme.muted)))]
+    } else {
+        let viewport = app.log_viewport_lines();
+        app.log_visible_lines[viewport.clone()]
             .iter()
-            .enumerate()
-            .map(|(idx, line)| {
-                let line_number = start + idx;
-
-                // Check if this line has a search match
-                if !app.search_query.is_empty() && app.search_results.contains(&line_number) {
-                    highlight_search_in_line(line, &app.search_query)
-                } else {
-                    line.clone()
+            .zip(viewport)
+            .flat_map(|(&line_number, offset)| {
+                // Lines skipped by a filter with context are marked like grep does
+                let separator = app
+                    .starts_filter_group(offset)
+                    .then(|| blank_gutter(app, Line::from(Span::styled("--", Style::default().fg(app.theme.muted)))));
+
+                // Section start markers render as a fold header, which is never wrapped
+                let sections = app.displayed_sections();
+                if let Ok(idx) = sections.binary_search_by_key(&line_number, |section| section.start_line) {
+                    let header = with_gutter(app, line_number, section_header_line(app, &sections[idx]));
+                    return separator.into_iter().chain([header]).collect::<Vec<_>>();
                 }
+
+                // A line that moves the cursor down takes several rows, each styled and wrapped alike
+                let rendered = app.log_line_rendered_rows(line_number);
+                let rows = rendered.iter().flat_map(|line| {
+                    // Links are underlined, `u` or ctrl+click opens them
+                    let line = highlight_search_in_line(line, &URL_RE, link_style(app.theme));
+
+                    // Check if this line has a search match
+                    let line = match &app.search_regex {
+                        Some(regex) if app.search_results.binary_search(&line_number).is_ok() => {
+                            // The line holding the current match gets a distinct highlight
+                            let is_current = app.search_results.get(app.current_search_result)
+                                == Some(&line_number);
+                            let highlight = if is_current {
+                                current_match_style(app.theme)
+                            } else {
+                                match_style(app.theme)
+                            };
+                            highlight_search_in_line(&line, regex, highlight)
+                        }
+                        _ => line,
+                    };
+
+                    let line = if app.wrap_enabled {
+                        line
+                    } else {
+                        skip_columns(line, app.horizontal_scroll_offset)
+                    };
+
+                    let line = if app.is_log_line_selected(line_number) {
+                        line.patch_style(Style::default().bg(app.theme.selection_bg))
+                    } else if app.log_outcome.as_ref().is_some_and(|outcome| outcome.line == line_number) {
+                        line.patch_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED))
+                    } else {
+                        line
+                    };
+
+                    match wrap_width {
+                        Some(width) => wrap_line(line, width),
+                        None => vec![line],
+                    }
+                });
+
+                // Continuation rows get a blank gutter so the numbers column stays clean
+                let rows: Vec<Line> = rows
+                    .enumerate()
+                    .map(|(row, line)| match row {
+                        0 => with_gutter(app, line_number, line),
+                        _ => blank_gutter(app, line),
+                    })
+                    .collect();
+                separator.into_iter().chain(rows).collect()
             })
+            .skip(app.log_top_rows_skipped())
             .collect()
-    } else {
-        vec![Line::from("(empty log)")]
     };
 
     let scroll_indicator = if total_lines > content_height {
         format!(
-            " [{}/{}] ",
+            " [{}/{}] [{}%] ",
             scroll_offset + 1,
-            max_offset + 1
+            max_offset + 1,
+            (scroll_offset * 100).checked_div(max_offset).unwrap_or(100)
         )
     } else {
         String::new()
     };
 
     let timestamp_indicator = match &app.timestamp_mode {
-        TimestampDisplayMode::Hidden => "[Timestamps: Hidden]",
-        TimestampDisplayMode::DateOnly => "[Timestamps: Date]",
-        TimestampDisplayMode::Full => "[Timestamps: Full]",
+        _ if app.raw_log_view => "[RAW]".to_string(),
+        TimestampDisplayMode::Hidden => "[Timestamps: Hidden]".to_string(),
+        TimestampDisplayMode::DateOnly => zoned_indicator("Date"),
+        TimestampDisplayMode::Full => zoned_indicator("Full"),
+        TimestampDisplayMode::Relative => "[Timestamps: Relative]".to_string(),
+    };
+
+    let wrap_indicator = if app.wrap_enabled {
+        String::new()
+    } else {
+        format!(" [No wrap, col {}]", app.horizontal_scroll_offset + 1)
+    };
+
+    let follow_indicator = if app.follow_mode { " [Following]" } else { "" };
+
+    let loading_indicator = match app.log_load_progress() {
+        Some(0) => " [Loading...]".to_string(),
+        Some(percent) => format!(" [Loading {}%]", percent),
+        None => String::new(),
     };
 
     // Build search indicator
-    let search_indicator = if !app.search_results.is_empty() {
+    let searching = app.search_progress().map(|percent| format!(", searching {}%", percent)).unwrap_or_default();
+    let search_indicator = if app.search_regex_error && !app.is_searching {
+        " [Invalid regex]".to_string()
+    } else if !app.search_results.is_empty() {
         format!(
-            " [Match {}/{}]",
+            " [Match {}/{}{}]",
             app.current_search_result + 1,
-            app.search_results.len()
+            app.search_results.len(),
+            searching
         )
+    } else if let Some(percent) = app.search_progress() {
+        format!(" [Searching {}%]", percent)
     } else if !app.search_query.is_empty() && !app.is_searching {
         " [No matches]".to_string()
     } else {
         String::new()
     };
 
+    let filter_indicator = match (&app.filter_regex, &app.filtered_line_indices) {
+        (Some(regex), Some(lines)) => format!(
+            " [Filter{}: {} ({} lines)]",
+            if app.filter_inverse { " -v" } else { "" },
+            regex.as_str(),
+            lines.len()
+        ),
+        _ => String::new(),
+    };
+
+    let visual_indicator = match app.log_selection {
+        Some((anchor, end)) if app.log_visual_mode => {
+            format!(" [VISUAL {} lines, y copy]", anchor.abs_diff(end) + 1)
+        }
+        _ => String::new(),
+    };
+
+    let count_indicator = match app.active_count() {
+        Some(count) => format!(" [{}]", count),
+        None => String::new(),
+    };
+
     let title = format!(
-        "Job Log: {}{}{}{} (q/Esc close, / search, n/N next/prev, t time)",
+        "Job Log: {}{}{}{}{}{}{}{}{}{} (q/Esc close, / search, n/N next/prev, t time, w wrap, f follow, e/E errors, Enter fold)",
         job_name,
         if scroll_indicator.is_empty() { " " } else { &scroll_indicator },
         timestamp_indicator,
-        search_indicator
+        wrap_indicator,
+        follow_indicator,
+        loading_indicator,
+        filter_indicator,
+        search_indicator,
+        visual_indicator,
+        count_indicator
     );
 
     // If searching, show search input bar at the bottom
-    let (render_area, search_area) = if app.is_searching {
+    let (render_area, search_area) = if app.is_searching || app.is_filtering {
         let chunks = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .constraints([
@@ -187,36 +579,118 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
         (log_area, None)
     };
 
-    let paragraph = Paragraph::new(visible_lines)
-        .block(
+    // Same status glyph as in the job list
+    let mut title_spans = Vec::new();
+    if let Some(job) = app.log_job() {
+        let (color, symbol) = status_style(app.theme, job.status.api_name());
+        title_spans.push(Span::styled(format!("{} ", symbol), Style::default().fg(color)));
+    }
+    title_spans.push(Span::raw(title));
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(Line::from(title_spans))
+        .style(Style::default());
+    if let Some(tabs) = tab_bar(app) {
+        block = block.title(tabs.alignment(Alignment::Right));
+    }
+    if let Some(outcome) = &app.log_outcome {
+        // Pinned so the result shows without scrolling to the end
+        let (color, symbol) = status_style(app.theme, if outcome.succeeded { "success" } else { "failed" });
+        block = block.title_bottom(
+            Line::from(Span::styled(
+                format!(" {} {} (line {}) ", symbol, outcome.summary, outcome.line + 1),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ))
+            .alignment(Alignment::Center),
+        );
+    }
+    if let Some(offline) = app.offline_label() {
+        block = block.title_bottom(
+            Line::from(Span::styled(format!(" {} ", offline), Style::default().fg(app.theme.muted)))
+                .alignment(Alignment::Right),
+        );
+    }
+    if let Some(error) = &app.log_error {
+        // The download broke off part way, what arrived so far stays readable
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" Failed to load the rest of the log: {} ({} retries) ", error, retry_keys(app)),
+            Style::default().fg(app.theme.error),
+        )));
+    } else if let Some(status) = app.visible_status() {
+        block = block.title_bottom(Line::from(Span::styled(
+            format!(" {} ", status),
+            Style::default().fg(app.theme.title),
+        )));
+    }
+
+    // Log lines arrive already split into rows, only the empty-state message needs wrapping
+    let mut paragraph = Paragraph::new(visible_lines).block(block);
+    if app.log_empty_message().is_some() {
+        paragraph = paragraph.wrap(Wrap { trim: false });
+    }
+
+    f.render_widget(paragraph, render_area);
+
+    if total_lines > content_height {
+        render_scrollbar(f, app, render_area, scroll_offset, max_offset, content_height);
+    }
+    let content = content_area(app, area);
+    if app.minimap_visible(content.height as usize) {
+        render_minimap(f, app, content);
+    }
+
+    // Render filter input bar if in filter mode
+    if let Some(filter_area) = search_area.filter(|_| app.is_filtering) {
+        let filter_line = Line::from(vec![
+            Span::raw(if app.filter_inverse { "Hide lines matching: " } else { "Show lines matching: " }),
+            Span::styled(
+                &app.filter_query,
+                Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                "█",
+                Style::default().fg(app.theme.text).add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]);
+
+        let filter_paragraph = Paragraph::new(filter_line).block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(title)
-                .style(Style::default()),
-        )
-        .wrap(Wrap { trim: false });
+                .title(" [Regex] Enter to filter (empty clears), Ctrl+V invert, Esc to clear ")
+                .style(Style::default().fg(app.theme.accent)),
+        );
 
-    f.render_widget(paragraph, render_area);
+        f.render_widget(filter_paragraph, filter_area);
+    }
 
     // Render search input bar if in search mode
-    if let Some(search_area) = search_area {
+    if let Some(search_area) = search_area.filter(|_| app.is_searching) {
         let search_line = Line::from(vec![
             Span::raw("Search: "),
             Span::styled(
                 &app.search_query,
-                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD),
             ),
             Span::styled(
                 "█",
-                Style::default().fg(Color::White).add_modifier(Modifier::SLOW_BLINK),
+                Style::default().fg(app.theme.text).add_modifier(Modifier::SLOW_BLINK),
             ),
         ]);
 
+        let search_title = format!(
+            " [{}]{} {}, Ctrl+R mode, Ctrl+W whole word, Esc to cancel ",
+            app.search_mode.label(),
+            if app.search_whole_word { " [word]" } else { "" },
+            // Searching while typing only needs Enter to close the bar
+            if app.search_debounce.is_some() { "Enter to confirm" } else { "Enter to search" }
+        );
+
         let search_paragraph = Paragraph::new(search_line).block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" Enter to search, Esc to cancel ")
-                .style(Style::default().fg(Color::Cyan)),
+                .title(search_title)
+                .style(Style::default().fg(app.theme.accent)),
         );
 
         f.render_widget(search_paragraph, search_area);
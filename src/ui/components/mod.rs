@@ -1,6 +1,17 @@
+pub mod artifact_browser;
+pub mod command_palette;
 pub mod comments_list;
+pub mod confirm;
+pub mod group_pipelines;
+pub mod head_pipelines;
 pub mod help;
+pub mod job_graph;
 pub mod job_list;
+pub mod log_diff;
 pub mod log_viewer;
 pub mod mr_tabs;
+pub mod pipeline_prompt;
+pub mod profile_picker;
 pub mod pipeline_list;
+pub mod status_bar;
+pub mod test_report;
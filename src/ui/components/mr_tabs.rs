@@ -1,8 +1,9 @@
 use crate::app::App;
+use crate::ui::status::status_style;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
-    text::Line,
+    style::{Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Tabs},
     Frame,
 };
@@ -28,27 +29,25 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
         .tracked_mrs
         .iter()
         .map(|tracked_mr| {
-            let status_indicator = match tracked_mr.pipelines.first() {
-                Some(p) => p.status.symbol(),
-                None if tracked_mr.loading => "⟳",
-                _ => "•",
+            let (color, status_indicator) = match tracked_mr.pipelines.first() {
+                Some(p) => status_style(app.theme, p.status.api_name()),
+                None if tracked_mr.loading => status_style(app.theme, "running"),
+                _ => (app.theme.muted, "•"),
             };
-            Line::from(format!(
-                "{} MR #{}: {}",
-                status_indicator,
-                tracked_mr.mr.iid,
-                truncate(&tracked_mr.mr.title, 25)
-            ))
+            Line::from(vec![
+                Span::styled(status_indicator, Style::default().fg(color)),
+                Span::raw(format!(" MR #{}: {}", tracked_mr.mr.iid, truncate(&tracked_mr.mr.title, 25))),
+            ])
         })
         .collect();
 
     let tabs = Tabs::new(titles)
         .block(Block::default().borders(Borders::ALL).title("Merge Requests"))
         .select(app.selected_mr_index)
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(app.theme.text))
         .highlight_style(
             Style::default()
-                .fg(Color::Yellow)
+                .fg(app.theme.title)
                 .add_modifier(Modifier::BOLD),
         );
 
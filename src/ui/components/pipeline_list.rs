@@ -1,15 +1,15 @@
-use crate::app::App;
-use crate::gitlab::PipelineStatus;
+use crate::app::{App, PipelineSort};
+use crate::ui::status::status_style;
 use chrono::Utc;
 use ratatui::{
     layout::Rect,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
     Frame,
 };
 
-fn format_relative_time(dt: &chrono::DateTime<Utc>) -> String {
+pub fn format_relative_time(dt: &chrono::DateTime<Utc>) -> String {
     let now = Utc::now();
     let duration = now.signed_duration_since(*dt);
 
@@ -25,12 +25,21 @@ fn format_relative_time(dt: &chrono::DateTime<Utc>) -> String {
 }
 
 pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    // The status filter is part of every title so an empty list is not mistaken for no pipelines
+    let mut name = match &app.pipeline_status_filter {
+        Some(status) => format!("Pipelines ({} only, 0 clears)", status.api_name()),
+        None => "Pipelines".to_string(),
+    };
+    if app.pipeline_sort != PipelineSort::Newest {
+        name.push_str(&format!(" [{}, Ctrl+S cycles]", app.pipeline_sort.label()));
+    }
+
     let selected_mr = match app.get_selected_mr() {
         Some(mr) => mr,
         None => {
             let block = Block::default()
                 .borders(Borders::ALL)
-                .title("Pipelines");
+                .title(name);
             f.render_widget(block, area);
             return;
         }
@@ -39,50 +48,76 @@ pub fn render(f: &mut Frame, app: &App, area: Rect) {
     if selected_mr.pipelines.is_empty() {
         let block = Block::default()
             .borders(Borders::ALL)
-            .title("Pipelines")
-            .style(Style::default().fg(Color::Gray));
+            .title(name)
+            .style(Style::default().fg(app.theme.inactive));
         f.render_widget(block, area);
         return;
     }
 
-    let items: Vec<ListItem> = selected_mr
+    let mut items: Vec<ListItem> = selected_mr
         .pipelines
         .iter()
         .map(|pipeline| {
-            let status_color = match pipeline.status {
-                PipelineStatus::Success => Color::Green,
-                PipelineStatus::Failed => Color::Red,
-                PipelineStatus::Running => Color::Yellow,
-                PipelineStatus::Canceled => Color::DarkGray,
-                _ => Color::Gray,
-            };
+            let status = pipeline.status.api_name();
+            let (status_color, symbol) = status_style(app.theme, status);
 
-            let line = Line::from(vec![
-                Span::styled(
-                    format!("{} ", pipeline.status.symbol()),
-                    Style::default().fg(status_color),
-                ),
+            let mut spans = vec![
+                Span::styled(format!("{} ", symbol), Style::default().fg(status_color)),
                 Span::raw(format!("Pipeline #{} ", pipeline.iid)),
                 Span::styled(
-                    format!("({:?})", pipeline.status).to_lowercase(),
+                    format!("({})", status.replace('_', " ")),
                     Style::default().fg(status_color),
                 ),
                 Span::raw(" - "),
                 Span::styled(
                     format_relative_time(&pipeline.created_at),
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(app.theme.muted),
                 ),
-            ]);
+            ];
+            if let Some(trigger) = pipeline.trigger() {
+                spans.push(Span::styled(format!(" · {}", trigger), Style::default().fg(app.theme.muted)));
+            }
+            let line = Line::from(spans);
 
             ListItem::new(line)
         })
         .collect();
 
+    // Older pages load when moving past the last pipeline
+    let more = if selected_mr.loading_more_pipelines {
+        Some("⟳ Loading older pipelines...")
+    } else if selected_mr.pipelines_next_page.is_some() {
+        Some("↓ More pipelines")
+    } else {
+        None
+    };
+    if let Some(more) = more {
+        items.push(ListItem::new(Line::from(Span::styled(more, Style::default().fg(app.theme.muted)))));
+    }
+
+    // Show that running pipelines are being re-polled, and when data last arrived
+    let updated = app
+        .last_refresh
+        .map(|at| format!("updated {}", at.with_timezone(&chrono::Local).format("%H:%M:%S")));
+    let title = if let Some(offline) = app.offline_label() {
+        format!("{} [{}]", name, offline)
+    } else if let Some(resume_at) = app.rate_limit_resume_at() {
+        let resume_at = resume_at.with_timezone(&chrono::Local).format("%H:%M:%S");
+        format!("{} [rate limited, resumes {}]", name, resume_at)
+    } else {
+        match (app.live_refresh_interval.is_some() && app.has_active_pipelines(), updated) {
+            (true, Some(updated)) => format!("{} [● live, {}]", name, updated),
+            (true, None) => format!("{} [● live]", name),
+            (false, Some(updated)) => format!("{} [{}]", name, updated),
+            (false, None) => name,
+        }
+    };
+
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Pipelines"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(
             Style::default()
-                .bg(Color::DarkGray)
+                .bg(app.theme.selection_bg)
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("> ");
@@ -0,0 +1,71 @@
+use crate::app::PlayPrompt;
+use crate::ui::theme::Theme;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+pub fn render(f: &mut Frame, input: &str, theme: &Theme, area: Rect) {
+    let popup_area = centered_rect(60, 20, area);
+    f.render_widget(Clear, popup_area);
+
+    let text = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Ref: "),
+            Span::styled(input, Style::default().fg(theme.title).add_modifier(Modifier::BOLD)),
+            Span::styled("_", Style::default().fg(theme.title).add_modifier(Modifier::SLOW_BLINK)),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "<branch|tag|sha> [KEY=value ...]   Enter run, Esc cancel",
+            Style::default().fg(theme.muted),
+        )),
+    ];
+
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.title))
+            .title(" Run Pipeline ")
+            .title_alignment(Alignment::Center),
+    );
+
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Variables prompt of a manual job, Enter plays it
+pub fn render_play(f: &mut Frame, prompt: &PlayPrompt, theme: &Theme, area: Rect) {
+    let popup_area = centered_rect(60, 20, area);
+    f.render_widget(Clear, popup_area);
+
+    let text = vec![
+        Line::from(format!("Play manual job '{}' (#{})?", prompt.job_name, prompt.job_id)),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Variables: "),
+            Span::styled(prompt.input.as_str(), Style::default().fg(theme.title).add_modifier(Modifier::BOLD)),
+            Span::styled("_", Style::default().fg(theme.title).add_modifier(Modifier::SLOW_BLINK)),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "[KEY=value ...]   Enter play, Esc cancel",
+            Style::default().fg(theme.muted),
+        )),
+    ];
+
+    let paragraph = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.title))
+            .title(" Play Job ")
+            .title_alignment(Alignment::Center),
+    );
+
+    f.render_widget(paragraph, popup_area);
+}
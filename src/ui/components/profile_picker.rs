@@ -0,0 +1,51 @@
+use crate::app::App;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let Some(selected) = app.profile_picker else {
+        return;
+    };
+    let popup_area = centered_rect(50, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.title))
+        .title(" GitLab Profiles ")
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(Span::styled(
+            " ↑/↓ select, Enter switch, Esc close ",
+            Style::default().fg(app.theme.muted),
+        )));
+
+    let items: Vec<ListItem> = app
+        .profiles
+        .iter()
+        .enumerate()
+        .map(|(index, profile)| {
+            let marker = if index == app.active_profile { "● " } else { "  " };
+            ListItem::new(Line::from(vec![
+                Span::styled(marker, Style::default().fg(app.theme.success)),
+                Span::raw(format!("{:<20}", profile.name)),
+                Span::styled(profile.host(), Style::default().fg(app.theme.accent)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(app.theme.selection_bg).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_stateful_widget(list, popup_area, &mut state);
+}
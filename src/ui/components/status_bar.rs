@@ -0,0 +1,61 @@
+use crate::app::{App, AppMode};
+use crate::gitlab::Job;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+/// Job name and who ran it, which differs from the pipeline's author for retried or manual jobs
+fn job_label(job: &Job) -> String {
+    match &job.user {
+        Some(user) => format!("{} (by {})", job.name, user.username),
+        None => job.name.clone(),
+    }
+}
+
+/// Where the user is: MR, pipeline and job, the log's job while one is open
+fn context(app: &App) -> Vec<String> {
+    let mut parts = Vec::new();
+    let Some(tracked_mr) = app.tracked_mrs.get(app.selected_mr_index) else {
+        return parts;
+    };
+    parts.push(format!("MR !{}", tracked_mr.mr.iid));
+    if let Some(pipeline) = app.get_selected_pipeline() {
+        match pipeline.trigger() {
+            Some(trigger) => parts.push(format!("pipeline #{} ({})", pipeline.id, trigger)),
+            None => parts.push(format!("pipeline #{}", pipeline.id)),
+        }
+    }
+    let job = match app.mode {
+        AppMode::ViewingLog => app.log_job().map(job_label).or_else(|| app.log_job_name.clone()),
+        _ => app.selected_job().map(job_label),
+    };
+    parts.extend(job);
+    parts
+}
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let theme = app.theme;
+    let muted = Style::default().fg(theme.muted);
+    let mut spans = vec![Span::styled(format!(" {}", app.instance_host), Style::default().fg(theme.accent))];
+    if let Some(profile) = app.profiles.get(app.active_profile).filter(|_| app.profiles.len() > 1) {
+        spans.push(Span::styled(format!(" [{}]", profile.name), muted));
+    }
+    if let Some(username) = &app.username {
+        spans.push(Span::styled(format!("  @{}", username), Style::default().fg(theme.text)));
+    }
+    if let Some(path) = &app.project_path {
+        spans.push(Span::styled(
+            format!("  {}", path),
+            Style::default().fg(theme.title).add_modifier(Modifier::BOLD),
+        ));
+    }
+    for part in context(app) {
+        spans.push(Span::styled("  › ", muted));
+        spans.push(Span::styled(part, muted));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
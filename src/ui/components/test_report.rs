@@ -0,0 +1,108 @@
+use crate::app::App;
+use crate::ui::status::status_style;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::help::centered_rect;
+
+/// Lines of a failure message shown under an expanded test
+const MAX_MESSAGE_LINES: usize = 12;
+
+pub fn render(f: &mut Frame, app: &App, area: Rect) {
+    let Some(report) = &app.test_report else {
+        return;
+    };
+    let theme = app.theme;
+    let popup = centered_rect(85, 80, area);
+    f.render_widget(Clear, popup);
+
+    let hint = if report.show_all { " a failures only" } else { " a all tests" };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.title))
+        .title(format!(" Tests of {} ", report.job_name))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(Span::styled(
+            format!(" ↑/↓ select, Enter expand/collapse,{}, Esc close ", hint),
+            Style::default().fg(theme.muted),
+        )));
+
+    let message = |text: String, style: Style| {
+        Paragraph::new(Line::from(Span::styled(text, style))).alignment(Alignment::Center).block(block.clone())
+    };
+    let suite = match (&report.error, &report.suite) {
+        (Some(error), _) => return f.render_widget(message(error.clone(), Style::default().fg(theme.error)), popup),
+        (None, None) => return f.render_widget(message("Loading…".to_string(), Style::default().fg(theme.muted)), popup),
+        (None, Some(suite)) => suite,
+    };
+
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(inner);
+
+    let count = |status: &str, value: u64| {
+        let (color, symbol) = status_style(theme, status);
+        Span::styled(format!("{} {} {}   ", symbol, value, status), Style::default().fg(color))
+    };
+    let summary = Line::from(vec![
+        Span::styled(format!("{} tests   ", suite.total_count), Style::default().add_modifier(Modifier::BOLD)),
+        count("success", suite.success_count),
+        count("failed", suite.failed_count),
+        count("skipped", suite.skipped_count),
+        Span::styled(format!("{} errors", suite.error_count), Style::default().fg(theme.error)),
+    ]);
+    f.render_widget(Paragraph::new(summary), chunks[0]);
+
+    let visible = report.visible_cases();
+    if visible.is_empty() {
+        let text = if report.show_all { "The report has no tests" } else { "No failing tests, press a to list them all" };
+        let paragraph = Paragraph::new(Span::styled(text, Style::default().fg(theme.success))).alignment(Alignment::Center);
+        f.render_widget(paragraph, chunks[1]);
+        return;
+    }
+
+    let items: Vec<ListItem> = visible
+        .iter()
+        .map(|&index| {
+            let case = &suite.test_cases[index];
+            let (color, symbol) = status_style(theme, if case.status == "error" { "failed" } else { &case.status });
+            let name = match &case.classname {
+                Some(classname) if !classname.is_empty() => format!("{} › {}", classname, case.name),
+                _ => case.name.clone(),
+            };
+            let mut lines = vec![Line::from(vec![
+                Span::styled(format!("{} ", symbol), Style::default().fg(color)),
+                Span::raw(name),
+                Span::styled(format!("  {:.2}s", case.execution_time), Style::default().fg(theme.muted)),
+            ])];
+
+            if case.is_failure() && !report.collapsed.contains(&index) {
+                let text = case.message().unwrap_or_else(|| "(no message)".to_string());
+                let message_lines: Vec<&str> = text.lines().collect();
+                lines.extend(message_lines.iter().take(MAX_MESSAGE_LINES).map(|line| {
+                    Line::from(Span::styled(format!("    {}", line), Style::default().fg(theme.error)))
+                }));
+                if message_lines.len() > MAX_MESSAGE_LINES {
+                    let more = format!("    … {} more lines", message_lines.len() - MAX_MESSAGE_LINES);
+                    lines.push(Line::from(Span::styled(more, Style::default().fg(theme.muted))));
+                }
+            }
+            ListItem::new(lines)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(theme.selection_bg))
+        .highlight_symbol("> ");
+    let mut state = ListState::default();
+    state.select(Some(report.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
@@ -7,12 +7,14 @@ use ratatui::{
 use super::components;
 
 pub fn render(f: &mut Frame, app: &App) {
+    let status_bar = if app.show_status_bar { 1 } else { 0 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),  // MR Tabs
-            Constraint::Length(10), // Pipeline list
-            Constraint::Min(10),    // Jobs table
+            Constraint::Length(3),          // MR Tabs
+            Constraint::Length(10),         // Pipeline list
+            Constraint::Min(10),            // Jobs table
+            Constraint::Length(status_bar), // Instance, user, project and selection
         ])
         .split(f.area());
 
@@ -25,14 +27,44 @@ pub fn render(f: &mut Frame, app: &App) {
     } else {
         components::job_list::render(f, app, chunks[2]);
     }
+    if app.show_status_bar {
+        components::status_bar::render(f, app, chunks[3]);
+    }
 
     // Render help popup on top if in help mode
     if app.mode == AppMode::ShowingHelp {
-        components::help::render(f, f.area());
+        components::help::render(f, app, f.area());
     }
 
     // Render log viewer on top if in log viewing mode
     if app.mode == AppMode::ViewingLog {
         components::log_viewer::render(f, app, f.area());
     }
+
+    if app.mode == AppMode::ComparingLogs {
+        components::log_diff::render(f, app, f.area());
+    }
+
+    components::command_palette::render(f, app, f.area());
+    components::profile_picker::render(f, app, f.area());
+    components::head_pipelines::render(f, app, f.area());
+    components::group_pipelines::render(f, app, f.area());
+    components::artifact_browser::render(f, app, f.area());
+    components::test_report::render(f, app, f.area());
+    components::job_graph::render(f, app, f.area());
+
+    if let Some(input) = &app.trigger_input {
+        components::pipeline_prompt::render(f, input, app.theme, f.area());
+    }
+
+    if let Some(prompt) = &app.play_prompt {
+        components::pipeline_prompt::render_play(f, prompt, app.theme, f.area());
+    }
+
+    // Confirmation prompts sit above everything else
+    if let Some(confirmation) = &app.pending_confirmation {
+        components::confirm::render(f, confirmation, app.theme, f.area());
+    }
+
+    app.color_support.apply(f.buffer_mut());
 }
@@ -1,4 +1,6 @@
 pub mod components;
 pub mod layout;
+pub mod status;
+pub mod theme;
 
 pub use layout::render;
@@ -0,0 +1,28 @@
+use crate::gitlab::models::status_symbol;
+use crate::ui::theme::Theme;
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+
+/// Color and glyph of a job or pipeline status given by its API name (`"failed"`, `"manual"`, ...).
+/// Every status has its own glyph so they stay distinguishable without relying on color.
+pub fn status_style(theme: &Theme, status: &str) -> (Color, &'static str) {
+    let color = match status {
+        "success" => theme.success,
+        "failed" => theme.failed,
+        "running" => theme.running,
+        "pending" | "created" | "preparing" | "waiting_for_resource" | "scheduled" => theme.pending,
+        "manual" => theme.manual,
+        "canceled" | "canceling" => theme.canceled,
+        _ => theme.skipped,
+    };
+    (color, status_symbol(status))
+}
+
+/// `<glyph> <status>` styled with the status color
+pub fn status_span(theme: &Theme, status: &str) -> Span<'static> {
+    let (color, symbol) = status_style(theme, status);
+    Span::styled(format!("{} {}", symbol, status.replace('_', " ")), Style::default().fg(color))
+}
+
+/// Statuses explained in the help overlay, in the order they are listed
+pub const LEGEND: &[&str] = &["success", "failed", "running", "pending", "manual", "canceled", "skipped"];
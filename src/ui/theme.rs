@@ -0,0 +1,114 @@
+use ratatui::style::Color;
+
+/// Semantic colors the components draw with, so a palette can be swapped as a whole
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub text: Color,         // Regular text where the terminal default is not enough
+    pub muted: Color,        // Timestamps, hints, line numbers
+    pub inactive: Color,     // Empty panels
+    pub accent: Color,       // Keys, input boxes, fold markers, authors
+    pub title: Color,        // Popup borders, active tab, typed input, status messages
+    pub heading: Color,      // Section headings in help
+    pub selection_bg: Color, // Selected list row and selected log lines
+    pub error: Color,        // Error line markers
+    pub warning: Color,      // Warning marks on the log minimap
+    pub search_match_bg: Color,
+    pub current_match_bg: Color,
+    pub match_fg: Color,     // Text of search matches
+    // Job and pipeline statuses
+    pub success: Color,
+    pub failed: Color,
+    pub running: Color,
+    pub pending: Color,
+    pub manual: Color,
+    pub canceled: Color,
+    pub skipped: Color,
+}
+
+/// The original look, used unless the config picks another theme
+pub const DARK: Theme = Theme {
+    name: "dark",
+    text: Color::White,
+    muted: Color::DarkGray,
+    inactive: Color::Gray,
+    accent: Color::Cyan,
+    title: Color::Yellow,
+    heading: Color::Green,
+    selection_bg: Color::DarkGray,
+    error: Color::Red,
+    warning: Color::Yellow,
+    search_match_bg: Color::Yellow,
+    current_match_bg: Color::LightRed,
+    match_fg: Color::Black,
+    success: Color::Green,
+    failed: Color::LightRed,
+    running: Color::Yellow,
+    pending: Color::Blue,
+    manual: Color::Magenta,
+    canceled: Color::Gray,
+    skipped: Color::DarkGray,
+};
+
+/// For terminals with a light background: no yellow or white text
+pub const LIGHT: Theme = Theme {
+    name: "light",
+    text: Color::Black,
+    muted: Color::DarkGray,
+    inactive: Color::DarkGray,
+    accent: Color::Blue,
+    title: Color::Magenta,
+    heading: Color::Green,
+    selection_bg: Color::Gray,
+    error: Color::Red,
+    warning: Color::Rgb(0xaf, 0x5f, 0x00),
+    search_match_bg: Color::LightYellow,
+    current_match_bg: Color::LightRed,
+    match_fg: Color::Black,
+    success: Color::Green,
+    failed: Color::Red,
+    running: Color::Blue,
+    pending: Color::Cyan,
+    manual: Color::Magenta,
+    canceled: Color::DarkGray,
+    skipped: Color::Gray,
+};
+
+/// Solarized accent colors, readable on both solarized backgrounds
+pub const SOLARIZED: Theme = Theme {
+    name: "solarized",
+    text: Color::Rgb(0x93, 0xa1, 0xa1),
+    muted: Color::Rgb(0x58, 0x6e, 0x75),
+    inactive: Color::Rgb(0x65, 0x7b, 0x83),
+    accent: Color::Rgb(0x26, 0x8b, 0xd2),
+    title: Color::Rgb(0xb5, 0x89, 0x00),
+    heading: Color::Rgb(0x85, 0x99, 0x00),
+    selection_bg: Color::Rgb(0x07, 0x36, 0x42),
+    error: Color::Rgb(0xdc, 0x32, 0x2f),
+    warning: Color::Rgb(0xcb, 0x4b, 0x16),
+    search_match_bg: Color::Rgb(0xb5, 0x89, 0x00),
+    current_match_bg: Color::Rgb(0xcb, 0x4b, 0x16),
+    match_fg: Color::Rgb(0x00, 0x2b, 0x36),
+    success: Color::Rgb(0x85, 0x99, 0x00),
+    failed: Color::Rgb(0xdc, 0x32, 0x2f),
+    running: Color::Rgb(0xb5, 0x89, 0x00),
+    pending: Color::Rgb(0x2a, 0xa1, 0x98),
+    manual: Color::Rgb(0xd3, 0x36, 0x82),
+    canceled: Color::Rgb(0x65, 0x7b, 0x83),
+    skipped: Color::Rgb(0x58, 0x6e, 0x75),
+};
+
+/// Built-in themes, in the order the theme key cycles through them
+pub const THEMES: [&Theme; 3] = [&DARK, &LIGHT, &SOLARIZED];
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<&'static Theme> {
+        THEMES.into_iter().find(|theme| theme.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The theme after this one, wrapping around
+    pub fn next(&self) -> &'static Theme {
+        let index = THEMES.iter().position(|theme| theme.name == self.name).unwrap_or(0);
+        THEMES[(index + 1) % THEMES.len()]
+    }
+}